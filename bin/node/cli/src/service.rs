@@ -290,6 +290,18 @@ pub fn new_full_base(
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(registry) = prometheus_registry.clone() {
+		task_manager.spawn_handle().spawn(
+			"bags-list-metrics",
+			pallet_bags_list_rpc::metrics::run_metrics_worker::<
+				_,
+				Block,
+				node_primitives::AccountId,
+				pallet_bags_list_rpc::VoteWeight,
+			>(client.clone(), registry),
+		);
+	}
+
 	let (block_import, grandpa_link, babe_link) = import_setup;
 
 	(with_startup_data)(&block_import, &babe_link);
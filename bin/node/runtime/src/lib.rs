@@ -119,7 +119,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 267,
+	spec_version: 268,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 2,
@@ -496,10 +496,11 @@ parameter_types! {
 	pub const SlashDeferDuration: pallet_staking::EraIndex = 24 * 7; // 1/4 the bonding duration.
 	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
 	pub const MaxNominatorRewardedPerValidator: u32 = 256;
+	pub const AutoChillNominators: bool = true;
 	pub OffchainRepeat: BlockNumber = 5;
 }
 
-use frame_election_provider_support::onchain;
+use frame_election_provider_support::{onchain, VoteWeight};
 impl onchain::Config for Runtime {
 	type Accuracy = Perbill;
 	type DataProvider = Staking;
@@ -532,6 +533,9 @@ impl pallet_staking::Config for Runtime {
 	// Alternatively, use pallet_staking::UseNominatorsMap<Runtime> to just use the nominators map.
 	// Note that the aforementioned does not scale to a very large number of nominators.
 	type SortedListProvider = BagsList;
+	// Alternatively, use pallet_staking::UseValidatorsMap<Runtime> to just use the validators map.
+	type TargetList = TargetBagsList;
+	type AutoChillNominators = AutoChillNominators;
 	type WeightInfo = pallet_staking::weights::SubstrateWeight<Runtime>;
 }
 
@@ -652,13 +656,85 @@ impl pallet_election_provider_multi_phase::Config for Runtime {
 
 parameter_types! {
 	pub const BagThresholds: &'static [u64] = &voter_bags::THRESHOLDS;
+	pub const MaxBagThresholds: u32 = 400;
+	pub const MaxAutoRebagPerBlock: u32 = 10;
+	pub const RefreshTopBags: u32 = 8;
+	pub const DisplacementMargin: Perbill = Perbill::from_percent(10);
+	pub const BagsListOffchainRepeat: BlockNumber = 10;
+	pub const MaxOffchainRebags: u32 = 16;
+	pub const BagsListUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 4;
+	pub const BagsListSanityCheckDepth: pallet_bags_list::SanityCheckLevel =
+		pallet_bags_list::SanityCheckLevel::Full;
+	// no bag holds more ids than a single voter snapshot does.
+	pub const MaxBagSize: u32 = VoterSnapshotPerBlock::get();
+	pub const BagOverflowPolicy: pallet_bags_list::BagOverflow =
+		pallet_bags_list::BagOverflow::SpillLower;
+	pub const BagInsertionPolicy: pallet_bags_list::BagInsertion =
+		pallet_bags_list::BagInsertion::Tail;
+	pub const BagsListPalletId: PalletId = PalletId(*b"py/bagsl");
+	pub const TargetBagsListPalletId: PalletId = PalletId(*b"py/tbags");
+	pub const RebagReward: Balance = 1 * CENTS;
+	pub const RebagRewardCooldown: BlockNumber = 1 * DAYS;
+	pub const RebagCooldown: BlockNumber = 10 * MINUTES;
+	pub const RebagDeposit: Balance = 10 * CENTS;
+	pub const PruneReward: Balance = 1 * CENTS;
 }
 
 impl pallet_bags_list::Config for Runtime {
 	type Event = Event;
-	type VoteWeightProvider = Staking;
+	type Score = VoteWeight;
+	type ScoreProvider = Staking;
 	type WeightInfo = pallet_bags_list::weights::SubstrateWeight<Runtime>;
 	type BagThresholds = BagThresholds;
+	type MaxBagThresholds = MaxBagThresholds;
+	type MaxBagSize = MaxBagSize;
+	type BagOverflowPolicy = BagOverflowPolicy;
+	type BagInsertionPolicy = BagInsertionPolicy;
+	type SanityCheckDepth = BagsListSanityCheckDepth;
+	type MaxAutoRebagPerBlock = MaxAutoRebagPerBlock;
+	type RefreshTopBags = RefreshTopBags;
+	type DisplacementMargin = DisplacementMargin;
+	type MinScoreBag = ();
+	type OffchainRepeat = BagsListOffchainRepeat;
+	type MaxOffchainRebags = MaxOffchainRebags;
+	type UnsignedPriority = BagsListUnsignedPriority;
+	type Currency = Balances;
+	type PalletId = BagsListPalletId;
+	type RebagReward = RebagReward;
+	type RebagRewardCooldown = RebagRewardCooldown;
+	type RebagCooldown = RebagCooldown;
+	type RebagDeposit = RebagDeposit;
+	type Members = pallet_staking::IsVoter<Runtime>;
+	type PruneReward = PruneReward;
+}
+
+type TargetBagsListInstance = pallet_bags_list::Instance1;
+impl pallet_bags_list::Config<TargetBagsListInstance> for Runtime {
+	type Event = Event;
+	type Score = VoteWeight;
+	type ScoreProvider = pallet_staking::TargetScoreProvider<Runtime>;
+	type WeightInfo = pallet_bags_list::weights::SubstrateWeight<Runtime>;
+	type BagThresholds = BagThresholds;
+	type MaxBagThresholds = MaxBagThresholds;
+	type MaxBagSize = MaxBagSize;
+	type BagOverflowPolicy = BagOverflowPolicy;
+	type BagInsertionPolicy = BagInsertionPolicy;
+	type SanityCheckDepth = BagsListSanityCheckDepth;
+	type MaxAutoRebagPerBlock = MaxAutoRebagPerBlock;
+	type RefreshTopBags = ();
+	type DisplacementMargin = DisplacementMargin;
+	type MinScoreBag = ();
+	type OffchainRepeat = BagsListOffchainRepeat;
+	type MaxOffchainRebags = MaxOffchainRebags;
+	type UnsignedPriority = BagsListUnsignedPriority;
+	type Currency = Balances;
+	type PalletId = TargetBagsListPalletId;
+	type RebagReward = RebagReward;
+	type RebagRewardCooldown = RebagRewardCooldown;
+	type RebagCooldown = RebagCooldown;
+	type RebagDeposit = RebagDeposit;
+	type Members = pallet_staking::IsTarget<Runtime>;
+	type PruneReward = PruneReward;
 }
 
 parameter_types! {
@@ -1286,7 +1362,8 @@ construct_runtime!(
 		Gilt: pallet_gilt::{Pallet, Call, Storage, Event<T>, Config},
 		Uniques: pallet_uniques::{Pallet, Call, Storage, Event<T>},
 		TransactionStorage: pallet_transaction_storage::{Pallet, Call, Storage, Inherent, Config<T>, Event<T>},
-		BagsList: pallet_bags_list::{Pallet, Call, Storage, Event<T>},
+		BagsList: pallet_bags_list::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		TargetBagsList: pallet_bags_list::<Instance1>::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -1327,7 +1404,11 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPallets,
-	(),
+	(
+		pallet_bags_list::migrations::PopulateBagCounters<Runtime>,
+		pallet_bags_list::migrations::AddScore<Runtime>,
+		pallet_staking::migrations::target_list::MigrateToTargetList<Runtime, TargetBagsList>,
+	),
 >;
 
 /// MMR helper types.
@@ -1543,6 +1624,49 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_bags_list_rpc_runtime_api::VoterBagsApi<Block, AccountId, VoteWeight> for Runtime {
+		fn bag_for(who: AccountId) -> Option<(VoteWeight, VoteWeight)> {
+			BagsList::bag_for(&who)
+		}
+
+		fn rebag_needed(who: AccountId) -> Option<VoteWeight> {
+			BagsList::rebag_needed(&who)
+		}
+
+		fn bag_stats() -> Vec<pallet_bags_list::BagStats<AccountId, VoteWeight>> {
+			BagsList::bag_stats()
+		}
+
+		fn bag_page(bag_upper: VoteWeight, after: Option<AccountId>, max: u32) -> Vec<AccountId> {
+			BagsList::bag_page(bag_upper, after, max)
+		}
+
+		fn electing_preview(n: u32) -> Vec<(AccountId, VoteWeight)> {
+			Staking::electing_preview(n)
+		}
+
+		fn bag_histogram() -> Vec<pallet_bags_list::BagOccupancy<VoteWeight>> {
+			BagsList::bag_histogram()
+		}
+
+		fn rebags_in_block() -> u32 {
+			System::events()
+				.iter()
+				.filter(|record| {
+					matches!(record.event, Event::BagsList(pallet_bags_list::Event::Rebagged(..)))
+				})
+				.count() as u32
+		}
+
+		fn thresholds() -> Vec<VoteWeight> {
+			BagsList::thresholds()
+		}
+
+		fn constant_ratio() -> Option<sp_runtime::FixedU128> {
+			BagsList::constant_ratio()
+		}
+	}
+
 	impl pallet_mmr::primitives::MmrApi<
 		Block,
 		mmr::Hash,
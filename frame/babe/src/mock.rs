@@ -217,6 +217,8 @@ impl pallet_staking::Config for Test {
 	type GenesisElectionProvider = Self::ElectionProvider;
 	type WeightInfo = ();
 	type SortedListProvider = pallet_staking::UseNominatorsMap<Self>;
+	type TargetList = pallet_staking::UseValidatorsMap<Self>;
+	type AutoChillNominators = ();
 }
 
 impl pallet_offences::Config for Test {
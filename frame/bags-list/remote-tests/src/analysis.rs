@@ -0,0 +1,228 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Analysis of candidate bag thresholds against the voters of a live chain.
+//!
+//! For each set of thresholds, the current voters are bucketed into bags the way the pallet would
+//! do it, and the resulting occupancy is reported, along with the effect of only taking the first
+//! `voter_limit` voters of the list, as the election snapshot does.
+
+use crate::{build_staking_ext, voter_weights, LOG_TARGET};
+use frame_election_provider_support::VoteWeight;
+use sp_runtime::traits::Block as BlockT;
+use std::{collections::BTreeMap, fmt};
+
+/// The effect of taking only the first `voter_limit` voters of a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncation {
+	/// The upper threshold of the bag in which the iteration stops.
+	pub bag_upper: VoteWeight,
+	/// How many voters of that bag are taken.
+	pub taken: usize,
+	/// How many voters of that bag are left out.
+	pub left_out: usize,
+	/// The lowest weight taken by an exact, fully sorted, selection.
+	pub exact_cutoff: VoteWeight,
+	/// The lowest weight that can be taken by iterating the bags, in the worst case.
+	pub worst_cutoff: VoteWeight,
+}
+
+/// The occupancy of the bags of a set of thresholds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdsReport {
+	/// The upper threshold and number of voters of each non-empty bag, from the highest bag to the
+	/// lowest.
+	pub bags: Vec<(VoteWeight, usize)>,
+	/// The number of voters in the largest bag.
+	pub largest_bag: usize,
+	/// The effect of taking only the first `voter_limit` voters, or `None` if all the voters are
+	/// taken (or none at all).
+	pub truncation: Option<Truncation>,
+}
+
+impl fmt::Display for ThresholdsReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let voters = self.bags.iter().map(|(_, count)| count).sum::<usize>();
+		writeln!(
+			f,
+			"{} voters in {} non-empty bags, the largest holding {}",
+			voters,
+			self.bags.len(),
+			self.largest_bag,
+		)?;
+		for (bag_upper, count) in &self.bags {
+			writeln!(f, "  bag {:>20}: {}", bag_upper, count)?;
+		}
+		match &self.truncation {
+			Some(t) => write!(
+				f,
+				"truncated in bag {}: {} taken, {} left out; cutoff {} at best, {} at worst",
+				t.bag_upper, t.taken, t.left_out, t.exact_cutoff, t.worst_cutoff,
+			),
+			None => write!(f, "not truncated"),
+		}
+	}
+}
+
+/// Bucket `weights` into the bags delimited by `thresholds`, and report the occupancy of the bags
+/// and the effect of taking only the first `voter_limit` voters.
+pub fn analyze(
+	weights: &[VoteWeight],
+	thresholds: &[VoteWeight],
+	voter_limit: usize,
+) -> ThresholdsReport {
+	let mut bags = BTreeMap::<VoteWeight, Vec<VoteWeight>>::new();
+	for weight in weights {
+		let bag_upper = pallet_bags_list::canonical_bag_in(thresholds, *weight);
+		bags.entry(bag_upper).or_default().push(*weight);
+	}
+
+	let truncation = (voter_limit > 0 && weights.len() > voter_limit).then(|| {
+		let mut sorted = weights.to_vec();
+		sorted.sort_unstable_by(|a, b| b.cmp(a));
+		let exact_cutoff = sorted[voter_limit - 1];
+
+		let mut before = 0;
+		let (bag_upper, members) = bags
+			.iter()
+			.rev()
+			.find(|(_, members)| {
+				let found = before + members.len() > voter_limit;
+				if !found {
+					before += members.len();
+				}
+				found
+			})
+			.expect("there are more voters than `voter_limit`; qed");
+
+		let taken = voter_limit - before;
+		let worst_cutoff = if taken == 0 {
+			exact_cutoff
+		} else {
+			members.iter().copied().min().expect("the bag is not empty; qed")
+		};
+		Truncation {
+			bag_upper: *bag_upper,
+			taken,
+			left_out: members.len() - taken,
+			exact_cutoff,
+			worst_cutoff,
+		}
+	});
+
+	ThresholdsReport {
+		largest_bag: bags.values().map(Vec::len).max().unwrap_or_default(),
+		bags: bags
+			.into_iter()
+			.rev()
+			.map(|(bag_upper, members)| (bag_upper, members.len()))
+			.collect(),
+		truncation,
+	}
+}
+
+/// Scrape the voters of `Runtime` from `ws_url`, and report how each of the named `candidates`
+/// threshold sets would bucket them.
+pub async fn analyze_thresholds<Runtime, Block>(
+	ws_url: String,
+	candidates: Vec<(&'static str, Vec<VoteWeight>)>,
+	voter_limit: usize,
+) -> Vec<(&'static str, ThresholdsReport)>
+where
+	Runtime: pallet_staking::Config,
+	Block: BlockT,
+{
+	let weights = build_staking_ext::<Runtime, Block>(ws_url)
+		.await
+		.execute_with(|| voter_weights::<Runtime>());
+	log::info!(target: LOG_TARGET, "scraped the weights of {} voters", weights.len());
+
+	candidates
+		.into_iter()
+		.map(|(name, thresholds)| {
+			let report = analyze(&weights, &thresholds, voter_limit);
+			log::info!(
+				target: LOG_TARGET,
+				"{} ({} thresholds): {}",
+				name,
+				thresholds.len(),
+				report
+			);
+			(name, report)
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn analyze_buckets_voters() {
+		let weights = [5, 15, 12, 30, 25, 1_000];
+
+		assert_eq!(
+			analyze(&weights, &[10, 20, 30], 10),
+			ThresholdsReport {
+				bags: vec![(VoteWeight::MAX, 1), (30, 2), (20, 2), (10, 1)],
+				largest_bag: 2,
+				truncation: None,
+			}
+		);
+	}
+
+	#[test]
+	fn analyze_reports_truncation() {
+		let weights = [5, 15, 12, 30, 25, 1_000];
+
+		// the limit falls within bag 20, which holds 12 and 15; only one of them is taken.
+		assert_eq!(
+			analyze(&weights, &[10, 20, 30], 4).truncation,
+			Some(Truncation {
+				bag_upper: 20,
+				taken: 1,
+				left_out: 1,
+				exact_cutoff: 15,
+				worst_cutoff: 12,
+			})
+		);
+
+		// the limit falls right at the end of bag 30, so the bags are as good as sorting.
+		assert_eq!(
+			analyze(&weights, &[10, 20, 30], 3).truncation,
+			Some(Truncation {
+				bag_upper: 20,
+				taken: 0,
+				left_out: 2,
+				exact_cutoff: 25,
+				worst_cutoff: 25,
+			})
+		);
+
+		// with a single bag, any voter may be taken.
+		assert_eq!(
+			analyze(&weights, &[], 2).truncation,
+			Some(Truncation {
+				bag_upper: VoteWeight::MAX,
+				taken: 2,
+				left_out: 4,
+				exact_cutoff: 30,
+				worst_cutoff: 5,
+			})
+		);
+	}
+}
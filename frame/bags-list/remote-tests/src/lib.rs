@@ -0,0 +1,161 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to test the bags-list pallet against the state of a live chain, scraped with
+//! remote externalities.
+//!
+//! The tests that actually hit the network are only compiled with the `remote-test` feature, and
+//! expect a node of the chosen runtime at `WS_ENDPOINT` (see [`ws_endpoint`]).
+
+use frame_election_provider_support::VoteWeight;
+use frame_support::traits::PalletInfoAccess;
+use remote_externalities::{Builder, Mode, OnlineConfig};
+use sp_runtime::traits::Block as BlockT;
+
+pub mod analysis;
+
+pub(crate) const LOG_TARGET: &str = "runtime::bags-list::remote-tests";
+
+/// The endpoint of the node to scrape, read from the `WS_ENDPOINT` environment variable, with a
+/// local node as the default.
+pub fn ws_endpoint() -> String {
+	std::env::var("WS_ENDPOINT").unwrap_or_else(|_| "ws://localhost:9944".to_owned())
+}
+
+/// Build the externalities of the staking pallet of `Runtime`, as found at `ws_url`, along with
+/// the total issuance needed to compute vote weights.
+pub async fn build_staking_ext<Runtime, Block>(ws_url: String) -> sp_io::TestExternalities
+where
+	Runtime: pallet_staking::Config,
+	Block: BlockT,
+{
+	Builder::<Block>::new()
+		.mode(Mode::Online(OnlineConfig {
+			transport: ws_url.into(),
+			pallets: vec![pallet_staking::Pallet::<Runtime>::name().to_owned()],
+			..Default::default()
+		}))
+		.inject_hashed_key(&frame_support::storage::storage_prefix(b"Balances", b"TotalIssuance"))
+		.build()
+		.await
+		.expect("Can't reach the remote node. Is it running?")
+}
+
+/// The vote weight of every nominator and validator of `Runtime`.
+///
+/// Must be called within the externalities built by [`build_staking_ext`].
+pub fn voter_weights<Runtime: pallet_staking::Config>() -> Vec<VoteWeight> {
+	let weight_of = pallet_staking::Pallet::<Runtime>::weight_of_fn();
+	pallet_staking::Nominators::<Runtime>::iter_keys()
+		.chain(pallet_staking::Validators::<Runtime>::iter_keys())
+		.map(|who| weight_of(&who))
+		.collect()
+}
+
+#[cfg(all(test, feature = "remote-test"))]
+mod remote_tests {
+	use super::*;
+	use frame_election_provider_support::SortedListProvider;
+	use frame_support::{
+		assert_ok,
+		storage::{storage_prefix, unhashed},
+		traits::{Get, OnRuntimeUpgrade},
+	};
+	use node_primitives::Block;
+	use node_runtime::{BagsList, Runtime};
+	use pallet_bags_list::SanityCheckLevel;
+	use std::{collections::BTreeMap, convert::TryInto};
+
+	/// The lower half of the current thresholds of the node runtime, as if the list had been
+	/// built before the higher bags were introduced.
+	struct OldThresholds;
+	impl Get<&'static [VoteWeight]> for OldThresholds {
+		fn get() -> &'static [VoteWeight] {
+			let current = <Runtime as pallet_bags_list::Config>::BagThresholds::get();
+			&current[..current.len() / 2]
+		}
+	}
+
+	#[tokio::test]
+	async fn migrations_build_a_sane_list() {
+		build_staking_ext::<Runtime, Block>(ws_endpoint()).await.execute_with(|| {
+			// build the list with the old thresholds, from scratch.
+			assert_eq!(BagsList::count(), 0);
+			assert_ok!(pallet_bags_list::Pallet::<Runtime>::set_bag_thresholds(
+				frame_system::RawOrigin::Root.into(),
+				OldThresholds::get().to_vec().try_into().unwrap(),
+			));
+			// `Releases::V7_0_0`, which is private to staking, right before the list is populated.
+			unhashed::put(&storage_prefix(b"Staking", b"StorageVersion"), &6u8);
+			pallet_staking::migrations::v8::MigrateToV8::<Runtime>::on_runtime_upgrade();
+			assert_eq!(BagsList::count(), pallet_staking::CounterForNominators::<Runtime>::get());
+
+			// then go back to the current thresholds, and migrate to them.
+			unhashed::kill(&storage_prefix(b"BagsList", b"ListBagThresholds"));
+			pallet_bags_list::migrations::MigrateThresholds::<Runtime, (), OldThresholds>::on_runtime_upgrade();
+
+			// the nodes and their links are all sane.
+			let nominators = pallet_staking::CounterForNominators::<Runtime>::get();
+			assert_eq!(BagsList::count(), nominators);
+			assert_eq!(BagsList::iter().count() as u32, nominators);
+			assert_eq!(
+				pallet_bags_list::Pallet::<Runtime>::try_state(SanityCheckLevel::Full),
+				Ok(())
+			);
+
+			// and the list is iterated from the highest bag to the lowest, each nominator being in
+			// the bag of its weight, as computed independently of the list.
+			let thresholds = <Runtime as pallet_bags_list::Config>::BagThresholds::get();
+			let weight_of = pallet_staking::Pallet::<Runtime>::weight_of_fn();
+			let bag_of =
+				|who: &_| pallet_bags_list::canonical_bag_in(thresholds, weight_of(who));
+			let mut expected = pallet_staking::Nominators::<Runtime>::iter_keys()
+				.map(|who| bag_of(&who))
+				.collect::<Vec<_>>();
+			expected.sort_unstable_by(|a, b| b.cmp(a));
+			let iterated = BagsList::iter_with_bag()
+				.map(|(who, bag_upper)| {
+					assert_eq!(bag_upper, bag_of(&who), "{:?} is not in the bag of its weight", who);
+					bag_upper
+				})
+				.collect::<Vec<_>>();
+			assert_eq!(iterated, expected);
+
+			let mut occupancy = BTreeMap::<VoteWeight, u32>::new();
+			iterated.iter().for_each(|bag_upper| *occupancy.entry(*bag_upper).or_default() += 1);
+			for (bag_upper, count) in occupancy {
+				assert_eq!(BagsList::count_in_bag(bag_upper), count);
+			}
+		});
+	}
+
+	#[tokio::test]
+	async fn analyze_node_runtime_thresholds() {
+		let current = <Runtime as pallet_bags_list::Config>::BagThresholds::get().to_vec();
+		// every other threshold of the current set, i.e. half the bags, each twice as wide.
+		let halved = current.iter().copied().step_by(2).collect::<Vec<_>>();
+
+		let reports = analysis::analyze_thresholds::<Runtime, Block>(
+			ws_endpoint(),
+			vec![("current", current), ("halved", halved)],
+			// the size of the voter snapshot of the node runtime's election.
+			10_000,
+		)
+		.await;
+		assert_eq!(reports.len(), 2);
+	}
+}
@@ -0,0 +1,67 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for bags list pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+pub use frame_election_provider_support::VoteWeight;
+pub use pallet_bags_list::{BagOccupancy, BagStats};
+pub use sp_runtime::FixedU128;
+
+sp_api::decl_runtime_apis! {
+	pub trait VoterBagsApi<AccountId, Score> where
+		AccountId: Codec,
+		Score: Codec,
+	{
+		/// The upper threshold of the bag `who` is currently in, and of the bag it belongs in
+		/// according to its current score, or `None` if `who` is not in the list.
+		fn bag_for(who: AccountId) -> Option<(Score, Score)>;
+
+		/// The upper threshold of the bag that calling `rebag` for `who` would currently move it
+		/// into, or `None` if the call would not move it.
+		fn rebag_needed(who: AccountId) -> Option<Score>;
+
+		/// The head, tail and number of ids of the bag of each threshold, from the lowest
+		/// threshold to the highest.
+		fn bag_stats() -> Vec<BagStats<AccountId, Score>>;
+
+		/// At most `max` ids of the bag with the upper threshold `bag_upper`, in order, starting
+		/// with its head, or right after `after` if given.
+		fn bag_page(bag_upper: Score, after: Option<AccountId>, max: u32) -> Vec<AccountId>;
+
+		/// The first `n` voters of the next election, with their score, in the order in which
+		/// the election snapshot takes them.
+		fn electing_preview(n: u32) -> Vec<(AccountId, Score)>;
+
+		/// The occupancy of every non-empty bag, from the lowest threshold to the highest.
+		fn bag_histogram() -> Vec<BagOccupancy<Score>>;
+
+		/// The number of ids that were moved into another bag in this block.
+		fn rebags_in_block() -> u32;
+
+		/// The bag thresholds currently in use, from the lowest to the highest.
+		fn thresholds() -> Vec<Score>;
+
+		/// The constant ratio between consecutive thresholds, as implied by the highest ones, or
+		/// `None` if there are too few thresholds to tell.
+		fn constant_ratio() -> Option<FixedU128>;
+	}
+}
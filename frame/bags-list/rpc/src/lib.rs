@@ -0,0 +1,129 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! RPC interface for the bags list pallet.
+
+pub use self::gen_client::Client as VoterBagsClient;
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_bags_list_rpc_runtime_api::BagStats;
+pub use pallet_bags_list_rpc_runtime_api::{VoteWeight, VoterBagsApi as VoterBagsRuntimeApi};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+pub mod metrics;
+
+/// The maximum number of ids returned by a single call to [`VoterBagsApi::bag_page`].
+pub const MAX_BAG_PAGE: u32 = 1_024;
+
+#[rpc]
+pub trait VoterBagsApi<BlockHash, AccountId, Score> {
+	/// The head, tail and number of ids of the bag of each threshold, from the lowest threshold
+	/// to the highest.
+	#[rpc(name = "voterBags_bagStats")]
+	fn bag_stats(&self, at: Option<BlockHash>) -> Result<Vec<BagStats<AccountId, Score>>>;
+
+	/// At most `max` ids of the bag with the upper threshold `bag_upper`, in order, starting with
+	/// its head, or right after `after` if given. `max` is capped at [`MAX_BAG_PAGE`].
+	///
+	/// The last id returned is the cursor of the next page, until fewer than `max` ids are
+	/// returned.
+	#[rpc(name = "voterBags_bagPage", alias("voterBags_nodesInBag"))]
+	fn bag_page(
+		&self,
+		bag_upper: Score,
+		after: Option<AccountId>,
+		max: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<AccountId>>;
+}
+
+/// A struct that implements the [`VoterBagsApi`].
+pub struct VoterBags<C, P> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> VoterBags<C, P> {
+	/// Create new `VoterBags` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+impl<C, Block, AccountId, Score> VoterBagsApi<<Block as BlockT>::Hash, AccountId, Score>
+	for VoterBags<C, Block>
+where
+	Block: BlockT,
+	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: VoterBagsRuntimeApi<Block, AccountId, Score>,
+	AccountId: Codec,
+	Score: Codec,
+{
+	fn bag_stats(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<BagStats<AccountId, Score>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		api.bag_stats(&at).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(Error::RuntimeError.into()),
+			message: "Unable to query bag stats.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+
+	fn bag_page(
+		&self,
+		bag_upper: Score,
+		after: Option<AccountId>,
+		max: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		api.bag_page(&at, bag_upper, after, max.min(MAX_BAG_PAGE))
+			.map_err(|e| RpcError {
+				code: ErrorCode::ServerError(Error::RuntimeError.into()),
+				message: "Unable to query bag page.".into(),
+				data: Some(format!("{:?}", e).into()),
+			})
+	}
+}
@@ -0,0 +1,151 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prometheus metrics of the bags list, updated from the runtime at every new best block.
+//!
+//! Spawn [`run_metrics_worker`] next to the other tasks of the node to export them.
+
+use crate::VoterBagsRuntimeApi;
+use codec::Codec;
+use futures::StreamExt;
+use prometheus_endpoint::{
+	register, Counter, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64,
+};
+use sc_client_api::BlockchainEvents;
+use sp_api::{ApiError, ProvideRuntimeApi};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, UniqueSaturatedInto},
+};
+use std::{fmt::Display, sync::Arc};
+
+const LOG_TARGET: &str = "bags-list-metrics";
+
+/// Bags list Prometheus metrics.
+pub struct Metrics {
+	voters: Gauge<U64>,
+	bag_voters: GaugeVec<U64>,
+	bag_score: GaugeVec<U64>,
+	rebags: Counter<U64>,
+}
+
+impl Metrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			voters: register(
+				Gauge::new("bags_list_voters", "Number of ids in the bags list")?,
+				registry,
+			)?,
+			bag_voters: register(
+				GaugeVec::new(
+					Opts::new("bags_list_bag_voters", "Number of ids in each non-empty bag"),
+					&["bag_upper"],
+				)?,
+				registry,
+			)?,
+			bag_score: register(
+				GaugeVec::new(
+					Opts::new(
+						"bags_list_bag_score",
+						"Total score of the ids in each non-empty bag, as of their last placement",
+					),
+					&["bag_upper"],
+				)?,
+				registry,
+			)?,
+			rebags: register(
+				Counter::new(
+					"bags_list_rebags",
+					"Total number of ids moved into another bag in the best blocks",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Update the metrics from the state of the block `at`.
+	fn update<C, Block, AccountId, Score>(
+		&self,
+		client: &C,
+		at: &BlockId<Block>,
+	) -> Result<(), ApiError>
+	where
+		Block: BlockT,
+		C: ProvideRuntimeApi<Block>,
+		C::Api: VoterBagsRuntimeApi<Block, AccountId, Score>,
+		AccountId: Codec,
+		Score: Codec + Display + UniqueSaturatedInto<u64>,
+	{
+		let api = client.runtime_api();
+		let histogram = api.bag_histogram(at)?;
+		let rebags = api.rebags_in_block(at)?;
+
+		// bags that became empty must not keep reporting their last count.
+		self.bag_voters.reset();
+		self.bag_score.reset();
+		let mut voters = 0u64;
+		for occupancy in histogram {
+			let bag_upper = occupancy.bag_upper.to_string();
+			self.bag_voters.with_label_values(&[&bag_upper]).set(occupancy.count.into());
+			self.bag_score
+				.with_label_values(&[&bag_upper])
+				.set(occupancy.total_score.unique_saturated_into());
+			voters = voters.saturating_add(occupancy.count.into());
+		}
+		self.voters.set(voters);
+		self.rebags.inc_by(rebags.into());
+
+		Ok(())
+	}
+}
+
+/// Keep the bags list metrics in `registry` up to date with every new best block imported by
+/// `client`.
+///
+/// This never returns, unless the import notifications of `client` end or the metrics cannot be
+/// registered.
+pub async fn run_metrics_worker<C, Block, AccountId, Score>(client: Arc<C>, registry: Registry)
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + BlockchainEvents<Block>,
+	C::Api: VoterBagsRuntimeApi<Block, AccountId, Score>,
+	AccountId: Codec,
+	Score: Codec + Display + UniqueSaturatedInto<u64>,
+{
+	let metrics = match Metrics::register(&registry) {
+		Ok(metrics) => metrics,
+		Err(err) => {
+			log::warn!(target: LOG_TARGET, "Failed to register prometheus metrics: {}", err);
+			return
+		},
+	};
+
+	let mut imports = client.import_notification_stream();
+	while let Some(notification) = imports.next().await {
+		if !notification.is_new_best {
+			continue
+		}
+		if let Err(err) = metrics.update(&*client, &BlockId::hash(notification.hash)) {
+			log::debug!(
+				target: LOG_TARGET,
+				"Failed to update the metrics at {}: {:?}",
+				notification.hash,
+				err,
+			);
+		}
+	}
+}
@@ -18,69 +18,122 @@
 //! Benchmarks for the bags list pallet.
 
 use super::*;
-use crate::list::List;
-use frame_benchmarking::{account, whitelisted_caller};
-use frame_election_provider_support::VoteWeightProvider;
-use frame_support::{assert_ok, traits::Get};
+use crate::list::{List, Node};
+use frame_benchmarking::{account, whitelist_account, whitelisted_caller};
+use frame_election_provider_support::ScoreProvider;
+use frame_support::{
+	assert_ok,
+	traits::{Get, PalletInfoAccess},
+	BoundedVec,
+};
 use frame_system::RawOrigin as SystemOrigin;
+use sp_std::convert::TryFrom;
 
-frame_benchmarking::benchmarks! {
+/// Fund the reward pot, such that it can pay a few rebag rewards.
+fn fund_reward_pot<T: Config<I>, I: 'static>() {
+	let balance = (T::Currency::minimum_balance() + T::RebagReward::get()) * 10u32.into();
+	let _ = T::Currency::make_free_balance_be(&Pallet::<T, I>::reward_pot(), balance);
+}
+
+/// The number of ids that are not in the bag of their score.
+fn misplaced_count<T: Config<I>, I: 'static>() -> usize {
+	ListNodes::<T, I>::iter_values()
+		.filter(|node| node.is_misplaced(T::ScoreProvider::score(node.id())))
+		.count()
+}
+
+/// The most ids a benchmark puts into a bag it fills up to [`Config::MaxBagSize`].
+const MAX_FILLED: u32 = 64;
+
+/// Insert `count` ids named `name` into the bag `bag_upper`, which must be empty, and return them
+/// from head to tail.
+fn seed_bag<T: Config<I>, I: 'static>(
+	name: &'static str,
+	bag_upper: T::Score,
+	count: u32,
+) -> Vec<T::AccountId> {
+	assert!(List::<T, I>::get_bags().iter().all(|(bag, _)| *bag != bag_upper));
+	(0..count)
+		.map(|i| {
+			let id: T::AccountId = account(name, i, 0);
+			assert_ok!(List::<T, I>::try_insert(id.clone(), bag_upper));
+			id
+		})
+		.collect()
+}
+
+/// Fill the empty bag `bag_upper` with as many ids named `name` as [`Config::MaxBagSize`] allows
+/// (up to [`MAX_FILLED`]), but at least `min`, and return them from head to tail.
+fn fill_bag<T: Config<I>, I: 'static>(
+	name: &'static str,
+	bag_upper: T::Score,
+	min: u32,
+) -> Vec<T::AccountId> {
+	seed_bag::<T, I>(name, bag_upper, T::MaxBagSize::get().min(MAX_FILLED).max(min))
+}
+
+/// Whitelist the keys that are already read by the time a call is dispatched.
+///
+/// The counter of the list nodes is read by every traversal of the list, and thus by `on_idle` in
+/// every block. The node of `dislocated`, if given, is read by [`CheckRebag`] before its rebag is
+/// dispatched.
+fn whitelist_list_keys<T: Config<I>, I: 'static>(dislocated: Option<&T::AccountId>) {
+	let counter = frame_support::storage::storage_prefix(
+		<Pallet<T, I> as PalletInfoAccess>::name().as_bytes(),
+		b"CounterForListNodes",
+	);
+	frame_benchmarking::benchmarking::add_to_whitelist(counter.to_vec().into());
+	if let Some(dislocated) = dislocated {
+		frame_benchmarking::benchmarking::add_to_whitelist(
+			ListNodes::<T, I>::hashed_key_for(dislocated).into(),
+		);
+	}
+}
+
+/// Give `rebagger` enough to reserve the rebag deposit.
+fn fund_rebagger<T: Config<I>, I: 'static>(rebagger: &T::AccountId) {
+	let balance = T::Currency::minimum_balance() + T::RebagDeposit::get();
+	let _ = T::Currency::make_free_balance_be(rebagger, balance);
+}
+
+frame_benchmarking::benchmarks_instance_pallet! {
 	rebag_non_terminal {
 		// An expensive case for rebag-ing (rebag a non-terminal node):
 		//
 		// - The node to be rebagged, _R_, should exist as a non-terminal node in a bag with at
 		//   least 2 other nodes. Thus _R_ will have both its `prev` and `next` nodes updated when
-		//   it is removed. (3 W/R)
+		//   it is removed. (3 W/R) That bag is as full as it can be.
 		// - The destination bag is not empty, thus we need to update the `next` pointer of the last
 		//   node in the destination in addition to the work we do otherwise. (2 W/R)
 
 		// clear any pre-existing storage.
-		List::<T>::clear(None);
+		List::<T, I>::unsafe_clear(None);
 
 		// define our origin and destination thresholds.
 		let origin_bag_thresh = T::BagThresholds::get()[0];
 		let dest_bag_thresh = T::BagThresholds::get()[1];
 
-		// seed items in the origin bag.
-		let origin_head: T::AccountId = account("origin_head", 0, 0);
-		assert_ok!(List::<T>::insert(origin_head.clone(), origin_bag_thresh));
-
-		let origin_middle: T::AccountId = account("origin_middle", 0, 0); // the node we rebag (_R_)
-		assert_ok!(List::<T>::insert(origin_middle.clone(), origin_bag_thresh));
-
-		let origin_tail: T::AccountId  = account("origin_tail", 0, 0);
-		assert_ok!(List::<T>::insert(origin_tail.clone(), origin_bag_thresh));
-
-		// seed items in the destination bag.
-		let dest_head: T::AccountId  = account("dest_head", 0, 0);
-		assert_ok!(List::<T>::insert(dest_head.clone(), dest_bag_thresh));
-
-		// the bags are in the expected state after initial setup.
-		assert_eq!(
-			List::<T>::get_bags(),
-			vec![
-				(origin_bag_thresh, vec![origin_head.clone(), origin_middle.clone(), origin_tail.clone()]),
-				(dest_bag_thresh, vec![dest_head.clone()])
-			]
-		);
+		// seed items in the origin and destination bags.
+		let mut origin = fill_bag::<T, I>("origin", origin_bag_thresh, 3);
+		let dest = seed_bag::<T, I>("dest", dest_bag_thresh, 1);
+		// the node we rebag (_R_).
+		let origin_middle = origin.remove(1);
 
 		let caller = whitelisted_caller();
+		fund_rebagger::<T, I>(&caller);
+		whitelist_list_keys::<T, I>(Some(&origin_middle));
 		// update the weight of `origin_middle` to guarantee it will be rebagged into the destination.
-		T::VoteWeightProvider::set_vote_weight_of(&origin_middle, dest_bag_thresh);
+		T::ScoreProvider::set_score_of(&origin_middle, dest_bag_thresh);
+		// and make sure the caller is rewarded for it.
+		fund_reward_pot::<T, I>();
 	}: rebag(SystemOrigin::Signed(caller), origin_middle.clone())
 	verify {
 		// check the bags have updated as expected.
 		assert_eq!(
-			List::<T>::get_bags(),
+			List::<T, I>::get_bags(),
 			vec![
-				(
-					origin_bag_thresh,
-					vec![origin_head, origin_tail],
-				),
-				(
-					dest_bag_thresh,
-					vec![dest_head, origin_middle],
-				)
+				(origin_bag_thresh, origin),
+				(dest_bag_thresh, dest.into_iter().chain(Some(origin_middle)).collect()),
 			]
 		);
 	}
@@ -89,51 +142,270 @@ frame_benchmarking::benchmarks! {
 		// An expensive case for rebag-ing (rebag a terminal node):
 		//
 		// - The node to be rebagged, _R_, is a terminal node; so _R_, the node pointing to _R_ and
-		//   the origin bag itself will need to be updated. (3 W/R)
+		//   the origin bag itself will need to be updated. (3 W/R) That bag is as full as it can be.
 		// - The destination bag is not empty, thus we need to update the `next` pointer of the last
 		//   node in the destination in addition to the work we do otherwise. (2 W/R)
 
 		// clear any pre-existing storage.
-		List::<T>::clear(None);
+		List::<T, I>::unsafe_clear(None);
 
 		// define our origin and destination thresholds.
 		let origin_bag_thresh = T::BagThresholds::get()[0];
 		let dest_bag_thresh = T::BagThresholds::get()[1];
 
-		// seed items in the origin bag.
-		let origin_head: T::AccountId = account("origin_head", 0, 0);
-		assert_ok!(List::<T>::insert(origin_head.clone(), origin_bag_thresh));
-
-		let origin_tail: T::AccountId  = account("origin_tail", 0, 0); // the node we rebag (_R_)
-		assert_ok!(List::<T>::insert(origin_tail.clone(), origin_bag_thresh));
+		// seed items in the origin and destination bags.
+		let mut origin = fill_bag::<T, I>("origin", origin_bag_thresh, 2);
+		let dest = seed_bag::<T, I>("dest", dest_bag_thresh, 1);
+		// the node we rebag (_R_).
+		let origin_tail = origin.pop().expect("the origin bag is not empty; qed");
 
-		// seed items in the destination bag.
-		let dest_head: T::AccountId  = account("dest_head", 0, 0);
-		assert_ok!(List::<T>::insert(dest_head.clone(), dest_bag_thresh));
-
-		// the bags are in the expected state after initial setup.
+		let caller = whitelisted_caller();
+		fund_rebagger::<T, I>(&caller);
+		whitelist_list_keys::<T, I>(Some(&origin_tail));
+		// update the weight of `origin_tail` to guarantee it will be rebagged into the destination.
+		T::ScoreProvider::set_score_of(&origin_tail, dest_bag_thresh);
+		// and make sure the caller is rewarded for it.
+		fund_reward_pot::<T, I>();
+	}: rebag(SystemOrigin::Signed(caller), origin_tail.clone())
+	verify {
+		// check the bags have updated as expected.
 		assert_eq!(
-			List::<T>::get_bags(),
+			List::<T, I>::get_bags(),
 			vec![
-				(origin_bag_thresh, vec![origin_head.clone(), origin_tail.clone()]),
-				(dest_bag_thresh, vec![dest_head.clone()])
+				(origin_bag_thresh, origin),
+				(dest_bag_thresh, dest.into_iter().chain(Some(origin_tail)).collect()),
 			]
 		);
+	}
+
+	rebag_noop {
+		// The cheap case for rebag-ing: the node is already in the right bag, so nothing is
+		// written; only the node and its score are read.
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let bag_thresh = T::BagThresholds::get()[1];
+
+		let placed = seed_bag::<T, I>("placed", bag_thresh, 1);
+		T::ScoreProvider::set_score_of(&placed[0], bag_thresh);
 
 		let caller = whitelisted_caller();
-		// update the weight of `origin_tail` to guarantee it will be rebagged into the destination.
-		T::VoteWeightProvider::set_vote_weight_of(&origin_tail, dest_bag_thresh);
-	}: rebag(SystemOrigin::Signed(caller), origin_tail.clone())
+		fund_rebagger::<T, I>(&caller);
+		whitelist_list_keys::<T, I>(Some(&placed[0]));
+	}: rebag(SystemOrigin::Signed(caller), placed[0].clone())
+	verify {
+		assert_eq!(List::<T, I>::get_bags(), vec![(bag_thresh, placed)]);
+	}
+
+	put_in_front_of {
+		// The most expensive case for `put_in_front_of`:
+		//
+		// - both heavier's `prev` and `next` are nodes that will need to be read and written.
+		// - `lighter` is the bag's `head`, so the bag will need to be read and written.
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let bag_thresh = T::BagThresholds::get()[0];
+
+		// insert the nodes in order
+		let ids = seed_bag::<T, I>("id", bag_thresh, 4);
+		let (lighter, heavier_prev, heavier, heavier_next) =
+			(ids[0].clone(), ids[1].clone(), ids[2].clone(), ids[3].clone());
+
+		List::<T, I>::set_cached_score(&lighter, T::Score::min_value());
+
+		assert_eq!(
+			List::<T, I>::iter().map(|n| n.id().clone()).collect::<Vec<_>>(),
+			vec![lighter.clone(), heavier_prev.clone(), heavier.clone(), heavier_next.clone()]
+		);
+
+		whitelist_account!(heavier);
+		whitelist_list_keys::<T, I>(None);
+	}: _(SystemOrigin::Signed(heavier.clone()), lighter.clone())
 	verify {
-		// check the bags have updated as expected.
 		assert_eq!(
-			List::<T>::get_bags(),
+			List::<T, I>::iter().map(|n| n.id().clone()).collect::<Vec<_>>(),
+			vec![heavier, lighter, heavier_prev, heavier_next]
+		)
+	}
+
+	sanity_check {
+		// The full check of a list of `n` ids, spread over all the bags, such that every bag is
+		// checked as well.
+		let n in 1 .. 1_000;
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let thresholds = T::BagThresholds::get();
+		for i in 0 .. n {
+			let id: T::AccountId = account("id", i, 0);
+			let score = thresholds.get(i as usize % thresholds.len().max(1)).copied()
+				.unwrap_or_else(T::Score::max_value);
+			assert_ok!(List::<T, I>::try_insert(id, score));
+		}
+	}: {
+		assert_ok!(Pallet::<T, I>::try_state(SanityCheckLevel::Full));
+	}
+	verify {
+		assert_eq!(ListNodes::<T, I>::count(), n);
+	}
+
+	rebag_scan_per_node {
+		// The expensive case for a node checked by `on_idle`: it is moved out of the middle of a
+		// bag into a non-empty bag.
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let origin_bag_thresh = T::BagThresholds::get()[0];
+		let dest_bag_thresh = T::BagThresholds::get()[1];
+
+		// every node belongs in the other bag, so whichever the scan checks first is moved.
+		for id in seed_bag::<T, I>("origin", origin_bag_thresh, 3) {
+			T::ScoreProvider::set_score_of(&id, dest_bag_thresh);
+		}
+		for id in seed_bag::<T, I>("dest", dest_bag_thresh, 3) {
+			T::ScoreProvider::set_score_of(&id, origin_bag_thresh);
+		}
+		assert_eq!(misplaced_count::<T, I>(), 6);
+		whitelist_list_keys::<T, I>(None);
+	}: {
+		assert_eq!(Pallet::<T, I>::auto_rebag(1), 1);
+	}
+	verify {
+		assert_eq!(misplaced_count::<T, I>(), 5);
+	}
+
+	set_bag_thresholds {
+		// Removing the `n` lowest thresholds, each of which has an id in its bag, so that `n` bags
+		// are affected.
+		let n in 1 .. (T::BagThresholds::get().len() as u32).saturating_sub(1);
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let old_thresholds = T::BagThresholds::get();
+		for i in 0 .. n {
+			let id: T::AccountId = account("id", i, 0);
+			assert_ok!(List::<T, I>::try_insert(id.clone(), old_thresholds[i as usize]));
+			T::ScoreProvider::set_score_of(&id, old_thresholds[i as usize]);
+		}
+		let new_thresholds =
+			BoundedVec::<_, T::MaxBagThresholds>::try_from(old_thresholds[n as usize..].to_vec())
+				.unwrap();
+	}: _(SystemOrigin::Root, new_thresholds.clone())
+	verify {
+		assert_eq!(ListBagThresholds::<T, I>::get(), Some(new_thresholds));
+		assert!(PendingThresholdMigration::<T, I>::exists());
+	}
+
+	migrate_chunk {
+		// A chunk of `n` ids, all of which are in the bag of a removed threshold, and thus moved.
+		let n in 1 .. 1_000;
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let old_thresholds = T::BagThresholds::get();
+		let removed_thresh = old_thresholds[0];
+		let dest_bag_thresh = old_thresholds[1];
+		for i in 0 .. n {
+			let id: T::AccountId = account("id", i, 0);
+			assert_ok!(List::<T, I>::try_insert(id.clone(), removed_thresh));
+			T::ScoreProvider::set_score_of(&id, removed_thresh);
+		}
+
+		ListBagThresholds::<T, I>::put(
+			BoundedVec::<_, T::MaxBagThresholds>::try_from(old_thresholds[1..].to_vec()).unwrap()
+		);
+		let old_thresholds =
+			BoundedVec::<_, T::MaxBagThresholds>::try_from(old_thresholds.to_vec()).unwrap();
+	}: _(SystemOrigin::Root, old_thresholds, n)
+	verify {
+		assert_eq!(
+			List::<T, I>::get_bags().into_iter().map(|(bag, ids)| (bag, ids.len())).collect::<Vec<_>>(),
+			vec![(dest_bag_thresh, n as usize)]
+		);
+	}
+
+	prune_dangling_nodes {
+		// A scan of `n` ids, all of which are pruned if they are not members, as ids inserted
+		// straight into the list are not for staking. The caller is rewarded for each of them.
+		let n in 1 .. 1_000;
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let bag_thresh = T::BagThresholds::get()[0];
+		let ids = seed_bag::<T, I>("dangling", bag_thresh, n);
+		let dangling = ids.iter().filter(|id| !T::Members::contains(id)).count() as u32;
+
+		let caller = whitelisted_caller();
+		let reward = T::PruneReward::get().saturating_mul(n.into());
+		let _ = T::Currency::make_free_balance_be(
+			&Pallet::<T, I>::reward_pot(),
+			T::Currency::minimum_balance() + reward,
+		);
+		whitelist_list_keys::<T, I>(None);
+	}: _(SystemOrigin::Signed(caller), n)
+	verify {
+		assert_eq!(ListNodes::<T, I>::count(), n - dangling);
+	}
+
+	force_set_bag {
+		// The expensive case of `force_set_bag`, as for `rebag_non_terminal`: the node is moved out
+		// of the middle of a full bag into a non-empty bag.
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let origin_bag_thresh = T::BagThresholds::get()[0];
+		let dest_bag_thresh = T::BagThresholds::get()[1];
+
+		let mut origin = fill_bag::<T, I>("origin", origin_bag_thresh, 3);
+		let dest = seed_bag::<T, I>("dest", dest_bag_thresh, 1);
+		let origin_middle = origin.remove(1);
+		whitelist_list_keys::<T, I>(None);
+	}: _(SystemOrigin::Root, origin_middle.clone(), dest_bag_thresh)
+	verify {
+		assert_eq!(
+			List::<T, I>::get_bags(),
 			vec![
-				(origin_bag_thresh, vec![origin_head.clone()]),
-				(dest_bag_thresh, vec![dest_head.clone(), origin_tail.clone()])
+				(origin_bag_thresh, origin),
+				(dest_bag_thresh, dest.into_iter().chain(Some(origin_middle)).collect()),
 			]
 		);
 	}
+
+	heal_broken_links {
+		// The node in the middle of a bag of `2 * w + 1` ids lost both its links, so that each of
+		// its neighbours is only found after walking `w` nodes of the bag.
+		let w in 1 .. 1_000;
+
+		// clear any pre-existing storage.
+		List::<T, I>::unsafe_clear(None);
+
+		let bag_thresh = T::BagThresholds::get()[0];
+		let mut ids = seed_bag::<T, I>("id", bag_thresh, 2 * w + 1);
+		let broken = ids.remove(w as usize);
+		T::ScoreProvider::set_score_of(&broken, bag_thresh);
+		ListNodes::<T, I>::insert(
+			&broken,
+			Node::<T, I>::from_parts(broken.clone(), None, None, bag_thresh, bag_thresh),
+		);
+
+		let caller = whitelisted_caller();
+		whitelist_list_keys::<T, I>(None);
+	}: _(SystemOrigin::Signed(caller), broken.clone(), w)
+	verify {
+		assert_eq!(
+			List::<T, I>::get_bags(),
+			vec![(bag_thresh, ids.into_iter().chain(Some(broken)).collect())]
+		);
+	}
 }
 
 use frame_benchmarking::impl_benchmark_test_suite;
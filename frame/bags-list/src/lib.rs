@@ -17,16 +17,25 @@
 
 //! # Bags-List Pallet
 //!
-//! A semi-sorted list, where items hold an `AccountId` based on some `VoteWeight`. The `AccountId`
-//! (`id` for short) might be synonym to a `voter` or `nominator` in some context, and `VoteWeight`
-//! signifies the chance of each id being included in the final [`VoteWeightProvider::iter`].
+//! A semi-sorted list, where items hold an `AccountId` based on some score. The `AccountId` (`id`
+//! for short) might be synonym to a `voter` or `nominator` in some context, and the score
+//! signifies the chance of each id being included in the final [`SortedListProvider::iter`]. The
+//! type of the score is [`Config::Score`], typically `VoteWeight`.
 //!
 //! It implements [`sp_election_provider_support::SortedListProvider`] to provide a semi-sorted list
 //! of accounts to another pallet. It needs some other pallet to give it some information about the
-//! weights of accounts via [`sp_election_provider_support::VoteWeightProvider`].
+//! weights of accounts via [`sp_election_provider_support::ScoreProvider`]. Nothing in this pallet
+//! depends on staking; any pallet that needs a semi-sorted list of accounts (e.g. nomination pools
+//! or queues) can act as the `ScoreProvider`.
 //!
-//! This pallet is not configurable at genesis. Whoever uses it should call appropriate functions of
-//! the `SortedListProvider` (e.g. `on_insert`, or `regenerate`) at their genesis.
+//! The pallet is instantiable, so several independent lists, each with its own thresholds and
+//! `ScoreProvider`, can be installed in the same runtime.
+//!
+//! At genesis, the bag thresholds can be derived from the existential deposit and total issuance
+//! of the currency (See [`GenesisThresholds`]), rather than spelled out in
+//! [`Config::BagThresholds`]. The ids are not configurable at genesis: whoever uses this pallet
+//! should call appropriate functions of the `SortedListProvider` (e.g. `on_insert`, or
+//! `unsafe_regenerate`) at their genesis.
 //!
 //! # Goals
 //!
@@ -44,32 +53,79 @@
 //! - items within a bag are iterated in order of insertion. Thus removing an item and re-inserting
 //!   it will worsen its position in list iteration; this reduces incentives for some types of spam
 //!   that involve consistently removing and inserting for better position. Further, ordering
-//!   granularity is thus dictated by range between each bag threshold.
+//!   granularity is thus dictated by range between each bag threshold. Runtimes can instead put the
+//!   latest items first (See [`Config::BagInsertionPolicy`]).
+//! - an item that was placed with a higher weight than another item in the same bag can move itself
+//!   directly in front of that item with [`Call::put_in_front_of`].
 //! - if an item's weight changes to a value no longer within the range of its current bag the
 //!   item's position will need to be updated by an external actor with rebag (update), or removal
-//!   and insertion.
+//!   and insertion. Additionally, the pallet scans the list in `on_idle` and rebags as many items
+//!   as the remaining block weight allows (See [`Config::MaxAutoRebagPerBlock`]).
+//! - the ids of the highest bags, which determine the electing set, can be re-checked at once when
+//!   many of their scores changed, e.g. at the end of each era (See [`Config::RefreshTopBags`]).
+//! - an offchain worker periodically scans the list and submits unsigned [`Call::rebag_unsigned`]
+//!   transactions for items that are in the wrong bag (See [`Config::OffchainRepeat`]).
+//! - callers of [`Call::rebag`] that actually move an item can be paid a bounty from a reward pot
+//!   (See [`Config::RebagReward`]).
+//! - signed [`Call::rebag`] transactions that would not move anything can be kept out of the
+//!   transaction pool with the [`CheckRebag`] signed extension.
+//! - the list can be locked against reordering (See [`SortedListProvider::lock`]), e.g. while an
+//!   election snapshot is taken across several blocks, or while the solutions of an election are
+//!   accepted. The ids whose score is updated in the meantime are rebagged in `on_idle` once the
+//!   list is unlocked.
+//! - the integrity of the list can be checked on a live chain a few nodes at a time, with
+//!   [`Pallet::sanity_check_bounded`].
+//! - a list too large to be migrated to new thresholds in a single block can be migrated a chunk at
+//!   a time, with [`Call::migrate_chunk`]. The thresholds set with [`Call::set_bag_thresholds`] are
+//!   migrated to that way in `on_idle`.
+//! - anyone can repair an item whose links were broken, e.g. by a storage corruption, with
+//!   [`Call::heal_broken_links`].
+//! - anyone can prune the items that are not members of the list anymore (See [`Config::Members`])
+//!   a chunk at a time, with [`Call::prune_dangling_nodes`].
+//! - the ids of the lowest bags can be kept in the list but skipped by its iteration, as a minimum
+//!   score that the users of the list need not enforce (See [`Config::MinScoreBag`]).
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_election_provider_support::{SortedListProvider, VoteWeight, VoteWeightProvider};
-use frame_system::ensure_signed;
+use frame_election_provider_support::{BudgetedIter, ListLock, ScoreProvider, SortedListProvider};
+use frame_support::{
+	traits::{Contains, Currency, ExistenceRequirement, Get, Imbalance, ReservableCurrency},
+	weights::Weight,
+	PalletId,
+};
+use frame_system::{ensure_none, ensure_root, ensure_signed};
+use sp_runtime::{
+	traits::{AccountIdConversion, Bounded, Saturating, UniqueSaturatedInto, Zero},
+	FixedPointNumber, FixedU128, Perbill,
+};
 use sp_std::prelude::*;
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
 mod benchmarks;
 
 mod list;
-#[cfg(test)]
-mod mock;
+#[cfg(any(feature = "std", test))]
+pub mod make_bags;
+pub mod migrations;
+#[cfg(any(test, feature = "fuzz"))]
+pub mod mock;
+mod signed_extension;
 #[cfg(test)]
 mod tests;
+mod unsigned;
 pub mod weights;
 
 pub use pallet::*;
+pub use signed_extension::CheckRebag;
 pub use weights::WeightInfo;
 
-pub use list::Error;
 use list::List;
+pub use list::{
+	bag_index_in, canonical_bag_in, BagInsertion, BagOverflow, Error as ListError, SanityCheckLevel,
+};
+
+pub type BalanceOf<T, I = ()> =
+	<<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 pub(crate) const LOG_TARGET: &'static str = "runtime::bags_list";
 
@@ -84,47 +140,154 @@ macro_rules! log {
 	};
 }
 
+/// The parameters of the bag thresholds derived at genesis, see [`make_bags::make_bags`].
+///
+/// The existential weight is derived with `U128CurrencyToVote`, as by `voter-bags-cli`.
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug, serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GenesisThresholds {
+	/// The existential deposit of the currency, whose weight is the upper threshold of the lowest
+	/// bag.
+	pub existential_deposit: u128,
+	/// The total issuance of the currency.
+	pub total_issuance: u128,
+	/// The number of bags, including the last one, up to `VoteWeight::MAX`.
+	pub count: u32,
+}
+
+/// The composition of a single bag, as reported by [`Pallet::bag_stats`].
+#[derive(Clone, Eq, PartialEq, codec::Encode, codec::Decode)]
+#[cfg_attr(feature = "std", derive(Debug, serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct BagStats<AccountId, Score> {
+	/// The upper threshold of the bag.
+	pub bag_upper: Score,
+	/// The first id in the bag, if any.
+	pub head: Option<AccountId>,
+	/// The last id in the bag, if any.
+	pub tail: Option<AccountId>,
+	/// The number of ids in the bag.
+	pub count: u32,
+}
+
+/// The occupancy of a single non-empty bag, as reported by [`Pallet::bag_histogram`].
+#[derive(
+	Clone,
+	Eq,
+	PartialEq,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+	sp_runtime::RuntimeDebug,
+)]
+pub struct BagOccupancy<Score> {
+	/// The upper threshold of the bag.
+	pub bag_upper: Score,
+	/// The number of ids in the bag.
+	pub count: u32,
+	/// The sum of the scores with which the ids of the bag were last placed, i.e. inserted or
+	/// rebagged.
+	///
+	/// This saturates, and then stays saturated until the bag is emptied.
+	pub total_score: Score,
+}
+
+/// The maximum number of bags in [`Pallet::bag_histogram`]: one per threshold, plus the implied bag
+/// of `Score::max_value()`.
+pub struct MaxHistogramBags<T, I = ()>(sp_std::marker::PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> Get<u32> for MaxHistogramBags<T, I> {
+	fn get() -> u32 {
+		T::MaxBagThresholds::get().saturating_add(1)
+	}
+}
+
+/// The ids right before and right after an id in its bag, as reported by [`Pallet::neighbours`].
+pub type Neighbours<AccountId> = (Option<AccountId>, Option<AccountId>);
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
-	use frame_support::pallet_prelude::*;
-	use frame_system::pallet_prelude::*;
+	use codec::FullCodec;
+	#[cfg(feature = "std")]
+	use frame_support::traits::U128CurrencyToVote;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{GetStorageVersion, StorageVersion},
+		transactional,
+	};
+	use frame_system::{offchain::SendTransactionTypes, pallet_prelude::*};
+	use scale_info::TypeInfo;
+	use sp_runtime::traits::SaturatedConversion;
+	#[cfg(feature = "std")]
+	use sp_runtime::traits::UniqueSaturatedFrom;
+	#[cfg(feature = "std")]
+	use sp_std::convert::TryFrom;
+
+	/// The current storage version, see [`crate::migrations`].
+	pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(crate) trait Store)]
 	#[pallet::generate_storage_info]
-	pub struct Pallet<T>(_);
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T, I = ()>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config<I: 'static = ()>:
+		frame_system::Config + SendTransactionTypes<Call<Self, I>>
+	{
 		/// The overarching event type.
-		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Event: From<Event<Self, I>> + IsType<<Self as frame_system::Config>::Event>;
 
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: weights::WeightInfo;
 
-		/// Something that provides the weights of ids.
-		type VoteWeightProvider: VoteWeightProvider<Self::AccountId>;
+		/// The type used to order ids, and separate them into bags.
+		///
+		/// This is typically `VoteWeight`, but can be any bounded, ordered type, e.g. `u128`.
+		type Score: Clone
+			+ Copy
+			+ Default
+			+ PartialEq
+			+ Eq
+			+ Ord
+			+ PartialOrd
+			+ sp_std::fmt::Debug
+			+ Bounded
+			+ Saturating
+			+ FullCodec
+			+ MaxEncodedLen
+			+ TypeInfo;
+
+		/// Something that provides the scores of ids.
+		///
+		/// This is typically the pallet that uses this list as its `SortedListProvider`, e.g.
+		/// staking, but any implementor of [`ScoreProvider`] can be used.
+		type ScoreProvider: ScoreProvider<Self::AccountId, Score = Self::Score>;
 
 		/// The list of thresholds separating the various bags.
 		///
-		/// Ids are separated into unsorted bags according to their vote weight. This specifies the
-		/// thresholds separating the bags. An id's bag is the largest bag for which the id's weight
+		/// Ids are separated into unsorted bags according to their score. This specifies the
+		/// thresholds separating the bags. An id's bag is the largest bag for which the id's score
 		/// is less than or equal to its upper threshold.
 		///
 		/// When ids are iterated, higher bags are iterated completely before lower bags. This means
-		/// that iteration is _semi-sorted_: ids of higher weight tend to come before ids of lower
-		/// weight, but peer ids within a particular bag are sorted in insertion order.
+		/// that iteration is _semi-sorted_: ids of higher score tend to come before ids of lower
+		/// score, but peer ids within a particular bag are sorted in insertion order.
 		///
 		/// # Expressing the constant
 		///
 		/// This constant must be sorted in strictly increasing order. Duplicate items are not
-		/// permitted.
+		/// permitted. It must not be longer than [`Config::MaxBagThresholds`]. Both are checked by
+		/// the `integrity_test` of the pallet.
 		///
-		/// There is an implied upper limit of `VoteWeight::MAX`; that value does not need to be
+		/// There is an implied upper limit of `Score::max_value()`; that value does not need to be
 		/// specified within the bag. For any two threshold lists, if one ends with
-		/// `VoteWeight::MAX`, the other one does not, and they are otherwise equal, the two lists
-		/// will behave identically.
+		/// `Score::max_value()`, the other one does not, and they are otherwise equal, the two
+		/// lists will behave identically.
 		///
 		/// # Calculation
 		///
@@ -132,7 +295,9 @@ pub mod pallet {
 		/// there exists some constant ratio such that `threshold[k + 1] == (threshold[k] *
 		/// constant_ratio).max(threshold[k] + 1)` for all `k`.
 		///
-		/// The helpers in the `/utils/frame/generate-bags` module can simplify this calculation.
+		/// The helpers in the [`make_bags`] module, available with the `make-bags` feature, can
+		/// simplify this calculation. The `utils/frame/voter-bags-cli` tool, built on them, prints
+		/// the thresholds as a module that can be included in the runtime.
 		///
 		/// # Examples
 		///
@@ -142,155 +307,1593 @@ pub mod pallet {
 		///   the procedure given above, then the constant ratio is equal to 2.
 		/// - If `BagThresholds::get().len() == 200`, and the thresholds are determined according to
 		///   the procedure given above, then the constant ratio is approximately equal to 1.248.
-		/// - If the threshold list begins `[1, 2, 3, ...]`, then an id with weight 0 or 1 will fall
-		///   into bag 0, an id with weight 2 will fall into bag 1, etc.
+		/// - If the threshold list begins `[1, 2, 3, ...]`, then an id with score 0 or 1 will fall
+		///   into bag 0, an id with score 2 will fall into bag 1, etc.
 		///
 		/// # Migration
 		///
 		/// In the event that this list ever changes, a copy of the old bags list must be retained.
-		/// With that `List::migrate` can be called, which will perform the appropriate migration.
+		/// With that, [`migrations::MigrateThresholds`] can be included in the runtime upgrade,
+		/// which will perform the appropriate migration.
+		///
+		/// # Overriding
+		///
+		/// This constant is only a default. Thresholds set with [`Call::set_bag_thresholds`] take
+		/// precedence over it.
 		#[pallet::constant]
-		type BagThresholds: Get<&'static [VoteWeight]>;
-	}
+		type BagThresholds: Get<&'static [Self::Score]>;
 
-	/// How many ids are registered.
-	// NOTE: This is merely a counter for `ListNodes`. It should someday be replaced by the
-	// `CountedMaop` storage.
-	#[pallet::storage]
-	pub(crate) type CounterForListNodes<T> = StorageValue<_, u32, ValueQuery>;
+		/// The maximum number of thresholds that can be set with [`Call::set_bag_thresholds`].
+		#[pallet::constant]
+		type MaxBagThresholds: Get<u32>;
+
+		/// The maximum number of ids in a single bag.
+		///
+		/// Bags are otherwise unbounded, which makes iterating them, and the proof of validity of a
+		/// block doing so, arbitrarily large. What happens to ids that belong in a full bag is
+		/// dictated by [`Config::BagOverflowPolicy`]. Note that bags can still grow beyond this
+		/// limit through [`Call::set_bag_thresholds`].
+		#[pallet::constant]
+		type MaxBagSize: Get<u32>;
+
+		/// What happens to an id that belongs in a bag which already holds
+		/// [`Config::MaxBagSize`] ids, when it is inserted or rebagged.
+		///
+		/// With [`BagOverflow::Reject`], inserting such an id fails; pallets which expect all their
+		/// ids to be in the list, like staking, should rather use [`BagOverflow::SpillLower`].
+		type BagOverflowPolicy: Get<BagOverflow>;
+
+		/// Where ids go within the bag they are inserted or rebagged into.
+		///
+		/// With [`BagInsertion::Tail`], ids that joined a bag earlier stay in front of it for as
+		/// long as they are not rebagged, while [`BagInsertion::Head`] favours the latest ones.
+		type BagInsertionPolicy: Get<BagInsertion>;
+
+		/// How thoroughly [`SortedListProvider::sanity_check`] verifies the list.
+		///
+		/// The sanity check is used in debug assertions of the pallets using this list. Tests
+		/// should generally use [`SanityCheckLevel::Full`], while runtimes may prefer a cheaper
+		/// level. The [`Pallet::try_state`] checks done through try-runtime are always `Full`.
+		type SanityCheckDepth: Get<SanityCheckLevel>;
+
+		/// The maximum number of ids that are checked, and rebagged if needed, in `on_idle`.
+		///
+		/// As many ids as the remaining block weight allows, up to this number, are checked per
+		/// block. Set to zero to disable automatic rebagging.
+		#[pallet::constant]
+		type MaxAutoRebagPerBlock: Get<u32>;
+
+		/// The number of the highest non-empty bags whose ids are all re-checked, and rebagged if
+		/// needed, after [`SortedListProvider::schedule_refresh`] is called.
+		///
+		/// The refresh is done in `on_idle` before the automatic rebagging, and takes from the same
+		/// `T::MaxAutoRebagPerBlock` ids per block. Set to zero to disable it.
+		#[pallet::constant]
+		type RefreshTopBags: Get<u32>;
+
+		/// The fraction of the range of a bag, from either of its thresholds, within which the
+		/// score cached in a node is considered near a boundary by [`Pallet::is_displaced`].
+		///
+		/// The larger the margin, the more, and larger, score changes since the last placement of
+		/// an id are detected, at the cost of reading more live scores.
+		#[pallet::constant]
+		type DisplacementMargin: Get<Perbill>;
+
+		/// The upper threshold of the lowest bag whose ids are iterated by
+		/// [`SortedListProvider::iter`] and its variants.
+		///
+		/// The ids of the lower bags are kept in the list, and rebagged as usual, but skipped by
+		/// the users of the list, e.g. when staking takes its voters. This gives a minimum score,
+		/// with the granularity of a bag, without the users of the list having to enforce it. Set
+		/// to zero to iterate every id.
+		#[pallet::constant]
+		type MinScoreBag: Get<Self::Score>;
+
+		/// How often, in blocks, the offchain worker scans the list for dislocated ids.
+		///
+		/// Set to zero to disable the offchain worker.
+		#[pallet::constant]
+		type OffchainRepeat: Get<Self::BlockNumber>;
+
+		/// The maximum number of `rebag_unsigned` transactions the offchain worker submits per
+		/// scan.
+		#[pallet::constant]
+		type MaxOffchainRebags: Get<u32>;
+
+		/// The priority of the `rebag_unsigned` transactions submitted by the offchain worker.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// The currency in which rebaggers are rewarded, and their deposits are reserved.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The id of the pallet, from which the account of the reward pot is derived.
+		///
+		/// The pot must be funded externally, e.g. by the treasury; rebags are no longer rewarded
+		/// once it runs dry.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The reward paid from the pot to the caller of a [`Call::rebag`] that moves an id into
+		/// another bag.
+		///
+		/// Set to zero to disable rewards.
+		#[pallet::constant]
+		type RebagReward: Get<BalanceOf<Self, I>>;
+
+		/// The number of blocks after a rebag of an id was rewarded, during which further rebags
+		/// of the same id are not rewarded.
+		#[pallet::constant]
+		type RebagRewardCooldown: Get<Self::BlockNumber>;
+
+		/// The number of blocks after an id was moved by [`Call::rebag`] or
+		/// [`Call::rebag_unsigned`], during which further rebags of the same id are rejected.
+		///
+		/// This keeps an id whose score is on the border of two bags from being moved back and
+		/// forth in every block. Set to zero to disable.
+		#[pallet::constant]
+		type RebagCooldown: Get<Self::BlockNumber>;
+
+		/// The deposit reserved from the caller of [`Call::rebag`] for the duration of the call.
+		///
+		/// It is returned if the call moves an id into another bag, and otherwise slashed into the
+		/// reward pot. Set to zero to disable.
+		#[pallet::constant]
+		type RebagDeposit: Get<BalanceOf<Self, I>>;
+
+		/// The ids that belong in the list, e.g. the voters of staking.
+		///
+		/// Ids of the list that are not contained anymore, e.g. because their removal was missed,
+		/// can be pruned by anyone with [`Call::prune_dangling_nodes`].
+		type Members: Contains<Self::AccountId>;
+
+		/// The reward paid from the pot to the caller of [`Call::prune_dangling_nodes`] for each id
+		/// it prunes.
+		///
+		/// Set to zero to disable rewards.
+		#[pallet::constant]
+		type PruneReward: Get<BalanceOf<Self, I>>;
+	}
 
 	/// A single node, within some bag.
 	///
-	/// Nodes store links forward and back within their respective bags.
+	/// Nodes store links forward and back within their respective bags. The number of nodes is
+	/// counted in `CounterForListNodes`, along with every insertion and removal.
 	#[pallet::storage]
-	pub(crate) type ListNodes<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, list::Node<T>>;
+	pub(crate) type ListNodes<T: Config<I>, I: 'static = ()> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, list::Node<T, I>>;
 
 	/// A bag stored in storage.
 	///
 	/// Stores a `Bag` struct, which stores head and tail pointers to itself.
 	#[pallet::storage]
-	pub(crate) type ListBags<T: Config> = StorageMap<_, Twox64Concat, VoteWeight, list::Bag<T>>;
+	pub(crate) type ListBags<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::Score, list::Bag<T, I>>;
+
+	/// The number of ids in each bag, keyed by the bag's upper threshold.
+	///
+	/// Bags with no ids have no entry.
+	#[pallet::storage]
+	pub(crate) type CounterForBag<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::Score, u32, ValueQuery>;
+
+	/// The occupancy of every non-empty bag, sorted by upper threshold.
+	///
+	/// This mirrors [`CounterForBag`], along with the total score of each bag, in a single value
+	/// that can be read without iterating the list.
+	#[pallet::storage]
+	pub(crate) type BagHistogram<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<BagOccupancy<T::Score>, MaxHistogramBags<T, I>>, ValueQuery>;
+
+	/// The bag thresholds set through [`Call::set_bag_thresholds`].
+	///
+	/// If this is not set, `T::BagThresholds` is used.
+	#[pallet::storage]
+	pub(crate) type ListBagThresholds<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<T::Score, T::MaxBagThresholds>>;
+
+	/// The last id checked by the automatic rebagging in `on_idle`.
+	///
+	/// The next scan resumes from the id after this one in `ListNodes`. If this is not set, the
+	/// scan starts from the beginning of `ListNodes`.
+	#[pallet::storage]
+	pub(crate) type AutoRebagCursor<T: Config<I>, I: 'static = ()> = StorageValue<_, T::AccountId>;
+
+	/// The progress of the refresh of the highest bags, see [`Config::RefreshTopBags`]: the upper
+	/// threshold of the lowest bag to refresh, and the next id to check.
+	///
+	/// This is only set while a refresh is ongoing.
+	#[pallet::storage]
+	pub(crate) type TopBagsRefresh<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, (T::Score, T::AccountId)>;
+
+	/// The last id checked by [`Pallet::sanity_check_bounded`].
+	///
+	/// The next check resumes from the id after this one in `ListNodes`. If this is not set, the
+	/// check starts from the beginning of `ListNodes`.
+	#[pallet::storage]
+	pub(crate) type SanityCheckCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::AccountId>;
+
+	/// The last id checked by [`Call::migrate_chunk`].
+	///
+	/// The next chunk resumes from the id after this one in `ListNodes`. If this is not set, the
+	/// next chunk starts from the beginning of `ListNodes`.
+	#[pallet::storage]
+	pub(crate) type ThresholdMigrationCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::AccountId>;
+
+	/// The thresholds that the ids are being migrated from, since [`Call::set_bag_thresholds`].
+	///
+	/// While this is set, `on_idle` migrates the ids to the current thresholds a chunk at a time,
+	/// resuming from [`ThresholdMigrationCursor`]. It is removed once all of them are migrated.
+	#[pallet::storage]
+	pub(crate) type PendingThresholdMigration<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<T::Score, T::MaxBagThresholds>>;
+
+	/// The last id checked by [`Call::prune_dangling_nodes`].
+	///
+	/// The next call resumes from the id after this one in `ListNodes`. If this is not set, the
+	/// next call starts from the beginning of `ListNodes`.
+	#[pallet::storage]
+	pub(crate) type PruneCursor<T: Config<I>, I: 'static = ()> = StorageValue<_, T::AccountId>;
+
+	/// The block in which the last rewarded rebag of each id happened.
+	///
+	/// Entries are removed along with the ids from the list.
+	#[pallet::storage]
+	pub(crate) type LastRewardedRebag<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::BlockNumber>;
+
+	/// The block in which each id was last moved by a rebag call, if `T::RebagCooldown` is not
+	/// zero.
+	///
+	/// Entries are removed along with the ids from the list.
+	#[pallet::storage]
+	pub(crate) type LastRebag<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::BlockNumber>;
+
+	/// The block in which each id was last placed into a bag, i.e. inserted into the list or
+	/// moved into another bag.
+	///
+	/// Entries are removed along with the ids from the list.
+	#[pallet::storage]
+	pub(crate) type LastPlaced<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::BlockNumber>;
+
+	/// The ids placed by [`Call::force_set_bag`], with the bag their score belonged in then.
+	///
+	/// Such an id is not misplaced for as long as its score still belongs in that bag, so that no
+	/// rebag undoes the placement. Entries are removed once the id is placed into a bag in any
+	/// other way, and along with the ids from the list.
+	#[pallet::storage]
+	pub(crate) type ForcedBags<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::Score>;
+
+	/// The owners of the locks held on the list, see [`SortedListProvider::lock`].
+	///
+	/// While any lock is held, no id is moved within the list or into another bag, and no node is
+	/// removed; ids can still be inserted, and removed as [`PendingRemovals`].
+	#[pallet::storage]
+	pub(crate) type Locks<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<ListLock, ConstU32<{ ListLock::COUNT }>>, ValueQuery>;
+
+	/// The ids whose score was updated while the list was locked, to be rebagged by `on_idle`
+	/// first once it is unlocked.
+	///
+	/// Entries are removed along with the ids from the list.
+	#[pallet::storage]
+	pub(crate) type PendingRebags<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, ()>;
+
+	/// The ids removed while the list was locked, whose nodes are only removed once the last lock
+	/// is released.
+	///
+	/// Until then, such an id is no longer part of the list to the outside, but its node is kept so
+	/// that an iteration can still resume from it.
+	#[pallet::storage]
+	pub(crate) type PendingRemovals<T: Config<I>, I: 'static = ()> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, ()>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
+		/// The bag thresholds to use instead of `T::BagThresholds`, if any.
+		///
+		/// They are stored as if set through [`Call::set_bag_thresholds`], and must not be more
+		/// than `T::MaxBagThresholds`. The ids already inserted at genesis, e.g. by staking, are
+		/// moved into their bag among them.
+		pub thresholds: Option<GenesisThresholds>,
+		pub phantom: PhantomData<(T, I)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config<I>, I: 'static> Default for GenesisConfig<T, I> {
+		fn default() -> Self {
+			Self { thresholds: None, phantom: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config<I>, I: 'static> GenesisBuild<T, I> for GenesisConfig<T, I>
+	where
+		T::Score: UniqueSaturatedFrom<u128>,
+	{
+		fn build(&self) {
+			if let Some(GenesisThresholds { existential_deposit, total_issuance, count }) =
+				self.thresholds.clone()
+			{
+				let (thresholds, _) = make_bags::make_bags::<u128, U128CurrencyToVote>(
+					existential_deposit,
+					total_issuance,
+					count as usize,
+				);
+				let thresholds = thresholds
+					.into_iter()
+					.map(|threshold| T::Score::unique_saturated_from(threshold as u128))
+					.collect::<Vec<_>>();
+				let thresholds = BoundedVec::<_, T::MaxBagThresholds>::try_from(thresholds)
+					.expect("more genesis thresholds than `T::MaxBagThresholds`");
+
+				let old = list::thresholds::<T, I>();
+				ListBagThresholds::<T, I>::put(thresholds);
+				// other pallets, e.g. staking, may have inserted ids at their genesis already.
+				List::<T, I>::migrate(&old);
+			}
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
-	pub enum Event<T: Config> {
-		/// Moved an account from one bag to another. \[who, from, to\].
-		Rebagged(T::AccountId, VoteWeight, VoteWeight),
+	pub enum Event<T: Config<I>, I: 'static = ()> {
+		/// Moved an account from one bag to another, along with the zero-based indices of these
+		/// bags, from the lowest. \[who, from, to, from_index, to_index\].
+		Rebagged(T::AccountId, T::Score, T::Score, u32, u32),
+		/// The bag thresholds have been updated, and the ids of the bags they change are to be
+		/// migrated in `on_idle`. \[affected\]
+		BagThresholdsUpdated(u32),
+		/// An account has been inserted into the list. \[who, bag\]
+		Inserted(T::AccountId, T::Score),
+		/// An account has been removed from the list. \[who\]
+		Removed(T::AccountId),
+		/// The score of an account in the list has been updated. \[who, new_score\]
+		ScoreUpdated(T::AccountId, T::Score),
+		/// An account belonging in a full bag has been put into a lower bag instead.
+		/// \[who, full_bag, bag\]
+		Spilled(T::AccountId, T::Score, T::Score),
+		/// A rebagger has been paid for moving an account into another bag. \[rebagger, reward\]
+		RebagRewarded(T::AccountId, BalanceOf<T, I>),
+		/// The deposit of a rebag that did not move anything has been slashed.
+		/// \[rebagger, amount\]
+		RebagDepositSlashed(T::AccountId, BalanceOf<T, I>),
+		/// A chunk of the ids has been migrated to the current thresholds. \[checked, moved\]
+		ThresholdMigrationProgressed(u32, u32),
+		/// All the ids have been migrated to the current thresholds.
+		ThresholdMigrationCompleted,
+		/// An account has been forced into a bag by root, regardless of its score.
+		/// \[who, from, to\]
+		BagForced(T::AccountId, T::Score, T::Score),
+		/// The broken links of an account have been repaired. \[who, bag\]
+		LinksHealed(T::AccountId, T::Score),
+		/// Ids that are not members anymore have been pruned from the list.
+		/// \[pruner, pruned, reward\]
+		DanglingNodesPruned(T::AccountId, u32, BalanceOf<T, I>),
+		/// An account has moved below [`Config::MinScoreBag`], and is not iterated anymore.
+		/// \[who\]
+		Excluded(T::AccountId),
+		/// An account has moved back above [`Config::MinScoreBag`], and is iterated again.
+		/// \[who\]
+		Included(T::AccountId),
+	}
+
+	#[pallet::error]
+	#[cfg_attr(test, derive(PartialEq))]
+	pub enum Error<T, I = ()> {
+		/// Attempted to place node in front of a node in another bag.
+		NotInSameBag,
+		/// Id not found in list.
+		NotInList,
+		/// An Id does not have a greater score than another Id.
+		NotHeavier,
+		/// The given thresholds are not strictly increasing.
+		ThresholdsInvalid,
+		/// An Id is already in the bag that matches its score.
+		NotMisplaced,
+		/// Id is already in the list.
+		AlreadyInList,
+		/// The bag an Id belongs in is full, and it could not be put elsewhere.
+		BagFull,
+		/// The list is locked, e.g. while an election snapshot is being taken, and cannot be
+		/// reordered.
+		Locked,
+		/// An Id was rebagged too recently to be rebagged again, see `Config::RebagCooldown`.
+		RebagTooSoon,
+		/// The caller of a rebag cannot afford `Config::RebagDeposit`.
+		InsufficientDeposit,
+		/// The given score is not the upper threshold of any bag.
+		NotAThreshold,
+		/// The links of an Id agree with those of its neighbours and of its bag.
+		NotBroken,
+		/// The links of an Id, or of its neighbours, could not be repaired.
+		StillBroken,
+		/// The storage of the list is inconsistent with an Id, e.g. its bag links to it while it
+		/// has no node.
+		Inconsistent,
+		/// The ids are still being migrated to the thresholds last set.
+		ThresholdMigrationOngoing,
+	}
+
+	impl<T, I> From<ListError> for Error<T, I> {
+		fn from(error: ListError) -> Self {
+			match error {
+				ListError::Duplicate => Error::AlreadyInList,
+				ListError::NodeNotFound => Error::NotInList,
+				ListError::BagFull => Error::BagFull,
+				ListError::Inconsistent => Error::Inconsistent,
+			}
+		}
 	}
 
 	#[pallet::call]
-	impl<T: Config> Pallet<T> {
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		/// Declare that some `dislocated` account has, through rewards or penalties, sufficiently
-		/// changed its weight that it should properly fall into a different bag than its current
+		/// changed its score that it should properly fall into a different bag than its current
 		/// one.
 		///
 		/// Anyone can call this function about any potentially dislocated account.
 		///
-		/// Will never return an error; if `dislocated` does not exist or doesn't need a rebag, then
-		/// it is a noop and fees are still collected from `origin`.
+		/// Fails with [`Error::NotInList`] if `dislocated` does not exist. If `dislocated` doesn't
+		/// need a rebag, then it is a noop and fees are still collected from `origin`. If
+		/// `dislocated` is moved into another bag, the call is free, and `origin` is paid
+		/// `T::RebagReward`, unless `dislocated` is in its reward cooldown. `T::RebagDeposit` is
+		/// reserved from `origin` during the call, and slashed if nothing is moved.
+		///
+		/// # <weight>
+		/// The worst case of moving a node is charged upfront, and the difference with what was
+		/// actually done is refunded: a node already in the right bag only costs
+		/// `WeightInfo::rebag_noop`, and a terminal node of its bag `WeightInfo::rebag_terminal`.
+		/// The deposit and the reward are charged on top, see [`Pallet::rebag_bookkeeping_weight`].
+		/// # </weight>
+		#[pallet::weight(
+			T::WeightInfo::rebag_non_terminal()
+				.max(T::WeightInfo::rebag_terminal())
+				.saturating_add(Pallet::<T, I>::rebag_bookkeeping_weight(true))
+		)]
+		pub fn rebag(origin: OriginFor<T>, dislocated: T::AccountId) -> DispatchResultWithPostInfo {
+			let rebagger = ensure_signed(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+			Self::ensure_rebag_cooled_down(&dislocated)?;
+			let node = list::Node::<T, I>::get(&dislocated).ok_or(Error::<T, I>::NotInList)?;
+			let current_score = T::ScoreProvider::score(&dislocated);
+			let move_weight = Self::rebag_weight(&node, current_score);
+			let deposit = T::RebagDeposit::get();
+			T::Currency::reserve(&rebagger, deposit)
+				.map_err(|_| Error::<T, I>::InsufficientDeposit)?;
+			match Pallet::<T, I>::do_rebag(&dislocated, current_score) {
+				Some(_) => {
+					T::Currency::unreserve(&rebagger, deposit);
+					Self::note_rebag(&dislocated);
+					Self::reward_rebagger(&rebagger, &dislocated);
+					let actual_weight =
+						move_weight.saturating_add(Self::rebag_bookkeeping_weight(true));
+					Ok((Some(actual_weight), Pays::No).into())
+				},
+				None => {
+					Self::slash_rebag_deposit(&rebagger, deposit);
+					let actual_weight =
+						move_weight.saturating_add(Self::rebag_bookkeeping_weight(false));
+					Ok((Some(actual_weight), Pays::Yes).into())
+				},
+			}
+		}
+
+		/// Same as [`Call::rebag`], but submitted as an unsigned transaction by the offchain
+		/// worker.
+		///
+		/// The dispatch origin for this call must be _None_. The transaction is only valid if
+		/// `dislocated` is in the list, and in the wrong bag.
 		#[pallet::weight(T::WeightInfo::rebag_non_terminal().max(T::WeightInfo::rebag_terminal()))]
-		pub fn rebag(origin: OriginFor<T>, dislocated: T::AccountId) -> DispatchResult {
+		pub fn rebag_unsigned(
+			origin: OriginFor<T>,
+			dislocated: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+			Self::ensure_rebag_cooled_down(&dislocated)?;
+			let current_score = T::ScoreProvider::score(&dislocated);
+			let actual_weight = list::Node::<T, I>::get(&dislocated)
+				.map(|node| Self::rebag_weight(&node, current_score));
+			if Pallet::<T, I>::do_rebag(&dislocated, current_score).is_some() {
+				Self::note_rebag(&dislocated);
+			}
+			Ok(actual_weight.into())
+		}
+
+		/// Move the caller's Id directly in front of `lighter`.
+		///
+		/// The dispatch origin for this call must be _Signed_ and can only be called by the Id of
+		/// the account going in front of `lighter`.
+		///
+		/// Only works if
+		/// - both nodes are within the same bag,
+		/// - and `origin` was placed with a greater `Score` than `lighter`, i.e. the scores the
+		///   nodes were last placed into a bag with are compared, not their current ones.
+		#[pallet::weight(T::WeightInfo::put_in_front_of())]
+		pub fn put_in_front_of(origin: OriginFor<T>, lighter: T::AccountId) -> DispatchResult {
+			let heavier = ensure_signed(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+			List::<T, I>::put_in_front_of(&lighter, &heavier).map_err(Into::into)
+		}
+
+		/// Set the thresholds separating the bags, overriding `T::BagThresholds`, and start the
+		/// migration of all ids affected by the change into their new bags.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// `new` must be strictly increasing, i.e. it must not contain duplicates, and the ids
+		/// must be done migrating to the thresholds set previously, if any. Emits
+		/// `BagThresholdsUpdated`.
+		///
+		/// The ids are then migrated in `on_idle`, as many per block as the remaining weight
+		/// allows, until `ThresholdMigrationCompleted` is emitted; `ThresholdMigrationProgressed`
+		/// is emitted by every block that migrates some of them. Until then, ids in the bags of
+		/// removed thresholds are not iterated.
+		///
+		/// # <weight>
+		/// Reads the counter of every bag affected by the change, at most one per old and new
+		/// threshold.
+		/// # </weight>
+		#[pallet::weight((
+			T::WeightInfo::set_bag_thresholds(
+				T::MaxBagThresholds::get().saturating_add(new.len() as u32)
+			),
+			DispatchClass::Operational,
+		))]
+		pub fn set_bag_thresholds(
+			origin: OriginFor<T>,
+			new: BoundedVec<T::Score, T::MaxBagThresholds>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+			ensure!(new.windows(2).all(|w| w[1] > w[0]), Error::<T, I>::ThresholdsInvalid);
+			ensure!(
+				!PendingThresholdMigration::<T, I>::exists(),
+				Error::<T, I>::ThresholdMigrationOngoing
+			);
+
+			let old = list::thresholds::<T, I>();
+			ListBagThresholds::<T, I>::put(new);
+			let affected_bags = List::<T, I>::bags_affected_by(&old);
+			let affected =
+				affected_bags.iter().map(|bag| CounterForBag::<T, I>::get(bag)).sum::<u32>();
+			if affected > 0 {
+				// `old` is either `T::BagThresholds`, bounded by the integrity test, or was set by
+				// this call.
+				let old = BoundedVec::<_, T::MaxBagThresholds>::try_from(old)
+					.map_err(|_| Error::<T, I>::ThresholdsInvalid)?;
+				PendingThresholdMigration::<T, I>::put(old);
+				ThresholdMigrationCursor::<T, I>::kill();
+			}
+
+			Self::deposit_event(Event::<T, I>::BagThresholdsUpdated(affected));
+			Ok(Some(T::WeightInfo::set_bag_thresholds(affected_bags.len() as u32)).into())
+		}
+
+		/// Move `who` into the bag of the upper threshold `bag_threshold`, regardless of its score.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// This is meant to repair the damage of bugs, or to settle disputed placements: the bag is
+		/// not checked against the score of `who`, nor against `T::MaxBagSize`. Emits `BagForced`,
+		/// unless `who` already is in that bag. `who` is then kept in that bag, by rebags of any
+		/// kind, until its score belongs in another bag than it does now.
+		#[pallet::weight(T::WeightInfo::force_set_bag())]
+		pub fn force_set_bag(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			bag_threshold: T::Score,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+
+			if let Some(from) = List::<T, I>::force_set_bag(&who, bag_threshold)? {
+				Self::deposit_event(Event::<T, I>::BagForced(who.clone(), from, bag_threshold));
+				Self::maybe_deposit_crossing(&who, from, bag_threshold);
+			}
+			Ok(())
+		}
+
+		/// Repair the links of `who`, if they disagree with those of its neighbours or its bag.
+		///
+		/// Anyone can call this function about any account whose links are broken, e.g. by a
+		/// storage corruption. `who` is unlinked from its neighbours, which are searched for among
+		/// at most `max_walk` nodes of its bag if they do not point back at it, and reinserted into
+		/// the bag of its current score.
+		///
+		/// The call is free if `who` is repaired. It fails with `NotBroken` if the links of `who`
+		/// are sound, and with `StillBroken`, leaving the storage untouched, if `who` or its former
+		/// neighbours are not sound afterwards. Emits `LinksHealed`.
+		///
+		/// # <weight>
+		/// On top of a rebag, the walk reads at most `2 * max_walk` nodes.
+		/// # </weight>
+		#[pallet::weight(T::WeightInfo::heal_broken_links(*max_walk))]
+		#[transactional]
+		pub fn heal_broken_links(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			max_walk: u32,
+		) -> DispatchResultWithPostInfo {
 			ensure_signed(origin)?;
-			let current_weight = T::VoteWeightProvider::vote_weight(&dislocated);
-			let _ = Pallet::<T>::do_rebag(&dislocated, current_weight);
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+
+			let bag = List::<T, I>::heal(&who, max_walk)?;
+			Self::deposit_event(Event::<T, I>::LinksHealed(who, bag));
+			Ok(Pays::No.into())
+		}
+
+		/// Migrate up to `limit` ids from the bag thresholds `old_thresholds` to the thresholds
+		/// currently in use, resuming from where the previous call stopped.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// This is the chunked equivalent of [`migrations::MigrateThresholds`], for lists too large
+		/// to be migrated in a single block. It should be called repeatedly once the thresholds
+		/// changed, until `ThresholdMigrationCompleted` is emitted; `ThresholdMigrationProgressed`
+		/// is emitted by every call. Until then, ids in the bags of removed thresholds are not
+		/// iterated. The migration started by [`Call::set_bag_thresholds`] can be sped up this
+		/// way, with the same `old_thresholds`.
+		#[pallet::weight((T::WeightInfo::migrate_chunk(*limit), DispatchClass::Operational))]
+		pub fn migrate_chunk(
+			origin: OriginFor<T>,
+			old_thresholds: BoundedVec<T::Score, T::MaxBagThresholds>,
+			limit: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+
+			let _ = Self::do_migrate_chunk(&old_thresholds, limit);
 			Ok(())
 		}
+
+		/// Scan up to `limit` ids of the list, resuming from where the previous call stopped, and
+		/// remove those that are not `T::Members` anymore.
+		///
+		/// Anyone can call this function. The caller is paid `T::PruneReward` from the reward pot
+		/// for each id pruned, and the call is free if any id is pruned. Emits `Removed` for each
+		/// id pruned, and then `DanglingNodesPruned`.
+		///
+		/// # <weight>
+		/// Each id checked may be removed.
+		/// # </weight>
+		#[pallet::weight(T::WeightInfo::prune_dangling_nodes(*limit))]
+		pub fn prune_dangling_nodes(
+			origin: OriginFor<T>,
+			limit: u32,
+		) -> DispatchResultWithPostInfo {
+			let pruner = ensure_signed(origin)?;
+			ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+
+			let dangling = List::<T, I>::scan_dangling(limit);
+			for id in &dangling {
+				<Self as SortedListProvider<T::AccountId>>::on_remove(id);
+			}
+
+			let pruned = dangling.len() as u32;
+			let reward = Self::reward_pruner(&pruner, pruned);
+			Self::deposit_event(Event::<T, I>::DanglingNodesPruned(pruner, pruned, reward));
+			Ok(if pruned > 0 { Pays::No } else { Pays::Yes }.into())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config<I>, I: 'static> ValidateUnsigned for Pallet<T, I> {
+		type Call = Call<T, I>;
+		fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::rebag_unsigned { dislocated } = call {
+				// Discard rebags not coming from the local OCW.
+				match source {
+					TransactionSource::Local | TransactionSource::InBlock => { /* allowed */ },
+					_ => return InvalidTransaction::Call.into(),
+				}
+
+				Self::rebag_pre_dispatch_checks(dislocated).map_err(|err| {
+					log!(debug, "unsigned rebag validation failed due to {:?}", err);
+					InvalidTransaction::Stale
+				})?;
+
+				ValidTransaction::with_tag_prefix("BagsListOffchainRebag")
+					.priority(T::UnsignedPriority::get())
+					// Used to deduplicate rebags of the same id.
+					.and_provides(dislocated)
+					// Transaction should stay in the pool until the next scan.
+					.longevity(T::OffchainRepeat::get().saturated_into::<u64>())
+					// We don't propagate this. This can never be validated at a remote node.
+					.propagate(false)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+
+		fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+			if let Call::rebag_unsigned { dislocated } = call {
+				Self::rebag_pre_dispatch_checks(dislocated)
+					.map_err(|_| InvalidTransaction::Stale.into())
+			} else {
+				Err(InvalidTransaction::Call.into())
+			}
+		}
 	}
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			// until the ids are migrated, those in the bags of removed thresholds are not iterated,
+			// so this goes first.
+			let migrate_weight = Self::do_migrate_thresholds(remaining_weight);
+			let remaining_weight = remaining_weight.saturating_sub(migrate_weight);
+			let (refreshed, refresh_weight) = Self::do_refresh_top_bags(remaining_weight);
+			migrate_weight
+				.saturating_add(refresh_weight)
+				.saturating_add(Self::do_auto_rebag(
+					remaining_weight.saturating_sub(refresh_weight),
+					T::MaxAutoRebagPerBlock::get().saturating_sub(refreshed),
+				))
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			// the migrations of the runtime run before this, so anything other than the current
+			// version means that one of them is missing, and that the nodes may not even decode.
+			let on_chain = Self::on_chain_storage_version();
+			if on_chain != STORAGE_VERSION {
+				log!(
+					error,
+					"storage version is {:?} instead of {:?}, a migration is missing",
+					on_chain,
+					STORAGE_VERSION,
+				);
+				// such an upgrade must not make it past try-runtime.
+				#[cfg(feature = "try-runtime")]
+				panic!(
+					"storage version is {:?} instead of {:?}, a migration is missing",
+					on_chain, STORAGE_VERSION,
+				);
+			}
+			T::DbWeight::get().reads(1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			ensure!(
+				Self::on_chain_storage_version() == STORAGE_VERSION,
+				"storage version does not match the current one"
+			);
+			Self::try_state(SanityCheckLevel::Full)
+		}
+
+		fn offchain_worker(now: BlockNumberFor<T>) {
+			let repeat = T::OffchainRepeat::get();
+			if repeat.is_zero() || !(now % repeat).is_zero() {
+				return
+			}
+
+			let _ = Self::offchain_rebag();
+		}
+
 		fn integrity_test() {
 			// ensure they are strictly increasing, this also implies that duplicates are detected.
 			assert!(
 				T::BagThresholds::get().windows(2).all(|window| window[1] > window[0]),
 				"thresholds must strictly increase, and have no duplicates",
 			);
+			// ensure they can also be set with `set_bag_thresholds`, e.g. by a migration.
+			assert!(
+				T::BagThresholds::get().len() <= T::MaxBagThresholds::get() as usize,
+				"thresholds must not be more than `MaxBagThresholds`",
+			);
 		}
 	}
 }
 
-impl<T: Config> Pallet<T> {
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// The number of ids in the bag with the upper threshold `bag_upper`.
+	pub fn count_in_bag(bag_upper: T::Score) -> u32 {
+		CounterForBag::<T, I>::get(bag_upper)
+	}
+
+	/// The upper threshold of the bag `who` is currently in, and of the bag it belongs in according
+	/// to its current score, or `None` if `who` is not in the list.
+	///
+	/// If the two differ, `who` can be moved into the right bag with [`Call::rebag`].
+	pub fn bag_for(who: &T::AccountId) -> Option<(T::Score, T::Score)> {
+		list::Node::<T, I>::get(who).map(|node| {
+			(node.bag_upper(), list::notional_bag_for::<T, I>(T::ScoreProvider::score(who)))
+		})
+	}
+
+	/// The upper threshold of the bag that a [`Call::rebag`] of `who` would move it into right
+	/// now, or `None` if the call would not move it.
+	///
+	/// This takes the lock of the list, the rebag cooldown of `who` and
+	/// [`Config::BagOverflowPolicy`] into account, so that the callers need not replicate any of
+	/// the logic of the thresholds. It is `None` as well if `who` is not in the list.
+	pub fn rebag_needed(who: &T::AccountId) -> Option<T::Score> {
+		if Self::is_locked() || Self::ensure_rebag_cooled_down(who).is_err() {
+			return None
+		}
+		list::Node::<T, I>::get(who).and_then(|node| node.destination(T::ScoreProvider::score(who)))
+	}
+
+	/// Whether `who` is likely in the wrong bag, judging by the score cached in its node when it
+	/// was last placed, and `false` if it is not in the list.
+	///
+	/// The live score of `who` is only read if the cached one is within
+	/// [`Config::DisplacementMargin`] of a threshold of its bag; an id whose cached score is well
+	/// within its bag is assumed not to have drifted out of it. This makes for a cheap filter of
+	/// the ids worth rebagging, but not an exact one: use [`Self::bag_for`] for that.
+	pub fn is_displaced(who: &T::AccountId) -> bool
+	where
+		T::Score: UniqueSaturatedInto<u128>,
+	{
+		let node = match list::Node::<T, I>::get(who) {
+			Some(node) => node,
+			None => return false,
+		};
+		// e.g. spilled into a lower bag, or left in a bag that is no threshold anymore.
+		if node.is_misplaced(node.score()) {
+			return true
+		}
+
+		let thresholds = Self::thresholds();
+		let upper: u128 = node.bag_upper().unique_saturated_into();
+		let lower: u128 = (bag_index_in(&thresholds, node.bag_upper()) as usize)
+			.checked_sub(1)
+			.and_then(|index| thresholds.get(index).copied())
+			.unwrap_or_else(T::Score::min_value)
+			.unique_saturated_into();
+		let cached: u128 = node.score().unique_saturated_into();
+		let margin = T::DisplacementMargin::get() * upper.saturating_sub(lower);
+
+		let near_boundary =
+			cached.saturating_sub(lower) <= margin || upper.saturating_sub(cached) <= margin;
+		near_boundary && node.is_misplaced(T::ScoreProvider::score(who))
+	}
+
+	/// The block in which `who` was last placed into a bag, i.e. inserted into the list or moved
+	/// into another bag, or `None` if it is not in the list.
+	///
+	/// Ids that were not placed since [`LastPlaced`] was introduced have no such block either.
+	pub fn last_placed(who: &T::AccountId) -> Option<T::BlockNumber> {
+		LastPlaced::<T, I>::get(who)
+	}
+
+	/// Iterate over the ids of the list from the lowest score to the highest, i.e. in the exact
+	/// reverse of [`SortedListProvider::iter`].
+	///
+	/// This is meant for walking the list from the bottom, e.g. to find the ids with the lowest
+	/// scores; like any iteration of the list, it should be limited with `take`.
+	pub fn iter_rev() -> impl Iterator<Item = T::AccountId> {
+		List::<T, I>::iter_rev().map(|node| node.id().clone())
+	}
+
+	/// The ids right before and right after `who` in its bag, or `None` if `who` is not in the
+	/// list.
+	///
+	/// The id before `who` is `None` if `who` is the head of its bag, and the id after it is
+	/// `None` if `who` is the tail.
+	pub fn neighbours(who: &T::AccountId) -> Option<Neighbours<T::AccountId>> {
+		list::Node::<T, I>::get(who).map(|node| (node.prev_id().cloned(), node.next_id().cloned()))
+	}
+
+	/// Iterate over the ids of the single bag with the upper threshold `bag_upper`, in order.
+	///
+	/// Unlike [`SortedListProvider::iter`], this does not walk all the higher bags first.
+	pub fn iter_bag(bag_upper: T::Score) -> impl Iterator<Item = T::AccountId> {
+		List::<T, I>::iter_bag(bag_upper, None).map(|node| node.id().clone())
+	}
+
+	/// A page of at most `max` ids of the bag with the upper threshold `bag_upper`, starting with
+	/// its head, or right after `after` if given.
+	///
+	/// The next page starts after the last id of this one. The page is empty if there is no such
+	/// bag, or if `after` is not in it.
+	pub fn bag_page(
+		bag_upper: T::Score,
+		after: Option<T::AccountId>,
+		max: u32,
+	) -> Vec<T::AccountId> {
+		List::<T, I>::iter_bag(bag_upper, after.as_ref())
+			.take(max as usize)
+			.map(|node| node.id().clone())
+			.collect()
+	}
+
+	/// The upper threshold of the bag that an id with `score` belongs to, with the thresholds
+	/// currently in use.
+	///
+	/// This is the bag [`Call::rebag`] moves an id into, unless that bag is full. See
+	/// [`canonical_bag_in`] to bucket scores with other thresholds.
+	pub fn canonical_bag_for(score: T::Score) -> T::Score {
+		list::notional_bag_for::<T, I>(score)
+	}
+
+	/// The zero-based index of the bag of upper threshold `bag_upper`, from the lowest, with the
+	/// thresholds currently in use. See [`bag_index_in`].
+	pub fn bag_index(bag_upper: T::Score) -> u32 {
+		bag_index_in(&list::thresholds::<T, I>(), bag_upper)
+	}
+
+	/// The bag thresholds currently in use, i.e. those set by [`Call::set_bag_thresholds`], or
+	/// else `T::BagThresholds`.
+	pub fn thresholds() -> Vec<T::Score> {
+		list::thresholds::<T, I>()
+	}
+
+	/// The constant ratio between consecutive [`Self::thresholds`], as implied by the two highest
+	/// thresholds below `Score::max_value()`, or `None` if there are not two of them.
+	///
+	/// The highest thresholds are the ones least distorted by the rounding of a geometric series
+	/// (see [`Config::BagThresholds`]), and the implied final threshold is not part of it.
+	pub fn constant_ratio() -> Option<FixedU128>
+	where
+		T::Score: UniqueSaturatedInto<u128>,
+	{
+		let mut below_max =
+			Self::thresholds().into_iter().rev().filter(|t| *t != T::Score::max_value());
+		let highest = below_max.next()?.unique_saturated_into();
+		let second = below_max.next()?.unique_saturated_into();
+		FixedU128::checked_from_rational(highest, second)
+	}
+
+	/// The composition of the bag of each threshold, including the empty ones, from the lowest
+	/// threshold to the highest.
+	pub fn bag_stats() -> Vec<BagStats<T::AccountId, T::Score>> {
+		List::<T, I>::bag_stats()
+	}
+
+	/// The occupancy of every non-empty bag, from the lowest threshold to the highest.
+	///
+	/// Unlike [`Self::bag_stats`], this is a single storage read.
+	pub fn bag_histogram() -> Vec<BagOccupancy<T::Score>> {
+		BagHistogram::<T, I>::get().into_inner()
+	}
+
+	/// Whether the list is currently locked against reordering, see [`SortedListProvider::lock`].
+	pub fn is_locked() -> bool {
+		!Locks::<T, I>::get().is_empty()
+	}
+
+	/// Whether `id` was removed while the list was locked, and its node is still to be removed.
+	fn is_pending_removal(id: &T::AccountId) -> bool {
+		PendingRemovals::<T, I>::contains_key(id)
+	}
+
+	/// Take `id` back into the list if it is pending removal.
+	///
+	/// Its node stays where it is, and is rebagged with its current score once the list is
+	/// unlocked. Returns the bag of the node, if `id` was pending removal.
+	fn cancel_removal(id: &T::AccountId) -> Option<T::Score> {
+		PendingRemovals::<T, I>::take(id)?;
+		PendingRebags::<T, I>::insert(id, ());
+		list::Node::<T, I>::get(id).map(|node| node.bag_upper())
+	}
+
+	/// The weight of rebagging `node`, given its current score.
+	///
+	/// This is [`WeightInfo::rebag_noop`] if `node` is already in the right bag. Otherwise, it
+	/// depends on whether `node` is a terminal node of its bag, i.e. whether the bag itself needs
+	/// to be updated once `node` is removed from it.
+	pub(crate) fn rebag_weight(node: &list::Node<T, I>, current_score: T::Score) -> Weight {
+		if node.destination(current_score).is_none() {
+			T::WeightInfo::rebag_noop()
+		} else if node.is_terminal() {
+			T::WeightInfo::rebag_terminal()
+		} else {
+			T::WeightInfo::rebag_non_terminal()
+		}
+	}
+
+	/// The weight of what [`Call::rebag`] does around the move itself, which its benchmarks do not
+	/// cover.
+	///
+	/// This is the lock and cooldown checks and the reserve of `T::RebagDeposit`, then either, if
+	/// the node `moved`, the unreserve, the cooldown record and the reward from the pot, or
+	/// otherwise the slash of the deposit into the pot.
+	pub(crate) fn rebag_bookkeeping_weight(moved: bool) -> Weight {
+		let db = T::DbWeight::get();
+		// `Locks`, `LastRebag` and the account of the rebagger.
+		let checks = db.reads_writes(3, 1);
+		if moved {
+			// `LastRewardedRebag` and the pot; the unreserve, `LastRebag`, `LastRewardedRebag` and
+			// both sides of the transfer.
+			checks.saturating_add(db.reads_writes(2, 5))
+		} else {
+			// the pot; the slash and its resolution into the pot.
+			checks.saturating_add(db.reads_writes(1, 2))
+		}
+	}
+
+	/// Rebag each of `accounts` with its current score, as [`Self::do_rebag`] does.
+	///
+	/// The scores are all read with a single [`ScoreProvider::score_fn`], so this is cheaper than
+	/// repeated calls to [`Self::do_rebag`]. Returns the outcome of each rebag, in order.
+	pub fn do_rebag_many(accounts: &[T::AccountId]) -> Vec<Option<(T::Score, T::Score)>> {
+		let score_of = T::ScoreProvider::score_fn();
+		accounts
+			.iter()
+			.map(|account| Self::do_rebag(account, score_of(account)))
+			.collect()
+	}
+
 	/// Move an account from one bag to another, depositing an event on success.
 	///
 	/// If the account changed bags, returns `Some((from, to))`.
-	pub fn do_rebag(
-		account: &T::AccountId,
-		new_weight: VoteWeight,
-	) -> Option<(VoteWeight, VoteWeight)> {
+	pub fn do_rebag(account: &T::AccountId, new_score: T::Score) -> Option<(T::Score, T::Score)> {
+		// moves are left to `on_idle`, once the list is unlocked.
+		if Self::is_locked() {
+			if List::<T, I>::contains(account) {
+				PendingRebags::<T, I>::insert(account, ());
+			}
+			return None
+		}
 		// if no voter at that node, don't do anything.
 		// the caller just wasted the fee to call this.
-		let maybe_movement = list::Node::<T>::get(&account)
-			.and_then(|node| List::update_position_for(node, new_weight));
+		let maybe_movement = list::Node::<T, I>::get(&account)
+			.and_then(|node| List::update_position_for(node, new_score));
 		if let Some((from, to)) = maybe_movement {
-			Self::deposit_event(Event::<T>::Rebagged(account.clone(), from, to));
+			let thresholds = list::thresholds::<T, I>();
+			Self::deposit_event(Event::<T, I>::Rebagged(
+				account.clone(),
+				from,
+				to,
+				bag_index_in(&thresholds, from),
+				bag_index_in(&thresholds, to),
+			));
+			Self::maybe_deposit_spilled(account, new_score, to);
+			Self::maybe_deposit_crossing(account, from, to);
 		};
 		maybe_movement
 	}
 
+	/// Ensure that `dislocated` was not moved by a rebag call within `T::RebagCooldown`.
+	pub(crate) fn ensure_rebag_cooled_down(dislocated: &T::AccountId) -> Result<(), Error<T, I>> {
+		let cooldown = T::RebagCooldown::get();
+		if cooldown.is_zero() {
+			return Ok(())
+		}
+		match LastRebag::<T, I>::get(dislocated) {
+			Some(last)
+				if frame_system::Pallet::<T>::block_number() < last.saturating_add(cooldown) =>
+				Err(Error::<T, I>::RebagTooSoon),
+			_ => Ok(()),
+		}
+	}
+
+	/// Record that `dislocated` was just moved by a rebag call, for `T::RebagCooldown`.
+	fn note_rebag(dislocated: &T::AccountId) {
+		if !T::RebagCooldown::get().is_zero() {
+			LastRebag::<T, I>::insert(dislocated, frame_system::Pallet::<T>::block_number());
+		}
+	}
+
+	/// The account of the pot from which rebaggers are rewarded.
+	pub fn reward_pot() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+
+	/// Pay `T::RebagReward` from the reward pot to `rebagger`, for having moved `dislocated`,
+	/// unless a rebag of `dislocated` was already rewarded within `T::RebagRewardCooldown`.
+	fn reward_rebagger(rebagger: &T::AccountId, dislocated: &T::AccountId) {
+		let reward = T::RebagReward::get();
+		if reward.is_zero() {
+			return
+		}
+
+		let now = frame_system::Pallet::<T>::block_number();
+		if let Some(last) = LastRewardedRebag::<T, I>::get(dislocated) {
+			if now < last.saturating_add(T::RebagRewardCooldown::get()) {
+				return
+			}
+		}
+
+		match T::Currency::transfer(
+			&Self::reward_pot(),
+			rebagger,
+			reward,
+			ExistenceRequirement::KeepAlive,
+		) {
+			Ok(()) => {
+				LastRewardedRebag::<T, I>::insert(dislocated, now);
+				Self::deposit_event(Event::<T, I>::RebagRewarded(rebagger.clone(), reward));
+			},
+			Err(err) => log!(debug, "failed to reward rebagger {:?}: {:?}", rebagger, err),
+		}
+	}
+
+	/// Pay `T::PruneReward` from the reward pot to `pruner` for each of the `pruned` ids, and
+	/// return the amount paid.
+	fn reward_pruner(pruner: &T::AccountId, pruned: u32) -> BalanceOf<T, I> {
+		let reward = T::PruneReward::get().saturating_mul(pruned.into());
+		if reward.is_zero() {
+			return reward
+		}
+
+		match T::Currency::transfer(
+			&Self::reward_pot(),
+			pruner,
+			reward,
+			ExistenceRequirement::KeepAlive,
+		) {
+			Ok(()) => reward,
+			Err(err) => {
+				log!(debug, "failed to reward pruner {:?}: {:?}", pruner, err);
+				Zero::zero()
+			},
+		}
+	}
+
+	/// Slash the `deposit` reserved from `rebagger` into the reward pot.
+	fn slash_rebag_deposit(rebagger: &T::AccountId, deposit: BalanceOf<T, I>) {
+		if deposit.is_zero() {
+			return
+		}
+		let (imbalance, _) = T::Currency::slash_reserved(rebagger, deposit);
+		Self::deposit_event(Event::<T, I>::RebagDepositSlashed(rebagger.clone(), imbalance.peek()));
+		T::Currency::resolve_creating(&Self::reward_pot(), imbalance);
+	}
+
+	/// Whether the ids of the bag `bag_upper` are iterated, see [`Config::MinScoreBag`].
+	pub(crate) fn is_iterated(bag_upper: T::Score) -> bool {
+		bag_upper >= T::MinScoreBag::get()
+	}
+
+	/// Deposit [`Event::Excluded`] or [`Event::Included`] if `who`, which just moved from the bag
+	/// `from` into the bag `to`, crossed [`Config::MinScoreBag`].
+	fn maybe_deposit_crossing(who: &T::AccountId, from: T::Score, to: T::Score) {
+		match (Self::is_iterated(from), Self::is_iterated(to)) {
+			(true, false) => Self::deposit_event(Event::<T, I>::Excluded(who.clone())),
+			(false, true) => Self::deposit_event(Event::<T, I>::Included(who.clone())),
+			_ => (),
+		}
+	}
+
+	/// Deposit [`Event::Spilled`] if `who`, with `score`, was put into `bag` because the bag it
+	/// belongs in is full.
+	fn maybe_deposit_spilled(who: &T::AccountId, score: T::Score, bag: T::Score) {
+		let notional = list::notional_bag_for::<T, I>(score);
+		if bag != notional {
+			Self::deposit_event(Event::<T, I>::Spilled(who.clone(), notional, bag));
+		}
+	}
+
+	/// Check, and rebag if needed, as many ids as `remaining_weight` and `max_per_block` allow,
+	/// resuming from [`AutoRebagCursor`].
+	///
+	/// Returns the weight consumed.
+	pub(crate) fn do_auto_rebag(remaining_weight: Weight, max_per_block: u32) -> Weight {
+		// reading and writing the cursor.
+		let base_weight = T::DbWeight::get().reads_writes(1, 1);
+		let max_ids = Self::max_ids_within(remaining_weight, base_weight, max_per_block);
+		if max_ids == 0 {
+			return 0
+		}
+		if Self::is_locked() {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let checked = Self::auto_rebag(max_ids);
+		base_weight.saturating_add(T::WeightInfo::rebag_scan_per_node().saturating_mul(checked))
+	}
+
+	/// Migrate a chunk of at most `limit` ids from `old_thresholds`, see [`Call::migrate_chunk`],
+	/// and deposit the events of its progress.
+	///
+	/// The migration started by [`Call::set_bag_thresholds`], if any, is over once all the ids
+	/// are migrated. Returns the number of ids checked.
+	fn do_migrate_chunk(old_thresholds: &[T::Score], limit: u32) -> u32 {
+		let (checked, moved, completed) = List::<T, I>::migrate_chunk(old_thresholds, limit);
+		Self::deposit_event(Event::<T, I>::ThresholdMigrationProgressed(checked, moved));
+		if completed {
+			PendingThresholdMigration::<T, I>::kill();
+			Self::deposit_event(Event::<T, I>::ThresholdMigrationCompleted);
+		}
+		checked
+	}
+
+	/// Migrate as many ids as `remaining_weight` allows to the thresholds set by
+	/// [`Call::set_bag_thresholds`], if they are not all migrated yet.
+	///
+	/// Returns the weight consumed.
+	pub(crate) fn do_migrate_thresholds(remaining_weight: Weight) -> Weight {
+		// reading the pending migration.
+		let read_weight = T::DbWeight::get().reads(1);
+		if remaining_weight < read_weight {
+			return 0
+		}
+		let old_thresholds = match PendingThresholdMigration::<T, I>::get() {
+			Some(old_thresholds) => old_thresholds,
+			None => return read_weight,
+		};
+
+		// reading the pending migration and the lock, and migrating an empty chunk.
+		let base_weight =
+			T::DbWeight::get().reads(2).saturating_add(T::WeightInfo::migrate_chunk(0));
+		let per_id_weight =
+			T::WeightInfo::migrate_chunk(1).saturating_sub(T::WeightInfo::migrate_chunk(0));
+		let limit = remaining_weight
+			.checked_sub(base_weight)
+			.map(|weight| weight.checked_div(per_id_weight).unwrap_or(u64::MAX))
+			.map_or(0, |limit| limit.min(u32::MAX as u64) as u32);
+		if limit == 0 {
+			return read_weight
+		}
+		if Self::is_locked() {
+			return T::DbWeight::get().reads(2)
+		}
+
+		let checked = Self::do_migrate_chunk(&old_thresholds, limit);
+		T::DbWeight::get()
+			.reads(2)
+			.saturating_add(T::WeightInfo::migrate_chunk(checked))
+	}
+
+	/// The number of ids that can be checked with `remaining_weight`, on top of `base_weight`,
+	/// and up to `max_per_block`.
+	fn max_ids_within(remaining_weight: Weight, base_weight: Weight, max_per_block: u32) -> u64 {
+		if remaining_weight < base_weight {
+			return 0
+		}
+		remaining_weight
+			.saturating_sub(base_weight)
+			.checked_div(T::WeightInfo::rebag_scan_per_node())
+			.unwrap_or(u64::MAX)
+			.min(max_per_block as u64)
+	}
+
+	/// Start a refresh of the `T::RefreshTopBags` highest non-empty bags, from the head of the
+	/// list, replacing any refresh that is still ongoing.
+	///
+	/// Returns the number of bags to refresh, which is zero if the refresh is disabled or the list
+	/// is empty.
+	pub(crate) fn start_refresh() -> u32 {
+		let wanted = T::RefreshTopBags::get() as usize;
+		if wanted == 0 {
+			return 0
+		}
+		let head = match List::<T, I>::iter().next() {
+			Some(head) => head,
+			None => return 0,
+		};
+
+		let mut thresholds = list::thresholds::<T, I>();
+		if thresholds.last() != Some(&T::Score::max_value()) {
+			thresholds.push(T::Score::max_value());
+		}
+		let top = thresholds
+			.into_iter()
+			.rev()
+			.filter(|bag_upper| !CounterForBag::<T, I>::get(*bag_upper).is_zero())
+			.take(wanted)
+			.collect::<Vec<_>>();
+		let lowest = *top.last().expect("the list has a head, so one bag is not empty; qed");
+
+		TopBagsRefresh::<T, I>::put((lowest, head.id().clone()));
+		top.len() as u32
+	}
+
+	/// Check, and rebag if needed, as many ids of the ongoing refresh of the highest bags as
+	/// `remaining_weight` and `T::MaxAutoRebagPerBlock` allow.
+	///
+	/// Returns the number of ids checked, and the weight consumed.
+	pub(crate) fn do_refresh_top_bags(remaining_weight: Weight) -> (u32, Weight) {
+		let read = T::DbWeight::get().reads(1);
+		if T::RefreshTopBags::get().is_zero() || remaining_weight < read {
+			return (0, 0)
+		}
+		let (lowest, next) = match TopBagsRefresh::<T, I>::get() {
+			Some(refresh) => refresh,
+			None => return (0, read),
+		};
+		// reading the lock, and writing the progress.
+		let base_weight = T::DbWeight::get().reads_writes(2, 1);
+		let max_ids =
+			Self::max_ids_within(remaining_weight, base_weight, T::MaxAutoRebagPerBlock::get());
+		if max_ids == 0 || Self::is_locked() {
+			return (0, read)
+		}
+
+		let start = match list::Node::<T, I>::get(&next) {
+			Some(start) => start,
+			None => {
+				// the next id was removed meanwhile, give up on this refresh.
+				TopBagsRefresh::<T, I>::kill();
+				return (0, base_weight)
+			},
+		};
+		let mut nodes = sp_std::iter::once(start)
+			.chain(List::<T, I>::iter_from(&next).into_iter().flatten())
+			.take_while(|node| node.bag_upper() >= lowest);
+		let ids = nodes
+			.by_ref()
+			.take(max_ids as usize)
+			.map(|n| n.id().clone())
+			.collect::<Vec<_>>();
+		match nodes.next() {
+			Some(next) => TopBagsRefresh::<T, I>::put((lowest, next.id().clone())),
+			None => {
+				crate::log!(debug, "refreshed the bags down to {:?}", lowest);
+				TopBagsRefresh::<T, I>::kill()
+			},
+		}
+
+		Self::do_rebag_many(&ids);
+		let checked = ids.len() as u32;
+		let scan_weight = T::WeightInfo::rebag_scan_per_node().saturating_mul(checked as Weight);
+		(checked, base_weight.saturating_add(scan_weight))
+	}
+
+	/// Check, and rebag if needed, up to `max_ids` ids, starting with the [`PendingRebags`] and
+	/// then resuming the scan from [`AutoRebagCursor`].
+	///
+	/// Returns the number of ids checked. The list is assumed to be unlocked.
+	pub(crate) fn auto_rebag(max_ids: u64) -> u64 {
+		// the ids updated while the list was locked go first, and the scan resumes once they are
+		// all rebagged.
+		let mut ids = PendingRebags::<T, I>::drain()
+			.take(max_ids as usize)
+			.map(|(id, ())| id)
+			.collect::<Vec<_>>();
+		let pending = ids.len();
+
+		let wanted = (max_ids as usize).saturating_sub(pending);
+		if wanted > 0 {
+			let scan = match AutoRebagCursor::<T, I>::get() {
+				Some(last) => ListNodes::<T, I>::iter_from(ListNodes::<T, I>::hashed_key_for(last)),
+				None => ListNodes::<T, I>::iter(),
+			};
+			ids.extend(scan.map(|(id, _)| id).take(wanted));
+			match ids[pending..].last() {
+				Some(last) if ids.len() - pending == wanted => AutoRebagCursor::<T, I>::put(last),
+				// reached the end, start from the beginning next time.
+				_ => AutoRebagCursor::<T, I>::kill(),
+			}
+		}
+
+		Self::do_rebag_many(&ids);
+		crate::log!(
+			debug,
+			"automatically rebagged {} pending ids and checked {} ids for rebagging",
+			pending,
+			ids.len() - pending,
+		);
+		ids.len() as u64
+	}
+
+	/// Check the integrity of the list, as thoroughly as `level` demands.
+	///
+	/// This is meant to be used in try-runtime and tests, and is checked with
+	/// [`SanityCheckLevel::Full`] after every runtime upgrade through try-runtime. At most
+	/// [`Pallet::try_state_weight`] is consumed.
+	#[cfg(any(feature = "std", feature = "try-runtime", feature = "runtime-benchmarks"))]
+	pub fn try_state(level: SanityCheckLevel) -> Result<(), &'static str> {
+		List::<T, I>::sanity_check(level)
+	}
+
+	/// Check the links of at most `max_nodes` nodes of the list, resuming from where the previous
+	/// call stopped, so that repeated calls eventually cover the whole list.
+	///
+	/// Unlike [`Pallet::try_state`], this is bounded, and thus safe to call on a live chain, e.g.
+	/// from `on_idle` or an offchain worker. Each node costs at most four reads, on top of reading
+	/// and writing the cursor once.
+	///
+	/// Returns the number of nodes checked, or the first inconsistency found.
+	pub fn sanity_check_bounded(max_nodes: u32) -> Result<u32, &'static str> {
+		List::<T, I>::sanity_check_bounded(max_nodes).map_err(|err| {
+			log!(error, "bounded sanity check of the list failed: {}", err);
+			err
+		})
+	}
+
+	/// The weight of checking the whole list with [`Pallet::try_state`], at its current length.
+	pub fn try_state_weight() -> Weight {
+		T::WeightInfo::sanity_check(ListNodes::<T, I>::count())
+	}
+
 	/// Equivalent to `ListBags::get`, but public. Useful for tests in outside of this crate.
 	#[cfg(feature = "std")]
-	pub fn list_bags_get(weight: VoteWeight) -> Option<list::Bag<T>> {
-		ListBags::get(weight)
+	pub fn list_bags_get(score: T::Score) -> Option<list::Bag<T, I>> {
+		ListBags::<T, I>::get(score)
 	}
 }
 
-impl<T: Config> SortedListProvider<T::AccountId> for Pallet<T> {
-	type Error = Error;
+impl<T: Config<I>, I: 'static> SortedListProvider<T::AccountId> for Pallet<T, I> {
+	type Error = ListError;
+	type Score = T::Score;
 
 	fn iter() -> Box<dyn Iterator<Item = T::AccountId>> {
-		Box::new(List::<T>::iter().map(|n| n.id().clone()))
+		Box::new(
+			List::<T, I>::iter()
+				.take_while(|n| Self::is_iterated(n.bag_upper()))
+				.filter(|n| !Self::is_pending_removal(n.id()))
+				.map(|n| n.id().clone()),
+		)
+	}
+
+	fn iter_from(
+		start: &T::AccountId,
+	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
+		// `start` may be pending removal, its node is still there to resume from.
+		let iter = List::<T, I>::iter_from(start)?;
+		Ok(Box::new(
+			iter.take_while(|n| Self::is_iterated(n.bag_upper()))
+				.filter(|n| !Self::is_pending_removal(n.id()))
+				.map(|n| n.id().clone()),
+		))
+	}
+
+	fn iter_with_bag() -> Box<dyn Iterator<Item = (T::AccountId, T::Score)>> {
+		Box::new(
+			List::<T, I>::iter()
+				.take_while(|n| Self::is_iterated(n.bag_upper()))
+				.filter(|n| !Self::is_pending_removal(n.id()))
+				.map(|n| (n.id().clone(), n.bag_upper())),
+		)
+	}
+
+	fn count_in_bag(bag_upper: T::Score) -> u32 {
+		CounterForBag::<T, I>::get(bag_upper)
+	}
+
+	fn iter_with_budget(
+		start: Option<&T::AccountId>,
+		budget: Weight,
+	) -> Result<BudgetedIter<T::AccountId>, Self::Error> {
+		let mut taken = List::<T, I>::iter_with_budget(start, budget)?;
+		// the ids pending removal are read all the same, but not returned.
+		taken.ids.retain(|id| !Self::is_pending_removal(id));
+		Ok(taken)
+	}
+
+	fn iter_with_score() -> Box<dyn Iterator<Item = (T::AccountId, T::Score)>> {
+		// every id that is iterated is in the list, no need to check it again.
+		let score_of = T::ScoreProvider::score_fn();
+		Box::new(
+			List::<T, I>::iter()
+				.take_while(|n| Self::is_iterated(n.bag_upper()))
+				.filter(|n| !Self::is_pending_removal(n.id()))
+				.map(move |node| {
+					let id = node.id().clone();
+					let score = score_of(&id);
+					(id, score)
+				}),
+		)
 	}
 
 	fn count() -> u32 {
-		CounterForListNodes::<T>::get()
+		ListNodes::<T, I>::count().saturating_sub(PendingRemovals::<T, I>::count())
 	}
 
 	fn contains(id: &T::AccountId) -> bool {
-		List::<T>::contains(id)
+		List::<T, I>::contains(id) && !Self::is_pending_removal(id)
 	}
 
-	fn on_insert(id: T::AccountId, weight: VoteWeight) -> Result<(), Error> {
-		List::<T>::insert(id, weight)
+	fn get_score(id: &T::AccountId) -> Option<T::Score> {
+		if Self::is_pending_removal(id) {
+			return None
+		}
+		list::Node::<T, I>::get(id).map(|node| node.score())
 	}
 
-	fn on_update(id: &T::AccountId, new_weight: VoteWeight) {
-		Pallet::<T>::do_rebag(id, new_weight);
+	fn on_insert(id: T::AccountId, score: T::Score) -> Result<(), ListError> {
+		if let Some(bag) = Self::cancel_removal(&id) {
+			Self::deposit_event(Event::<T, I>::Inserted(id, bag));
+			return Ok(())
+		}
+		let bag = List::<T, I>::try_insert(id.clone(), score)?;
+		Self::deposit_event(Event::<T, I>::Inserted(id.clone(), bag));
+		Self::maybe_deposit_spilled(&id, score, bag);
+		Ok(())
+	}
+
+	fn on_insert_with_hint(
+		id: T::AccountId,
+		score: T::Score,
+		hint: &T::AccountId,
+	) -> Result<(), ListError> {
+		if let Some(bag) = Self::cancel_removal(&id) {
+			Self::deposit_event(Event::<T, I>::Inserted(id, bag));
+			return Ok(())
+		}
+		let bag = List::<T, I>::insert_with_hint(id.clone(), score, hint)?;
+		Self::deposit_event(Event::<T, I>::Inserted(id.clone(), bag));
+		Self::maybe_deposit_spilled(&id, score, bag);
+		Ok(())
+	}
+
+	fn on_update(id: &T::AccountId, new_score: T::Score) -> bool {
+		if !Self::contains(id) {
+			return false
+		}
+		let moved = Pallet::<T, I>::do_rebag(id, new_score).is_some();
+		Self::deposit_event(Event::<T, I>::ScoreUpdated(id.clone(), new_score));
+		moved
 	}
 
 	fn on_remove(id: &T::AccountId) {
-		List::<T>::remove(id)
+		if !Self::contains(id) {
+			return
+		}
+		if Self::is_locked() {
+			// the node is removed once the list is unlocked, see `unlock`.
+			PendingRemovals::<T, I>::insert(id, ());
+		} else {
+			List::<T, I>::remove(id);
+		}
+		LastRewardedRebag::<T, I>::remove(id);
+		LastRebag::<T, I>::remove(id);
+		LastPlaced::<T, I>::remove(id);
+		ForcedBags::<T, I>::remove(id);
+		PendingRebags::<T, I>::remove(id);
+		Self::deposit_event(Event::<T, I>::Removed(id.clone()));
 	}
 
-	fn regenerate(
+	fn lock(owner: ListLock) {
+		Locks::<T, I>::mutate(|locks| {
+			if !locks.contains(&owner) {
+				// there is room for a lock of every owner.
+				let _ = locks.try_push(owner);
+			}
+		});
+	}
+
+	fn unlock(owner: ListLock) {
+		Locks::<T, I>::mutate(|locks| locks.retain(|lock| *lock != owner));
+		if !Self::is_locked() {
+			// there are at most as many as the ids removed while the list was locked.
+			for (id, ()) in PendingRemovals::<T, I>::drain() {
+				List::<T, I>::remove(&id);
+			}
+		}
+	}
+
+	fn unsafe_regenerate(
 		all: impl IntoIterator<Item = T::AccountId>,
-		weight_of: Box<dyn Fn(&T::AccountId) -> VoteWeight>,
-	) -> u32 {
-		List::<T>::regenerate(all, weight_of)
+		score_of: Box<dyn Fn(&T::AccountId) -> T::Score>,
+	) -> (u32, Vec<T::AccountId>) {
+		List::<T, I>::unsafe_regenerate(all, score_of)
 	}
 
-	#[cfg(feature = "std")]
 	fn sanity_check() -> Result<(), &'static str> {
-		List::<T>::sanity_check()
+		List::<T, I>::sanity_check(T::SanityCheckDepth::get())
 	}
 
-	#[cfg(not(feature = "std"))]
-	fn sanity_check() -> Result<(), &'static str> {
-		Ok(())
+	fn bag_thresholds() -> Vec<T::Score> {
+		list::thresholds::<T, I>()
+	}
+
+	fn schedule_refresh() {
+		Self::start_refresh();
+	}
+
+	fn lowest() -> Option<T::AccountId> {
+		List::<T, I>::iter_rev()
+			.map(|node| node.id().clone())
+			.find(|id| !Self::is_pending_removal(id))
 	}
 
-	fn clear(maybe_count: Option<u32>) -> u32 {
-		List::<T>::clear(maybe_count)
+	fn unsafe_clear(maybe_count: Option<u32>) -> u32 {
+		List::<T, I>::unsafe_clear(maybe_count)
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
-	fn weight_update_worst_case(who: &T::AccountId, is_increase: bool) -> VoteWeight {
-		use frame_support::traits::Get as _;
-		let thresholds = T::BagThresholds::get();
-		let node = list::Node::<T>::get(who).unwrap();
+	fn weight_update_worst_case(who: &T::AccountId, is_increase: bool) -> T::Score {
+		let thresholds = list::thresholds::<T, I>();
+		let node = list::Node::<T, I>::get(who).unwrap();
 		let current_bag_idx = thresholds
 			.iter()
-			.chain(sp_std::iter::once(&VoteWeight::MAX))
-			.position(|w| w == &node.bag_upper())
+			.copied()
+			.chain(sp_std::iter::once(T::Score::max_value()))
+			.position(|w| w == node.bag_upper())
 			.unwrap();
 
 		if is_increase {
@@ -18,7 +18,7 @@
 //! Implementation of a "bags list": a semi-sorted list where ordering granularity is dictated by
 //! configurable thresholds that delineate the boundaries of bags. It uses a pattern of composite
 //! data structures, where multiple storage items are masked by one outer API. See [`ListNodes`],
-//! [`CounterForListNodes`] and [`ListBags`] for more information.
+//! [`ListNodes`] and [`ListBags`] for more information.
 //!
 //! The outer API of this module is the [`List`] struct. It wraps all acceptable operations on top
 //! of the aggregate linked list. All operations with the bags list should happen through this
@@ -26,9 +26,10 @@
 
 use crate::Config;
 use codec::{Decode, Encode, MaxEncodedLen};
-use frame_election_provider_support::{VoteWeight, VoteWeightProvider};
-use frame_support::{traits::Get, DefaultNoBound};
+use frame_election_provider_support::{BudgetedIter, ScoreProvider};
+use frame_support::{traits::Get, weights::Weight, DefaultNoBound};
 use scale_info::TypeInfo;
+use sp_runtime::traits::{Bounded, Saturating};
 use sp_std::{
 	boxed::Box,
 	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
@@ -41,28 +42,168 @@ use sp_std::{
 pub enum Error {
 	/// A duplicate id has been detected.
 	Duplicate,
+	/// An id does not exist in the list.
+	NodeNotFound,
+	/// The bag an id belongs in is full, and it could not be put elsewhere.
+	BagFull,
+	/// The storage of the list is inconsistent with an id, e.g. its bag links to it while it has
+	/// no node.
+	Inconsistent,
 }
 
 #[cfg(test)]
 mod tests;
 
-/// Given a certain vote weight, to which bag does it belong to?
+/// How thoroughly [`List::sanity_check`] verifies the integrity of the list.
+///
+/// Each level performs all the checks of the levels before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SanityCheckLevel {
+	/// Only check that the count of `ListNodes` matches the number of stored nodes.
+	Counter,
+	/// Additionally check that there are no duplicates and that the links of every bag are sound.
+	Links,
+	/// Additionally check every individual node against the bag and thresholds it claims.
+	Full,
+}
+
+/// What happens to an id that belongs in a bag which already holds [`Config::MaxBagSize`] ids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BagOverflow {
+	/// The id is not inserted, failing with [`Error::BagFull`], or not moved, if it was being
+	/// rebagged.
+	Reject,
+	/// The id goes into the closest lower bag that is not full. If they are all full, it is
+	/// handled as with [`BagOverflow::Reject`].
+	SpillLower,
+}
+
+/// Where an id goes within the bag it is inserted or rebagged into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BagInsertion {
+	/// The id becomes the tail of the bag, behind every id that joined the bag before it.
+	Tail,
+	/// The id becomes the head of the bag, in front of every id that joined the bag before it.
+	Head,
+}
+
+/// The thresholds currently used by the list.
+///
+/// These are the thresholds stored in [`crate::ListBagThresholds`], if they have been set through
+/// [`crate::Call::set_bag_thresholds`]. Otherwise, they are `T::BagThresholds`.
+pub(crate) fn thresholds<T: Config<I>, I: 'static>() -> Vec<T::Score> {
+	crate::ListBagThresholds::<T, I>::get()
+		.map(|thresholds| thresholds.into_inner())
+		.unwrap_or_else(|| T::BagThresholds::get().to_vec())
+}
+
+/// Given a certain score, to which bag does it belong to?
 ///
 /// Bags are identified by their upper threshold; the value returned by this function is guaranteed
-/// to be a member of `T::BagThresholds`.
+/// to be a member of [`thresholds`].
 ///
-/// Note that even if the thresholds list does not have `VoteWeight::MAX` as its final member, this
-/// function behaves as if it does.
-pub(crate) fn notional_bag_for<T: Config>(weight: VoteWeight) -> VoteWeight {
-	let thresholds = T::BagThresholds::get();
-	let idx = thresholds.partition_point(|&threshold| weight > threshold);
-	thresholds.get(idx).copied().unwrap_or(VoteWeight::MAX)
+/// Note that even if the thresholds list does not have `T::Score::max_value()` as its final member,
+/// this function behaves as if it does.
+pub(crate) fn notional_bag_for<T: Config<I>, I: 'static>(weight: T::Score) -> T::Score {
+	canonical_bag_in(&thresholds::<T, I>(), weight)
+}
+
+/// The upper threshold of the bag that `score` belongs to, among the bags delimited by
+/// `thresholds`.
+///
+/// `thresholds` must be strictly increasing, as [`Config::BagThresholds`] are. `Score::max_value()`
+/// is returned for scores above the highest threshold, whether or not it is part of `thresholds`.
+///
+/// This does not depend on the state of any list, so that off-chain tools can use it to bucket
+/// scores with candidate thresholds.
+pub fn canonical_bag_in<Score: Copy + Ord + Bounded>(thresholds: &[Score], score: Score) -> Score {
+	let idx = thresholds.partition_point(|&threshold| score > threshold);
+	thresholds.get(idx).copied().unwrap_or_else(Score::max_value)
+}
+
+/// The zero-based index of the bag of upper threshold `bag_upper` among the bags delimited by
+/// `thresholds`, from the lowest bag to the highest.
+///
+/// The implied bag of `Score::max_value()` comes last, whether or not it is part of `thresholds`.
+/// The bag of a threshold that is not part of `thresholds`, e.g. one that was removed, gets the
+/// index of the bag it falls into.
+pub fn bag_index_in<Score: Copy + Ord>(thresholds: &[Score], bag_upper: Score) -> u32 {
+	thresholds.partition_point(|&threshold| threshold < bag_upper) as u32
+}
+
+/// The bag an id with `score` goes into, taking [`Config::MaxBagSize`] and
+/// [`Config::BagOverflowPolicy`] into account, or `None` if there is no room for it.
+///
+/// `current` is the bag the id is already in, if any; it is never considered to be full.
+/// `pending` is the number of ids about to be added to a bag, on top of those it already holds.
+fn bag_with_room_for<T: Config<I>, I: 'static>(
+	score: T::Score,
+	current: Option<T::Score>,
+	pending: impl Fn(T::Score) -> u32,
+) -> Option<T::Score> {
+	let has_room = |bag_upper: T::Score| {
+		Some(bag_upper) == current ||
+			crate::CounterForBag::<T, I>::get(bag_upper).saturating_add(pending(bag_upper)) <
+				T::MaxBagSize::get()
+	};
+
+	let notional = notional_bag_for::<T, I>(score);
+	if has_room(notional) {
+		return Some(notional)
+	}
+
+	match T::BagOverflowPolicy::get() {
+		BagOverflow::Reject => None,
+		BagOverflow::SpillLower => thresholds::<T, I>()
+			.into_iter()
+			.rev()
+			.skip_while(|threshold| *threshold >= notional)
+			.find(|threshold| has_room(*threshold)),
+	}
+}
+
+/// How many nodes past [`crate::ListNodes`]'s count a traversal may visit before it is considered
+/// to be spinning, to tolerate a slightly off counter.
+const TRAVERSAL_SLACK: u32 = 16;
+
+/// The most nodes a single traversal may visit, see [`traverse`].
+///
+/// This reads the count of [`crate::ListNodes`], so it should be computed once per iteration of
+/// the list, not once per bag.
+fn traversal_cap<T: Config<I>, I: 'static>() -> u32 {
+	crate::ListNodes::<T, I>::count().saturating_add(TRAVERSAL_SLACK)
+}
+
+/// Follow the links of `step`, starting at `first` and yielding it.
+///
+/// A sane list never hits them, but this stops at any node that was already visited, and after
+/// visiting `cap` nodes, so that the traversal of a corrupted list is truncated, and logged,
+/// instead of spinning forever.
+fn traverse<T: Config<I>, I: 'static>(
+	first: Option<Node<T, I>>,
+	step: impl Fn(&Node<T, I>) -> Option<Node<T, I>>,
+	cap: u32,
+) -> impl Iterator<Item = Node<T, I>> {
+	let mut visited = BTreeSet::new();
+	iter::successors(first, move |current| {
+		visited.insert(current.id.clone());
+		if visited.len() as u32 >= cap {
+			crate::log!(error, "traversal exceeded {} nodes at {:?}, truncating", cap, current.id);
+			return None
+		}
+		let next = step(current)?;
+		if visited.contains(&next.id) {
+			crate::log!(error, "cycle detected from {:?} to {:?}, truncating", current.id, next.id);
+			return None
+		}
+		Some(next)
+	})
 }
 
 /// The **ONLY** entry point of this module. All operations to the bags-list should happen through
 /// this interface. It is forbidden to access other module members directly.
 //
-// Data structure providing efficient mostly-accurate selection of the top N id by `VoteWeight`.
+// Data structure providing efficient mostly-accurate selection of the top N id by `Score`.
 //
 // It's implemented as a set of linked lists. Each linked list comprises a bag of ids of
 // arbitrary and unbounded length, all having a vote weight within a particular constant range.
@@ -73,38 +214,71 @@ pub(crate) fn notional_bag_for<T: Config>(weight: VoteWeight) -> VoteWeight {
 // weight decreases as successive bags are reached. This means that it is valid to truncate
 // iteration at any desired point; only those ids in the lowest bag can be excluded. This
 // satisfies both the desire for fairness and the requirement for efficiency.
-pub struct List<T: Config>(PhantomData<T>);
-
-impl<T: Config> List<T> {
-	/// Remove all data associated with the list from storage. Parameter `items` is the number of
-	/// items to clear from the list. WARNING: `None` will clear all items and should generally not
-	/// be used in production as it could lead to an infinite number of storage accesses.
-	pub(crate) fn clear(maybe_count: Option<u32>) -> u32 {
-		crate::ListBags::<T>::remove_all(maybe_count);
-		crate::ListNodes::<T>::remove_all(maybe_count);
-		if let Some(count) = maybe_count {
-			crate::CounterForListNodes::<T>::mutate(|items| *items - count);
-			count
-		} else {
-			crate::CounterForListNodes::<T>::take()
+pub struct List<T: Config<I>, I: 'static = ()>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> List<T, I> {
+	/// Remove all data associated with the list from storage: the nodes, the bags and the
+	/// counters.
+	///
+	/// At most `maybe_count` nodes are removed, along with the entries of their ids in the other
+	/// maps. Once no node is left, the bags, the counters and the cursors are removed as well.
+	/// Returns the number of nodes actually removed.
+	///
+	/// WARNING: if not all the nodes are removed, the list is left in an inconsistent state. This
+	/// should only be used for regeneration, migrations and tests. `None` removes everything in one
+	/// go, and should generally not be used in production as it could lead to an unbounded number
+	/// of storage accesses.
+	pub(crate) fn unsafe_clear(maybe_count: Option<u32>) -> u32 {
+		let count = maybe_count.map_or(usize::MAX, |count| count as usize);
+		// draining keeps the count of the nodes in sync.
+		let mut removed = 0u32;
+		for (id, _) in crate::ListNodes::<T, I>::iter().drain().take(count) {
+			Self::remove_id_entries(&id);
+			removed.saturating_inc();
 		}
+
+		if crate::ListNodes::<T, I>::count() == 0 {
+			crate::ListBags::<T, I>::remove_all(None);
+			crate::CounterForBag::<T, I>::remove_all(None);
+			crate::BagHistogram::<T, I>::kill();
+			crate::TopBagsRefresh::<T, I>::kill();
+			crate::AutoRebagCursor::<T, I>::kill();
+			crate::SanityCheckCursor::<T, I>::kill();
+			crate::ThresholdMigrationCursor::<T, I>::kill();
+			crate::PendingThresholdMigration::<T, I>::kill();
+			crate::PruneCursor::<T, I>::kill();
+		}
+		removed
+	}
+
+	/// Remove the entries of `id` from all the maps keyed by id, other than [`crate::ListNodes`].
+	fn remove_id_entries(id: &T::AccountId) {
+		crate::LastRewardedRebag::<T, I>::remove(id);
+		crate::LastRebag::<T, I>::remove(id);
+		crate::LastPlaced::<T, I>::remove(id);
+		crate::ForcedBags::<T, I>::remove(id);
+		crate::PendingRebags::<T, I>::remove(id);
+		crate::PendingRemovals::<T, I>::remove(id);
 	}
 
 	/// Regenerate all of the data from the given ids.
 	///
 	/// WARNING: this is expensive and should only ever be performed when the list needs to be
-	/// generated from scratch. Care needs to be taken to ensure
+	/// generated from scratch, e.g. for disaster recovery or in a migration. Care needs to be
+	/// taken to ensure that `all` contains every id that should be in the list, since all the
+	/// existing data is wiped first.
 	///
 	/// This may or may not need to be called at genesis as well, based on the configuration of the
 	/// pallet using this `List`.
 	///
-	/// Returns the number of ids migrated.
-	pub fn regenerate(
+	/// Returns the number of ids migrated, and the ids for which there was no room, see
+	/// [`Self::insert_many`].
+	pub fn unsafe_regenerate(
 		all: impl IntoIterator<Item = T::AccountId>,
-		weight_of: Box<dyn Fn(&T::AccountId) -> VoteWeight>,
-	) -> u32 {
-		Self::clear(None);
-		Self::insert_many(all, weight_of)
+		weight_of: Box<dyn Fn(&T::AccountId) -> T::Score>,
+	) -> (u32, Vec<T::AccountId>) {
+		Self::unsafe_clear(None);
+		Self::insert_many(all, weight_of, false)
 	}
 
 	/// Migrate the list from one set of thresholds to another.
@@ -117,78 +291,60 @@ impl<T: Config> List<T> {
 	///
 	/// - `old_thresholds` is the previous list of thresholds.
 	/// - All `bag_upper` currently in storage are members of `old_thresholds`.
-	/// - `T::BagThresholds` has already been updated and is the new set of thresholds.
+	/// - [`thresholds`] has already been updated and is the new set of thresholds.
 	///
 	/// Postconditions:
 	///
-	/// - All `bag_upper` currently in storage are members of `T::BagThresholds`.
+	/// - All `bag_upper` currently in storage are members of [`thresholds`].
 	/// - No id is changed unless required to by the difference between the old threshold list and
 	///   the new.
 	/// - ids whose bags change at all are implicitly rebagged into the appropriate bag in the new
 	///   threshold set.
-	#[allow(dead_code)]
-	pub fn migrate(old_thresholds: &[VoteWeight]) -> u32 {
-		let new_thresholds = T::BagThresholds::get();
+	pub fn migrate(old_thresholds: &[T::Score]) -> u32 {
+		let new_thresholds = thresholds::<T, I>();
 		if new_thresholds == old_thresholds {
 			return 0
 		}
 
+		// `T::Score::max_value()` is an implied member of every threshold list, so it is never
+		// inserted nor removed.
+		let old_set: BTreeSet<_> = old_thresholds
+			.iter()
+			.copied()
+			.chain(iter::once(T::Score::max_value()))
+			.collect();
+		let new_set: BTreeSet<_> = new_thresholds
+			.iter()
+			.copied()
+			.chain(iter::once(T::Score::max_value()))
+			.collect();
+
 		// we can't check all preconditions, but we can check one
 		debug_assert!(
-			crate::ListBags::<T>::iter().all(|(threshold, _)| old_thresholds.contains(&threshold)),
+			crate::ListBags::<T, I>::iter().all(|(threshold, _)| old_set.contains(&threshold)),
 			"not all `bag_upper` currently in storage are members of `old_thresholds`",
 		);
 		debug_assert!(
-			crate::ListNodes::<T>::iter().all(|(_, node)| old_thresholds.contains(&node.bag_upper)),
+			crate::ListNodes::<T, I>::iter().all(|(_, node)| old_set.contains(&node.bag_upper)),
 			"not all `node.bag_upper` currently in storage are members of `old_thresholds`",
 		);
 
-		let old_set: BTreeSet<_> = old_thresholds.iter().copied().collect();
-		let new_set: BTreeSet<_> = new_thresholds.iter().copied().collect();
-
 		// accounts that need to be rebagged
 		let mut affected_accounts = BTreeSet::new();
-		// track affected old bags to make sure we only iterate them once
-		let mut affected_old_bags = BTreeSet::new();
-
-		let new_bags = new_set.difference(&old_set).copied();
-		// a new bag means that all accounts previously using the old bag's threshold must now
-		// be rebagged
-		for inserted_bag in new_bags {
-			let affected_bag = {
-				// this recreates `notional_bag_for` logic, but with the old thresholds.
-				let idx = old_thresholds.partition_point(|&threshold| inserted_bag > threshold);
-				old_thresholds.get(idx).copied().unwrap_or(VoteWeight::MAX)
-			};
-			if !affected_old_bags.insert(affected_bag) {
-				// If the previous threshold list was [10, 20], and we insert [3, 5], then there's
-				// no point iterating through bag 10 twice.
-				continue
-			}
-
-			if let Some(bag) = Bag::<T>::get(affected_bag) {
+		for affected_bag in Self::bags_affected_by(old_thresholds) {
+			if let Some(bag) = Bag::<T, I>::get(affected_bag) {
 				affected_accounts.extend(bag.iter().map(|node| node.id));
 			}
 		}
-
 		let removed_bags = old_set.difference(&new_set).copied();
-		// a removed bag means that all members of that bag must be rebagged
-		for removed_bag in removed_bags.clone() {
-			if !affected_old_bags.insert(removed_bag) {
-				continue
-			}
-
-			if let Some(bag) = Bag::<T>::get(removed_bag) {
-				affected_accounts.extend(bag.iter().map(|node| node.id));
-			}
-		}
 
 		// migrate the voters whose bag has changed
 		let num_affected = affected_accounts.len() as u32;
-		let weight_of = T::VoteWeightProvider::vote_weight;
+		let weight_of = T::ScoreProvider::score_fn();
 		let _removed = Self::remove_many(&affected_accounts);
 		debug_assert_eq!(_removed, num_affected);
-		let _inserted = Self::insert_many(affected_accounts.into_iter(), weight_of);
+		// the ids are only moved, so bags may grow beyond `MaxBagSize` here.
+		let (_inserted, _) = Self::insert_many(affected_accounts.into_iter(), weight_of, true);
 		debug_assert_eq!(_inserted, num_affected);
 
 		// we couldn't previously remove the old bags because both insertion and removal assume that
@@ -199,99 +355,528 @@ impl<T: Config> List<T> {
 		// lookups.
 		for removed_bag in removed_bags {
 			debug_assert!(
-				!crate::ListNodes::<T>::iter().any(|(_, node)| node.bag_upper == removed_bag),
+				!crate::ListNodes::<T, I>::iter().any(|(_, node)| node.bag_upper == removed_bag),
 				"no id should be present in a removed bag",
 			);
-			crate::ListBags::<T>::remove(removed_bag);
+			crate::ListBags::<T, I>::remove(removed_bag);
 		}
 
-		debug_assert_eq!(Self::sanity_check(), Ok(()));
+		debug_assert_eq!(Self::sanity_check(T::SanityCheckDepth::get()), Ok(()));
 
 		num_affected
 	}
 
+	/// The bags of `old_thresholds` whose members may need to be rebagged into the bags of
+	/// [`thresholds`]: the bags that are split by a new threshold, and those that are removed.
+	pub(crate) fn bags_affected_by(old_thresholds: &[T::Score]) -> BTreeSet<T::Score> {
+		// `T::Score::max_value()` is an implied member of every threshold list, so it is never
+		// inserted nor removed.
+		let old_set: BTreeSet<_> = old_thresholds
+			.iter()
+			.copied()
+			.chain(iter::once(T::Score::max_value()))
+			.collect();
+		let new_set: BTreeSet<_> = thresholds::<T, I>()
+			.into_iter()
+			.chain(iter::once(T::Score::max_value()))
+			.collect();
+
+		// a new bag means that all accounts previously using the old bag's threshold must now
+		// be rebagged.
+		let split_bags = new_set.difference(&old_set).map(|inserted_bag| {
+			// this recreates `notional_bag_for` logic, but with the old thresholds.
+			let idx = old_thresholds.partition_point(|threshold| inserted_bag > threshold);
+			old_thresholds.get(idx).copied().unwrap_or(T::Score::max_value())
+		});
+		// a removed bag means that all members of that bag must be rebagged.
+		let removed_bags = old_set.difference(&new_set).copied();
+
+		split_bags.chain(removed_bags).collect()
+	}
+
+	/// Migrate at most `limit` ids from `old_thresholds` to [`thresholds`], resuming from the one
+	/// after [`crate::ThresholdMigrationCursor`], and wrapping around once all of `ListNodes` is
+	/// checked.
+	///
+	/// This is the chunked equivalent of [`Self::migrate`]. Only the ids in the bags it would touch
+	/// are considered, and they are only moved if they are not in the right bag for their current
+	/// score, like a rebag would. An id whose bag is not part of [`thresholds`] anymore is moved
+	/// into the bag its score belongs to even if that bag has no room left, as [`Self::migrate`]
+	/// would, since it would not be iterated otherwise.
+	///
+	/// Returns the number of ids checked and moved, and whether all of `ListNodes` is checked.
+	pub(crate) fn migrate_chunk(old_thresholds: &[T::Score], limit: u32) -> (u32, u32, bool) {
+		let affected_bags = Self::bags_affected_by(old_thresholds);
+		let mut nodes = match crate::ThresholdMigrationCursor::<T, I>::get() {
+			Some(last) =>
+				crate::ListNodes::<T, I>::iter_from(crate::ListNodes::<T, I>::hashed_key_for(last)),
+			None => crate::ListNodes::<T, I>::iter(),
+		};
+
+		let current = thresholds::<T, I>();
+		let score_of = T::ScoreProvider::score_fn();
+		let (mut checked, mut moved) = (0u32, 0u32);
+		let mut last = None;
+		let mut exhausted = false;
+		while checked < limit {
+			match nodes.next() {
+				Some((id, node)) => {
+					if affected_bags.contains(&node.bag_upper) {
+						let score = score_of(&id);
+						let removed = node.bag_upper != T::Score::max_value() &&
+							current.binary_search(&node.bag_upper).is_err();
+						let new_bag_upper = match node.destination(score) {
+							Some(bag_upper) => Some(bag_upper),
+							None if removed => Some(canonical_bag_in(&current, score)),
+							None => None,
+						};
+						if let Some(new_bag_upper) = new_bag_upper {
+							Self::move_node_unchecked(node, new_bag_upper, score);
+							moved += 1;
+						}
+					}
+					last = Some(id);
+					checked += 1;
+				},
+				None => {
+					exhausted = true;
+					break
+				},
+			}
+		}
+
+		match (exhausted, last) {
+			(true, _) => crate::ThresholdMigrationCursor::<T, I>::kill(),
+			(false, Some(last)) => crate::ThresholdMigrationCursor::<T, I>::put(last),
+			(false, None) => (),
+		}
+
+		(checked, moved, exhausted)
+	}
+
+	/// Scan up to `limit` ids, resuming after [`crate::PruneCursor`], and return those that are
+	/// not [`Config::Members`] anymore.
+	///
+	/// The returned ids are left in the list.
+	pub(crate) fn scan_dangling(limit: u32) -> Vec<T::AccountId> {
+		use frame_support::traits::Contains;
+
+		let ids = match crate::PruneCursor::<T, I>::get() {
+			Some(last) =>
+				crate::ListNodes::<T, I>::iter_from(crate::ListNodes::<T, I>::hashed_key_for(last)),
+			None => crate::ListNodes::<T, I>::iter(),
+		}
+		.map(|(id, _)| id);
+
+		let mut checked = 0u32;
+		let mut last = None;
+		let mut dangling = Vec::new();
+		for id in ids.take(limit as usize) {
+			if !T::Members::contains(&id) {
+				dangling.push(id.clone());
+			}
+			last = Some(id);
+			checked += 1;
+		}
+
+		match last {
+			// reached the end, start from the beginning next time.
+			_ if checked < limit => crate::PruneCursor::<T, I>::kill(),
+			Some(last) => crate::PruneCursor::<T, I>::put(last),
+			None => (),
+		}
+
+		dangling
+	}
+
 	/// Returns `true` if the list contains `id`, otherwise returns `false`.
 	pub(crate) fn contains(id: &T::AccountId) -> bool {
-		crate::ListNodes::<T>::contains_key(id)
+		crate::ListNodes::<T, I>::contains_key(id)
 	}
 
 	/// Iterate over all nodes in all bags in the list.
 	///
 	/// Full iteration can be expensive; it's recommended to limit the number of items with
 	/// `.take(n)`.
-	pub(crate) fn iter() -> impl Iterator<Item = Node<T>> {
+	pub(crate) fn iter() -> impl Iterator<Item = Node<T, I>> {
 		// We need a touch of special handling here: because we permit `T::BagThresholds` to
 		// omit the final bound, we need to ensure that we explicitly include that threshold in the
 		// list.
 		//
 		// It's important to retain the ability to omit the final bound because it makes tests much
 		// easier; they can just configure `type BagThresholds = ()`.
-		let thresholds = T::BagThresholds::get();
-		let iter = thresholds.iter().copied();
-		let iter: Box<dyn Iterator<Item = u64>> = if thresholds.last() == Some(&VoteWeight::MAX) {
+		let thresholds = thresholds::<T, I>();
+		let includes_max = thresholds.last() == Some(&T::Score::max_value());
+		let iter = thresholds.into_iter();
+		let iter: Box<dyn Iterator<Item = T::Score>> = if includes_max {
 			// in the event that they included it, we can just pass the iterator through unchanged.
 			Box::new(iter.rev())
 		} else {
 			// otherwise, insert it here.
-			Box::new(iter.chain(iter::once(VoteWeight::MAX)).rev())
+			Box::new(iter.chain(iter::once(T::Score::max_value())).rev())
 		};
 
-		iter.filter_map(Bag::get).flat_map(|bag| bag.iter())
+		let cap = traversal_cap::<T, I>();
+		iter.filter_map(Bag::get).flat_map(move |bag| bag.iter_capped(cap))
+	}
+
+	/// Iterate over all nodes in all bags in the list, in reverse: from the lowest bag to the
+	/// highest, and from the tail to the head of each bag.
+	///
+	/// This is the exact reverse of [`Self::iter`], and just as expensive.
+	pub(crate) fn iter_rev() -> impl Iterator<Item = Node<T, I>> {
+		let mut thresholds = thresholds::<T, I>();
+		if thresholds.last() != Some(&T::Score::max_value()) {
+			thresholds.push(T::Score::max_value());
+		}
+
+		let cap = traversal_cap::<T, I>();
+		thresholds.into_iter().filter_map(Bag::get).flat_map(move |bag| bag.iter_rev_capped(cap))
+	}
+
+	/// Iterate over the nodes of the single bag with the upper threshold `bag_upper`, from its
+	/// head to its tail, or starting right after the node of `after` if given.
+	///
+	/// The iteration is empty if there is no such bag, or if `after` is not in it.
+	pub(crate) fn iter_bag(
+		bag_upper: T::Score,
+		after: Option<&T::AccountId>,
+	) -> impl Iterator<Item = Node<T, I>> {
+		let first = match after {
+			Some(after) => Node::<T, I>::get(after)
+				.filter(|node| node.bag_upper == bag_upper)
+				.and_then(|node| node.next()),
+			None => Bag::<T, I>::get(bag_upper).and_then(|bag| bag.head()),
+		};
+		traverse(first, Node::next, traversal_cap::<T, I>())
+	}
+
+	/// Iterate over the list, starting right after the node of `start`, which is not included.
+	///
+	/// The iteration continues through the rest of the bag of `start`, and then through all the
+	/// lower bags. Returns an error if `start` is not in the list.
+	pub(crate) fn iter_from(
+		start: &T::AccountId,
+	) -> Result<impl Iterator<Item = Node<T, I>>, Error> {
+		// We chain two iterators:
+		// 1. from the node of `start` to the end of its bag
+		// 2. all the bags that come after the bag of `start`, i.e. all lower bags.
+		let start_node = Node::<T, I>::get(start).ok_or(Error::NodeNotFound)?;
+		let start_node_upper = start_node.bag_upper;
+		let cap = traversal_cap::<T, I>();
+		let start_bag = traverse(start_node.next(), Node::next, cap);
+
+		let thresholds = thresholds::<T, I>();
+		let idx = thresholds.partition_point(|&threshold| start_node_upper > threshold);
+		let leftover_bags = thresholds
+			.into_iter()
+			.take(idx)
+			.rev()
+			.filter_map(Bag::get)
+			.flat_map(move |bag| bag.iter_capped(cap));
+
+		Ok(start_bag.chain(leftover_bags))
+	}
+
+	/// Take ids in the order of [`Self::iter`], or of [`Self::iter_from`] if `start` is given, for
+	/// as long as the storage reads needed to find them fit in `budget`.
+	///
+	/// Unlike these, the bags below [`Config::MinScoreBag`] are skipped, as by the iterators of
+	/// `SortedListProvider`. The thresholds, `start`, every bag looked up, empty or not, and every
+	/// node taken each cost one read. Returns an error if `start` is not in the list.
+	pub(crate) fn iter_with_budget(
+		start: Option<&T::AccountId>,
+		budget: Weight,
+	) -> Result<BudgetedIter<T::AccountId>, Error> {
+		sp_tracing::enter_span!(sp_tracing::debug_span!(
+			target: crate::LOG_TARGET,
+			"iterate",
+			start = ?start,
+			budget,
+		));
+		let read = T::DbWeight::get().reads(1);
+		let mut taken =
+			BudgetedIter { ids: Vec::new(), consumed: 0, cursor: start.cloned(), exhausted: false };
+		let try_read = |taken: &mut BudgetedIter<T::AccountId>| {
+			let within_budget = taken.consumed.saturating_add(read) <= budget;
+			if within_budget {
+				taken.consumed = taken.consumed.saturating_add(read);
+			}
+			within_budget
+		};
+
+		if !try_read(&mut taken) {
+			return Ok(taken)
+		}
+		let mut thresholds = thresholds::<T, I>();
+		if thresholds.last() != Some(&T::Score::max_value()) {
+			thresholds.push(T::Score::max_value());
+		}
+		thresholds.retain(|threshold| crate::Pallet::<T, I>::is_iterated(*threshold));
+
+		// the next node of the current bag, and the lower bags left to look up.
+		let (mut next, lower_bags) = match start {
+			Some(start) => {
+				if !try_read(&mut taken) {
+					return Ok(taken)
+				}
+				let start_node = Node::<T, I>::get(start).ok_or(Error::NodeNotFound)?;
+				let idx = thresholds.partition_point(|&threshold| start_node.bag_upper > threshold);
+				thresholds.truncate(idx);
+				let start_iterated = crate::Pallet::<T, I>::is_iterated(start_node.bag_upper);
+				let next = start_node.next.filter(|_| start_iterated);
+				(next, thresholds)
+			},
+			None => (None, thresholds),
+		};
+		let mut lower_bags = lower_bags.into_iter().rev();
+
+		loop {
+			match next {
+				Some(id) => {
+					if !try_read(&mut taken) {
+						return Ok(taken)
+					}
+					next = match Node::<T, I>::get(&id) {
+						Some(node) => node.next,
+						None => {
+							crate::log!(warn, "node {:?} of a bag is not in the list", id);
+							None
+						},
+					};
+					taken.cursor = Some(id.clone());
+					taken.ids.push(id);
+				},
+				None => match lower_bags.next() {
+					Some(bag_upper) => {
+						if !try_read(&mut taken) {
+							return Ok(taken)
+						}
+						next = Bag::<T, I>::get(bag_upper).and_then(|bag| bag.head);
+					},
+					None => {
+						taken.exhausted = true;
+						return Ok(taken)
+					},
+				},
+			}
+		}
 	}
 
 	/// Insert several ids into the appropriate bags in the list. Continues with insertions
-	/// if duplicates are detected.
+	/// if duplicates are detected, or if there is no room for an id.
+	///
+	/// Each id goes where [`Self::try_insert`] would put it, i.e. [`Config::BagOverflowPolicy`]
+	/// applies to full bags. If none has room for an id, it is put into the bag of its score
+	/// anyway if `overfill`, and left out otherwise.
+	///
+	/// This is more efficient than repeated calls to `Self::insert`: the ids are first grouped by
+	/// destination bag, then each bag and its old tail are written once, each new node and its
+	/// placement are written once, and the counters are updated once per bag.
 	///
-	/// Returns the final count of number of ids inserted.
+	/// Returns the final count of number of ids inserted, and the ids that were left out.
 	fn insert_many(
 		ids: impl IntoIterator<Item = T::AccountId>,
-		weight_of: impl Fn(&T::AccountId) -> VoteWeight,
-	) -> u32 {
-		let mut count = 0;
-		ids.into_iter().for_each(|v| {
-			let weight = weight_of(&v);
-			if Self::insert(v, weight).is_ok() {
-				count += 1;
+		weight_of: impl Fn(&T::AccountId) -> T::Score,
+		overfill: bool,
+	) -> (u32, Vec<T::AccountId>) {
+		let mut seen = BTreeSet::<T::AccountId>::new();
+		let mut pending = BTreeMap::<T::Score, Vec<(T::AccountId, T::Score)>>::new();
+		let mut rejected = Vec::new();
+		for id in ids {
+			if Self::contains(&id) || !seen.insert(id.clone()) {
+				continue
 			}
-		});
 
-		count
+			let score = weight_of(&id);
+			let in_pending = |bag_upper| pending.get(&bag_upper).map_or(0, |ids| ids.len() as u32);
+			match bag_with_room_for::<T, I>(score, None, in_pending)
+				.or_else(|| overfill.then(|| notional_bag_for::<T, I>(score)))
+			{
+				Some(bag_upper) => pending.entry(bag_upper).or_default().push((id, score)),
+				None => {
+					crate::log!(warn, "no room for {:?} with score {:?}", id, score);
+					rejected.push(id);
+				},
+			}
+		}
+
+		let at_head = T::BagInsertionPolicy::get() == BagInsertion::Head;
+		let mut count = 0u32;
+		for (bag_upper, mut ids) in pending {
+			let added = ids.len() as u32;
+			let mut bag = Bag::<T, I>::get_or_make(bag_upper);
+
+			// the new ids are chained in their final order, i.e. as if each was inserted in turn,
+			// then the chain is linked to the old tail, or to the old head.
+			if at_head {
+				ids.reverse();
+			}
+			let first = ids.first().map(|(id, _)| id.clone());
+			let (mut prev, last_next) = match at_head {
+				true => {
+					if let (Some(mut old_head), Some((last, _))) = (bag.head(), ids.last()) {
+						old_head.prev = Some(last.clone());
+						old_head.put();
+					}
+					(None, bag.head.clone())
+				},
+				false => {
+					if let (Some(mut old_tail), Some(first)) = (bag.tail(), &first) {
+						old_tail.next = Some(first.clone());
+						old_tail.put();
+					}
+					(bag.tail.clone(), None)
+				},
+			};
+
+			let mut total_score = T::Score::default();
+			let mut ids = ids.into_iter().peekable();
+			while let Some((id, score)) = ids.next() {
+				let next = ids.peek().map(|(next, _)| next.clone()).or_else(|| last_next.clone());
+				Node::<T, I>::from_parts(id.clone(), prev, next, bag_upper, score).put();
+				Self::note_placed(&id);
+				total_score = total_score.saturating_add(score);
+				prev = Some(id);
+			}
+			if at_head || bag.head.is_none() {
+				bag.head = first;
+			}
+			if !at_head || bag.tail.is_none() {
+				bag.tail = prev;
+			}
+			bag.put();
+
+			Self::increment_bag_count(bag_upper, added, total_score);
+			count = count.saturating_add(added);
+		}
+
+		crate::log!(
+			debug,
+			"inserted {} ids, new count is {}",
+			count,
+			crate::ListNodes::<T, I>::count(),
+		);
+
+		(count, rejected)
 	}
 
 	/// Insert a new id into the appropriate bag in the list.
 	///
-	/// Returns an error if the list already contains `id`.
-	pub(crate) fn insert(id: T::AccountId, weight: VoteWeight) -> Result<(), Error> {
+	/// If that bag is full, the id is handled according to [`Config::BagOverflowPolicy`].
+	///
+	/// Returns the upper threshold of the bag `id` was inserted into, or an error if the list
+	/// already contains `id`, if there is no room for it, or if its bag already links to it
+	/// without it having a node. Nothing is written in case of an error.
+	pub(crate) fn try_insert(id: T::AccountId, weight: T::Score) -> Result<T::Score, Error> {
 		if Self::contains(&id) {
 			return Err(Error::Duplicate)
 		}
 
-		let bag_weight = notional_bag_for::<T>(weight);
-		let mut bag = Bag::<T>::get_or_make(bag_weight);
-		// unchecked insertion is okay; we just got the correct `notional_bag_for`.
-		bag.insert_unchecked(id.clone());
+		let bag_weight = bag_with_room_for::<T, I>(weight, None, |_| 0).ok_or(Error::BagFull)?;
+		sp_tracing::enter_span!(sp_tracing::debug_span!(
+			target: crate::LOG_TARGET,
+			"insert",
+			account = ?id,
+			bag = ?bag_weight,
+			score = ?weight,
+		));
+		let mut bag = Bag::<T, I>::get_or_make(bag_weight);
+		// inserting would otherwise link `id` to itself, see `Bag::push_back_unchecked`.
+		if bag.head.as_ref() == Some(&id) || bag.tail.as_ref() == Some(&id) {
+			crate::log!(warn, "bag {:?} links to {:?}, which has no node", bag_weight, id);
+			return Err(Error::Inconsistent)
+		}
+		// unchecked insertion is okay; the bag is either the `notional_bag_for` the id, or the one
+		// it overflows into.
+		bag.insert_unchecked(id.clone(), weight);
 
-		// new inserts are always the tail, so we must write the bag.
+		// new inserts are always the head or the tail, so we must write the bag.
 		bag.put();
+		Self::increment_bag_count(bag_weight, 1, weight);
+		Self::note_placed(&id);
 
-		crate::CounterForListNodes::<T>::mutate(|prev_count| {
-			*prev_count = prev_count.saturating_add(1)
-		});
+		crate::log!(
+			debug,
+			"inserted {:?} with score {:?} into bag {:?}, new count is {}",
+			id,
+			weight,
+			bag_weight,
+			crate::ListNodes::<T, I>::count(),
+		);
+
+		Ok(bag_weight)
+	}
+
+	/// Insert a new id into the list, right next to `hint`, an id already in the bag it belongs in.
+	///
+	/// The hint only needs its own node and one of its neighbours to be checked and spliced in, so
+	/// the bag itself is neither read nor written, and [`Config::BagInsertionPolicy`] does not
+	/// apply. If the hint is wrong, e.g. because it is not in the right bag, or if it is alone in
+	/// its bag, this falls back to [`Self::try_insert`].
+	pub(crate) fn insert_with_hint(
+		id: T::AccountId,
+		weight: T::Score,
+		hint: &T::AccountId,
+	) -> Result<T::Score, Error> {
+		if Self::contains(&id) {
+			return Err(Error::Duplicate)
+		}
+
+		let bag_weight = bag_with_room_for::<T, I>(weight, None, |_| 0).ok_or(Error::BagFull)?;
+		sp_tracing::enter_span!(sp_tracing::debug_span!(
+			target: crate::LOG_TARGET,
+			"insert_with_hint",
+			account = ?id,
+			bag = ?bag_weight,
+			score = ?weight,
+			hint = ?hint,
+		));
+		// splice the id in front of a node that has a `prev`, so that the head cannot change: the
+		// hint itself, or the node behind it.
+		let at = Node::<T, I>::get(hint)
+			.filter(|node| node.bag_upper == bag_weight)
+			.and_then(|node| if node.prev.is_some() { Some(node) } else { node.next() });
+		let at = match at {
+			// linking to a missing node of `id` is an inconsistency `try_insert` reports.
+			Some(at) if at.prev.as_ref() != Some(&id) && at.next.as_ref() != Some(&id) => at,
+			_ => {
+				crate::log!(debug, "hint {:?} is of no use to insert {:?}", hint, id);
+				return Self::try_insert(id, weight)
+			},
+		};
+
+		let node = Node::<T, I> {
+			id: id.clone(),
+			prev: None,
+			next: None,
+			bag_upper: bag_weight,
+			score: weight,
+			_phantom: PhantomData,
+		};
+		Self::insert_at_unchecked(at, node);
+		Self::note_placed(&id);
 
 		crate::log!(
 			debug,
-			"inserted {:?} with weight {} into bag {:?}, new count is {}",
+			"inserted {:?} with score {:?} into bag {:?} next to {:?}, new count is {}",
 			id,
 			weight,
 			bag_weight,
-			crate::CounterForListNodes::<T>::get(),
+			hint,
+			crate::ListNodes::<T, I>::count(),
 		);
 
-		Ok(())
+		Ok(bag_weight)
 	}
 
 	/// Remove an id from the list.
 	pub(crate) fn remove(id: &T::AccountId) {
+		sp_tracing::enter_span!(sp_tracing::debug_span!(
+			target: crate::LOG_TARGET,
+			"remove",
+			account = ?id,
+		));
 		Self::remove_many(sp_std::iter::once(id));
 	}
 
@@ -305,7 +890,7 @@ impl<T: Config> List<T> {
 		let mut count = 0;
 
 		for id in ids.into_iter() {
-			let node = match Node::<T>::get(id) {
+			let node = match Node::<T, I>::get(id) {
 				Some(node) => node,
 				None => continue,
 			};
@@ -318,11 +903,13 @@ impl<T: Config> List<T> {
 				// this node is a head or tail, so the bag needs to be updated
 				let bag = bags
 					.entry(node.bag_upper)
-					.or_insert_with(|| Bag::<T>::get_or_make(node.bag_upper));
+					.or_insert_with(|| Bag::<T, I>::get_or_make(node.bag_upper));
 				// node.bag_upper must be correct, therefore this bag will contain this node.
 				bag.remove_node_unchecked(&node);
 			}
 
+			Self::decrement_bag_count(node.bag_upper, node.score);
+
 			// now get rid of the node itself
 			node.remove_from_storage_unchecked()
 		}
@@ -331,96 +918,378 @@ impl<T: Config> List<T> {
 			bag.put();
 		}
 
-		crate::CounterForListNodes::<T>::mutate(|prev_count| {
-			*prev_count = prev_count.saturating_sub(count)
-		});
-
 		count
 	}
 
 	/// Update a node's position in the list.
 	///
 	/// If the node was in the correct bag, no effect. If the node was in the incorrect bag, they
-	/// are moved into the correct bag.
+	/// are moved into the correct bag, or wherever [`Config::BagOverflowPolicy`] dictates if that
+	/// bag is full.
 	///
-	/// Returns `Some((old_idx, new_idx))` if the node moved, otherwise `None`.
+	/// Returns `Some((old_idx, new_idx))` if the node moved, otherwise `None`. The score of a node
+	/// that moved is recorded as `new_weight`; the score of a node that did not move is left as it
+	/// was.
 	///
 	/// This operation is somewhat more efficient than simply calling [`self.remove`] followed by
 	/// [`self.insert`]. However, given large quantities of nodes to move, it may be more efficient
 	/// to call [`self.remove_many`] followed by [`self.insert_many`].
 	pub(crate) fn update_position_for(
-		node: Node<T>,
-		new_weight: VoteWeight,
-	) -> Option<(VoteWeight, VoteWeight)> {
-		node.is_misplaced(new_weight).then(move || {
-			let old_bag_upper = node.bag_upper;
+		node: Node<T, I>,
+		new_weight: T::Score,
+	) -> Option<(T::Score, T::Score)> {
+		sp_tracing::enter_span!(sp_tracing::debug_span!(
+			target: crate::LOG_TARGET,
+			"rebag",
+			account = ?node.id,
+			bag = ?node.bag_upper,
+			score = ?new_weight,
+		));
+		node.destination(new_weight)
+			.map(move |new_bag_upper| Self::move_node_unchecked(node, new_bag_upper, new_weight))
+	}
 
-			if !node.is_terminal() {
-				// this node is not a head or a tail, so we can just cut it out of the list. update
-				// and put the prev and next of this node, we do `node.put` inside `insert_note`.
-				node.excise();
-			} else if let Some(mut bag) = Bag::<T>::get(node.bag_upper) {
-				// this is a head or tail, so the bag must be updated.
-				bag.remove_node_unchecked(&node);
-				bag.put();
-			} else {
-				crate::log!(
-					error,
-					"Node {:?} did not have a bag; ListBags is in an inconsistent state",
-					node.id,
-				);
-				debug_assert!(false, "every node must have an extant bag associated with it");
-			}
+	/// Move `node` into the bag `new_bag_upper`, wherever its score belongs, and record
+	/// `new_score` as its score.
+	///
+	/// Returns `(old_bag_upper, new_bag_upper)`. This is naive: `new_bag_upper` must be a
+	/// threshold, and not the current bag of `node`.
+	fn move_node_unchecked(
+		node: Node<T, I>,
+		new_bag_upper: T::Score,
+		new_score: T::Score,
+	) -> (T::Score, T::Score) {
+		let old_bag_upper = node.bag_upper;
+		let old_score = node.score;
+
+		if !node.is_terminal() {
+			// this node is not a head or a tail, so we can just cut it out of the list. update
+			// and put the prev and next of this node, we do `node.put` inside `insert_note`.
+			node.excise();
+		} else if let Some(mut bag) = Bag::<T, I>::get(node.bag_upper) {
+			// this is a head or tail, so the bag must be updated.
+			bag.remove_node_unchecked(&node);
+			bag.put();
+		} else {
+			crate::log!(
+				error,
+				"Node {:?} did not have a bag; ListBags is in an inconsistent state",
+				node.id,
+			);
+			debug_assert!(false, "every node must have an extant bag associated with it");
+		}
+		Self::decrement_bag_count(old_bag_upper, old_score);
+
+		// put the node into the appropriate new bag.
+		let mut bag = Bag::<T, I>::get_or_make(new_bag_upper);
+		// prev, next, and bag_upper of the node are updated inside `insert_node`, also
+		// `node.put` is in there.
+		let mut node = node;
+		node.score = new_score;
+		Self::note_placed(&node.id);
+		bag.insert_node_unchecked(node);
+		bag.put();
+		Self::increment_bag_count(new_bag_upper, 1, new_score);
+
+		(old_bag_upper, new_bag_upper)
+	}
+
+	/// Record that `id` was just placed into a bag, which ends any placement of `id` by
+	/// [`Self::force_set_bag`].
+	fn note_placed(id: &T::AccountId) {
+		crate::LastPlaced::<T, I>::insert(id, frame_system::Pallet::<T>::block_number());
+		crate::ForcedBags::<T, I>::remove(id);
+	}
 
-			// put the node into the appropriate new bag.
-			let new_bag_upper = notional_bag_for::<T>(new_weight);
-			let mut bag = Bag::<T>::get_or_make(new_bag_upper);
-			// prev, next, and bag_upper of the node are updated inside `insert_node`, also
-			// `node.put` is in there.
-			bag.insert_node_unchecked(node);
+	/// Move `id` into the bag `bag_upper`, regardless of its score and of [`Config::MaxBagSize`].
+	///
+	/// `id` is then not misplaced until its score belongs in another bag than it does now, see
+	/// [`crate::ForcedBags`]. Returns `Some(old_bag_upper)` if `id` moved, or `None` if it already
+	/// was in `bag_upper`.
+	pub(crate) fn force_set_bag(
+		id: &T::AccountId,
+		bag_upper: T::Score,
+	) -> Result<Option<T::Score>, crate::pallet::Error<T, I>> {
+		use crate::pallet;
+
+		let is_threshold = bag_upper == T::Score::max_value() ||
+			thresholds::<T, I>().binary_search(&bag_upper).is_ok();
+		frame_support::ensure!(is_threshold, pallet::Error::NotAThreshold);
+
+		let node = Node::<T, I>::get(id).ok_or(pallet::Error::NotInList)?;
+		let score = T::ScoreProvider::score(id);
+		let moved = (node.bag_upper != bag_upper)
+			.then(|| Self::move_node_unchecked(node, bag_upper, score).0);
+		crate::ForcedBags::<T, I>::insert(id, notional_bag_for::<T, I>(score));
+		Ok(moved)
+	}
+
+	/// Repair the links of `id`, if they disagree with those of its neighbours or of its bag.
+	///
+	/// `id` is unlinked defensively: a node or bag that `id` points to is only relinked if it
+	/// points back at `id`. Otherwise, the node that does is searched for by walking at most
+	/// `max_walk` nodes of the bag of `id`, from the head for its prev and from the tail for its
+	/// next. `id` is then reinserted into the bag of its current score.
+	///
+	/// Fails with `NotBroken` if the links of `id` are sound, and with `StillBroken` if `id` or
+	/// its former neighbours are not sound afterwards, in which case the storage changes must be
+	/// discarded. Returns the bag `id` is now in.
+	pub(crate) fn heal(
+		id: &T::AccountId,
+		max_walk: u32,
+	) -> Result<T::Score, crate::pallet::Error<T, I>> {
+		use crate::pallet;
+		use frame_support::ensure;
+
+		let node = Node::<T, I>::get(id).ok_or(pallet::Error::NotInList)?;
+		ensure!(node.check_links().is_err(), pallet::Error::NotBroken);
+
+		let bag = Bag::<T, I>::get(node.bag_upper);
+		let prev = node.prev().filter(|prev| prev.next.as_ref() == Some(id)).or_else(|| {
+			let mut forward = bag.iter().flat_map(|bag| bag.iter()).take(max_walk as usize);
+			forward.find(|n| n.next.as_ref() == Some(id))
+		});
+		let next = node.next().filter(|next| next.prev.as_ref() == Some(id)).or_else(|| {
+			let mut backward = bag.iter().flat_map(|bag| bag.iter_rev()).take(max_walk as usize);
+			backward.find(|n| n.prev.as_ref() == Some(id))
+		});
+		let prev_id = prev.as_ref().map(|prev| prev.id.clone());
+		let next_id = next.as_ref().map(|next| next.id.clone());
+
+		// link the neighbours of `id` to each other, and its bag past it.
+		if let Some(mut prev) = prev {
+			prev.next = next_id.clone();
+			prev.put();
+		}
+		if let Some(mut next) = next {
+			next.prev = prev_id.clone();
+			next.put();
+		}
+		if let Some(mut bag) = bag {
+			if bag.head.as_ref() == Some(id) {
+				bag.head = next_id.clone();
+			}
+			if bag.tail.as_ref() == Some(id) {
+				bag.tail = prev_id.clone();
+			}
 			bag.put();
+		}
+		Self::decrement_bag_count(node.bag_upper, node.score);
+		node.remove_from_storage_unchecked();
+
+		let bag_upper = Self::try_insert(id.clone(), T::ScoreProvider::score(id))?;
+
+		let healed = sp_std::iter::once(id)
+			.chain(prev_id.as_ref())
+			.chain(next_id.as_ref())
+			.filter_map(Node::<T, I>::get)
+			.all(|node| node.check_links().is_ok());
+		ensure!(healed, pallet::Error::StillBroken);
+		Ok(bag_upper)
+	}
 
-			(old_bag_upper, new_bag_upper)
-		})
+	/// Put `heavier_id` to the position directly in front of `lighter_id`. Both ids must be in the
+	/// same bag and the cached score of `lighter_id` must be less than that of `heavier_id`, see
+	/// [`Node::score`].
+	pub(crate) fn put_in_front_of(
+		lighter_id: &T::AccountId,
+		heavier_id: &T::AccountId,
+	) -> Result<(), crate::pallet::Error<T, I>> {
+		use crate::pallet;
+		use frame_support::ensure;
+
+		let lighter_node = Node::<T, I>::get(lighter_id).ok_or(pallet::Error::NotInList)?;
+		let heavier_node = Node::<T, I>::get(heavier_id).ok_or(pallet::Error::NotInList)?;
+
+		ensure!(lighter_node.bag_upper == heavier_node.bag_upper, pallet::Error::NotInSameBag);
+		// the nodes are compared by the scores they were placed into the bag with.
+		ensure!(heavier_node.score > lighter_node.score, pallet::Error::NotHeavier);
+
+		// remove the heavier node from this list. Note that this removes the node from storage and
+		// decrements the node counter.
+		Self::remove(heavier_id);
+
+		// re-fetch `lighter_node` from storage since it may have been updated when `heavier_node`
+		// was removed.
+		let lighter_node = Node::<T, I>::get(lighter_id).ok_or_else(|| {
+			debug_assert!(false, "id that should exist cannot be found");
+			crate::log!(warn, "id that should exist cannot be found");
+			pallet::Error::NotInList
+		})?;
+
+		// insert `heavier_node` directly in front of `lighter_node`. This will update both nodes
+		// in storage and update the node counter.
+		Self::insert_at_unchecked(lighter_node, heavier_node);
+
+		Ok(())
+	}
+
+	/// Set the score that `id` was placed with to `score`, keeping the counters of its bag in
+	/// line, e.g. to set up the comparison of [`Self::put_in_front_of`].
+	#[cfg(any(test, feature = "runtime-benchmarks"))]
+	pub(crate) fn set_cached_score(id: &T::AccountId, score: T::Score) {
+		if let Some(mut node) = Node::<T, I>::get(id) {
+			Self::decrement_bag_count(node.bag_upper, node.score);
+			Self::increment_bag_count(node.bag_upper, 1, score);
+			node.score = score;
+			node.put();
+		}
 	}
 
-	/// Sanity check the list.
+	/// Insert `node` directly in front of `at`.
+	///
+	/// This is a naive function in that it does not check if `node` belongs to the same bag as
+	/// `at`, nor that `node` has already been removed from the list. It is expected that the call
+	/// site will check these preconditions.
+	fn insert_at_unchecked(mut at: Node<T, I>, mut node: Node<T, I>) {
+		// connect `node` to its new `prev`.
+		node.prev = at.prev.clone();
+		if let Some(mut prev) = at.prev() {
+			prev.next = Some(node.id().clone());
+			prev.put()
+		}
+
+		// connect `node` and `at`.
+		node.next = Some(at.id().clone());
+		at.prev = Some(node.id().clone());
+
+		if node.prev.is_none() {
+			// `node` is the new head, so we make sure the bag is updated. Note, since `node` is
+			// always in front of `at` we know that there are always at least 2 nodes in the bag
+			// and only `node` could be the new head; the tail never changes.
+			match Bag::<T, I>::get(at.bag_upper) {
+				Some(mut bag) => {
+					bag.head = Some(node.id().clone());
+					bag.put()
+				},
+				None => {
+					crate::log!(
+						error,
+						"Node {:?} did not have a bag; ListBags is in an inconsistent state",
+						at.id,
+					);
+					debug_assert!(false, "every node must have an extant bag associated with it");
+				},
+			}
+		};
+
+		// write the updated nodes to storage.
+		let (bag_upper, score) = (at.bag_upper, node.score);
+		at.put();
+		node.put();
+
+		// account for `node` being added to the list.
+		Self::increment_bag_count(bag_upper, 1, score);
+	}
+
+	/// Account for `added` ids with a total of `score` being added to the bag `bag_upper`.
+	fn increment_bag_count(bag_upper: T::Score, added: u32, score: T::Score) {
+		crate::CounterForBag::<T, I>::mutate(bag_upper, |count| {
+			*count = count.saturating_add(added)
+		});
+		crate::BagHistogram::<T, I>::mutate(|histogram| {
+			match histogram.binary_search_by_key(&bag_upper, |occupancy| occupancy.bag_upper) {
+				Ok(idx) => {
+					let occupancy = &mut histogram[idx];
+					occupancy.count = occupancy.count.saturating_add(added);
+					occupancy.total_score = occupancy.total_score.saturating_add(score);
+				},
+				Err(idx) => {
+					let occupancy =
+						crate::BagOccupancy { bag_upper, count: added, total_score: score };
+					if histogram.try_insert(idx, occupancy).is_err() {
+						crate::log!(error, "no room in the histogram for bag {:?}", bag_upper);
+						debug_assert!(false, "the histogram has room for every threshold");
+					}
+				},
+			}
+		});
+	}
+
+	/// Account for an id with `score` being removed from the bag `bag_upper`, removing the
+	/// counter and the histogram entry once the bag is empty.
+	fn decrement_bag_count(bag_upper: T::Score, score: T::Score) {
+		crate::CounterForBag::<T, I>::mutate_exists(bag_upper, |maybe_count| {
+			*maybe_count = maybe_count.and_then(|count| count.checked_sub(1)).filter(|c| *c > 0);
+		});
+		crate::BagHistogram::<T, I>::mutate(|histogram| {
+			if let Ok(idx) =
+				histogram.binary_search_by_key(&bag_upper, |occupancy| occupancy.bag_upper)
+			{
+				let occupancy = &mut histogram[idx];
+				occupancy.count = occupancy.count.saturating_sub(1);
+				// once saturated, the total is unknown until the bag is emptied.
+				if occupancy.total_score != T::Score::max_value() {
+					occupancy.total_score = occupancy.total_score.saturating_sub(score);
+				}
+				if occupancy.count == 0 {
+					histogram.remove(idx);
+				}
+			}
+		});
+	}
+
+	/// Sanity check the list, as thoroughly as `level` demands.
 	///
 	/// This should be called from the call-site, whenever one of the mutating apis (e.g. `insert`)
-	/// is being used, after all other staking data (such as counter) has been updated. It checks:
+	/// is being used, after all other staking data (such as counter) has been updated. Depending on
+	/// `level`, it checks:
 	///
-	/// * there are no duplicate ids,
-	/// * length of this list is in sync with `CounterForListNodes`,
-	/// * and sanity-checks all bags. This will cascade down all the checks and makes sure all bags
-	///   are checked per *any* update to `List`.
-	#[cfg(feature = "std")]
-	pub(crate) fn sanity_check() -> Result<(), &'static str> {
+	/// * [`SanityCheckLevel::Counter`]: length of this list is in sync with the count of
+	///   `ListNodes`.
+	/// * [`SanityCheckLevel::Links`]: additionally, there are no duplicate ids, and sanity-checks
+	///   all bags. This will cascade down all the checks and makes sure all bags are checked per
+	///   *any* update to `List`. The length of every bag must match its `CounterForBag`, and its
+	///   count and total score in the `BagHistogram`.
+	/// * [`SanityCheckLevel::Full`]: additionally, every node is in the bag which its `bag_upper`
+	///   names, and is that bag's head or tail if it is terminal.
+	#[cfg(any(feature = "std", feature = "try-runtime", feature = "runtime-benchmarks"))]
+	pub(crate) fn sanity_check(level: SanityCheckLevel) -> Result<(), &'static str> {
 		use frame_support::ensure;
-		let mut seen_in_list = BTreeSet::new();
-		ensure!(
-			Self::iter().map(|node| node.id).all(|id| seen_in_list.insert(id)),
-			"duplicate identified",
-		);
+		let stored_count = crate::ListNodes::<T, I>::count();
+		let nodes_count = crate::ListNodes::<T, I>::iter().count() as u32;
+
+		if level >= SanityCheckLevel::Links {
+			let mut seen_in_list = BTreeSet::new();
+			ensure!(
+				Self::iter().map(|node| node.id).all(|id| seen_in_list.insert(id)),
+				"duplicate identified",
+			);
 
-		let iter_count = Self::iter().count() as u32;
-		let stored_count = crate::CounterForListNodes::<T>::get();
-		let nodes_count = crate::ListNodes::<T>::iter().count() as u32;
-		ensure!(iter_count == stored_count, "iter_count != stored_count");
-		ensure!(stored_count == nodes_count, "stored_count != nodes_count");
+			// until they are migrated, the ids in the bags of removed thresholds are not iterated.
+			let iter_count = Self::iter().count() as u32;
+			ensure!(
+				iter_count == stored_count ||
+					(crate::PendingThresholdMigration::<T, I>::exists() &&
+						iter_count < stored_count),
+				"iter_count != stored_count"
+			);
+		}
 
+		ensure!(stored_count == nodes_count, "stored_count != nodes_count");
 		crate::log!(debug, "count of nodes: {}", stored_count);
 
+		ensure!(
+			crate::PendingRemovals::<T, I>::iter()
+				.all(|(id, _)| crate::ListNodes::<T, I>::contains_key(id)),
+			"pending removal has no node",
+		);
+
+		if level == SanityCheckLevel::Counter {
+			return Ok(())
+		}
+
 		let active_bags = {
-			let thresholds = T::BagThresholds::get().iter().copied();
-			let thresholds: Vec<u64> = if thresholds.clone().last() == Some(VoteWeight::MAX) {
-				// in the event that they included it, we don't need to make any changes
-				// Box::new(thresholds.collect()
-				thresholds.collect()
-			} else {
-				// otherwise, insert it here.
-				thresholds.chain(iter::once(VoteWeight::MAX)).collect()
-			};
-			thresholds.into_iter().filter_map(|t| Bag::<T>::get(t))
+			// the bags of the thresholds that the ids are still being migrated from are active
+			// too, and `T::Score::max_value()` is an implied threshold.
+			let thresholds: Vec<T::Score> = thresholds::<T, I>()
+				.into_iter()
+				.chain(crate::PendingThresholdMigration::<T, I>::get().unwrap_or_default())
+				.chain(iter::once(T::Score::max_value()))
+				.collect::<BTreeSet<_>>()
+				.into_iter()
+				.collect();
+			thresholds.into_iter().filter_map(|t| Bag::<T, I>::get(t))
 		};
 
 		let _ = active_bags.clone().map(|b| b.sanity_check()).collect::<Result<_, _>>()?;
@@ -429,40 +1298,154 @@ impl<T: Config> List<T> {
 			active_bags.clone().fold(0u32, |acc, cur| acc + cur.iter().count() as u32);
 		ensure!(nodes_count == nodes_in_bags_count, "stored_count != nodes_in_bags_count");
 
-		crate::log!(debug, "count of active bags {}", active_bags.count());
+		ensure!(
+			active_bags
+				.clone()
+				.all(|b| b.iter().count() as u32 == crate::CounterForBag::<T, I>::get(b.bag_upper)),
+			"bag length != CounterForBag"
+		);
+		// since every active bag matches its counter, this ensures there are no stale counters.
+		ensure!(
+			crate::CounterForBag::<T, I>::iter_values().sum::<u32>() == nodes_count,
+			"CounterForBag values do not sum to stored_count"
+		);
+
+		let histogram = crate::BagHistogram::<T, I>::get();
+		ensure!(
+			histogram.len() == crate::CounterForBag::<T, I>::iter_keys().count(),
+			"BagHistogram and CounterForBag do not have the same bags"
+		);
+		ensure!(
+			histogram.iter().all(|occupancy| {
+				let nodes = Bag::<T, I>::get(occupancy.bag_upper)
+					.map(|bag| bag.iter().collect::<Vec<_>>())
+					.unwrap_or_default();
+				let total_score = nodes
+					.iter()
+					.fold(T::Score::default(), |total, node| total.saturating_add(node.score));
+				nodes.len() as u32 == occupancy.count &&
+					(total_score == occupancy.total_score ||
+						occupancy.total_score == T::Score::max_value())
+			}),
+			"BagHistogram does not match the bags"
+		);
+
+		crate::log!(debug, "count of active bags {}", active_bags.clone().count());
+
+		if level == SanityCheckLevel::Links {
+			return Ok(())
+		}
 
-		// check that all nodes are sane. We check the `ListNodes` storage item directly in case we
-		// have some "stale" nodes that are not in a bag.
-		for (_id, node) in crate::ListNodes::<T>::iter() {
-			node.sanity_check()?
+		// check that all nodes are sane. Since the bags hold as many distinct nodes as `ListNodes`,
+		// there are no "stale" nodes that are not in a bag, and iterating the bags reaches them all.
+		for bag in active_bags {
+			for node in bag.iter() {
+				node.sanity_check(&bag)?
+			}
 		}
 
 		Ok(())
 	}
 
-	#[cfg(not(feature = "std"))]
-	pub(crate) fn sanity_check() -> Result<(), &'static str> {
+	#[cfg(not(any(feature = "std", feature = "try-runtime", feature = "runtime-benchmarks")))]
+	pub(crate) fn sanity_check(_level: SanityCheckLevel) -> Result<(), &'static str> {
 		Ok(())
 	}
 
+	/// Check the links of at most `max_nodes` nodes, resuming from the one after
+	/// [`crate::SanityCheckCursor`], and wrapping around once all of `ListNodes` is checked.
+	///
+	/// Unlike [`Self::sanity_check`], every node is checked on its own, with a constant number of
+	/// reads: its bag exists, and its neighbours exist in the same bag and point back to it, or it
+	/// is the head (tail) of its bag if it has no previous (next) node. Over a full pass, this
+	/// ensures that the bags are consistent doubly linked lists, but not that the counters are
+	/// right.
+	///
+	/// Returns the number of nodes checked. On error, the cursor is left before the faulty node, so
+	/// that the next call reports it again.
+	pub(crate) fn sanity_check_bounded(max_nodes: u32) -> Result<u32, &'static str> {
+		let mut nodes = match crate::SanityCheckCursor::<T, I>::get() {
+			Some(last) =>
+				crate::ListNodes::<T, I>::iter_from(crate::ListNodes::<T, I>::hashed_key_for(last)),
+			None => crate::ListNodes::<T, I>::iter(),
+		};
+
+		let mut checked = 0u32;
+		let mut last = None;
+		let mut exhausted = false;
+		let result = loop {
+			if checked >= max_nodes {
+				break Ok(checked)
+			}
+			match nodes.next() {
+				Some((id, node)) => {
+					if let Err(err) = node.check_links() {
+						break Err(err)
+					}
+					last = Some(id);
+					checked += 1;
+				},
+				None => {
+					// reached the end, start from the beginning next time.
+					exhausted = true;
+					break Ok(checked)
+				},
+			}
+		};
+
+		match (exhausted, last) {
+			(true, _) => crate::SanityCheckCursor::<T, I>::kill(),
+			(false, Some(last)) => crate::SanityCheckCursor::<T, I>::put(last),
+			(false, None) => (),
+		}
+
+		result
+	}
+
+	/// The head, tail and number of ids of the bag of each threshold, from the lowest threshold to
+	/// the highest.
+	pub(crate) fn bag_stats() -> Vec<crate::BagStats<T::AccountId, T::Score>> {
+		let thresholds = thresholds::<T, I>();
+		let includes_max = thresholds.last() == Some(&T::Score::max_value());
+		let iter = thresholds.into_iter();
+		let iter: Box<dyn Iterator<Item = T::Score>> = if includes_max {
+			Box::new(iter)
+		} else {
+			// we must explicitly include the `T::Score::max_value()` bag.
+			Box::new(iter.chain(iter::once(T::Score::max_value())))
+		};
+
+		iter.map(|bag_upper| {
+			let (head, tail) =
+				Bag::<T, I>::get(bag_upper).map_or((None, None), |bag| (bag.head, bag.tail));
+			crate::BagStats {
+				bag_upper,
+				head,
+				tail,
+				count: crate::CounterForBag::<T, I>::get(bag_upper),
+			}
+		})
+		.collect()
+	}
+
 	/// Returns the nodes of all non-empty bags. For testing and benchmarks.
 	#[cfg(any(feature = "std", feature = "runtime-benchmarks"))]
 	#[allow(dead_code)]
-	pub(crate) fn get_bags() -> Vec<(VoteWeight, Vec<T::AccountId>)> {
-		use frame_support::traits::Get as _;
-
-		let thresholds = T::BagThresholds::get();
-		let iter = thresholds.iter().copied();
-		let iter: Box<dyn Iterator<Item = u64>> = if thresholds.last() == Some(&VoteWeight::MAX) {
+	pub(crate) fn get_bags() -> Vec<(T::Score, Vec<T::AccountId>)> {
+		let thresholds = thresholds::<T, I>();
+		let includes_max = thresholds.last() == Some(&T::Score::max_value());
+		let iter = thresholds.into_iter();
+		let iter: Box<dyn Iterator<Item = T::Score>> = if includes_max {
 			// in the event that they included it, we can just pass the iterator through unchanged.
 			Box::new(iter)
 		} else {
 			// otherwise, insert it here.
-			Box::new(iter.chain(sp_std::iter::once(VoteWeight::MAX)))
+			Box::new(iter.chain(sp_std::iter::once(T::Score::max_value())))
 		};
 
 		iter.filter_map(|t| {
-			Bag::<T>::get(t).map(|bag| (t, bag.iter().map(|n| n.id().clone()).collect::<Vec<_>>()))
+			Bag::<T, I>::get(t)
+				.map(|bag| (t, bag.iter().map(|n| n.id().clone()).collect::<Vec<_>>()))
 		})
 		.collect::<Vec<_>>()
 	}
@@ -476,30 +1459,32 @@ impl<T: Config> List<T> {
 /// iteration so that there's no incentive to churn ids positioning to improve the chances of
 /// appearing within the ids set.
 #[derive(DefaultNoBound, Encode, Decode, MaxEncodedLen, TypeInfo)]
-#[codec(mel_bound(T: Config))]
-#[scale_info(skip_type_params(T))]
+#[codec(mel_bound(T: Config<I>))]
+#[scale_info(skip_type_params(T, I))]
 #[cfg_attr(feature = "std", derive(frame_support::DebugNoBound, Clone, PartialEq))]
-pub struct Bag<T: Config> {
+pub struct Bag<T: Config<I>, I: 'static = ()> {
 	head: Option<T::AccountId>,
 	tail: Option<T::AccountId>,
 
 	#[codec(skip)]
-	bag_upper: VoteWeight,
+	bag_upper: T::Score,
+	#[codec(skip)]
+	_phantom: PhantomData<I>,
 }
 
-impl<T: Config> Bag<T> {
+impl<T: Config<I>, I: 'static> Bag<T, I> {
 	#[cfg(test)]
 	pub(crate) fn new(
 		head: Option<T::AccountId>,
 		tail: Option<T::AccountId>,
-		bag_upper: VoteWeight,
+		bag_upper: T::Score,
 	) -> Self {
-		Self { head, tail, bag_upper }
+		Self { head, tail, bag_upper, _phantom: PhantomData }
 	}
 
 	/// Get a bag by its upper vote weight.
-	pub(crate) fn get(bag_upper: VoteWeight) -> Option<Bag<T>> {
-		crate::ListBags::<T>::try_get(bag_upper).ok().map(|mut bag| {
+	pub(crate) fn get(bag_upper: T::Score) -> Option<Bag<T, I>> {
+		crate::ListBags::<T, I>::try_get(bag_upper).ok().map(|mut bag| {
 			bag.bag_upper = bag_upper;
 			bag
 		})
@@ -507,7 +1492,7 @@ impl<T: Config> Bag<T> {
 
 	/// Get a bag by its upper vote weight or make it, appropriately initialized. Does not check if
 	/// if `bag_upper` is a valid threshold.
-	fn get_or_make(bag_upper: VoteWeight) -> Bag<T> {
+	fn get_or_make(bag_upper: T::Score) -> Bag<T, I> {
 		Self::get(bag_upper).unwrap_or(Bag { bag_upper, ..Default::default() })
 	}
 
@@ -519,49 +1504,114 @@ impl<T: Config> Bag<T> {
 	/// Put the bag back into storage.
 	fn put(self) {
 		if self.is_empty() {
-			crate::ListBags::<T>::remove(self.bag_upper);
+			crate::ListBags::<T, I>::remove(self.bag_upper);
 		} else {
-			crate::ListBags::<T>::insert(self.bag_upper, self);
+			crate::ListBags::<T, I>::insert(self.bag_upper, self);
 		}
 	}
 
 	/// Get the head node in this bag.
-	fn head(&self) -> Option<Node<T>> {
+	fn head(&self) -> Option<Node<T, I>> {
 		self.head.as_ref().and_then(|id| Node::get(id))
 	}
 
 	/// Get the tail node in this bag.
-	fn tail(&self) -> Option<Node<T>> {
+	fn tail(&self) -> Option<Node<T, I>> {
 		self.tail.as_ref().and_then(|id| Node::get(id))
 	}
 
 	/// Iterate over the nodes in this bag.
-	pub(crate) fn iter(&self) -> impl Iterator<Item = Node<T>> {
-		sp_std::iter::successors(self.head(), |prev| prev.next())
+	pub(crate) fn iter(&self) -> impl Iterator<Item = Node<T, I>> {
+		self.iter_capped(traversal_cap::<T, I>())
+	}
+
+	/// Iterate over the nodes in this bag, visiting at most `cap` of them.
+	fn iter_capped(&self, cap: u32) -> impl Iterator<Item = Node<T, I>> {
+		traverse(self.head(), Node::next, cap)
+	}
+
+	/// Iterate over the nodes in this bag, from the tail to the head.
+	pub(crate) fn iter_rev(&self) -> impl Iterator<Item = Node<T, I>> {
+		self.iter_rev_capped(traversal_cap::<T, I>())
+	}
+
+	/// Iterate over the nodes in this bag, from the tail to the head, visiting at most `cap` of
+	/// them.
+	fn iter_rev_capped(&self, cap: u32) -> impl Iterator<Item = Node<T, I>> {
+		traverse(self.tail(), Node::prev, cap)
 	}
 
 	/// Insert a new id into this bag.
 	///
 	/// This is private on purpose because it's naive: it doesn't check whether this is the
-	/// appropriate bag for this id at all. Generally, use [`List::insert`] instead.
+	/// appropriate bag for this id at all. Generally, use [`List::try_insert`] instead.
 	///
 	/// Storage note: this modifies storage, but only for the nodes. You still need to call
 	/// `self.put()` after use.
-	fn insert_unchecked(&mut self, id: T::AccountId) {
+	fn insert_unchecked(&mut self, id: T::AccountId, score: T::Score) {
 		// insert_node will overwrite `prev`, `next` and `bag_upper` to the proper values. As long
 		// as this bag is the correct one, we're good. All calls to this must come after getting the
 		// correct [`notional_bag_for`].
-		self.insert_node_unchecked(Node::<T> { id, prev: None, next: None, bag_upper: 0 });
+		self.insert_node_unchecked(Node::<T, I> {
+			id,
+			prev: None,
+			next: None,
+			bag_upper: Default::default(),
+			score,
+			_phantom: PhantomData,
+		});
 	}
 
-	/// Insert a node into this bag.
+	/// Insert a node into this bag, at the end dictated by [`Config::BagInsertionPolicy`].
 	///
 	/// This is private on purpose because it's naive; it doesn't check whether this is the
-	/// appropriate bag for this node at all. Generally, use [`List::insert`] instead.
+	/// appropriate bag for this node at all. Generally, use [`List::try_insert`] instead.
 	///
 	/// Storage note: this modifies storage, but only for the node. You still need to call
 	/// `self.put()` after use.
-	fn insert_node_unchecked(&mut self, mut node: Node<T>) {
+	fn insert_node_unchecked(&mut self, node: Node<T, I>) {
+		match T::BagInsertionPolicy::get() {
+			BagInsertion::Tail => self.push_back_unchecked(node),
+			BagInsertion::Head => self.push_front_unchecked(node),
+		}
+	}
+
+	/// Insert a node into this bag, as its new head. See [`Self::insert_node_unchecked`].
+	fn push_front_unchecked(&mut self, mut node: Node<T, I>) {
+		if let Some(head) = &self.head {
+			if *head == node.id {
+				// this should never happen, but this check prevents one path to a worst case
+				// infinite loop.
+				debug_assert!(false, "system logic error: inserting a node who has the id of head");
+				crate::log!(warn, "system logic error: inserting a node who has the id of head");
+				return
+			};
+		}
+
+		node.bag_upper = self.bag_upper;
+
+		let id = node.id.clone();
+		// update this node now, treating it as the new head.
+		node.prev = None;
+		node.next = self.head.clone();
+		node.put();
+
+		// update the previous head.
+		if let Some(mut old_head) = self.head() {
+			old_head.prev = Some(id.clone());
+			old_head.put();
+		}
+		self.head = Some(id.clone());
+
+		// ensure tail exist, in case this is the first insertion into the bag.
+		if self.tail.is_none() {
+			self.tail = Some(id);
+			debug_assert!(self.iter().count() == 1);
+		}
+	}
+
+	/// Insert a node into this bag, as its new tail. See [`Self::insert_node_unchecked`].
+	fn push_back_unchecked(&mut self, mut node: Node<T, I>) {
 		if let Some(tail) = &self.tail {
 			if *tail == node.id {
 				// this should never happen, but this check prevents one path to a worst case
@@ -605,7 +1655,7 @@ impl<T: Config> Bag<T> {
 	///
 	/// Storage note: this modifies storage, but only for adjacent nodes. You still need to call
 	/// `self.put()` and `ListNodes::remove(id)` to update storage for the bag and `node`.
-	fn remove_node_unchecked(&mut self, node: &Node<T>) {
+	fn remove_node_unchecked(&mut self, node: &Node<T, I>) {
 		// reassign neighboring nodes.
 		node.excise();
 
@@ -626,7 +1676,7 @@ impl<T: Config> Bag<T> {
 	/// * Ensures head has no prev.
 	/// * Ensures tail has no next.
 	/// * Ensures there are no loops, traversal from head to tail is correct.
-	#[cfg(feature = "std")]
+	#[cfg(any(feature = "std", feature = "try-runtime", feature = "runtime-benchmarks"))]
 	fn sanity_check(&self) -> Result<(), &'static str> {
 		frame_support::ensure!(
 			self.head()
@@ -658,7 +1708,7 @@ impl<T: Config> Bag<T> {
 		Ok(())
 	}
 
-	#[cfg(not(feature = "std"))]
+	#[cfg(not(any(feature = "std", feature = "try-runtime", feature = "runtime-benchmarks")))]
 	fn sanity_check(&self) -> Result<(), &'static str> {
 		Ok(())
 	}
@@ -666,38 +1716,46 @@ impl<T: Config> Bag<T> {
 	/// Iterate over the nodes in this bag (public for tests).
 	#[cfg(feature = "std")]
 	#[allow(dead_code)]
-	pub fn std_iter(&self) -> impl Iterator<Item = Node<T>> {
-		sp_std::iter::successors(self.head(), |prev| prev.next())
-	}
-
-	/// Check if the bag contains a node with `id`.
-	#[cfg(feature = "std")]
-	fn contains(&self, id: &T::AccountId) -> bool {
-		self.iter().find(|n| n.id() == id).is_some()
+	pub fn std_iter(&self) -> impl Iterator<Item = Node<T, I>> {
+		self.iter()
 	}
 }
 
 /// A Node is the fundamental element comprising the doubly-linked list described by `Bag`.
 #[derive(Encode, Decode, MaxEncodedLen, TypeInfo)]
-#[codec(mel_bound(T: Config))]
-#[scale_info(skip_type_params(T))]
+#[codec(mel_bound(T: Config<I>))]
+#[scale_info(skip_type_params(T, I))]
 #[cfg_attr(feature = "std", derive(frame_support::DebugNoBound, Clone, PartialEq))]
-pub struct Node<T: Config> {
+pub struct Node<T: Config<I>, I: 'static = ()> {
 	id: T::AccountId,
 	prev: Option<T::AccountId>,
 	next: Option<T::AccountId>,
-	bag_upper: VoteWeight,
+	bag_upper: T::Score,
+	score: T::Score,
+	#[codec(skip)]
+	_phantom: PhantomData<I>,
 }
 
-impl<T: Config> Node<T> {
+impl<T: Config<I>, I: 'static> Node<T, I> {
+	/// Create a node from its parts, e.g. when migrating nodes from an older layout.
+	pub(crate) fn from_parts(
+		id: T::AccountId,
+		prev: Option<T::AccountId>,
+		next: Option<T::AccountId>,
+		bag_upper: T::Score,
+		score: T::Score,
+	) -> Self {
+		Self { id, prev, next, bag_upper, score, _phantom: PhantomData }
+	}
+
 	/// Get a node by id.
-	pub(crate) fn get(id: &T::AccountId) -> Option<Node<T>> {
-		crate::ListNodes::<T>::try_get(id).ok()
+	pub(crate) fn get(id: &T::AccountId) -> Option<Node<T, I>> {
+		crate::ListNodes::<T, I>::try_get(id).ok()
 	}
 
 	/// Put the node back into storage.
 	fn put(self) {
-		crate::ListNodes::<T>::insert(self.id.clone(), self);
+		crate::ListNodes::<T, I>::insert(self.id.clone(), self);
 	}
 
 	/// Update neighboring nodes to point to reach other.
@@ -721,26 +1779,40 @@ impl<T: Config> Node<T> {
 	///
 	/// It is naive because it does not check if the node has first been removed from its bag.
 	fn remove_from_storage_unchecked(&self) {
-		crate::ListNodes::<T>::remove(&self.id)
+		crate::ListNodes::<T, I>::remove(&self.id)
 	}
 
 	/// Get the previous node in the bag.
-	fn prev(&self) -> Option<Node<T>> {
+	fn prev(&self) -> Option<Node<T, I>> {
 		self.prev.as_ref().and_then(|id| Node::get(id))
 	}
 
 	/// Get the next node in the bag.
-	fn next(&self) -> Option<Node<T>> {
+	fn next(&self) -> Option<Node<T, I>> {
 		self.next.as_ref().and_then(|id| Node::get(id))
 	}
 
-	/// `true` when this voter is in the wrong bag.
-	pub(crate) fn is_misplaced(&self, current_weight: VoteWeight) -> bool {
-		notional_bag_for::<T>(current_weight) != self.bag_upper
+	/// `true` when this voter is in the wrong bag, and not kept there by [`List::force_set_bag`].
+	pub(crate) fn is_misplaced(&self, current_weight: T::Score) -> bool {
+		let notional = notional_bag_for::<T, I>(current_weight);
+		// an id placed by `force_set_bag` stays put while its score is in the same bag as then.
+		notional != self.bag_upper && crate::ForcedBags::<T, I>::get(&self.id) != Some(notional)
+	}
+
+	/// The bag this node is moved into if its position is updated with `current_weight`.
+	///
+	/// This is `None` if the node is in the right bag already, or if it is misplaced but cannot be
+	/// moved because of [`Config::BagOverflowPolicy`].
+	pub(crate) fn destination(&self, current_weight: T::Score) -> Option<T::Score> {
+		if !self.is_misplaced(current_weight) {
+			return None
+		}
+		bag_with_room_for::<T, I>(current_weight, Some(self.bag_upper), |_| 0)
+			.filter(|bag_upper| *bag_upper != self.bag_upper)
 	}
 
 	/// `true` when this voter is a bag head or tail.
-	fn is_terminal(&self) -> bool {
+	pub(crate) fn is_terminal(&self) -> bool {
 		self.prev.is_none() || self.next.is_none()
 	}
 
@@ -756,28 +1828,65 @@ impl<T: Config> Node<T> {
 		&self.id
 	}
 
-	/// The bag this nodes belongs to (public for benchmarks).
-	#[cfg(feature = "runtime-benchmarks")]
-	#[allow(dead_code)]
-	pub fn bag_upper(&self) -> VoteWeight {
+	/// The bag this nodes belongs to.
+	pub fn bag_upper(&self) -> T::Score {
 		self.bag_upper
 	}
 
-	#[cfg(feature = "std")]
-	fn sanity_check(&self) -> Result<(), &'static str> {
-		let expected_bag = Bag::<T>::get(self.bag_upper).ok_or("bag not found for node")?;
+	/// The score this node had when it was inserted, or last moved into another bag.
+	pub fn score(&self) -> T::Score {
+		self.score
+	}
+
+	/// The id of the previous node in the bag, or `None` if this is the head of the bag.
+	pub fn prev_id(&self) -> Option<&T::AccountId> {
+		self.prev.as_ref()
+	}
+
+	/// The id of the next node in the bag, or `None` if this is the tail of the bag.
+	pub fn next_id(&self) -> Option<&T::AccountId> {
+		self.next.as_ref()
+	}
+
+	/// Check that this node is consistently linked to its neighbours and its bag, without
+	/// iterating the bag.
+	fn check_links(&self) -> Result<(), &'static str> {
+		use frame_support::ensure;
+		let bag = Bag::<T, I>::get(self.bag_upper).ok_or("bag not found for node")?;
+
+		match &self.prev {
+			Some(prev) => {
+				let prev = Node::<T, I>::get(prev).ok_or("prev node not found")?;
+				ensure!(prev.next.as_ref() == Some(&self.id), "prev node does not point to node");
+				ensure!(prev.bag_upper == self.bag_upper, "prev node is in another bag");
+			},
+			None => ensure!(bag.head.as_ref() == Some(&self.id), "node without prev is not head"),
+		}
+		match &self.next {
+			Some(next) => {
+				let next = Node::<T, I>::get(next).ok_or("next node not found")?;
+				ensure!(next.prev.as_ref() == Some(&self.id), "next node does not point to node");
+				ensure!(next.bag_upper == self.bag_upper, "next node is in another bag");
+			},
+			None => ensure!(bag.tail.as_ref() == Some(&self.id), "node without next is not tail"),
+		}
+
+		Ok(())
+	}
 
+	/// Check that this node, found while iterating `bag`, is in the bag its `bag_upper` names, and
+	/// is the head or tail of it if it is terminal.
+	#[cfg(any(feature = "std", feature = "try-runtime", feature = "runtime-benchmarks"))]
+	fn sanity_check(&self, bag: &Bag<T, I>) -> Result<(), &'static str> {
 		let id = self.id();
 
 		frame_support::ensure!(
-			expected_bag.contains(id),
+			self.bag_upper == bag.bag_upper,
 			"node does not exist in the expected bag"
 		);
 
 		frame_support::ensure!(
-			!self.is_terminal() ||
-				expected_bag.head.as_ref() == Some(id) ||
-				expected_bag.tail.as_ref() == Some(id),
+			!self.is_terminal() || bag.head.as_ref() == Some(id) || bag.tail.as_ref() == Some(id),
 			"a terminal node is neither its bag head or tail"
 		);
 
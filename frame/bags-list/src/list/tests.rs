@@ -18,18 +18,26 @@
 use super::*;
 use crate::{
 	mock::{test_utils::*, *},
-	CounterForListNodes, ListBags, ListNodes,
+	ListBags, ListNodes,
 };
-use frame_election_provider_support::SortedListProvider;
-use frame_support::{assert_ok, assert_storage_noop};
+use frame_election_provider_support::{BudgetedIter, SortedListProvider, VoteWeight};
+use frame_support::{assert_ok, assert_storage_noop, weights::RuntimeDbWeight};
 
 #[test]
 fn basic_setup_works() {
 	ExtBuilder::default().build_and_execute(|| {
-		// syntactic sugar to create a raw node
-		let node = |id, prev, next, bag_upper| Node::<Runtime> { id, prev, next, bag_upper };
+		// syntactic sugar to create a raw node. The score of every genesis id is the upper
+		// threshold of its bag.
+		let node = |id, prev, next, bag_upper| Node::<Runtime> {
+			score: bag_upper,
+			id,
+			prev,
+			next,
+			bag_upper,
+			_phantom: PhantomData,
+		};
 
-		assert_eq!(CounterForListNodes::<Runtime>::get(), 4);
+		assert_eq!(ListNodes::<Runtime>::count(), 4);
 		assert_eq!(ListNodes::<Runtime>::iter().count(), 4);
 		assert_eq!(ListBags::<Runtime>::iter().count(), 2);
 
@@ -38,11 +46,11 @@ fn basic_setup_works() {
 		// the state of the bags is as expected
 		assert_eq!(
 			ListBags::<Runtime>::get(10).unwrap(),
-			Bag::<Runtime> { head: Some(1), tail: Some(1), bag_upper: 0 }
+			Bag::<Runtime> { head: Some(1), tail: Some(1), bag_upper: 0, _phantom: PhantomData }
 		);
 		assert_eq!(
 			ListBags::<Runtime>::get(1_000).unwrap(),
-			Bag::<Runtime> { head: Some(2), tail: Some(4), bag_upper: 0 }
+			Bag::<Runtime> { head: Some(2), tail: Some(4), bag_upper: 0, _phantom: PhantomData }
 		);
 
 		assert_eq!(ListNodes::<Runtime>::get(2).unwrap(), node(2, None, Some(3), 1_000));
@@ -64,25 +72,27 @@ fn basic_setup_works() {
 
 #[test]
 fn notional_bag_for_works() {
-	// under a threshold gives the next threshold.
-	assert_eq!(notional_bag_for::<Runtime>(0), 10);
-	assert_eq!(notional_bag_for::<Runtime>(9), 10);
+	ExtBuilder::default().build_and_execute(|| {
+		// under a threshold gives the next threshold.
+		assert_eq!(notional_bag_for::<Runtime, ()>(0), 10);
+		assert_eq!(notional_bag_for::<Runtime, ()>(9), 10);
 
-	// at a threshold gives that threshold.
-	assert_eq!(notional_bag_for::<Runtime>(10), 10);
+		// at a threshold gives that threshold.
+		assert_eq!(notional_bag_for::<Runtime, ()>(10), 10);
 
-	// above the threshold, gives the next threshold.
-	assert_eq!(notional_bag_for::<Runtime>(11), 20);
+		// above the threshold, gives the next threshold.
+		assert_eq!(notional_bag_for::<Runtime, ()>(11), 20);
 
-	let max_explicit_threshold = *<Runtime as Config>::BagThresholds::get().last().unwrap();
-	assert_eq!(max_explicit_threshold, 10_000);
+		let max_explicit_threshold = *<Runtime as Config>::BagThresholds::get().last().unwrap();
+		assert_eq!(max_explicit_threshold, 10_000);
 
-	// if the max explicit threshold is less than VoteWeight::MAX,
-	assert!(VoteWeight::MAX > max_explicit_threshold);
+		// if the max explicit threshold is less than VoteWeight::MAX,
+		assert!(VoteWeight::MAX > max_explicit_threshold);
 
-	// then anything above it will belong to the VoteWeight::MAX bag.
-	assert_eq!(notional_bag_for::<Runtime>(max_explicit_threshold), max_explicit_threshold);
-	assert_eq!(notional_bag_for::<Runtime>(max_explicit_threshold + 1), VoteWeight::MAX);
+		// then anything above it will belong to the VoteWeight::MAX bag.
+		assert_eq!(notional_bag_for::<Runtime, ()>(max_explicit_threshold), max_explicit_threshold);
+		assert_eq!(notional_bag_for::<Runtime, ()>(max_explicit_threshold + 1), VoteWeight::MAX);
+	});
 }
 
 #[test]
@@ -93,13 +103,13 @@ fn remove_last_node_in_bags_cleans_bag() {
 
 		// bump 1 to a bigger bag
 		List::<Runtime>::remove(&1);
-		assert_ok!(List::<Runtime>::insert(1, 10_000));
+		assert_ok!(List::<Runtime>::try_insert(1, 10_000));
 
 		// then the bag with bound 10 is wiped from storage.
 		assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![2, 3, 4]), (10_000, vec![1])]);
 
 		// and can be recreated again as needed.
-		assert_ok!(List::<Runtime>::insert(77, 10));
+		assert_ok!(List::<Runtime>::try_insert(77, 10));
 		assert_eq!(
 			List::<Runtime>::get_bags(),
 			vec![(10, vec![77]), (1_000, vec![2, 3, 4]), (10_000, vec![1])]
@@ -150,6 +160,120 @@ fn migrate_works() {
 mod list {
 	use super::*;
 
+	#[test]
+	fn iter_rev_works() {
+		ExtBuilder::default()
+			.add_ids(vec![(5, 2_000), (6, 2_000), (7, u64::MAX)])
+			.build_and_execute(|| {
+				// given
+				assert_eq!(get_list_as_ids(), vec![7, 5, 6, 2, 3, 4, 1]);
+
+				// then the lowest bag comes first, each from its tail to its head
+				assert_eq!(
+					List::<Runtime>::iter_rev().map(|n| *n.id()).collect::<Vec<_>>(),
+					vec![1, 4, 3, 2, 6, 5, 7]
+				);
+				assert_eq!(BagsList::iter_rev().collect::<Vec<_>>(), vec![1, 4, 3, 2, 6, 5, 7]);
+				assert_eq!(BagsList::iter_rev().take(2).collect::<Vec<_>>(), vec![1, 4]);
+			});
+	}
+
+	#[test]
+	fn iteration_is_truncated_on_cycles() {
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			let link = |id: AccountId, next: AccountId| {
+				crate::ListNodes::<Runtime>::mutate(id, |node| {
+					node.as_mut().unwrap().next = Some(next)
+				})
+			};
+
+			// given the tail of bag 1_000 links back to its head
+			link(4, 2);
+			// then the bag is iterated once
+			assert_eq!(get_list_as_ids(), vec![2, 3, 4, 1]);
+
+			// given a node links to itself
+			link(3, 3);
+			// then the iteration stops at it
+			assert_eq!(get_list_as_ids(), vec![2, 3, 1]);
+
+			// given the tail links back to the middle of the bag
+			link(3, 4);
+			link(4, 3);
+			// then the iteration stops at the first node visited twice
+			assert_eq!(get_list_as_ids(), vec![2, 3, 4, 1]);
+			assert_eq!(
+				List::<Runtime>::iter_bag(1_000, None).map(|n| *n.id()).collect::<Vec<_>>(),
+				vec![2, 3, 4]
+			);
+			// and the lower bags are still iterated after it.
+			assert_eq!(
+				List::<Runtime>::iter_from(&2).unwrap().map(|n| *n.id()).collect::<Vec<_>>(),
+				vec![3, 4, 1]
+			);
+
+			// and in any case after `cap` nodes.
+			assert_eq!(traverse(Node::<Runtime>::get(&2), Node::next, 2).count(), 2);
+		});
+	}
+
+	#[test]
+	fn iter_with_budget_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given every read costs 1
+			DbWeight::set(RuntimeDbWeight { read: 1, write: 0 });
+			let take = |start: Option<AccountId>, budget| {
+				List::<Runtime>::iter_with_budget(start.as_ref(), budget).unwrap()
+			};
+
+			// when the budget allows to read everything: the thresholds, the 10 bags, and the 4
+			// nodes.
+			let all = take(None, 15);
+
+			// then
+			assert_eq!(
+				all,
+				BudgetedIter {
+					ids: vec![2, 3, 4, 1],
+					consumed: 15,
+					cursor: Some(1),
+					exhausted: true
+				}
+			);
+			assert_eq!(all.ids, get_list_as_ids());
+			assert_eq!(take(None, 100), all);
+
+			// when the budget only allows to reach the first node of bag 1_000
+			assert_eq!(
+				take(None, 6),
+				BudgetedIter { ids: vec![2], consumed: 6, cursor: Some(2), exhausted: false }
+			);
+
+			// then the iteration can be resumed from it
+			assert_eq!(
+				take(Some(2), 3),
+				BudgetedIter { ids: vec![3], consumed: 3, cursor: Some(3), exhausted: false }
+			);
+			assert_eq!(
+				take(Some(4), 100),
+				BudgetedIter { ids: vec![1], consumed: 9, cursor: Some(1), exhausted: true }
+			);
+
+			// and a budget too small to read anything takes nothing
+			assert_eq!(
+				take(Some(2), 1),
+				BudgetedIter { ids: vec![], consumed: 1, cursor: Some(2), exhausted: false }
+			);
+			assert_eq!(
+				take(None, 0),
+				BudgetedIter { ids: vec![], consumed: 0, cursor: None, exhausted: false }
+			);
+
+			// an id not in the list can not be a start
+			assert_eq!(List::<Runtime>::iter_with_budget(Some(&42), 100), Err(Error::NodeNotFound));
+		});
+	}
+
 	#[test]
 	fn iteration_is_semi_sorted() {
 		ExtBuilder::default()
@@ -170,7 +294,7 @@ mod list {
 				);
 
 				// when adding an id that has a higher weight than pre-existing ids in the bag
-				assert_ok!(List::<Runtime>::insert(7, 10));
+				assert_ok!(List::<Runtime>::try_insert(7, 10));
 
 				// then
 				assert_eq!(
@@ -215,14 +339,14 @@ mod list {
 	fn insert_works() {
 		ExtBuilder::default().build_and_execute(|| {
 			// when inserting into an existing bag
-			assert_ok!(List::<Runtime>::insert(5, 1_000));
+			assert_ok!(List::<Runtime>::try_insert(5, 1_000));
 
 			// then
 			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4, 5])]);
 			assert_eq!(get_list_as_ids(), vec![2, 3, 4, 5, 1]);
 
 			// when inserting into a non-existent bag
-			assert_ok!(List::<Runtime>::insert(6, 1_001));
+			assert_ok!(List::<Runtime>::try_insert(6, 1_001));
 
 			// then
 			assert_eq!(
@@ -233,6 +357,111 @@ mod list {
 		});
 	}
 
+	#[test]
+	fn insert_many_matches_sequential_inserts() {
+		// a duplicate of an id in the list, a duplicate within the batch, an extension of an
+		// existing bag, a new bag, and an overflow into a lower bag.
+		let ids = [(5, 1_000), (6, 1_001), (3, 20), (7, 15), (5, 30), (8, 1_000)];
+		let weight_of = |id: &AccountId| ids.iter().find(|(i, _)| i == id).unwrap().1;
+		let state = || {
+			let mut nodes = crate::ListNodes::<Runtime>::iter().collect::<Vec<_>>();
+			nodes.sort_by_key(|(id, _)| *id);
+			(
+				nodes,
+				List::<Runtime>::get_bags(),
+				crate::ListBags::<Runtime>::iter().collect::<BTreeMap<_, _>>(),
+				crate::CounterForBag::<Runtime>::iter().collect::<BTreeMap<_, _>>(),
+				crate::BagHistogram::<Runtime>::get(),
+				ListNodes::<Runtime>::count(),
+				crate::LastPlaced::<Runtime>::iter().collect::<BTreeMap<_, _>>(),
+			)
+		};
+
+		MaxBagSize::set(4);
+		for (policy, bag_1_000) in
+			// the genesis ids are inserted with the same policy.
+			[(BagInsertion::Tail, vec![2, 3, 4, 5]), (BagInsertion::Head, vec![5, 4, 3, 2])]
+		{
+			BagInsertionPolicy::set(policy);
+			let sequential = ExtBuilder::default().build().execute_with(|| {
+				let inserted = ids
+					.iter()
+					.filter(|(id, _)| List::<Runtime>::try_insert(*id, weight_of(id)).is_ok());
+				assert_eq!(inserted.count(), 4);
+				state()
+			});
+
+			ExtBuilder::default().build_and_execute(|| {
+				let ids = ids.iter().map(|(id, _)| *id);
+				assert_eq!(List::<Runtime>::insert_many(ids, weight_of, false), (4, vec![]));
+
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![
+						(10, vec![1]),
+						(20, vec![7]),
+						(60, vec![8]),
+						(1_000, bag_1_000),
+						(2_000, vec![6])
+					]
+				);
+				assert_eq!(state(), sequential);
+			});
+		}
+	}
+
+	#[test]
+	fn insert_at_head_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			BagInsertionPolicy::set(BagInsertion::Head);
+
+			// when inserting into an existing bag
+			assert_ok!(List::<Runtime>::try_insert(5, 1_000));
+
+			// then it goes in front of the bag
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![5, 2, 3, 4])]);
+			assert_eq!(get_list_as_ids(), vec![5, 2, 3, 4, 1]);
+
+			// and when rebagged into a bag with an id already in it, it goes in front of it too.
+			let node = Node::<Runtime>::get(&4).unwrap();
+			assert_eq!(List::<Runtime>::update_position_for(node, 10), Some((1_000, 10)));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![4, 1]), (1_000, vec![5, 2, 3])]);
+			assert_eq!(Bag::<Runtime>::get(10).unwrap(), Bag::new(Some(4), Some(1), 10));
+		});
+	}
+
+	#[test]
+	fn insert_with_hint_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// when the hint is in the middle of the right bag, then the id goes in front of it
+			assert_eq!(List::<Runtime>::insert_with_hint(5, 1_000, &3), Ok(1_000));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 5, 3, 4])]);
+
+			// when the hint is the head, then the id goes behind it, and the head is unchanged
+			assert_eq!(List::<Runtime>::insert_with_hint(6, 1_000, &2), Ok(1_000));
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (1_000, vec![2, 6, 5, 3, 4])]
+			);
+
+			// when the hint is in another bag, alone in its bag, or not in the list, then the
+			// insertion falls back to the tail of the right bag
+			assert_eq!(List::<Runtime>::insert_with_hint(7, 1_000, &1), Ok(1_000));
+			assert_eq!(List::<Runtime>::insert_with_hint(8, 10, &1), Ok(10));
+			assert_eq!(List::<Runtime>::insert_with_hint(9, 1_000, &42), Ok(1_000));
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 8]), (1_000, vec![2, 6, 5, 3, 4, 7, 9])]
+			);
+
+			// and duplicates are rejected, whatever the hint
+			assert_storage_noop!(assert_eq!(
+				List::<Runtime>::insert_with_hint(3, 1_000, &4),
+				Err(Error::Duplicate)
+			));
+		});
+	}
+
 	#[test]
 	fn insert_errors_with_duplicate_id() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -241,18 +470,77 @@ mod list {
 
 			// then
 			assert_storage_noop!(assert_eq!(
-				List::<Runtime>::insert(3, 20).unwrap_err(),
+				List::<Runtime>::try_insert(3, 20).unwrap_err(),
 				Error::Duplicate
 			));
 		});
 	}
 
+	#[test]
+	fn try_insert_errors_if_bag_links_to_missing_node() {
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			// given the node of 4, the tail of bag 1_000, is lost
+			crate::ListNodes::<Runtime>::remove(&4);
+
+			// then it cannot be inserted again, as it would link to itself.
+			assert_storage_noop!(assert_eq!(
+				List::<Runtime>::try_insert(4, 1_000).unwrap_err(),
+				Error::Inconsistent
+			));
+		});
+	}
+
+	#[test]
+	fn insert_into_full_bag_spills_lower() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given bag 1_000 is full
+			MaxBagSize::set(3);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// when inserting into it, then the id goes into the closest lower bag
+			assert_eq!(List::<Runtime>::try_insert(5, 1_000), Ok(60));
+			assert_eq!(List::<Runtime>::try_insert(6, 1_000), Ok(60));
+
+			// but ids belonging in other bags are unaffected
+			assert_eq!(List::<Runtime>::try_insert(7, 2_000), Ok(2_000));
+
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (60, vec![5, 6]), (1_000, vec![2, 3, 4]), (2_000, vec![7])]
+			);
+		});
+	}
+
+	#[test]
+	fn insert_into_full_bag_errors_without_room() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given bag 10, the lowest, is full
+			MaxBagSize::set(1);
+
+			// then there is no room to spill into
+			assert_storage_noop!(assert_eq!(
+				List::<Runtime>::try_insert(5, 10).unwrap_err(),
+				Error::BagFull
+			));
+
+			// when full bags reject ids
+			BagOverflowPolicy::set(BagOverflow::Reject);
+
+			// then there is no room either, even if lower bags are not full
+			assert_storage_noop!(assert_eq!(
+				List::<Runtime>::try_insert(5, 1_000).unwrap_err(),
+				Error::BagFull
+			));
+			assert_eq!(List::<Runtime>::try_insert(5, 60), Ok(60));
+		});
+	}
+
 	#[test]
 	fn remove_works() {
-		use crate::{CounterForListNodes, ListBags, ListNodes};
+		use crate::{ListBags, ListNodes};
 		let ensure_left = |id, counter| {
 			assert!(!ListNodes::<Runtime>::contains_key(id));
-			assert_eq!(CounterForListNodes::<Runtime>::get(), counter);
+			assert_eq!(ListNodes::<Runtime>::count(), counter);
 			assert_eq!(ListNodes::<Runtime>::iter().count() as u32, counter);
 		};
 
@@ -345,23 +633,201 @@ mod list {
 		});
 	}
 
+	#[test]
+	fn update_position_for_respects_max_bag_size() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given bag 1_000 is full
+			MaxBagSize::set(3);
+			let node = |id| Node::<Runtime>::get(&id).unwrap();
+
+			// when 1 should move into it, then it spills into the closest lower bag
+			assert_eq!(List::<Runtime>::update_position_for(node(1), 1_000), Some((10, 60)));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(60, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// when 2 moves out of bag 1_000, then 1 can move into it
+			assert_eq!(List::<Runtime>::update_position_for(node(2), 2_000), Some((1_000, 2_000)));
+			assert_eq!(List::<Runtime>::update_position_for(node(1), 1_000), Some((60, 1_000)));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![3, 4, 1]), (2_000, vec![2])]);
+
+			// when full bags reject ids, then 2 stays where it is
+			BagOverflowPolicy::set(BagOverflow::Reject);
+			assert_eq!(List::<Runtime>::update_position_for(node(2), 1_000), None);
+			assert_eq!(node(2).bag_upper(), 2_000);
+
+			// and 3 stays in its bag if none of the lower bags is free either
+			MaxBagSize::set(0);
+			BagOverflowPolicy::set(BagOverflow::SpillLower);
+			assert_eq!(List::<Runtime>::update_position_for(node(3), 10), None);
+			assert_eq!(node(3).bag_upper(), 1_000);
+		});
+	}
+
 	#[test]
 	fn sanity_check_works() {
 		ExtBuilder::default().build_and_execute_no_post_check(|| {
-			assert_ok!(List::<Runtime>::sanity_check());
+			assert_ok!(List::<Runtime>::sanity_check(SanityCheckLevel::Full));
 		});
 
 		// make sure there are no duplicates.
 		ExtBuilder::default().build_and_execute_no_post_check(|| {
-			Bag::<Runtime>::get(10).unwrap().insert_unchecked(2);
-			assert_eq!(List::<Runtime>::sanity_check(), Err("duplicate identified"));
+			Bag::<Runtime>::get(10).unwrap().insert_unchecked(2, 1_000);
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Full),
+				Err("duplicate identified")
+			);
+		});
+
+		// ensure count is in sync with the count of `ListNodes`.
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			set_node_count(5);
+			assert_eq!(ListNodes::<Runtime>::count(), 5);
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Full),
+				Err("iter_count != stored_count")
+			);
+		});
+
+		// ensure the length of each bag is in sync with `CounterForBag`.
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			crate::CounterForBag::<Runtime>::mutate(1_000, |counter| *counter += 1);
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Full),
+				Err("bag length != CounterForBag")
+			);
+		});
+
+		// ensure the histogram is in sync with the bags.
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			crate::BagHistogram::<Runtime>::mutate(|histogram| histogram[0].total_score += 1);
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Full),
+				Err("BagHistogram does not match the bags")
+			);
+		});
+	}
+
+	#[test]
+	fn sanity_check_levels_work() {
+		// a duplicate is only detected when the links are checked.
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			Bag::<Runtime>::get(10).unwrap().insert_unchecked(2, 1_000);
+			assert_ok!(List::<Runtime>::sanity_check(SanityCheckLevel::Counter));
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Links),
+				Err("duplicate identified")
+			);
+		});
+
+		// a counter mismatch is detected at every level.
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			set_node_count(5);
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Counter),
+				Err("stored_count != nodes_count")
+			);
+		});
+
+		// a node that claims the wrong bag is only detected by the full check.
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			crate::ListNodes::<Runtime>::mutate(3, |node| node.as_mut().unwrap().bag_upper = 10);
+
+			assert_ok!(List::<Runtime>::sanity_check(SanityCheckLevel::Links));
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Full),
+				Err("node does not exist in the expected bag")
+			);
+		});
+	}
+
+	#[test]
+	fn sanity_check_bounded_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// the 4 genesis nodes are checked over two calls, then from the beginning again.
+			assert_eq!(List::<Runtime>::sanity_check_bounded(3), Ok(3));
+			assert!(crate::SanityCheckCursor::<Runtime>::get().is_some());
+			assert_eq!(List::<Runtime>::sanity_check_bounded(3), Ok(1));
+			assert_eq!(crate::SanityCheckCursor::<Runtime>::get(), None);
+			assert_eq!(List::<Runtime>::sanity_check_bounded(10), Ok(4));
+
+			// nothing to check, nothing changes.
+			assert_storage_noop!(assert_eq!(List::<Runtime>::sanity_check_bounded(0), Ok(0)));
 		});
 
-		// ensure count is in sync with `CounterForListNodes`.
 		ExtBuilder::default().build_and_execute_no_post_check(|| {
-			crate::CounterForListNodes::<Runtime>::mutate(|counter| *counter += 1);
-			assert_eq!(crate::CounterForListNodes::<Runtime>::get(), 5);
-			assert_eq!(List::<Runtime>::sanity_check(), Err("iter_count != stored_count"));
+			// node 3 claims the bag of 1, while linked to 2 and 4.
+			crate::ListNodes::<Runtime>::mutate(3, |node| node.as_mut().unwrap().bag_upper = 10);
+
+			let err = List::<Runtime>::sanity_check_bounded(10).unwrap_err();
+			// the next call reports the same node again.
+			assert_eq!(List::<Runtime>::sanity_check_bounded(10), Err(err));
+		});
+
+		ExtBuilder::default().build_and_execute_no_post_check(|| {
+			// the tail of bag 1_000 is not what its last node thinks.
+			crate::ListBags::<Runtime>::mutate(1_000, |bag| bag.as_mut().unwrap().tail = Some(3));
+
+			assert_eq!(
+				List::<Runtime>::sanity_check_bounded(10),
+				Err("node without next is not tail")
+			);
+		});
+	}
+
+	#[test]
+	fn bag_counters_are_maintained() {
+		use crate::CounterForBag;
+		let counters = || CounterForBag::<Runtime>::iter().collect::<BTreeMap<_, _>>();
+
+		ExtBuilder::default().add_ids(vec![(5, 1_000)]).build_and_execute(|| {
+			// given
+			assert_eq!(counters(), BTreeMap::from([(10, 1), (1_000, 4)]));
+
+			// when inserting into a new bag
+			assert_ok!(List::<Runtime>::try_insert(6, 20));
+			assert_eq!(counters(), BTreeMap::from([(10, 1), (20, 1), (1_000, 4)]));
+
+			// when an id is rebagged, the old bag's counter is removed once it is empty
+			let node = Node::<Runtime>::get(&1).unwrap();
+			assert_eq!(List::<Runtime>::update_position_for(node, 2_000), Some((10, 2_000)));
+			assert_eq!(counters(), BTreeMap::from([(20, 1), (1_000, 4), (2_000, 1)]));
+
+			// when moving within a bag
+			List::<Runtime>::set_cached_score(&2, 500);
+			assert_ok!(List::<Runtime>::put_in_front_of(&2, &5));
+			assert_eq!(BagsList::count_in_bag(1_000), 4);
+
+			// when removing
+			List::<Runtime>::remove_many(&vec![2, 3, 6]);
+			assert_eq!(counters(), BTreeMap::from([(1_000, 2), (2_000, 1)]));
+			assert_eq!(BagsList::count_in_bag(20), 0);
+		});
+	}
+
+	#[test]
+	fn bag_histogram_total_score_saturates() {
+		ExtBuilder::default().build_and_execute(|| {
+			let total_score = |bag_upper| {
+				BagsList::bag_histogram()
+					.into_iter()
+					.find(|occupancy| occupancy.bag_upper == bag_upper)
+					.map(|occupancy| occupancy.total_score)
+			};
+
+			// given a bag whose total score saturates
+			assert_ok!(List::<Runtime>::try_insert(5, u64::MAX / 2 + 1));
+			assert_ok!(List::<Runtime>::try_insert(6, u64::MAX / 2 + 1));
+			assert_eq!(total_score(VoteWeight::MAX), Some(VoteWeight::MAX));
+
+			// when one of the ids leaves, the total stays saturated
+			List::<Runtime>::remove(&6);
+			assert_eq!(total_score(VoteWeight::MAX), Some(VoteWeight::MAX));
+			assert_ok!(List::<Runtime>::sanity_check(SanityCheckLevel::Full));
+
+			// until the bag is emptied
+			List::<Runtime>::remove(&5);
+			assert_eq!(total_score(VoteWeight::MAX), None);
+			assert_ok!(List::<Runtime>::try_insert(5, 1 << 40));
+			assert_eq!(total_score(VoteWeight::MAX), Some(1 << 40));
 		});
 	}
 
@@ -386,7 +852,7 @@ mod bags {
 				let bag = Bag::<Runtime>::get(bag_upper).unwrap();
 				let bag_ids = bag.iter().map(|n| *n.id()).collect::<Vec<_>>();
 
-				assert_eq!(bag, Bag::<Runtime> { head, tail, bag_upper });
+				assert_eq!(bag, Bag::<Runtime> { head, tail, bag_upper, _phantom: PhantomData });
 				assert_eq!(bag_ids, ids);
 			};
 
@@ -417,7 +883,14 @@ mod bags {
 	#[test]
 	fn insert_node_sets_proper_bag() {
 		ExtBuilder::default().build_and_execute_no_post_check(|| {
-			let node = |id, bag_upper| Node::<Runtime> { id, prev: None, next: None, bag_upper };
+			let node = |id, bag_upper| Node::<Runtime> {
+				score: bag_upper,
+				id,
+				prev: None,
+				next: None,
+				bag_upper,
+				_phantom: PhantomData,
+			};
 
 			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
 
@@ -426,7 +899,14 @@ mod bags {
 
 			assert_eq!(
 				ListNodes::<Runtime>::get(&42).unwrap(),
-				Node { bag_upper: 10, prev: Some(1), next: None, id: 42 }
+				Node {
+					bag_upper: 10,
+					score: 5,
+					prev: Some(1),
+					next: None,
+					id: 42,
+					_phantom: PhantomData
+				}
 			);
 		});
 	}
@@ -434,7 +914,14 @@ mod bags {
 	#[test]
 	fn insert_node_happy_paths_works() {
 		ExtBuilder::default().build_and_execute_no_post_check(|| {
-			let node = |id, bag_upper| Node::<Runtime> { id, prev: None, next: None, bag_upper };
+			let node = |id, bag_upper| Node::<Runtime> {
+				score: bag_upper,
+				id,
+				prev: None,
+				next: None,
+				bag_upper,
+				_phantom: PhantomData,
+			};
 
 			// when inserting into a bag with 1 node
 			let mut bag_10 = Bag::<Runtime>::get(10).unwrap();
@@ -455,15 +942,28 @@ mod bags {
 			assert_eq!(bag_as_ids(&bag_20), vec![62]);
 
 			// when inserting a node pointing to the accounts not in the bag
-			let node_61 =
-				Node::<Runtime> { id: 61, prev: Some(21), next: Some(101), bag_upper: 20 };
+			let node_61 = Node::<Runtime> {
+				id: 61,
+				prev: Some(21),
+				next: Some(101),
+				bag_upper: 20,
+				score: 20,
+				_phantom: PhantomData,
+			};
 			bag_20.insert_node_unchecked(node_61);
 			// then ids are in order
 			assert_eq!(bag_as_ids(&bag_20), vec![62, 61]);
 			// and when the node is re-fetched all the info is correct
 			assert_eq!(
 				Node::<Runtime>::get(&61).unwrap(),
-				Node::<Runtime> { id: 61, prev: Some(62), next: None, bag_upper: 20 }
+				Node::<Runtime> {
+					id: 61,
+					prev: Some(62),
+					next: None,
+					bag_upper: 20,
+					score: 20,
+					_phantom: PhantomData,
+				}
 			);
 
 			// state of all bags is as expected
@@ -478,7 +978,14 @@ mod bags {
 	// Document improper ways `insert_node` may be getting used.
 	#[test]
 	fn insert_node_bad_paths_documented() {
-		let node = |id, prev, next, bag_upper| Node::<Runtime> { id, prev, next, bag_upper };
+		let node = |id, prev, next, bag_upper| Node::<Runtime> {
+			score: 1_000,
+			id,
+			prev,
+			next,
+			bag_upper,
+			_phantom: PhantomData,
+		};
 		ExtBuilder::default().build_and_execute_no_post_check(|| {
 			// when inserting a node with both prev & next pointing at an account in an incorrect
 			// bag.
@@ -531,7 +1038,10 @@ mod bags {
 			);
 			//         ^^^ despite being the bags head, it has a prev
 
-			assert_eq!(bag_1000, Bag { head: Some(2), tail: Some(2), bag_upper: 1_000 })
+			assert_eq!(
+				bag_1000,
+				Bag { head: Some(2), tail: Some(2), bag_upper: 1_000, _phantom: PhantomData }
+			)
 		});
 	}
 
@@ -543,7 +1053,14 @@ mod bags {
 	)]
 	fn insert_node_duplicate_tail_panics_with_debug_assert() {
 		ExtBuilder::default().build_and_execute(|| {
-			let node = |id, prev, next, bag_upper| Node::<Runtime> { id, prev, next, bag_upper };
+			let node = |id, prev, next, bag_upper| Node::<Runtime> {
+				score: 1_000,
+				id,
+				prev,
+				next,
+				bag_upper,
+				_phantom: PhantomData,
+			};
 
 			// given
 			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])],);
@@ -675,6 +1192,8 @@ mod bags {
 				prev: None,
 				next: Some(3),
 				bag_upper: 10, // should be 1_000
+				score: 1_000,
+				_phantom: PhantomData,
 			};
 			let mut bag_1000 = Bag::<Runtime>::get(1_000).unwrap();
 
@@ -738,3 +1257,109 @@ mod node {
 		});
 	}
 }
+
+mod proptests {
+	use super::*;
+	use proptest::{collection::vec, prelude::*, test_runner::TestCaseError};
+	use std::collections::BTreeSet;
+
+	#[derive(Clone, Debug)]
+	enum Op {
+		Insert(AccountId, VoteWeight),
+		Remove(AccountId),
+		Update(AccountId, VoteWeight),
+	}
+
+	/// Weights that are mostly around the thresholds of the mock, with some anywhere in the range.
+	fn weight() -> impl Strategy<Value = VoteWeight> {
+		prop_oneof![
+			4 => 0..=70u64,
+			3 => prop::sample::select(vec![10, 20, 30, 40, 50, 60, 1_000, 2_000, 10_000, u64::MAX]),
+			2 => 0..=20_000u64,
+			1 => any::<u64>(),
+		]
+	}
+
+	fn op() -> impl Strategy<Value = Op> {
+		// a small id space, so that operations often hit ids that are already in the list.
+		let id = 0..16u32;
+		prop_oneof![
+			3 => (id.clone(), weight()).prop_map(|(id, weight)| Op::Insert(id, weight)),
+			1 => id.clone().prop_map(Op::Remove),
+			3 => (id, weight()).prop_map(|(id, weight)| Op::Update(id, weight)),
+		]
+	}
+
+	fn apply(op: Op) {
+		match op {
+			Op::Insert(id, weight) => {
+				// duplicates and full bags are rejected, and must leave the list intact.
+				let _ = List::<Runtime>::try_insert(id, weight);
+			},
+			Op::Remove(id) => List::<Runtime>::remove(&id),
+			Op::Update(id, weight) =>
+				if let Some(node) = Node::<Runtime>::get(&id) {
+					let _ = List::<Runtime>::update_position_for(node, weight);
+				},
+		}
+	}
+
+	/// Check the invariants of the list from the raw storage, independently of `sanity_check`.
+	fn check_invariants() -> Result<(), TestCaseError> {
+		let nodes = ListNodes::<Runtime>::iter().collect::<BTreeMap<_, _>>();
+
+		// every node's prev and next agree with each other.
+		for (id, node) in nodes.iter() {
+			prop_assert_eq!(&node.id, id);
+			if let Some(prev) = &node.prev {
+				let prev = nodes.get(prev).ok_or_else(|| TestCaseError::fail("dangling prev"))?;
+				prop_assert_eq!(prev.next.as_ref(), Some(id));
+			}
+			if let Some(next) = &node.next {
+				let next = nodes.get(next).ok_or_else(|| TestCaseError::fail("dangling next"))?;
+				prop_assert_eq!(next.prev.as_ref(), Some(id));
+			}
+		}
+
+		// every node is reachable from exactly one bag head, that of its own bag.
+		let mut reached = BTreeSet::new();
+		for (bag_upper, bag) in ListBags::<Runtime>::iter() {
+			let mut last = None;
+			let mut cursor = bag.head;
+			while let Some(id) = cursor {
+				prop_assert!(reached.insert(id), "{} reached twice", id);
+				let node = nodes.get(&id).ok_or_else(|| TestCaseError::fail("dangling head"))?;
+				prop_assert_eq!(node.bag_upper, bag_upper);
+				last = Some(id);
+				cursor = node.next;
+			}
+			prop_assert_eq!(last, bag.tail);
+		}
+		prop_assert_eq!(reached, nodes.keys().copied().collect::<BTreeSet<_>>());
+
+		prop_assert_eq!(ListNodes::<Runtime>::count() as usize, nodes.len());
+		Ok(())
+	}
+
+	proptest! {
+		#![proptest_config(ProptestConfig::with_cases(128))]
+
+		#[test]
+		fn operations_preserve_list_invariants(
+			max_bag_size in prop_oneof![Just(u32::MAX), 1..4u32],
+			insertion in prop_oneof![Just(BagInsertion::Tail), Just(BagInsertion::Head)],
+			ops in vec(op(), 0..64),
+		) {
+			ExtBuilder::default().build().execute_with(|| {
+				MaxBagSize::set(max_bag_size);
+				BagInsertionPolicy::set(insertion);
+				for op in ops {
+					apply(op);
+					check_invariants()?;
+				}
+				prop_assert_eq!(List::<Runtime>::sanity_check(SanityCheckLevel::Full), Ok(()));
+				Ok(())
+			})?;
+		}
+	}
+}
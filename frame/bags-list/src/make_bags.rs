@@ -0,0 +1,109 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to generate the bag thresholds of a runtime, see [`crate::Config::BagThresholds`].
+//!
+//! The thresholds are a geometric series: each threshold is a constant multiple of the previous
+//! one, starting at the existential weight and ending at `VoteWeight::MAX`, such that the bags
+//! fully occupy the `VoteWeight` space.
+//!
+//! This module is only available with the `std` feature, as it relies on floating point
+//! arithmetic; it is meant to be used from offchain code, e.g. a script whose output is pasted
+//! into the runtime, or at genesis (See [`crate::GenesisThresholds`]).
+
+use frame_election_provider_support::VoteWeight;
+use frame_support::traits::CurrencyToVote;
+
+/// Compute the existential weight of a currency.
+///
+/// This is the vote weight of an account holding exactly the existential deposit. Note that it
+/// depends on the total issuance, a quantity known to change over time.
+pub fn existential_weight<Balance, C: CurrencyToVote<Balance>>(
+	existential_deposit: Balance,
+	total_issuance: Balance,
+) -> VoteWeight {
+	C::to_vote(existential_deposit, total_issuance)
+}
+
+/// Compute the constant ratio for the thresholds.
+///
+/// This ratio ensures that each bag, with the possible exceptions of certain small ones and the
+/// final one, is a constant multiple of the previous, while fully occupying the `VoteWeight`
+/// space.
+pub fn constant_ratio(existential_weight: VoteWeight, n_bags: usize) -> f64 {
+	((VoteWeight::MAX as f64 / existential_weight as f64).ln() / (n_bags.saturating_sub(1) as f64))
+		.exp()
+}
+
+/// Compute the list of bag thresholds.
+///
+/// Returns a list of exactly `n_bags` elements, except in the case of overflow.
+/// The first element is always `existential_weight`.
+/// The last element is always `VoteWeight::MAX`.
+///
+/// All other elements are computed from the previous according to the formula
+/// `threshold[k + 1] = (threshold[k] * ratio).max(threshold[k] + 1)`.
+pub fn thresholds(
+	existential_weight: VoteWeight,
+	constant_ratio: f64,
+	n_bags: usize,
+) -> Vec<VoteWeight> {
+	const WEIGHT_LIMIT: f64 = VoteWeight::MAX as f64;
+
+	let mut thresholds = Vec::with_capacity(n_bags);
+
+	if n_bags > 1 {
+		thresholds.push(existential_weight);
+	}
+
+	while n_bags > 0 && thresholds.len() < n_bags - 1 {
+		let last = thresholds.last().copied().unwrap_or(existential_weight);
+		let successor = (last as f64 * constant_ratio).round().max(last as f64 + 1.0);
+		if successor < WEIGHT_LIMIT {
+			thresholds.push(successor as VoteWeight);
+		} else {
+			log::warn!(
+				target: crate::LOG_TARGET,
+				"unexpectedly exceeded weight limit; breaking threshold generation"
+			);
+			break
+		}
+	}
+
+	if n_bags > 0 {
+		thresholds.push(VoteWeight::MAX);
+	}
+
+	debug_assert!(n_bags < 2 || thresholds[0] == existential_weight);
+	debug_assert!(n_bags == 0 || thresholds[thresholds.len() - 1] == VoteWeight::MAX);
+
+	thresholds
+}
+
+/// Compute `n_bags` thresholds for a currency with the given existential deposit and total
+/// issuance.
+///
+/// Returns the thresholds, and the constant ratio between them.
+pub fn make_bags<Balance, C: CurrencyToVote<Balance>>(
+	existential_deposit: Balance,
+	total_issuance: Balance,
+	n_bags: usize,
+) -> (Vec<VoteWeight>, f64) {
+	let existential_weight = existential_weight::<Balance, C>(existential_deposit, total_issuance);
+	let constant_ratio = constant_ratio(existential_weight, n_bags);
+	(thresholds(existential_weight, constant_ratio, n_bags), constant_ratio)
+}
@@ -0,0 +1,168 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for the bags-list pallet.
+//!
+//! The layout of the storage is tracked by the storage version of the pallet (see
+//! [`crate::STORAGE_VERSION`]). Each change of layout lives in its own module, named after the
+//! version it migrates to, and only runs on top of the version right before it; chains that
+//! skipped some versions must include all the migrations in between, in order.
+//!
+//! [`MigrateThresholds`] does not change the layout, and is needed whenever the thresholds change.
+
+use crate::{list::List, Config, WeightInfo};
+use frame_support::{
+	traits::{Get, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use sp_std::marker::PhantomData;
+
+pub mod v1;
+pub mod v2;
+
+pub use v1::PopulateBagCounters;
+pub use v2::AddScore;
+
+#[cfg(feature = "try-runtime")]
+use crate::SanityCheckLevel;
+#[cfg(any(feature = "try-runtime", test))]
+use codec::{Decode, Encode};
+#[cfg(feature = "try-runtime")]
+use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+#[cfg(any(feature = "try-runtime", test))]
+use sp_runtime::traits::Bounded;
+#[cfg(any(feature = "try-runtime", test))]
+use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+
+/// Migrate the list from the bag thresholds `Old` to the thresholds that are currently in use,
+/// moving every id whose bag has changed into its new, correct bag.
+///
+/// This must be included in the runtime upgrade that changes `T::BagThresholds`, with `Old` being
+/// the thresholds of the previous runtime.
+pub struct MigrateThresholds<T, I, Old>(PhantomData<(T, I, Old)>);
+
+impl<T: Config<I>, I: 'static, Old: Get<&'static [T::Score]>> OnRuntimeUpgrade
+	for MigrateThresholds<T, I, Old>
+{
+	fn on_runtime_upgrade() -> Weight {
+		// at worst, every id is checked and moved.
+		let count = crate::ListNodes::<T, I>::count();
+		let affected = List::<T, I>::migrate(Old::get());
+		crate::log!(info, "migrated {} ids to the new bag thresholds", affected);
+
+		T::WeightInfo::migrate_chunk(count)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		List::<T, I>::sanity_check(SanityCheckLevel::Full)?;
+		frame_support::ensure!(
+			bags_within::<T, I>(Old::get()),
+			"not all bags are members of the old thresholds"
+		);
+
+		Self::set_temp_storage(occupancy::<T, I>(), "occupancy");
+		crate::log!(info, "bags-list thresholds migration passes PRE migrate checks ✅");
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		List::<T, I>::sanity_check(SanityCheckLevel::Full)?;
+		frame_support::ensure!(
+			bags_within::<T, I>(&crate::list::thresholds::<T, I>()),
+			"not all bags are members of the new thresholds"
+		);
+
+		let pre: Occupancy<T::Score> =
+			Self::get_temp_storage("occupancy").ok_or("occupancy not stored")?;
+		let post = occupancy::<T, I>();
+		for (bag_upper, count) in &post.bags {
+			crate::log!(
+				info,
+				"bag {:?}: {} -> {} ids",
+				bag_upper,
+				pre.bags.get(bag_upper).copied().unwrap_or_default(),
+				count
+			);
+		}
+		check_occupancy(&pre, &post, &List::<T, I>::bags_affected_by(Old::get()))?;
+
+		crate::log!(info, "bags-list thresholds migration passes POST migrate checks ✅");
+		Ok(())
+	}
+}
+
+/// The number of ids in each bag, and in the whole list.
+///
+/// This is recorded before a migration, to be checked against afterwards.
+#[cfg(any(feature = "try-runtime", test))]
+#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+pub(crate) struct Occupancy<Score: Ord> {
+	/// The number of ids in each non-empty bag, by upper threshold.
+	pub(crate) bags: BTreeMap<Score, u32>,
+	/// The number of ids in the list.
+	pub(crate) total: u32,
+}
+
+/// The current occupancy of the list.
+#[cfg(any(feature = "try-runtime", test))]
+pub(crate) fn occupancy<T: Config<I>, I: 'static>() -> Occupancy<T::Score> {
+	Occupancy {
+		bags: crate::CounterForBag::<T, I>::iter().filter(|(_, count)| *count > 0).collect(),
+		total: crate::ListNodes::<T, I>::count(),
+	}
+}
+
+/// Check that a migration that moved ids out of the `affected` bags only, from the occupancy
+/// `pre` to `post`, preserved every id.
+///
+/// Ids can move into the bags that are not affected, but not out of them.
+#[cfg(any(feature = "try-runtime", test))]
+pub(crate) fn check_occupancy<Score: Ord + Copy>(
+	pre: &Occupancy<Score>,
+	post: &Occupancy<Score>,
+	affected: &BTreeSet<Score>,
+) -> Result<(), &'static str> {
+	frame_support::ensure!(post.total == pre.total, "number of ids changed during migration");
+	frame_support::ensure!(
+		post.bags.values().map(|count| *count as u64).sum::<u64>() == post.total as u64,
+		"the bags do not hold every id of the list"
+	);
+	frame_support::ensure!(
+		pre.bags
+			.iter()
+			.filter(|(bag_upper, _)| !affected.contains(bag_upper))
+			.all(|(bag_upper, count)| post.bags.get(bag_upper).copied().unwrap_or_default() >=
+				*count),
+		"ids left a bag whose range did not change"
+	);
+	Ok(())
+}
+
+/// `true` if all bags, and all nodes, use a member of `thresholds` as their upper bound.
+///
+/// `T::Score::max_value()` is implicitly a member of `thresholds`.
+#[cfg(any(feature = "try-runtime", test))]
+pub(crate) fn bags_within<T: Config<I>, I: 'static>(thresholds: &[T::Score]) -> bool {
+	let is_member = |bag_upper: T::Score| {
+		bag_upper == T::Score::max_value() || thresholds.binary_search(&bag_upper).is_ok()
+	};
+
+	crate::ListBags::<T, I>::iter_keys().all(is_member) &&
+		crate::ListNodes::<T, I>::iter_values().all(|node| is_member(node.bag_upper()))
+}
@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Version 1: the number of ids in each bag is counted in [`crate::CounterForBag`].
+
+use super::v2::PreScoreNode;
+use crate::{Config, Pallet};
+use frame_support::{
+	storage::migration::storage_key_iter,
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+	weights::Weight,
+	Twox64Concat,
+};
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData};
+
+#[cfg(feature = "try-runtime")]
+use crate::{list::List, SanityCheckLevel};
+
+/// Populate [`crate::CounterForBag`] from the bags and nodes already in storage.
+///
+/// This must be included in the runtime upgrade that introduces the per-bag counters, on any chain
+/// whose list is not empty. It only runs on top of version 0, and bumps the storage version to 1.
+///
+/// The nodes are read as they were stored until version 1, so that this can run before
+/// [`super::AddScore`] in the same upgrade.
+pub struct PopulateBagCounters<T, I = ()>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for PopulateBagCounters<T, I> {
+	fn on_runtime_upgrade() -> Weight {
+		let on_chain = Pallet::<T, I>::on_chain_storage_version();
+		if on_chain != 0 {
+			crate::log!(warn, "skipping the v1 migration, the storage version is {:?}", on_chain);
+			return T::DbWeight::get().reads(1)
+		}
+
+		crate::CounterForBag::<T, I>::remove_all(None);
+
+		let mut counts = BTreeMap::<T::Score, u32>::new();
+		let mut nodes = 0u64;
+		let pallet = <Pallet<T, I> as PalletInfoAccess>::name();
+		for (_, node) in storage_key_iter::<T::AccountId, PreScoreNode<T, I>, Twox64Concat>(
+			pallet.as_bytes(),
+			b"ListNodes",
+		) {
+			*counts.entry(node.bag_upper).or_default() += 1;
+			nodes += 1;
+		}
+
+		let bags = counts.len() as u64;
+		for (bag_upper, count) in counts {
+			crate::CounterForBag::<T, I>::insert(bag_upper, count);
+		}
+		StorageVersion::new(1).put::<Pallet<T, I>>();
+		crate::log!(info, "populated the counters of {} bags, holding {} ids", bags, nodes);
+
+		T::DbWeight::get().reads_writes(nodes.saturating_add(1), bags + 1)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			Pallet::<T, I>::on_chain_storage_version() >= 1,
+			"storage version not bumped to 1"
+		);
+		List::<T, I>::sanity_check(SanityCheckLevel::Links)?;
+		crate::log!(info, "bags-list counters migration passes POST migrate checks ✅");
+		Ok(())
+	}
+}
@@ -0,0 +1,98 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Version 2: each node records its score, and the bags are summed up in [`crate::BagHistogram`].
+
+use crate::{list::Node, BagOccupancy, Config, MaxHistogramBags, Pallet};
+use codec::{Decode, Encode};
+use frame_election_provider_support::ScoreProvider;
+use frame_support::{
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+	BoundedVec,
+};
+use sp_runtime::traits::Saturating;
+use sp_std::{collections::btree_map::BTreeMap, convert::TryFrom, marker::PhantomData, prelude::*};
+
+#[cfg(feature = "try-runtime")]
+use crate::{list::List, SanityCheckLevel};
+
+/// A node as it was stored before it recorded its score, i.e. up to version 1.
+#[derive(Encode, Decode)]
+pub(super) struct PreScoreNode<T: Config<I>, I: 'static = ()> {
+	pub(super) id: T::AccountId,
+	pub(super) prev: Option<T::AccountId>,
+	pub(super) next: Option<T::AccountId>,
+	pub(super) bag_upper: T::Score,
+	#[codec(skip)]
+	_phantom: PhantomData<I>,
+}
+
+/// Record the current score of every node in storage, and populate [`crate::BagHistogram`] from
+/// them.
+///
+/// This must be included in the runtime upgrade that introduces the score of the nodes, on any
+/// chain whose list is not empty. It only runs on top of version 1, and bumps the storage version
+/// to 2.
+pub struct AddScore<T, I = ()>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for AddScore<T, I> {
+	fn on_runtime_upgrade() -> Weight {
+		let on_chain = Pallet::<T, I>::on_chain_storage_version();
+		if on_chain != 1 {
+			crate::log!(warn, "skipping the v2 migration, the storage version is {:?}", on_chain);
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut nodes = 0u64;
+		let mut bags = BTreeMap::<T::Score, (u32, T::Score)>::new();
+		crate::ListNodes::<T, I>::translate::<PreScoreNode<T, I>, _>(|_id, old| {
+			let score = T::ScoreProvider::score(&old.id);
+			let (count, total_score) = bags.entry(old.bag_upper).or_default();
+			*count = count.saturating_add(1);
+			*total_score = total_score.saturating_add(score);
+			nodes += 1;
+			Some(Node::from_parts(old.id, old.prev, old.next, old.bag_upper, score))
+		});
+
+		let histogram = bags
+			.into_iter()
+			.map(|(bag_upper, (count, total_score))| BagOccupancy { bag_upper, count, total_score })
+			.collect::<Vec<_>>();
+		match BoundedVec::<_, MaxHistogramBags<T, I>>::try_from(histogram) {
+			Ok(histogram) => crate::BagHistogram::<T, I>::put(histogram),
+			Err(_) => crate::log!(error, "there are more bags than the histogram can hold"),
+		}
+		StorageVersion::new(2).put::<Pallet<T, I>>();
+		crate::log!(info, "recorded the score of {} ids", nodes);
+
+		// reading a score is at least one more read per node.
+		T::DbWeight::get()
+			.reads_writes(nodes.saturating_mul(2).saturating_add(1), nodes.saturating_add(2))
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			Pallet::<T, I>::on_chain_storage_version() >= 2,
+			"storage version not bumped to 2"
+		);
+		List::<T, I>::sanity_check(SanityCheckLevel::Links)?;
+		crate::log!(info, "bags-list score migration passes POST migrate checks ✅");
+		Ok(())
+	}
+}
@@ -20,29 +20,72 @@
 use super::*;
 use crate::{self as bags_list};
 use frame_election_provider_support::VoteWeight;
-use frame_support::parameter_types;
+use frame_support::{
+	instances::{Instance1, Instance2},
+	parameter_types,
+	weights::RuntimeDbWeight,
+	PalletId,
+};
+use parking_lot::RwLock;
+use sp_core::offchain::{
+	testing::{PoolState, TestOffchainExt, TestTransactionPoolExt},
+	OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+};
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
 
 pub type AccountId = u32;
 pub type Balance = u32;
 
 parameter_types! {
 	pub static NextVoteWeight: VoteWeight = 0;
+	pub static NextVoteWeightMap: HashMap<AccountId, VoteWeight> = Default::default();
+	pub static DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 0, write: 0 };
 }
 
 pub struct StakingMock;
-impl frame_election_provider_support::VoteWeightProvider<AccountId> for StakingMock {
-	fn vote_weight(id: &AccountId) -> VoteWeight {
+impl frame_election_provider_support::ScoreProvider<AccountId> for StakingMock {
+	type Score = VoteWeight;
+
+	fn score(id: &AccountId) -> VoteWeight {
 		match id {
 			710 => 15,
 			711 => 16,
 			712 => 2_000, // special cases used for migrate test
-			_ => NextVoteWeight::get(),
+			_ => *NextVoteWeightMap::get().get(id).unwrap_or(&NextVoteWeight::get()),
 		}
 	}
 	#[cfg(any(feature = "runtime-benchmarks", test))]
-	fn set_vote_weight_of(_: &AccountId, weight: VoteWeight) {
-		// we don't really keep a mapping, just set weight for everyone.
-		NextVoteWeight::set(weight)
+	fn set_score_of(id: &AccountId, weight: VoteWeight) {
+		NEXT_VOTE_WEIGHT_MAP.with(|m| m.borrow_mut().insert(*id, weight));
+	}
+}
+
+/// Provides the scores of [`StakingMock`] as `u128`, for the second list instance.
+pub struct WideStakingMock;
+impl frame_election_provider_support::ScoreProvider<AccountId> for WideStakingMock {
+	type Score = u128;
+
+	fn score(id: &AccountId) -> u128 {
+		<StakingMock as frame_election_provider_support::ScoreProvider<AccountId>>::score(id).into()
+	}
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn set_score_of(id: &AccountId, score: u128) {
+		<StakingMock as frame_election_provider_support::ScoreProvider<AccountId>>::set_score_of(
+			id,
+			score.try_into().unwrap_or(VoteWeight::MAX),
+		)
+	}
+}
+
+/// Scores the bids of a queue by the balance reserved for them, for the third list instance.
+///
+/// This is an adapter around an arbitrary pallet: nothing about it is related to staking.
+pub struct ReservedBalanceScore;
+impl frame_election_provider_support::ScoreProvider<AccountId> for ReservedBalanceScore {
+	type Score = Balance;
+
+	fn score(id: &AccountId) -> Balance {
+		Balances::reserved_balance(id)
 	}
 }
 
@@ -60,7 +103,7 @@ impl frame_system::Config for Runtime {
 	type Header = sp_runtime::testing::Header;
 	type Event = Event;
 	type BlockHashCount = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type BlockLength = ();
 	type BlockWeights = ();
 	type Version = ();
@@ -72,18 +115,156 @@ impl frame_system::Config for Runtime {
 	type OnSetCode = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Runtime {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Runtime
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
 parameter_types! {
 	pub static BagThresholds: &'static [VoteWeight] = &[10, 20, 30, 40, 50, 60, 1_000, 2_000, 10_000];
+	pub const MaxBagThresholds: u32 = 20;
+	pub static MaxAutoRebagPerBlock: u32 = 2;
+	pub static RefreshTopBags: u32 = 0;
+	pub static DisplacementMargin: Perbill = Perbill::from_percent(10);
+	pub static MinScoreBag: VoteWeight = 0;
+	pub static OffchainRepeat: u64 = 5;
+	pub static MaxOffchainRebags: u32 = 10;
+	pub const UnsignedPriority: u64 = 100;
+	pub const SanityCheckDepth: SanityCheckLevel = SanityCheckLevel::Full;
+	pub static MaxBagSize: u32 = u32::MAX;
+	pub static BagOverflowPolicy: BagOverflow = BagOverflow::SpillLower;
+	pub static BagInsertionPolicy: BagInsertion = BagInsertion::Tail;
+	pub const BagsListPalletId: PalletId = PalletId(*b"py/bagsl");
+	pub static RebagReward: Balance = 0;
+	pub static RebagRewardCooldown: u64 = 10;
+	pub static RebagCooldown: u64 = 0;
+	pub static RebagDeposit: Balance = 0;
+	pub static DanglingIds: Vec<AccountId> = vec![];
+	pub static PruneReward: Balance = 0;
+}
+
+/// The members of the lists: every id but [`DanglingIds`].
+pub struct NotDangling;
+impl frame_support::traits::Contains<AccountId> for NotDangling {
+	fn contains(id: &AccountId) -> bool {
+		!DanglingIds::get().contains(id)
+	}
 }
 
 impl bags_list::Config for Runtime {
 	type Event = Event;
 	type WeightInfo = ();
 	type BagThresholds = BagThresholds;
-	type VoteWeightProvider = StakingMock;
+	type MaxBagThresholds = MaxBagThresholds;
+	type MaxBagSize = MaxBagSize;
+	type BagOverflowPolicy = BagOverflowPolicy;
+	type BagInsertionPolicy = BagInsertionPolicy;
+	type SanityCheckDepth = SanityCheckDepth;
+	type MaxAutoRebagPerBlock = MaxAutoRebagPerBlock;
+	type RefreshTopBags = RefreshTopBags;
+	type DisplacementMargin = DisplacementMargin;
+	type MinScoreBag = MinScoreBag;
+	type OffchainRepeat = OffchainRepeat;
+	type MaxOffchainRebags = MaxOffchainRebags;
+	type UnsignedPriority = UnsignedPriority;
+	type Currency = Balances;
+	type PalletId = BagsListPalletId;
+	type RebagReward = RebagReward;
+	type RebagRewardCooldown = RebagRewardCooldown;
+	type RebagCooldown = RebagCooldown;
+	type RebagDeposit = RebagDeposit;
+	type Members = NotDangling;
+	type PruneReward = PruneReward;
+	type Score = VoteWeight;
+	type ScoreProvider = StakingMock;
+}
+
+parameter_types! {
+	pub static SecondBagThresholds: &'static [u128] = &[100, 200, 1 << 64];
+	pub const SecondBagsListPalletId: PalletId = PalletId(*b"py/tbags");
+}
+
+impl bags_list::Config<Instance1> for Runtime {
+	type Event = Event;
+	type WeightInfo = ();
+	type BagThresholds = SecondBagThresholds;
+	type MaxBagThresholds = MaxBagThresholds;
+	type MaxBagSize = MaxBagSize;
+	type BagOverflowPolicy = BagOverflowPolicy;
+	type BagInsertionPolicy = BagInsertionPolicy;
+	type SanityCheckDepth = SanityCheckDepth;
+	type MaxAutoRebagPerBlock = MaxAutoRebagPerBlock;
+	type RefreshTopBags = ();
+	type DisplacementMargin = DisplacementMargin;
+	type MinScoreBag = ();
+	type OffchainRepeat = OffchainRepeat;
+	type MaxOffchainRebags = MaxOffchainRebags;
+	type UnsignedPriority = UnsignedPriority;
+	type Currency = Balances;
+	type PalletId = SecondBagsListPalletId;
+	type RebagReward = RebagReward;
+	type RebagRewardCooldown = RebagRewardCooldown;
+	type RebagCooldown = RebagCooldown;
+	type RebagDeposit = RebagDeposit;
+	type Members = NotDangling;
+	type PruneReward = PruneReward;
+	type Score = u128;
+	type ScoreProvider = WideStakingMock;
+}
+
+parameter_types! {
+	pub const BidQueueThresholds: &'static [Balance] = &[10, 100, 1_000];
+	pub const BidQueuePalletId: PalletId = PalletId(*b"py/bidsq");
 }
 
-type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
+impl bags_list::Config<Instance2> for Runtime {
+	type Event = Event;
+	type WeightInfo = ();
+	type BagThresholds = BidQueueThresholds;
+	type MaxBagThresholds = MaxBagThresholds;
+	type MaxBagSize = MaxBagSize;
+	type BagOverflowPolicy = BagOverflowPolicy;
+	type BagInsertionPolicy = BagInsertionPolicy;
+	type SanityCheckDepth = SanityCheckDepth;
+	type MaxAutoRebagPerBlock = MaxAutoRebagPerBlock;
+	type RefreshTopBags = ();
+	type DisplacementMargin = DisplacementMargin;
+	type MinScoreBag = ();
+	type OffchainRepeat = OffchainRepeat;
+	type MaxOffchainRebags = MaxOffchainRebags;
+	type UnsignedPriority = UnsignedPriority;
+	type Currency = Balances;
+	type PalletId = BidQueuePalletId;
+	type RebagReward = RebagReward;
+	type RebagRewardCooldown = RebagRewardCooldown;
+	type RebagCooldown = RebagCooldown;
+	type RebagDeposit = RebagDeposit;
+	type Members = NotDangling;
+	type PruneReward = PruneReward;
+	type Score = Balance;
+	type ScoreProvider = ReservedBalanceScore;
+}
+
+pub(crate) type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
 type Block = frame_system::mocking::MockBlock<Runtime>;
 frame_support::construct_runtime!(
 	pub enum Runtime where
@@ -92,52 +273,71 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Storage, Event<T>, Config},
-		BagsList: bags_list::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Event<T>, Config<T>},
+		BagsList: bags_list::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		SecondBagsList: bags_list::<Instance1>::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		BidQueue: bags_list::<Instance2>::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
 /// Default AccountIds and their weights.
-pub(crate) const GENESIS_IDS: [(AccountId, VoteWeight); 4] =
-	[(1, 10), (2, 1_000), (3, 1_000), (4, 1_000)];
+pub const GENESIS_IDS: [(AccountId, VoteWeight); 4] = [(1, 10), (2, 1_000), (3, 1_000), (4, 1_000)];
 
 #[derive(Default)]
-pub(crate) struct ExtBuilder {
+pub struct ExtBuilder {
 	ids: Vec<(AccountId, VoteWeight)>,
 }
 
 impl ExtBuilder {
 	/// Add some AccountIds to insert into `List`.
-	pub(crate) fn add_ids(mut self, ids: Vec<(AccountId, VoteWeight)>) -> Self {
+	pub fn add_ids(mut self, ids: Vec<(AccountId, VoteWeight)>) -> Self {
 		self.ids = ids;
 		self
 	}
 
-	pub(crate) fn build(self) -> sp_io::TestExternalities {
+	pub fn build(self) -> sp_io::TestExternalities {
 		sp_tracing::try_init_simple();
 		let storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
 
 		let mut ext = sp_io::TestExternalities::from(storage);
 		ext.execute_with(|| {
+			// as a full genesis build would, e.g. to set the storage versions.
+			<AllPalletsWithSystem as frame_support::traits::OnGenesis>::on_genesis();
 			for (id, weight) in GENESIS_IDS.iter().chain(self.ids.iter()) {
-				frame_support::assert_ok!(List::<Runtime>::insert(*id, *weight));
+				frame_support::assert_ok!(List::<Runtime>::try_insert(*id, *weight));
 			}
 		});
 
 		ext
 	}
 
-	pub(crate) fn build_and_execute(self, test: impl FnOnce() -> ()) {
+	/// Build the externalities, with the extensions needed to run the offchain worker.
+	pub fn build_offchainify(self) -> (sp_io::TestExternalities, Arc<RwLock<PoolState>>) {
+		let mut ext = self.build();
+		let (offchain, _) = TestOffchainExt::new();
+		let (pool, pool_state) = TestTransactionPoolExt::new();
+
+		ext.register_extension(OffchainDbExt::new(offchain.clone()));
+		ext.register_extension(OffchainWorkerExt::new(offchain));
+		ext.register_extension(TransactionPoolExt::new(pool));
+
+		(ext, pool_state)
+	}
+
+	pub fn build_and_execute(self, test: impl FnOnce() -> ()) {
 		self.build().execute_with(|| {
 			test();
-			List::<Runtime>::sanity_check().expect("Sanity check post condition failed")
+			List::<Runtime>::sanity_check(SanityCheckLevel::Full)
+				.expect("Sanity check post condition failed")
 		})
 	}
 
-	pub(crate) fn build_and_execute_no_post_check(self, test: impl FnOnce() -> ()) {
+	pub fn build_and_execute_no_post_check(self, test: impl FnOnce() -> ()) {
 		self.build().execute_with(test)
 	}
 }
 
+#[cfg(test)]
 pub(crate) mod test_utils {
 	use super::*;
 	use list::Bag;
@@ -151,4 +351,10 @@ pub(crate) mod test_utils {
 	pub(crate) fn get_list_as_ids() -> Vec<AccountId> {
 		List::<Runtime>::iter().map(|n| *n.id()).collect::<Vec<_>>()
 	}
+
+	/// Overwrite the count of the nodes of the list, as if it drifted from the actual nodes.
+	pub(crate) fn set_node_count(count: u32) {
+		let key = frame_support::storage::storage_prefix(b"BagsList", b"CounterForListNodes");
+		frame_support::storage::unhashed::put(&key, &count);
+	}
 }
@@ -0,0 +1,96 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A signed extension that keeps redundant `rebag` transactions out of the transaction pool.
+
+use crate::{Call, Config, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{traits::IsSubType, CloneNoBound, EqNoBound, PartialEqNoBound};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+use sp_std::marker::PhantomData;
+
+/// Rejects [`Call::rebag`] transactions that would be no-ops, i.e. for ids that are not in the
+/// list, or that are already in the right bag.
+///
+/// A valid `rebag` provides a tag unique to the id being rebagged, such that only one rebag of
+/// any given id, from any sender, is kept in the pool. All the other calls pass through.
+///
+/// Add this to the `SignedExtra` of the runtime to use it.
+#[derive(Encode, Decode, CloneNoBound, EqNoBound, PartialEqNoBound, TypeInfo)]
+#[scale_info(skip_type_params(T, I))]
+pub struct CheckRebag<T: Config<I> + Send + Sync, I: 'static + Send + Sync = ()>(
+	PhantomData<(T, I)>,
+);
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> CheckRebag<T, I> {
+	/// Create a new instance of the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> Default for CheckRebag<T, I> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> sp_std::fmt::Debug for CheckRebag<T, I> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckRebag")
+	}
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> SignedExtension for CheckRebag<T, I>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T, I>>,
+{
+	const IDENTIFIER: &'static str = "CheckRebag";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		match call.is_sub_type() {
+			Some(Call::rebag { dislocated }) => {
+				Pallet::<T, I>::rebag_pre_dispatch_checks(dislocated)
+					.map_err(|_| InvalidTransaction::Stale)?;
+				ValidTransaction::with_tag_prefix("BagsListRebag")
+					.and_provides(dislocated)
+					.build()
+			},
+			_ => Ok(Default::default()),
+		}
+	}
+}
@@ -15,15 +15,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use frame_support::{assert_ok, assert_storage_noop, traits::IntegrityTest};
+use frame_support::{
+	assert_noop, assert_ok, assert_storage_noop,
+	traits::IntegrityTest,
+	weights::{GetDispatchInfo, Pays, RuntimeDbWeight},
+};
 
 use super::*;
-use frame_election_provider_support::SortedListProvider;
+use frame_election_provider_support::{ScoreProvider, SortedListProvider, VoteWeight};
 use list::Bag;
 use mock::{test_utils::*, *};
+use sp_std::convert::TryInto;
 
 mod pallet {
 	use super::*;
+	use frame_support::weights::Weight;
 
 	#[test]
 	fn rebag_works() {
@@ -78,6 +84,26 @@ mod pallet {
 
 	// Rebagging the tail of a bag results in the old bag having a new tail and an overall correct
 	// state.
+	#[test]
+	fn do_rebag_many_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 15), (3, 1_000), (4, 45)].into_iter().collect(),
+			);
+
+			// when
+			let outcomes = BagsList::do_rebag_many(&[2, 1, 4, 42, 3]);
+
+			// then each id is moved into the bag of its current score, if needed
+			assert_eq!(outcomes, vec![Some((1_000, 20)), None, Some((1_000, 50)), None, None]);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![2]), (50, vec![4]), (1_000, vec![3])]
+			);
+		});
+	}
+
 	#[test]
 	fn rebag_tail_works() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -141,249 +167,2756 @@ mod pallet {
 	}
 
 	#[test]
-	fn wrong_rebag_is_noop() {
+	fn rebag_is_free_only_if_it_moves() {
 		ExtBuilder::default().build_and_execute(|| {
-			let node_3 = list::Node::<Runtime>::get(&3).unwrap();
-			// when account 3 is _not_ misplaced with weight 500
-			NextVoteWeight::set(500);
-			assert!(!node_3.is_misplaced(500));
-
-			// then calling rebag on account 3 with weight 500 is a noop
-			assert_storage_noop!(assert_eq!(BagsList::rebag(Origin::signed(0), 3), Ok(())));
+			// given account 2 and 3 are correctly placed in bag 1_000, but 2 is not with weight 20
+			NextVoteWeightMap::set(vec![(2, 20), (3, 1_000)].into_iter().collect());
 
-			// when account 42 is not in the list
-			assert!(!BagsList::contains(&42));
+			// then rebagging 2 is free
+			assert_eq!(
+				BagsList::rebag(Origin::signed(0), 2).map(|info| info.pays_fee),
+				Ok(Pays::No)
+			);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![2]), (1_000, vec![3, 4])]
+			);
 
-			// then rebag-ing account 42 is a noop
-			assert_storage_noop!(assert_eq!(BagsList::rebag(Origin::signed(0), 42), Ok(())));
+			// but rebagging 3 is not
+			assert_eq!(
+				BagsList::rebag(Origin::signed(0), 3).map(|info| info.pays_fee),
+				Ok(Pays::Yes)
+			);
 		});
 	}
 
 	#[test]
-	#[should_panic = "thresholds must strictly increase, and have no duplicates"]
-	fn duplicate_in_bags_threshold_panics() {
-		const DUPE_THRESH: &[VoteWeight; 4] = &[10, 20, 30, 30];
-		BagThresholds::set(DUPE_THRESH);
-		BagsList::integrity_test();
+	fn rebag_refunds_unused_weight() {
+		ExtBuilder::default().build_and_execute(|| {
+			let actual_weight = |dislocated| {
+				BagsList::rebag(Origin::signed(0), dislocated).unwrap().actual_weight.unwrap()
+			};
+
+			// given 2, 3 and 4 in bag 1_000, of which 3 is not terminal
+			NextVoteWeight::set(1_000);
+			DbWeight::set(RuntimeDbWeight { read: 1, write: 10 });
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// when 3 does not move, then only the noop and the slash of the deposit are charged
+			assert_eq!(actual_weight(3), <() as WeightInfo>::rebag_noop() + 4 + 30);
+
+			// when 3 moves, then the non terminal case and the reward are charged
+			StakingMock::set_score_of(&3, 20);
+			assert_eq!(actual_weight(3), <() as WeightInfo>::rebag_non_terminal() + 5 + 60);
+
+			// when 4 moves, then the terminal case and the reward are charged
+			StakingMock::set_score_of(&4, 20);
+			assert_eq!(actual_weight(4), <() as WeightInfo>::rebag_terminal() + 5 + 60);
+
+			// and all of it is charged upfront.
+			let upfront =
+				crate::Call::<Runtime>::rebag { dislocated: 4 }.get_dispatch_info().weight;
+			assert_eq!(
+				upfront,
+				<() as WeightInfo>::rebag_non_terminal().max(<() as WeightInfo>::rebag_terminal()) +
+					5 + 60
+			);
+
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![3, 4]), (1_000, vec![2])]
+			);
+		});
 	}
 
 	#[test]
-	#[should_panic = "thresholds must strictly increase, and have no duplicates"]
-	fn decreasing_in_bags_threshold_panics() {
-		const DECREASING_THRESH: &[VoteWeight; 4] = &[10, 30, 20, 40];
-		BagThresholds::set(DECREASING_THRESH);
-		BagsList::integrity_test();
+	fn rebag_rewards_the_rebagger() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			let rebag = |rebagger, dislocated| {
+				BagsList::rebag(Origin::signed(rebagger), dislocated).unwrap().pays_fee
+			};
+
+			// given a reward pot that can pay two rewards
+			RebagReward::set(5);
+			let _ = Balances::make_free_balance_be(&BagsList::reward_pot(), 11);
+			NextVoteWeight::set(1_000);
+
+			// when a rebag does not move anything, then it is not rewarded
+			assert_eq!(rebag(42, 2), Pays::Yes);
+			assert_eq!(Balances::free_balance(42), 0);
+
+			// when 2 is moved, then the rebagger is rewarded
+			StakingMock::set_score_of(&2, 20);
+			assert_eq!(rebag(42, 2), Pays::No);
+			assert_eq!(Balances::free_balance(42), 5);
+			assert!(System::events()
+				.into_iter()
+				.any(|r| r.event == mock::Event::BagsList(crate::Event::RebagRewarded(42, 5))));
+
+			// when 2 is moved again within its cooldown, then it is not rewarded
+			StakingMock::set_score_of(&2, 1_000);
+			assert_eq!(rebag(42, 2), Pays::No);
+			assert_eq!(Balances::free_balance(42), 5);
+
+			// but another id is
+			StakingMock::set_score_of(&3, 20);
+			assert_eq!(rebag(43, 3), Pays::No);
+			assert_eq!(Balances::free_balance(43), 5);
+
+			// when the pot can not pay anymore, then rebags are free but not rewarded
+			StakingMock::set_score_of(&4, 20);
+			assert_eq!(rebag(43, 4), Pays::No);
+			assert_eq!(Balances::free_balance(43), 5);
+			assert_eq!(Balances::free_balance(BagsList::reward_pot()), 1);
+
+			// when the cooldown of 2 is over, then it can be rewarded again
+			System::set_block_number(1 + RebagRewardCooldown::get());
+			let _ = Balances::make_free_balance_be(&BagsList::reward_pot(), 11);
+			StakingMock::set_score_of(&2, 20);
+			assert_eq!(rebag(42, 2), Pays::No);
+			assert_eq!(Balances::free_balance(42), 10);
+
+			// and its cooldown is forgotten once it is removed
+			BagsList::on_remove(&2);
+			assert!(!LastRewardedRebag::<Runtime>::contains_key(2));
+		});
 	}
 
 	#[test]
-	fn empty_threshold_works() {
-		BagThresholds::set(Default::default()); // which is the same as passing `()` to `Get<_>`.
-
+	fn rebag_deposit_is_slashed_unless_it_moves() {
 		ExtBuilder::default().build_and_execute(|| {
-			// everyone in the same bag.
-			assert_eq!(List::<Runtime>::get_bags(), vec![(VoteWeight::MAX, vec![1, 2, 3, 4])]);
+			System::set_block_number(1);
+			RebagDeposit::set(5);
+			NextVoteWeight::set(1_000);
+			let _ = Balances::make_free_balance_be(&42, 12);
+
+			// when a rebag does not move anything, then the deposit is slashed into the pot
+			assert_ok!(BagsList::rebag(Origin::signed(42), 2));
+			assert_eq!(Balances::free_balance(42), 7);
+			assert_eq!(Balances::reserved_balance(42), 0);
+			assert_eq!(Balances::free_balance(BagsList::reward_pot()), 5);
+			assert!(System::events().into_iter().any(
+				|r| r.event == mock::Event::BagsList(crate::Event::RebagDepositSlashed(42, 5))
+			));
 
-			// any insertion goes there as well.
-			assert_ok!(List::<Runtime>::insert(5, 999));
-			assert_ok!(List::<Runtime>::insert(6, 0));
+			// when 2 is moved, then the deposit is returned
+			StakingMock::set_score_of(&2, 20);
+			assert_ok!(BagsList::rebag(Origin::signed(42), 2));
+			assert_eq!(Balances::free_balance(42), 7);
+			assert_eq!(Balances::reserved_balance(42), 0);
+
+			// when the caller cannot afford the deposit, then nothing happens
+			StakingMock::set_score_of(&3, 20);
+			assert_noop!(
+				BagsList::rebag(Origin::signed(43), 3),
+				Error::<Runtime>::InsufficientDeposit
+			);
+		});
+	}
+
+	#[test]
+	fn rebag_is_rate_limited() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			RebagCooldown::set(5);
+
+			// when 2 is moved
+			StakingMock::set_score_of(&2, 20);
+			assert_ok!(BagsList::rebag(Origin::signed(42), 2));
+
+			// then it can not be rebagged again within the cooldown, by anyone
+			StakingMock::set_score_of(&2, 1_000);
+			assert_noop!(BagsList::rebag(Origin::signed(43), 2), Error::<Runtime>::RebagTooSoon);
+			assert_noop!(
+				BagsList::rebag_unsigned(Origin::none(), 2),
+				Error::<Runtime>::RebagTooSoon
+			);
 			assert_eq!(
-				List::<Runtime>::get_bags(),
-				vec![(VoteWeight::MAX, vec![1, 2, 3, 4, 5, 6])]
+				BagsList::rebag_pre_dispatch_checks(&2),
+				Err(Error::<Runtime>::RebagTooSoon)
 			);
 
-			// any rebag is noop.
-			assert_storage_noop!(assert!(BagsList::rebag(Origin::signed(0), 1).is_ok()));
-			assert_storage_noop!(assert!(BagsList::rebag(Origin::signed(0), 10).is_ok()));
-		})
-	}
-}
+			// but the `ScoreProvider` can still move it
+			assert!(BagsList::on_update(&2, 1_000));
 
-mod sorted_list_provider {
-	use super::*;
+			// and other ids are not affected
+			StakingMock::set_score_of(&3, 20);
+			assert_ok!(BagsList::rebag(Origin::signed(43), 3));
+
+			// when the cooldown is over, then 2 can be rebagged again
+			System::set_block_number(6);
+			StakingMock::set_score_of(&2, 20);
+			assert_ok!(BagsList::rebag(Origin::signed(43), 2));
+			assert_eq!(BagsList::bag_for(&2), Some((20, 20)));
+
+			// and its cooldown is forgotten once it is removed
+			BagsList::on_remove(&2);
+			assert!(!LastRebag::<Runtime>::contains_key(2));
+		});
+	}
 
 	#[test]
-	fn iter_works() {
+	fn neighbours_works() {
 		ExtBuilder::default().build_and_execute(|| {
-			let expected = vec![2, 3, 4, 1];
-			for (i, id) in BagsList::iter().enumerate() {
-				assert_eq!(id, expected[i])
-			}
+			// given
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// then
+			assert_eq!(BagsList::neighbours(&1), Some((None, None)));
+			assert_eq!(BagsList::neighbours(&2), Some((None, Some(3))));
+			assert_eq!(BagsList::neighbours(&3), Some((Some(2), Some(4))));
+			assert_eq!(BagsList::neighbours(&4), Some((Some(3), None)));
+			assert_eq!(BagsList::neighbours(&42), None);
+
+			// when 4 goes in front of 2
+			List::<Runtime>::set_cached_score(&2, 999);
+			assert_ok!(BagsList::put_in_front_of(Origin::signed(4), 2));
+
+			// then
+			assert_eq!(BagsList::neighbours(&4), Some((None, Some(2))));
+			assert_eq!(BagsList::neighbours(&3), Some((Some(2), None)));
 		});
 	}
 
 	#[test]
-	fn count_works() {
+	fn iter_bag_and_bag_page_work() {
+		ExtBuilder::default()
+			.add_ids(vec![(5, 1_000), (6, 2_000)])
+			.build_and_execute(|| {
+				// given
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![(10, vec![1]), (1_000, vec![2, 3, 4, 5]), (2_000, vec![6])]
+				);
+
+				// then a single bag can be iterated
+				assert_eq!(BagsList::iter_bag(1_000).collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+				assert_eq!(BagsList::iter_bag(10).collect::<Vec<_>>(), vec![1]);
+				assert_eq!(BagsList::iter_bag(20).count(), 0);
+
+				// and read page by page
+				assert_eq!(BagsList::bag_page(1_000, None, 3), vec![2, 3, 4]);
+				assert_eq!(BagsList::bag_page(1_000, Some(4), 3), vec![5]);
+				assert!(BagsList::bag_page(1_000, Some(5), 3).is_empty());
+
+				// but not from an id of another bag, or not in the list
+				assert!(BagsList::bag_page(1_000, Some(6), 3).is_empty());
+				assert!(BagsList::bag_page(1_000, Some(42), 3).is_empty());
+			});
+	}
+
+	#[test]
+	fn bag_for_works() {
 		ExtBuilder::default().build_and_execute(|| {
-			// given
-			assert_eq!(BagsList::count(), 4);
+			// given account 1 in bag 10 and account 2 in bag 1_000
+			NextVoteWeightMap::set(vec![(1, 10), (2, 1_000)].into_iter().collect());
 
-			// when inserting
-			assert_ok!(BagsList::on_insert(201, 0));
-			// then the count goes up
-			assert_eq!(BagsList::count(), 5);
+			// then they are in their canonical bags
+			assert_eq!(BagsList::bag_for(&1), Some((10, 10)));
+			assert_eq!(BagsList::bag_for(&2), Some((1_000, 1_000)));
 
-			// when removing
-			BagsList::on_remove(&201);
-			// then the count goes down
-			assert_eq!(BagsList::count(), 4);
+			// when the weight of account 2 drops
+			StakingMock::set_score_of(&2, 15);
 
-			// when updating
-			BagsList::on_update(&201, VoteWeight::MAX);
-			// then the count stays the same
-			assert_eq!(BagsList::count(), 4);
+			// then its canonical bag differs from its current bag
+			assert_eq!(BagsList::bag_for(&2), Some((1_000, 20)));
+
+			// until it is rebagged
+			assert_ok!(BagsList::rebag(Origin::signed(0), 2));
+			assert_eq!(BagsList::bag_for(&2), Some((20, 20)));
+
+			// and accounts that are not in the list have no bag
+			assert_eq!(BagsList::bag_for(&42), None);
 		});
 	}
 
 	#[test]
-	fn on_insert_works() {
+	fn rebag_needed_works() {
 		ExtBuilder::default().build_and_execute(|| {
-			// when
-			assert_ok!(BagsList::on_insert(6, 1_000));
+			// given account 2 in its canonical bag
+			NextVoteWeightMap::set(vec![(2, 1_000)].into_iter().collect());
+			assert_eq!(BagsList::rebag_needed(&2), None);
+
+			// when its score drops
+			StakingMock::set_score_of(&2, 15);
+
+			// then a rebag would move it into bag 20
+			assert_eq!(BagsList::rebag_needed(&2), Some(20));
+
+			// unless that bag is full and overflowing ids are rejected
+			MaxBagSize::set(0);
+			BagOverflowPolicy::set(BagOverflow::Reject);
+			assert_eq!(BagsList::rebag_needed(&2), None);
+			MaxBagSize::set(u32::MAX);
+			BagOverflowPolicy::set(BagOverflow::SpillLower);
+
+			// or the list is locked
+			<BagsList as SortedListProvider<_>>::lock(ListLock::Election);
+			assert_eq!(BagsList::rebag_needed(&2), None);
+			<BagsList as SortedListProvider<_>>::unlock(ListLock::Election);
+
+			// and once rebagged, it is in a cooldown
+			RebagCooldown::set(5);
+			assert_ok!(BagsList::rebag(Origin::signed(0), 2));
+			StakingMock::set_score_of(&2, 1_000);
+			assert_eq!(BagsList::rebag_needed(&2), None);
+			System::set_block_number(5);
+			assert_eq!(BagsList::rebag_needed(&2), Some(1_000));
 
-			// then the bags
-			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4, 6])]);
-			// and list correctly include the new id,
-			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![2, 3, 4, 6, 1]);
-			// and the count is incremented.
-			assert_eq!(BagsList::count(), 5);
+			// while ids that are not in the list need no rebag
+			assert_eq!(BagsList::rebag_needed(&42), None);
+		});
+	}
 
-			// when
-			assert_ok!(BagsList::on_insert(7, 1_001));
+	#[test]
+	fn is_displaced_works() {
+		ExtBuilder::default().add_ids(vec![(5, 500), (6, 70)]).build_and_execute(|| {
+			// given ids placed in bag 1_000, which spans (60, 1_000] with a margin of 94
+			NextVoteWeightMap::set(vec![(2, 1_000), (5, 500), (6, 70)].into_iter().collect());
+			assert!(!BagsList::is_displaced(&2));
+			assert!(!BagsList::is_displaced(&5));
+			assert!(!BagsList::is_displaced(&6));
+
+			// when the live scores of ids cached near a threshold leave the bag
+			StakingMock::set_score_of(&2, 1_001);
+			StakingMock::set_score_of(&6, 50);
+
+			// then they are displaced
+			assert!(BagsList::is_displaced(&2));
+			assert!(BagsList::is_displaced(&6));
+
+			// but the live score of an id cached well within the bag is not even read
+			StakingMock::set_score_of(&5, 15);
+			assert!(!BagsList::is_displaced(&5));
+			assert_eq!(BagsList::bag_for(&5), Some((1_000, 20)));
+
+			// until it is placed again
+			assert_ok!(BagsList::rebag(Origin::signed(0), 5));
+			assert!(!BagsList::is_displaced(&5));
+
+			// while an id forced out of the bag of its score is not, as it is kept there
+			StakingMock::set_score_of(&3, 1_000);
+			assert_ok!(BagsList::force_set_bag(Origin::root(), 3, 10));
+			assert!(!BagsList::is_displaced(&3));
+
+			// and ids that are not in the list are not
+			assert!(!BagsList::is_displaced(&42));
+		});
+	}
 
-			// then the bags
+	#[test]
+	fn last_placed_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given the genesis ids placed in block 0
+			assert_eq!(BagsList::last_placed(&1), Some(0));
+			assert_eq!(BagsList::last_placed(&2), Some(0));
+
+			// when an id is moved into another bag in block 5
+			System::set_block_number(5);
+			assert!(<BagsList as SortedListProvider<_>>::on_update(&2, 15));
+
+			// then its placement is recorded
+			assert_eq!(BagsList::last_placed(&2), Some(5));
+
+			// but not when it is only reordered, or stays in its bag
+			System::set_block_number(6);
+			List::<Runtime>::set_cached_score(&4, 999);
+			assert_ok!(BagsList::put_in_front_of(Origin::signed(3), 4));
+			assert!(!<BagsList as SortedListProvider<_>>::on_update(&3, 900));
+			assert_eq!(BagsList::last_placed(&3), Some(0));
+
+			// while new ids are placed in the block they are inserted
+			assert_ok!(<BagsList as SortedListProvider<_>>::on_insert(5, 30));
+			assert_eq!(BagsList::last_placed(&5), Some(6));
+
+			// and removed ids are forgotten
+			<BagsList as SortedListProvider<_>>::on_remove(&2);
+			assert_eq!(BagsList::last_placed(&2), None);
+			assert!(!LastPlaced::<Runtime>::contains_key(2));
+		});
+	}
+
+	#[test]
+	fn canonical_bag_in_works() {
+		let thresholds: &[VoteWeight] = &[10, 20, 30];
+
+		// a score up to a threshold belongs in the bag of that threshold,
+		assert_eq!(canonical_bag_in(thresholds, 0), 10);
+		assert_eq!(canonical_bag_in(thresholds, 10), 10);
+		assert_eq!(canonical_bag_in(thresholds, 11), 20);
+		assert_eq!(canonical_bag_in(thresholds, 30), 30);
+		// and above the highest one in the implied bag of `max_value`, whether or not it is given.
+		assert_eq!(canonical_bag_in(thresholds, 31), VoteWeight::MAX);
+		assert_eq!(canonical_bag_in(&[10, VoteWeight::MAX], 31), VoteWeight::MAX);
+		assert_eq!(canonical_bag_in(thresholds, VoteWeight::MAX), VoteWeight::MAX);
+
+		// without thresholds, everything is in a single bag.
+		assert_eq!(canonical_bag_in::<VoteWeight>(&[], 0), VoteWeight::MAX);
+		assert_eq!(canonical_bag_in::<u128>(&[], 1 << 100), u128::MAX);
+	}
+
+	#[test]
+	fn genesis_thresholds_are_stored() {
+		use frame_support::traits::{GenesisBuild, U128CurrencyToVote};
+
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			assert_eq!(list::thresholds::<Runtime, ()>(), BagThresholds::get().to_vec());
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000)].into_iter().collect(),
+			);
+
+			// when
+			GenesisBuild::<Runtime>::build(&crate::GenesisConfig::<Runtime> {
+				thresholds: Some(GenesisThresholds {
+					existential_deposit: 10,
+					total_issuance: 1_000_000,
+					count: 4,
+				}),
+				..Default::default()
+			});
+
+			// then they are those of `make_bags`,
+			let (expected, _) =
+				crate::make_bags::make_bags::<u128, U128CurrencyToVote>(10, 1_000_000, 4);
+			let thresholds = list::thresholds::<Runtime, ()>();
+			assert_eq!(thresholds, expected);
+			assert_eq!(thresholds.len(), 4);
+			assert_eq!(thresholds[0], 10);
+			assert_eq!(thresholds[3], VoteWeight::MAX);
+			assert_eq!(BagsList::canonical_bag_for(50), thresholds[1]);
+
+			// and the ids inserted before are moved into their new bags.
 			assert_eq!(
 				List::<Runtime>::get_bags(),
-				vec![(10, vec![1]), (1_000, vec![2, 3, 4, 6]), (2_000, vec![7])]
+				vec![(10, vec![1]), (thresholds[1], vec![2, 3, 4])]
 			);
-			// and list correctly include the new id,
-			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![7, 2, 3, 4, 6, 1]);
-			// and the count is incremented.
-			assert_eq!(BagsList::count(), 6);
-		})
+		});
 	}
 
 	#[test]
-	fn on_insert_errors_with_duplicate_id() {
+	fn bag_index_works() {
+		let thresholds: &[VoteWeight] = &[10, 20, 30];
+
+		assert_eq!(bag_index_in(thresholds, 10), 0);
+		assert_eq!(bag_index_in(thresholds, 30), 2);
+		// the implied bag of `max_value` is the last one, whether or not it is given.
+		assert_eq!(bag_index_in(thresholds, VoteWeight::MAX), 3);
+		assert_eq!(bag_index_in(&[10, VoteWeight::MAX], VoteWeight::MAX), 1);
+		// and a removed threshold has the index of the bag it falls into.
+		assert_eq!(bag_index_in(thresholds, 15), 1);
+
 		ExtBuilder::default().build_and_execute(|| {
-			// given
-			assert!(get_list_as_ids().contains(&3));
+			assert_eq!(BagsList::bag_index(10), 0);
+			assert_eq!(BagsList::bag_index(1_000), 6);
+			assert_eq!(BagsList::bag_index(VoteWeight::MAX), 9);
+
+			// rebagging reports the indices of both bags.
+			System::set_block_number(1);
+			StakingMock::set_score_of(&1, 1_000);
+			assert_ok!(BagsList::rebag(Origin::signed(0), 1));
+			assert!(System::events().iter().any(|record| record.event ==
+				mock::Event::BagsList(crate::Event::Rebagged(1, 10, 1_000, 0, 6))));
+		});
+	}
 
-			// then
-			assert_storage_noop!(assert_eq!(
-				BagsList::on_insert(3, 20).unwrap_err(),
-				Error::Duplicate
-			));
+	#[test]
+	fn canonical_bag_for_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// the bag of a score is the one ids with that score are inserted into.
+			assert_eq!(BagsList::canonical_bag_for(15), 20);
+			assert_eq!(BagsList::canonical_bag_for(10_001), VoteWeight::MAX);
+			assert_eq!(SecondBagsList::canonical_bag_for(150), 200);
+			assert_ok!(List::<Runtime>::try_insert(5, 15));
+			assert_eq!(BagsList::bag_for(&5).map(|(bag, _)| bag), Some(20));
+
+			// when the thresholds are updated, then they are used.
+			let new = vec![10, 15, 1_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+			assert_eq!(BagsList::canonical_bag_for(15), 15);
+			assert_eq!(BagsList::canonical_bag_for(16), 1_000);
 		});
 	}
 
 	#[test]
-	fn on_update_works() {
-		ExtBuilder::default().add_ids(vec![(42, 20)]).build_and_execute(|| {
+	fn thresholds_and_constant_ratio_work() {
+		ExtBuilder::default().build_and_execute(|| {
+			// the configured thresholds are in use, the two highest implying a ratio of 5.
+			assert_eq!(BagsList::thresholds(), vec![10, 20, 30, 40, 50, 60, 1_000, 2_000, 10_000]);
+			assert_eq!(BagsList::constant_ratio(), Some(FixedU128::saturating_from_integer(5)));
+
+			// when the thresholds are updated, then the new ones are reported; the implied
+			// `max_value` threshold does not count towards the ratio,
+			let new = vec![10, 15, VoteWeight::MAX].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+			assert_eq!(BagsList::thresholds(), vec![10, 15, VoteWeight::MAX]);
+			assert_eq!(BagsList::constant_ratio(), Some(FixedU128::saturating_from_rational(3, 2)));
+			BagsList::do_migrate_thresholds(Weight::MAX);
+
+			// so a single threshold below it implies no ratio.
+			let new = vec![10, VoteWeight::MAX].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+			assert_eq!(BagsList::constant_ratio(), None);
+		});
+	}
+
+	#[test]
+	fn bag_stats_works() {
+		ExtBuilder::default().add_ids(vec![(5, 1_000)]).build_and_execute(|| {
+			let stats = |bag_upper, head, tail, count| BagStats { bag_upper, head, tail, count };
+
+			assert_eq!(
+				BagsList::bag_stats(),
+				vec![
+					stats(10, Some(1), Some(1), 1),
+					stats(20, None, None, 0),
+					stats(30, None, None, 0),
+					stats(40, None, None, 0),
+					stats(50, None, None, 0),
+					stats(60, None, None, 0),
+					stats(1_000, Some(2), Some(5), 4),
+					stats(2_000, None, None, 0),
+					stats(10_000, None, None, 0),
+					stats(VoteWeight::MAX, None, None, 0),
+				]
+			);
+		});
+	}
+
+	#[test]
+	fn bag_histogram_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			let occupancy =
+				|bag_upper, count, total_score| BagOccupancy { bag_upper, count, total_score };
+
 			// given
 			assert_eq!(
-				List::<Runtime>::get_bags(),
-				vec![(10, vec![1]), (20, vec![42]), (1_000, vec![2, 3, 4])]
+				BagsList::bag_histogram(),
+				vec![occupancy(10, 1, 10), occupancy(1_000, 3, 3_000)]
 			);
-			assert_eq!(BagsList::count(), 5);
 
-			// when increasing weight to the level of non-existent bag
-			BagsList::on_update(&42, 2_000);
+			// when an id is inserted
+			assert_ok!(BagsList::on_insert(5, 15));
+			// then
+			assert_eq!(
+				BagsList::bag_histogram(),
+				vec![occupancy(10, 1, 10), occupancy(20, 1, 15), occupancy(1_000, 3, 3_000)]
+			);
 
-			// then the bag is created with the id in it,
+			// when an id is rebagged
+			StakingMock::set_score_of(&2, 2_000);
+			assert_ok!(BagsList::rebag(Origin::signed(0), 2));
+			// then its new score is recorded in its new bag
 			assert_eq!(
-				List::<Runtime>::get_bags(),
-				vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (2000, vec![42])]
+				BagsList::bag_histogram(),
+				vec![
+					occupancy(10, 1, 10),
+					occupancy(20, 1, 15),
+					occupancy(1_000, 2, 2_000),
+					occupancy(2_000, 1, 2_000)
+				]
 			);
-			// and the id position is updated in the list.
-			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![42, 2, 3, 4, 1]);
 
-			// when decreasing weight within the range of the current bag
-			BagsList::on_update(&42, 1_001);
+			// when an id's score changes within its bag
+			BagsList::on_update(&3, 999);
+			// then the score it was placed with is kept
+			assert_eq!(BagsList::bag_histogram()[2], occupancy(1_000, 2, 2_000));
 
-			// then the id does not change bags,
+			// when the last id of a bag is removed
+			BagsList::on_remove(&1);
+			// then the bag is gone from the histogram
 			assert_eq!(
-				List::<Runtime>::get_bags(),
-				vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (2000, vec![42])]
+				BagsList::bag_histogram(),
+				vec![occupancy(20, 1, 15), occupancy(1_000, 2, 2_000), occupancy(2_000, 1, 2_000)]
 			);
-			// or change position in the list.
-			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![42, 2, 3, 4, 1]);
+		});
+	}
 
-			// when increasing weight to the level of a non-existent bag with the max threshold
-			BagsList::on_update(&42, VoteWeight::MAX);
+	#[test]
+	fn wrong_rebag_errs() {
+		ExtBuilder::default().build_and_execute(|| {
+			let node_3 = list::Node::<Runtime>::get(&3).unwrap();
+			// when account 3 is _not_ misplaced with weight 500
+			NextVoteWeight::set(500);
+			assert!(!node_3.is_misplaced(500));
 
-			// the the new bag is created with the id in it,
+			// then calling rebag on account 3 with weight 500 is a noop
+			assert_storage_noop!(assert_eq!(
+				BagsList::rebag(Origin::signed(0), 3),
+				Ok((Some(<() as WeightInfo>::rebag_noop()), Pays::Yes).into())
+			));
+
+			// when account 42 is not in the list
+			assert!(!BagsList::contains(&42));
+
+			// then rebag-ing account 42 fails, without changing storage
+			assert_noop!(
+				BagsList::rebag(Origin::signed(0), 42),
+				crate::Error::<Runtime>::NotInList
+			);
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_two_node_bag_heavier_is_tail() {
+		ExtBuilder::default().add_ids(vec![(10, 15), (11, 16)]).build_and_execute(|| {
+			// given
 			assert_eq!(
 				List::<Runtime>::get_bags(),
-				vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (VoteWeight::MAX, vec![42])]
+				vec![(10, vec![1]), (20, vec![10, 11]), (1_000, vec![2, 3, 4])]
 			);
-			// and the id position is updated in the list.
-			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![42, 2, 3, 4, 1]);
 
-			// when decreasing the weight to a pre-existing bag
-			BagsList::on_update(&42, 1_000);
+			// when
+			assert_ok!(BagsList::put_in_front_of(Origin::signed(11), 10));
 
-			// then id is moved to the correct bag (as the last member),
+			// then
 			assert_eq!(
 				List::<Runtime>::get_bags(),
-				vec![(10, vec![1]), (1_000, vec![2, 3, 4, 42])]
+				vec![(10, vec![1]), (20, vec![11, 10]), (1_000, vec![2, 3, 4])]
 			);
-			// and the id position is updated in the list.
-			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![2, 3, 4, 42, 1]);
-
-			// since we have only called on_update, the `count` has not changed.
-			assert_eq!(BagsList::count(), 5);
+			assert_eq!(Bag::<Runtime>::get(20).unwrap(), Bag::new(Some(11), Some(10), 20));
+			// and the cached scores are left as they are.
+			assert_eq!(list::Node::<Runtime>::get(&11).unwrap().score(), 16);
+			assert_eq!(list::Node::<Runtime>::get(&10).unwrap().score(), 15);
 		});
 	}
 
 	#[test]
-	fn on_remove_works() {
-		let ensure_left = |id, counter| {
-			assert!(!ListNodes::<Runtime>::contains_key(id));
-			assert_eq!(BagsList::count(), counter);
-			assert_eq!(CounterForListNodes::<Runtime>::get(), counter);
-			assert_eq!(ListNodes::<Runtime>::iter().count() as u32, counter);
-		};
-
-		ExtBuilder::default().build_and_execute(|| {
-			// it is a noop removing a non-existent id
-			assert!(!ListNodes::<Runtime>::contains_key(42));
-			assert_storage_noop!(BagsList::on_remove(&42));
+	fn put_in_front_of_non_terminal_nodes_heavier_behind() {
+		ExtBuilder::default().add_ids(vec![(5, 1_000)]).build_and_execute(|| {
+			// given
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4, 5])]);
+			List::<Runtime>::set_cached_score(&3, 999);
+			List::<Runtime>::set_cached_score(&4, 1_000);
 
-			// when removing a node from a bag with multiple nodes
-			BagsList::on_remove(&2);
+			// when
+			assert_ok!(BagsList::put_in_front_of(Origin::signed(4), 3));
 
 			// then
-			assert_eq!(get_list_as_ids(), vec![3, 4, 1]);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 4, 3, 5])]);
+			assert_eq!(Bag::<Runtime>::get(1_000).unwrap(), Bag::new(Some(2), Some(5), 1_000));
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_lighter_is_head_heavier_is_non_terminal() {
+		ExtBuilder::default().add_ids(vec![(5, 1_000)]).build_and_execute(|| {
+			// given
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4, 5])]);
+			List::<Runtime>::set_cached_score(&2, 999);
+			List::<Runtime>::set_cached_score(&4, 1_000);
+
+			// when
+			assert_ok!(BagsList::put_in_front_of(Origin::signed(4), 2));
+
+			// then the heavier node becomes the new head of the bag
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![4, 2, 3, 5])]);
+			assert_eq!(Bag::<Runtime>::get(1_000).unwrap(), Bag::new(Some(4), Some(5), 1_000));
+			assert_eq!(BagsList::count(), 5);
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_heavier_is_already_in_front() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+			List::<Runtime>::set_cached_score(&2, 1_000);
+			List::<Runtime>::set_cached_score(&4, 999);
+
+			// when
+			assert_ok!(BagsList::put_in_front_of(Origin::signed(2), 4));
+
+			// then
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![3, 2, 4])]);
+			assert_eq!(Bag::<Runtime>::get(1_000).unwrap(), Bag::new(Some(3), Some(4), 1_000));
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_errors_if_heavier_is_less_than_lighter() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			List::<Runtime>::set_cached_score(&3, 999);
+			List::<Runtime>::set_cached_score(&2, 1_000);
+
+			// then
+			assert_noop!(
+				BagsList::put_in_front_of(Origin::signed(3), 2),
+				crate::pallet::Error::<Runtime>::NotHeavier
+			);
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_errors_if_heavier_is_equal_weight_to_lighter() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			List::<Runtime>::set_cached_score(&3, 1_000);
+			List::<Runtime>::set_cached_score(&4, 1_000);
+
+			// then
+			assert_noop!(
+				BagsList::put_in_front_of(Origin::signed(3), 4),
+				crate::pallet::Error::<Runtime>::NotHeavier
+			);
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_compares_the_cached_scores() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given 4 got heavier than 3 since they were placed with the same score
+			StakingMock::set_score_of(&3, 999);
+
+			// then 4 cannot go in front of 3 until it is placed with its new score.
+			assert_noop!(
+				BagsList::put_in_front_of(Origin::signed(4), 3),
+				crate::pallet::Error::<Runtime>::NotHeavier
+			);
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_errors_if_nodes_not_found() {
+		ExtBuilder::default().build_and_execute(|| {
+			// `heavier` not in list
+			assert!(!ListNodes::<Runtime>::contains_key(5));
+			assert_noop!(
+				BagsList::put_in_front_of(Origin::signed(5), 4),
+				crate::pallet::Error::<Runtime>::NotInList
+			);
+
+			// `lighter` not in list
+			assert_noop!(
+				BagsList::put_in_front_of(Origin::signed(4), 5),
+				crate::pallet::Error::<Runtime>::NotInList
+			);
+		});
+	}
+
+	#[test]
+	fn put_in_front_of_errors_if_nodes_not_in_same_bag() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// then
+			assert_noop!(
+				BagsList::put_in_front_of(Origin::signed(4), 1),
+				crate::pallet::Error::<Runtime>::NotInSameBag
+			);
+		});
+	}
+
+	#[test]
+	fn force_set_bag_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+
+			// only root can force a bag,
+			assert_noop!(
+				BagsList::force_set_bag(Origin::signed(2), 2, 10),
+				sp_runtime::DispatchError::BadOrigin
+			);
+			// into an actual bag,
+			assert_noop!(
+				BagsList::force_set_bag(Origin::root(), 2, 15),
+				Error::<Runtime>::NotAThreshold
+			);
+			// and for an id in the list.
+			assert_noop!(
+				BagsList::force_set_bag(Origin::root(), 42, 10),
+				Error::<Runtime>::NotInList
+			);
+
+			// when an id is forced into a full bag that does not match its score
+			MaxBagSize::set(1);
+			assert_ok!(BagsList::force_set_bag(Origin::root(), 2, 10));
+
+			// then it is moved anyway.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![3, 4])]);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::BagForced(2, 1_000, 10))
+			);
+
+			// when it is forced into the bag it is in, then nothing happens.
+			let events = System::events().len();
+			assert_storage_noop!(assert_ok!(BagsList::force_set_bag(Origin::root(), 2, 10)));
+			assert_eq!(System::events().len(), events);
+
+			// the implied highest bag can be used too.
+			assert_ok!(BagsList::force_set_bag(Origin::root(), 3, VoteWeight::MAX));
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 2]), (1_000, vec![4]), (VoteWeight::MAX, vec![3])]
+			);
+
+			// but not while the list is locked.
+			BagsList::lock(ListLock::Election);
+			assert_noop!(BagsList::force_set_bag(Origin::root(), 4, 10), Error::<Runtime>::Locked);
+		});
+	}
+
+	#[test]
+	fn heal_broken_links_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+
+			// an id with sound links cannot be healed,
+			assert_noop!(
+				BagsList::heal_broken_links(Origin::signed(5), 3, 10),
+				Error::<Runtime>::NotBroken
+			);
+			// nor can an id that is not in the list.
+			assert_noop!(
+				BagsList::heal_broken_links(Origin::signed(5), 42, 10),
+				Error::<Runtime>::NotInList
+			);
+
+			// when 3 points at 1 as its next, while 4 still points back at 3
+			crate::ListNodes::<Runtime>::insert(
+				3,
+				list::Node::<Runtime>::from_parts(3, Some(2), Some(1), 1_000, 1_000),
+			);
+			assert!(List::<Runtime>::sanity_check(SanityCheckLevel::Full).is_err());
+
+			// then it cannot be healed without walking its bag to find 4, as 2 would be left
+			// pointing at nothing while not being the tail,
+			assert_noop!(
+				BagsList::heal_broken_links(Origin::signed(5), 3, 0),
+				Error::<Runtime>::StillBroken
+			);
+
+			// but it can be with a walk, for free, into the bag of its current score.
+			assert_eq!(
+				BagsList::heal_broken_links(Origin::signed(5), 3, 10).map(|info| info.pays_fee),
+				Ok(Pays::No)
+			);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 3]), (1_000, vec![2, 4])]);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::LinksHealed(3, 10))
+			);
+			assert_ok!(List::<Runtime>::sanity_check(SanityCheckLevel::Full));
+
+			// not while the list is locked though.
+			crate::ListNodes::<Runtime>::insert(
+				4,
+				list::Node::<Runtime>::from_parts(4, None, None, 1_000, 1_000),
+			);
+			BagsList::lock(ListLock::Election);
+			assert_noop!(
+				BagsList::heal_broken_links(Origin::signed(5), 4, 10),
+				Error::<Runtime>::Locked
+			);
+			BagsList::unlock(ListLock::Election);
+			assert_ok!(BagsList::heal_broken_links(Origin::signed(5), 4, 10));
+		});
+	}
+
+	#[test]
+	fn prune_dangling_nodes_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			let prune =
+				|limit| BagsList::prune_dangling_nodes(Origin::signed(5), limit).unwrap().pays_fee;
+
+			// given 2 and 4 are not members anymore, and a pot that can pay the rewards
+			DanglingIds::set(vec![2, 4]);
+			PruneReward::set(3);
+			let _ = Balances::make_free_balance_be(&BagsList::reward_pot(), 10);
+
+			// when they are pruned, then the caller is paid for each, for free
+			assert_eq!(prune(10), Pays::No);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![3])]);
+			assert_eq!(Balances::free_balance(5), 6);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::DanglingNodesPruned(5, 2, 6))
+			);
+			assert!(System::events()
+				.into_iter()
+				.any(|r| r.event == mock::Event::BagsList(crate::Event::Removed(4))));
+
+			// when nothing is left to prune, then the call is paid for.
+			assert_eq!(prune(10), Pays::Yes);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::DanglingNodesPruned(5, 0, 0))
+			);
+
+			// but not while the list is locked.
+			BagsList::lock(ListLock::Election);
+			assert_noop!(
+				BagsList::prune_dangling_nodes(Origin::signed(5), 10),
+				Error::<Runtime>::Locked
+			);
+		});
+	}
+
+	#[test]
+	fn prune_dangling_nodes_resumes() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			DanglingIds::set(vec![2, 4]);
+
+			// when the list is scanned one id at a time
+			for _ in 0..4 {
+				assert_ok!(BagsList::prune_dangling_nodes(Origin::signed(5), 1));
+				assert!(crate::PruneCursor::<Runtime>::get().is_some());
+			}
+
+			// then every id is checked once, and the next scan starts over.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![3])]);
+			assert_ok!(BagsList::prune_dangling_nodes(Origin::signed(5), 1));
+			assert_eq!(crate::PruneCursor::<Runtime>::get(), None);
+		});
+	}
+
+	#[test]
+	fn set_bag_thresholds_works() {
+		ExtBuilder::default().add_ids(vec![(42, 20), (43, 25)]).build_and_execute(|| {
+			System::set_block_number(1);
+
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (30, vec![43]), (1_000, vec![2, 3, 4])]
+			);
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000), (42, 20), (43, 25)]
+					.into_iter()
+					.collect(),
+			);
+
+			// when bag 30 is removed and bag 500 is inserted
+			let new = vec![10, 20, 500, 1_000, 2_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+
+			// then the thresholds are updated
+			assert_eq!(list::thresholds::<Runtime, ()>(), vec![10, 20, 500, 1_000, 2_000]);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::BagThresholdsUpdated(4))
+			);
+			// but the affected ids are not moved yet, and those of the removed bag are not
+			// iterated until they are.
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (1_000, vec![2, 3, 4])]
+			);
+			assert_eq!(BagsList::count(), 6);
+
+			// and the thresholds cannot be set again until they are moved.
+			assert_noop!(
+				BagsList::set_bag_thresholds(Origin::root(), vec![10].try_into().unwrap()),
+				Error::<Runtime>::ThresholdMigrationOngoing
+			);
+
+			// when
+			BagsList::do_migrate_thresholds(Weight::MAX);
+
+			// then the affected ids are moved into their new bags.
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (500, vec![43]), (1_000, vec![2, 3, 4])]
+			);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::ThresholdMigrationCompleted)
+			);
+			assert!(!crate::PendingThresholdMigration::<Runtime>::exists());
+
+			// and new insertions respect the new thresholds.
+			assert_ok!(List::<Runtime>::try_insert(5, 40));
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (500, vec![43, 5]), (1_000, vec![2, 3, 4])]
+			);
+		});
+	}
+
+	#[test]
+	fn set_bag_thresholds_without_affected_ids_needs_no_migration() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+
+			// when only empty bags are removed
+			let new = vec![10, 1_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+
+			// then nothing is left to migrate.
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::BagThresholdsUpdated(0))
+			);
+			assert!(!crate::PendingThresholdMigration::<Runtime>::exists());
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), vec![10].try_into().unwrap()));
+		});
+	}
+
+	#[test]
+	fn set_bag_thresholds_keeps_ids_beyond_max_bag_size() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given a bag that holds more than `MaxBagSize` ids, and no room elsewhere
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000)].into_iter().collect(),
+			);
+			MaxBagSize::set(1);
+			BagOverflowPolicy::set(BagOverflow::Reject);
+
+			// when its range changes
+			let new = vec![10, 500, 1_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+			BagsList::do_migrate_thresholds(Weight::MAX);
+
+			// then all of its ids are kept in the bag of their score.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+			assert_eq!(BagsList::count(), 4);
+		});
+	}
+
+	#[test]
+	fn set_bag_thresholds_moves_ids_beyond_max_bag_size() {
+		ExtBuilder::default().add_ids(vec![(42, 20), (43, 25)]).build_and_execute(|| {
+			// given no room in the bag of a removed threshold
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000), (42, 20), (43, 25)]
+					.into_iter()
+					.collect(),
+			);
+			MaxBagSize::set(1);
+			BagOverflowPolicy::set(BagOverflow::Reject);
+
+			// when bags 20 and 30 are removed
+			let new = vec![10, 1_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+			BagsList::do_migrate_thresholds(Weight::MAX);
+
+			// then their ids are moved into the bag of their score regardless, while the ids of
+			// the other bags are kept.
+			let mut bags = List::<Runtime>::get_bags();
+			bags.iter_mut().for_each(|(_, ids)| ids.sort());
+			assert_eq!(bags, vec![(10, vec![1]), (1_000, vec![2, 3, 4, 42, 43])]);
+			assert_eq!(BagsList::count(), 6);
+		});
+	}
+
+	#[test]
+	fn migrate_chunk_works() {
+		ExtBuilder::default().add_ids(vec![(42, 20), (43, 25)]).build_and_execute(|| {
+			System::set_block_number(1);
+			let old: frame_support::BoundedVec<_, _> =
+				BagThresholds::get().to_vec().try_into().unwrap();
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000), (42, 20), (43, 25)]
+					.into_iter()
+					.collect(),
+			);
+
+			// when a runtime upgrade removes bag 30 and inserts bag 500
+			BagThresholds::set(&[10, 20, 500, 1_000, 2_000]);
+
+			// then only root can migrate
+			assert_noop!(
+				BagsList::migrate_chunk(Origin::signed(1), old.clone(), 4),
+				sp_runtime::DispatchError::BadOrigin
+			);
+
+			// and the 6 ids are migrated over two chunks.
+			assert_ok!(BagsList::migrate_chunk(Origin::root(), old.clone(), 4));
+			assert!(matches!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::ThresholdMigrationProgressed(4, _))
+			));
+			assert!(crate::ThresholdMigrationCursor::<Runtime>::get().is_some());
+
+			assert_ok!(BagsList::migrate_chunk(Origin::root(), old, 4));
+			assert_eq!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::ThresholdMigrationCompleted)
+			);
+			assert_eq!(crate::ThresholdMigrationCursor::<Runtime>::get(), None);
+
+			let progress = System::events()
+				.into_iter()
+				.filter_map(|record| match record.event {
+					mock::Event::BagsList(crate::Event::ThresholdMigrationProgressed(
+						checked,
+						moved,
+					)) => Some((checked, moved)),
+					_ => None,
+				})
+				.collect::<Vec<_>>();
+			assert_eq!(progress.iter().map(|(checked, _)| checked).sum::<u32>(), 6);
+			assert_eq!(progress.iter().map(|(_, moved)| moved).sum::<u32>(), 1);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (500, vec![43]), (1_000, vec![2, 3, 4])]
+			);
+		});
+	}
+
+	#[test]
+	fn set_bag_thresholds_with_max_threshold_works() {
+		ExtBuilder::default()
+			.add_ids(vec![(42, VoteWeight::MAX)])
+			.build_and_execute(|| {
+				// given an id in the implied `VoteWeight::MAX` bag
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (VoteWeight::MAX, vec![42])]
+				);
+
+				// when the new thresholds explicitly include `VoteWeight::MAX`
+				let new = vec![10, 1_000, VoteWeight::MAX].try_into().unwrap();
+				assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+
+				// then nothing moves.
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (VoteWeight::MAX, vec![42])]
+				);
+			});
+	}
+
+	#[test]
+	fn set_bag_thresholds_errors_with_invalid_thresholds() {
+		ExtBuilder::default().build_and_execute(|| {
+			// not strictly increasing
+			assert_noop!(
+				BagsList::set_bag_thresholds(Origin::root(), vec![10, 30, 20].try_into().unwrap()),
+				crate::pallet::Error::<Runtime>::ThresholdsInvalid
+			);
+
+			// duplicates
+			assert_noop!(
+				BagsList::set_bag_thresholds(Origin::root(), vec![10, 20, 20].try_into().unwrap()),
+				crate::pallet::Error::<Runtime>::ThresholdsInvalid
+			);
+		});
+	}
+
+	#[test]
+	fn set_bag_thresholds_requires_root() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_noop!(
+				BagsList::set_bag_thresholds(Origin::signed(1), vec![10, 20].try_into().unwrap()),
+				sp_runtime::DispatchError::BadOrigin
+			);
+		});
+	}
+
+	#[test]
+	#[should_panic = "thresholds must strictly increase, and have no duplicates"]
+	fn duplicate_in_bags_threshold_panics() {
+		const DUPE_THRESH: &[VoteWeight; 4] = &[10, 20, 30, 30];
+		BagThresholds::set(DUPE_THRESH);
+		BagsList::integrity_test();
+	}
+
+	#[test]
+	#[should_panic = "thresholds must strictly increase, and have no duplicates"]
+	fn decreasing_in_bags_threshold_panics() {
+		const DECREASING_THRESH: &[VoteWeight; 4] = &[10, 30, 20, 40];
+		BagThresholds::set(DECREASING_THRESH);
+		BagsList::integrity_test();
+	}
+
+	#[test]
+	#[should_panic = "thresholds must not be more than `MaxBagThresholds`"]
+	fn too_many_bags_thresholds_panics() {
+		const TOO_MANY_THRESH: &[VoteWeight; 21] =
+			&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21];
+		assert_eq!(MaxBagThresholds::get(), 20);
+		BagThresholds::set(TOO_MANY_THRESH);
+		BagsList::integrity_test();
+	}
+
+	#[test]
+	fn empty_threshold_works() {
+		BagThresholds::set(Default::default()); // which is the same as passing `()` to `Get<_>`.
+
+		ExtBuilder::default().build_and_execute(|| {
+			// everyone in the same bag.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(VoteWeight::MAX, vec![1, 2, 3, 4])]);
+
+			// any insertion goes there as well.
+			assert_ok!(List::<Runtime>::try_insert(5, 999));
+			assert_ok!(List::<Runtime>::try_insert(6, 0));
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(VoteWeight::MAX, vec![1, 2, 3, 4, 5, 6])]
+			);
+
+			// any rebag is noop.
+			assert_storage_noop!(assert!(BagsList::rebag(Origin::signed(0), 1).is_ok()));
+			assert_storage_noop!(assert!(BagsList::rebag(Origin::signed(0), 6).is_ok()));
+		})
+	}
+}
+
+mod sorted_list_provider {
+	use super::*;
+
+	#[test]
+	fn iter_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			let expected = vec![2, 3, 4, 1];
+			for (i, id) in BagsList::iter().enumerate() {
+				assert_eq!(id, expected[i])
+			}
+		});
+	}
+
+	#[test]
+	fn iter_skips_bags_below_min_score_bag() {
+		ExtBuilder::default().add_ids(vec![(5, 15), (6, 2_000)]).build_and_execute(|| {
+			System::set_block_number(1);
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000), (5, 15), (6, 2_000)]
+					.into_iter()
+					.collect(),
+			);
+
+			// given the bags below 30 are excluded
+			MinScoreBag::set(30);
+
+			// then their ids are not iterated, in any way
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![6, 2, 3, 4]);
+			assert_eq!(BagsList::iter_from(&3).unwrap().collect::<Vec<_>>(), vec![4]);
+			assert_eq!(BagsList::iter_from(&5).unwrap().count(), 0);
+			assert_eq!(
+				BagsList::iter_with_score().map(|(id, _)| id).collect::<Vec<_>>(),
+				vec![6, 2, 3, 4]
+			);
+			let taken = BagsList::iter_with_budget(None, Weight::MAX).unwrap();
+			assert_eq!(taken.ids, vec![6, 2, 3, 4]);
+			assert!(taken.exhausted);
+			assert!(BagsList::iter_with_budget(Some(&5), Weight::MAX).unwrap().ids.is_empty());
+
+			// but they are still in the list
+			assert_eq!(BagsList::count(), 6);
+			assert!(BagsList::contains(&1));
+
+			// when an id moves into an excluded bag, and another one out of it
+			assert!(BagsList::on_update(&2, 20));
+			assert_ok!(BagsList::rebag(Origin::signed(0), 5));
+			StakingMock::set_score_of(&5, 50);
+			assert_ok!(BagsList::rebag(Origin::signed(0), 5));
+
+			// then they are excluded and included accordingly
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![6, 3, 4, 5]);
+			let crossings = System::events()
+				.into_iter()
+				.filter_map(|record| match record.event {
+					mock::Event::BagsList(e @ crate::Event::Excluded(_)) |
+					mock::Event::BagsList(e @ crate::Event::Included(_)) => Some(e),
+					_ => None,
+				})
+				.collect::<Vec<_>>();
+			assert_eq!(crossings, vec![crate::Event::Excluded(2), crate::Event::Included(5)]);
+		});
+	}
+
+	#[test]
+	fn iter_with_score_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5)]).build_and_execute(|| {
+			// the current score is given, not the one the ids were placed with.
+			StakingMock::set_score_of(&3, 999);
+
+			assert_eq!(
+				BagsList::iter_with_score().collect::<Vec<_>>(),
+				vec![(2, 0), (3, 999), (4, 0), (1, 0), (5, 0)]
+			);
+		});
+	}
+
+	#[test]
+	fn iter_from_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 5]), (20, vec![6]), (1000, vec![2, 3, 4])]
+			);
+
+			// then it starts right after the given id, and continues through the lower bags
+			assert_eq!(BagsList::iter_from(&2).unwrap().collect::<Vec<_>>(), vec![3, 4, 6, 1, 5]);
+			assert_eq!(BagsList::iter_from(&3).unwrap().collect::<Vec<_>>(), vec![4, 6, 1, 5]);
+			assert_eq!(BagsList::iter_from(&4).unwrap().collect::<Vec<_>>(), vec![6, 1, 5]);
+			assert_eq!(BagsList::iter_from(&6).unwrap().collect::<Vec<_>>(), vec![1, 5]);
+			assert_eq!(BagsList::iter_from(&1).unwrap().collect::<Vec<_>>(), vec![5]);
+			assert!(BagsList::iter_from(&5).unwrap().next().is_none());
+
+			// and it can have `take` called on it
+			assert_eq!(BagsList::iter_from(&2).unwrap().take(2).collect::<Vec<_>>(), vec![3, 4]);
+
+			// an id in the implied `VoteWeight::MAX` bag continues through all bags
+			assert_ok!(BagsList::on_insert(7, VoteWeight::MAX));
+			assert_ok!(BagsList::on_insert(8, VoteWeight::MAX));
+			assert_eq!(
+				BagsList::iter_from(&7).unwrap().collect::<Vec<_>>(),
+				vec![8, 2, 3, 4, 6, 1, 5]
+			);
+		});
+	}
+
+	#[test]
+	fn iter_from_errors_for_unknown_id() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(BagsList::iter_from(&42).map(|_| ()), Err(ListError::NodeNotFound));
+		});
+	}
+
+	#[test]
+	fn iter_with_bag_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(
+				BagsList::iter_with_bag().collect::<Vec<_>>(),
+				vec![(2, 1_000), (3, 1_000), (4, 1_000), (1, 10)]
+			);
+			assert_eq!(<BagsList as SortedListProvider<_>>::count_in_bag(1_000), 3);
+		});
+	}
+
+	#[test]
+	fn lowest_works() {
+		ExtBuilder::default().add_ids(vec![(5, 10)]).build_and_execute(|| {
+			// the tail of the lowest bag.
+			assert_eq!(BagsList::lowest(), Some(5));
+
+			// when the lowest bag is emptied
+			BagsList::on_remove(&5);
+			BagsList::on_remove(&1);
+
+			// then the tail of the next non-empty bag is the lowest.
+			assert_eq!(BagsList::lowest(), Some(4));
+
+			// when the list is emptied
+			BagsList::unsafe_clear(None);
+
+			// then there is no lowest id.
+			assert_eq!(BagsList::lowest(), None);
+		});
+	}
+
+	#[test]
+	fn count_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			assert_eq!(BagsList::count(), 4);
+
+			// when inserting
+			assert_ok!(BagsList::on_insert(201, 0));
+			// then the count goes up
+			assert_eq!(BagsList::count(), 5);
+
+			// when removing
+			BagsList::on_remove(&201);
+			// then the count goes down
+			assert_eq!(BagsList::count(), 4);
+
+			// when updating
+			BagsList::on_update(&201, VoteWeight::MAX);
+			// then the count stays the same
+			assert_eq!(BagsList::count(), 4);
+		});
+	}
+
+	#[test]
+	fn on_insert_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// when
+			assert_ok!(BagsList::on_insert(6, 1_000));
+
+			// then the bags
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4, 6])]);
+			// and list correctly include the new id,
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![2, 3, 4, 6, 1]);
+			// and the count is incremented.
+			assert_eq!(BagsList::count(), 5);
+
+			// when
+			assert_ok!(BagsList::on_insert(7, 1_001));
+
+			// then the bags
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (1_000, vec![2, 3, 4, 6]), (2_000, vec![7])]
+			);
+			// and list correctly include the new id,
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![7, 2, 3, 4, 6, 1]);
+			// and the count is incremented.
+			assert_eq!(BagsList::count(), 6);
+		})
+	}
+
+	#[test]
+	fn on_insert_errors_with_duplicate_id() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			assert!(get_list_as_ids().contains(&3));
+
+			// then
+			assert_storage_noop!(assert_eq!(
+				BagsList::on_insert(3, 20).unwrap_err(),
+				ListError::Duplicate
+			));
+			// which the pallet surfaces as
+			assert_eq!(
+				crate::Error::<Runtime>::from(ListError::Duplicate),
+				crate::Error::<Runtime>::AlreadyInList
+			);
+		});
+	}
+
+	#[test]
+	fn on_update_works() {
+		ExtBuilder::default().add_ids(vec![(42, 20)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (1_000, vec![2, 3, 4])]
+			);
+			assert_eq!(BagsList::count(), 5);
+
+			// when increasing weight to the level of non-existent bag
+			assert!(BagsList::on_update(&42, 2_000));
+
+			// then the bag is created with the id in it,
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (2000, vec![42])]
+			);
+			// and the id position is updated in the list.
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![42, 2, 3, 4, 1]);
+
+			// when decreasing weight within the range of the current bag
+			assert!(!BagsList::on_update(&42, 1_001));
+
+			// then the id does not change bags,
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (2000, vec![42])]
+			);
+			// or change position in the list, and it keeps the score it was placed with.
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![42, 2, 3, 4, 1]);
+			assert_eq!(list::Node::<Runtime>::get(&42).unwrap().score(), 2_000);
+
+			// when increasing weight to the level of a non-existent bag with the max threshold
+			assert!(BagsList::on_update(&42, VoteWeight::MAX));
+
+			// the the new bag is created with the id in it,
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (1_000, vec![2, 3, 4]), (VoteWeight::MAX, vec![42])]
+			);
+			// and the id position is updated in the list.
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![42, 2, 3, 4, 1]);
+
+			// when decreasing the weight to a pre-existing bag
+			assert!(BagsList::on_update(&42, 1_000));
+
+			// then id is moved to the correct bag (as the last member),
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (1_000, vec![2, 3, 4, 42])]
+			);
+			// and the id position is updated in the list.
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![2, 3, 4, 42, 1]);
+
+			// since we have only called on_update, the `count` has not changed.
+			assert_eq!(BagsList::count(), 5);
+
+			// and updating an id that is not in the list does nothing.
+			assert!(!BagsList::on_update(&777, 2_000));
+			assert!(!BagsList::contains(&777));
+		});
+	}
+
+	#[test]
+	fn on_remove_works() {
+		let ensure_left = |id, counter| {
+			assert!(!ListNodes::<Runtime>::contains_key(id));
+			assert_eq!(BagsList::count(), counter);
+			assert_eq!(ListNodes::<Runtime>::count(), counter);
+			assert_eq!(ListNodes::<Runtime>::iter().count() as u32, counter);
+		};
+
+		ExtBuilder::default().build_and_execute(|| {
+			// it is a noop removing a non-existent id
+			assert!(!ListNodes::<Runtime>::contains_key(42));
+			assert_storage_noop!(BagsList::on_remove(&42));
+
+			// when removing a node from a bag with multiple nodes
+			BagsList::on_remove(&2);
+
+			// then
+			assert_eq!(get_list_as_ids(), vec![3, 4, 1]);
 			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![3, 4])]);
 			ensure_left(2, 3);
 
-			// when removing a node from a bag with only one node
-			BagsList::on_remove(&1);
+			// when removing a node from a bag with only one node
+			BagsList::on_remove(&1);
+
+			// then
+			assert_eq!(get_list_as_ids(), vec![3, 4]);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![3, 4])]);
+			ensure_left(1, 2);
+
+			// when removing all remaining ids
+			BagsList::on_remove(&4);
+			assert_eq!(get_list_as_ids(), vec![3]);
+			ensure_left(4, 1);
+			BagsList::on_remove(&3);
+
+			// then the storage is completely cleaned up
+			assert_eq!(get_list_as_ids(), Vec::<AccountId>::new());
+			ensure_left(3, 0);
+		});
+	}
+
+	#[test]
+	fn unsafe_regenerate_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 5]), (20, vec![6]), (1_000, vec![2, 3, 4])]
+			);
+
+			// when the list is regenerated from some of its ids, with new scores
+			let migrated = BagsList::unsafe_regenerate(
+				vec![2, 6, 7],
+				Box::new(|id| if *id == 2 { 5 } else { 2_000 }),
+			);
+
+			// then only those are left, each in the bag of its new score
+			assert_eq!(migrated, (3, vec![]));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![2]), (2_000, vec![6, 7])]);
+			assert_eq!(BagsList::count(), 3);
+			assert_eq!(ListNodes::<Runtime>::count(), 3);
+			assert!(!BagsList::contains(&1));
+
+			// when some of the ids do not fit into the list
+			MaxBagSize::set(1);
+			BagOverflowPolicy::set(BagOverflow::Reject);
+			let migrated = BagsList::unsafe_regenerate(
+				vec![2, 3, 6],
+				Box::new(|id| if *id == 6 { 15 } else { 1_000 }),
+			);
+
+			// then they are left out, and returned
+			assert_eq!(migrated, (2, vec![3]));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(20, vec![6]), (1_000, vec![2])]);
+			assert!(!BagsList::contains(&3));
+		});
+	}
+
+	#[test]
+	fn unsafe_clear_works() {
+		let mut ext = ExtBuilder::default().build();
+		// the number of removed ids is only reported for ids in the backend.
+		ext.commit_all().unwrap();
+		ext.execute_with(|| {
+			// given
+			assert_eq!(BagsList::count(), 4);
+
+			// when only some of the ids are cleared
+			assert_eq!(BagsList::unsafe_clear(Some(3)), 3);
+
+			// then the counter is kept in line with the nodes
+			assert_eq!(BagsList::count(), 1);
+			assert_eq!(ListNodes::<Runtime>::iter().count(), 1);
+			// and only the entries of the removed ids are gone.
+			let left = ListNodes::<Runtime>::iter().map(|(id, _)| id).collect::<Vec<_>>();
+			assert_eq!(LastPlaced::<Runtime>::iter_keys().collect::<Vec<_>>(), left);
+			assert!(ListBags::<Runtime>::iter().count() > 0);
+			assert!(CounterForBag::<Runtime>::iter().count() > 0);
+
+			// when everything is cleared
+			assert_eq!(BagsList::unsafe_clear(None), 1);
+
+			// then nothing is left
+			assert_eq!(BagsList::count(), 0);
+			assert_eq!(ListNodes::<Runtime>::iter().count(), 0);
+			assert_eq!(ListBags::<Runtime>::iter().count(), 0);
+			assert_eq!(CounterForBag::<Runtime>::iter().count(), 0);
+			assert_eq!(LastPlaced::<Runtime>::iter().count(), 0);
+			assert!(BagHistogram::<Runtime>::get().is_empty());
+		});
+	}
+
+	#[test]
+	fn contains_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(GENESIS_IDS.iter().all(|(id, _)| BagsList::contains(id)));
+
+			let non_existent_ids = vec![&42, &666, &13];
+			assert!(non_existent_ids.iter().all(|id| !BagsList::contains(id)));
+		})
+	}
+
+	#[test]
+	fn get_score_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			NextVoteWeightMap::set(vec![(1, 10), (2, 1_000)].into_iter().collect());
+
+			// then
+			assert_eq!(BagsList::get_score(&1), Some(10));
+			assert_eq!(BagsList::get_score(&2), Some(1_000));
+
+			// when the score of 2 changes, then the one it was placed with is still given
+			StakingMock::set_score_of(&2, 20);
+			assert_eq!(BagsList::get_score(&2), Some(1_000));
+			// until it is placed again.
+			assert_ok!(BagsList::rebag(Origin::signed(0), 2));
+			assert_eq!(BagsList::get_score(&2), Some(20));
+			// and ids that are not in the list have no score, even if they have some weight.
+			StakingMock::set_score_of(&42, 20);
+			assert_eq!(BagsList::get_score(&42), None);
+		})
+	}
+
+	#[test]
+	fn lifecycle_events_work() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			let events = || {
+				System::events()
+					.into_iter()
+					.filter_map(|r| match r.event {
+						mock::Event::BagsList(e) => Some(e),
+						_ => None,
+					})
+					.collect::<Vec<_>>()
+			};
+
+			// when
+			assert_ok!(BagsList::on_insert(5, 15));
+			BagsList::on_update(&5, 1_000);
+			BagsList::on_update(&5, 999);
+			BagsList::on_remove(&5);
+
+			// then
+			assert_eq!(
+				events(),
+				vec![
+					crate::Event::Inserted(5, 20),
+					crate::Event::Rebagged(5, 20, 1_000, 1, 6),
+					crate::Event::ScoreUpdated(5, 1_000),
+					crate::Event::ScoreUpdated(5, 999),
+					crate::Event::Removed(5),
+				]
+			);
+
+			// and nothing is emitted for ids that are not in the list
+			assert_eq!(BagsList::on_insert(1, 10), Err(ListError::Duplicate));
+			BagsList::on_update(&42, 10);
+			BagsList::on_remove(&42);
+			assert_eq!(events().len(), 5);
+		})
+	}
+
+	#[test]
+	fn spilled_events_work() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			// given bag 1_000 is full
+			MaxBagSize::set(3);
+
+			// when
+			assert_ok!(BagsList::on_insert(5, 1_000));
+			BagsList::on_update(&1, 1_000);
+
+			// then
+			assert_eq!(
+				System::events()
+					.into_iter()
+					.filter_map(|r| match r.event {
+						mock::Event::BagsList(e) => Some(e),
+						_ => None,
+					})
+					.collect::<Vec<_>>(),
+				vec![
+					crate::Event::Inserted(5, 60),
+					crate::Event::Spilled(5, 1_000, 60),
+					crate::Event::Rebagged(1, 10, 60, 0, 5),
+					crate::Event::Spilled(1, 1_000, 60),
+					crate::Event::ScoreUpdated(1, 1_000),
+				]
+			);
+
+			// and inserting fails once there is no room left
+			BagOverflowPolicy::set(BagOverflow::Reject);
+			assert_eq!(BagsList::on_insert(6, 1_000), Err(ListError::BagFull));
+		})
+	}
+
+	#[test]
+	fn lock_prevents_reordering() {
+		use frame_support::{traits::Hooks, weights::Weight};
+
+		ExtBuilder::default().build_and_execute(|| {
+			// given ids that belong in other bags
+			StakingMock::set_score_of(&1, 1_000);
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 1_000);
+			StakingMock::set_score_of(&4, 10);
+
+			// when
+			BagsList::lock(ListLock::Election);
+			assert!(BagsList::is_locked());
+
+			// then nothing moves
+			assert!(!BagsList::on_update(&1, 1_000));
+			assert_noop!(BagsList::rebag(Origin::signed(0), 2), Error::<Runtime>::Locked);
+			assert_noop!(BagsList::rebag_unsigned(Origin::none(), 2), Error::<Runtime>::Locked);
+			assert_noop!(BagsList::put_in_front_of(Origin::signed(3), 2), Error::<Runtime>::Locked);
+			assert_storage_noop!(BagsList::on_idle(1, Weight::MAX));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// but ids can still come and go, although the node of a removed id stays until the
+			// list is unlocked.
+			assert_ok!(BagsList::on_insert(5, 20));
+			BagsList::on_remove(&4);
+			assert!(!BagsList::contains(&4));
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![5]), (1_000, vec![2, 3, 4])]
+			);
+
+			// when
+			BagsList::unlock(ListLock::Election);
+
+			// then
+			assert!(!BagsList::is_locked());
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![5]), (1_000, vec![2, 3])]
+			);
+			assert!(BagsList::on_update(&1, 1_000));
+			assert_ok!(BagsList::rebag(Origin::signed(0), 2));
+		});
+	}
+
+	#[test]
+	fn removal_while_locked_keeps_the_node_to_resume_from() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given an iteration that stopped at 3
+			BagsList::lock(ListLock::Election);
+			assert_eq!(BagsList::iter().take(2).collect::<Vec<_>>(), vec![2, 3]);
+
+			// when 3 and 4 are removed
+			BagsList::on_remove(&3);
+			BagsList::on_remove(&4);
+
+			// then they are not part of the list anymore
+			assert!(!BagsList::contains(&3));
+			assert_eq!(BagsList::get_score(&3), None);
+			assert_eq!(BagsList::count(), 2);
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![2, 1]);
+			assert_eq!(BagsList::lowest(), Some(1));
+
+			// but the iteration can still resume after 3.
+			assert_eq!(BagsList::iter_from(&3).unwrap().collect::<Vec<_>>(), vec![1]);
+
+			// when 4 comes back before the list is unlocked
+			assert_ok!(BagsList::on_insert(4, 1_000));
+
+			// then it keeps its node
+			assert!(BagsList::contains(&4));
+			assert_eq!(BagsList::count(), 3);
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![2, 4, 1]);
+
+			// when
+			BagsList::unlock(ListLock::Election);
+
+			// then only the node of 3 is removed.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 4])]);
+			assert_eq!(PendingRemovals::<Runtime>::count(), 0);
+		});
+	}
+
+	#[test]
+	fn locks_are_held_per_owner() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given a list locked by two owners
+			BagsList::lock(ListLock::Election);
+			BagsList::lock(ListLock::Rebuild);
+			// locking again is idempotent
+			BagsList::lock(ListLock::Election);
+
+			// when one of them unlocks it
+			BagsList::unlock(ListLock::Election);
+
+			// then it is still locked by the other.
+			assert!(BagsList::is_locked());
+			assert_noop!(BagsList::rebag(Origin::signed(0), 2), Error::<Runtime>::Locked);
+
+			// when the other one unlocks it too
+			BagsList::unlock(ListLock::Rebuild);
+
+			// then it is unlocked.
+			assert!(!BagsList::is_locked());
+		});
+	}
+}
+
+mod on_idle {
+	use super::*;
+	use frame_support::{traits::Hooks, weights::Weight};
+
+	fn per_id_weight() -> Weight {
+		<() as WeightInfo>::rebag_scan_per_node()
+	}
+
+	fn misplaced_count() -> usize {
+		ListNodes::<Runtime>::iter_values()
+			.filter(|node| node.is_misplaced(StakingMock::score(node.id())))
+			.count()
+	}
+
+	#[test]
+	fn on_idle_rebags_across_blocks() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given all ids are in the wrong bag
+			StakingMock::set_score_of(&1, 1_000);
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 10);
+			StakingMock::set_score_of(&4, 10_000);
+			assert_eq!(misplaced_count(), 4);
+
+			// when
+			let consumed = BagsList::on_idle(1, Weight::MAX);
+
+			// then only `MaxAutoRebagPerBlock` ids are checked
+			assert_eq!(consumed, 2 * per_id_weight());
+			assert_eq!(misplaced_count(), 2);
+			assert!(AutoRebagCursor::<Runtime>::get().is_some());
+
+			// when
+			let consumed = BagsList::on_idle(2, Weight::MAX);
+
+			// then the scan resumes where it left off
+			assert_eq!(consumed, 2 * per_id_weight());
+			assert_eq!(misplaced_count(), 0);
+			let mut bags = List::<Runtime>::get_bags();
+			bags.iter_mut().for_each(|(_, ids)| ids.sort());
+			assert_eq!(bags, vec![(10, vec![2, 3]), (1_000, vec![1]), (10_000, vec![4])]);
+
+			// when
+			let consumed = BagsList::on_idle(3, Weight::MAX);
+
+			// then the end is reached, and the next scan starts from the beginning
+			assert_eq!(consumed, 0);
+			assert_eq!(AutoRebagCursor::<Runtime>::get(), None);
+		});
+	}
+
+	#[test]
+	fn on_idle_respects_remaining_weight() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 10);
+			StakingMock::set_score_of(&4, 10);
+
+			// when there is not enough weight for a single id, nothing happens
+			assert_storage_noop!(assert_eq!(BagsList::on_idle(1, per_id_weight() - 1), 0));
+
+			// when there is enough weight for exactly one id
+			let consumed = BagsList::on_idle(1, per_id_weight() + 1);
+
+			// then only one id is checked
+			assert_eq!(consumed, per_id_weight());
+			assert!(AutoRebagCursor::<Runtime>::get().is_some());
+		});
+	}
+
+	#[test]
+	fn on_idle_rebags_ids_updated_while_locked_first() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given ids updated while the list is locked
+			BagsList::lock(ListLock::Election);
+			StakingMock::set_score_of(&4, 10);
+			StakingMock::set_score_of(&1, 1_000);
+			assert!(!BagsList::on_update(&4, 10));
+			assert!(!BagsList::on_update(&1, 1_000));
+			assert_eq!(PendingRebags::<Runtime>::iter().count(), 2);
+
+			// they stay put while locked
+			assert_eq!(BagsList::on_idle(1, Weight::MAX), 0);
+			assert_eq!(PendingRebags::<Runtime>::iter().count(), 2);
+
+			// when
+			BagsList::unlock(ListLock::Election);
+			let consumed = BagsList::on_idle(2, Weight::MAX);
+
+			// then they are rebagged before the scan goes on
+			assert_eq!(consumed, 2 * per_id_weight());
+			assert_eq!(PendingRebags::<Runtime>::iter().count(), 0);
+			assert_eq!(AutoRebagCursor::<Runtime>::get(), None);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![4]), (1_000, vec![2, 3, 1])]);
+
+			// and an id removed from the list is not rebagged anymore
+			BagsList::lock(ListLock::Election);
+			assert!(!BagsList::on_update(&2, 1_000));
+			BagsList::on_remove(&2);
+			assert_eq!(PendingRebags::<Runtime>::iter().count(), 0);
+		});
+	}
+
+	#[test]
+	fn on_idle_refreshes_top_bags_first() {
+		ExtBuilder::default().add_ids(vec![(5, 20), (6, 2_000)]).build_and_execute(|| {
+			// given the 2 highest bags are refreshed
+			RefreshTopBags::set(2);
+			StakingMock::set_score_of(&2, 1_000);
+			StakingMock::set_score_of(&6, 2_000);
+			StakingMock::set_score_of(&3, 10_000);
+			StakingMock::set_score_of(&4, 10);
+			StakingMock::set_score_of(&5, 1_000);
+
+			// nothing is refreshed until it is scheduled
+			assert_eq!(TopBagsRefresh::<Runtime>::get(), None);
+			BagsList::schedule_refresh();
+			assert_eq!(TopBagsRefresh::<Runtime>::get(), Some((1_000, 6)));
+
+			// when
+			let consumed = BagsList::on_idle(1, Weight::MAX);
+
+			// then the refresh takes all of `MaxAutoRebagPerBlock`, from the head of the list
+			assert_eq!(consumed, 2 * per_id_weight());
+			assert_eq!(TopBagsRefresh::<Runtime>::get(), Some((1_000, 3)));
+			assert_eq!(AutoRebagCursor::<Runtime>::get(), None);
+
+			// when
+			let consumed = BagsList::on_idle(2, Weight::MAX);
+
+			// then the rest of bag 1_000 is refreshed, and the refresh is over
+			assert_eq!(consumed, 2 * per_id_weight());
+			assert_eq!(TopBagsRefresh::<Runtime>::get(), None);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![
+					(10, vec![1, 4]),
+					(20, vec![5]),
+					(1_000, vec![2]),
+					(2_000, vec![6]),
+					(10_000, vec![3])
+				]
+			);
+
+			// and 5, which is not in a refreshed bag, is left to the scan.
+			assert!(ListNodes::<Runtime>::get(5).unwrap().is_misplaced(1_000));
+		});
+	}
+
+	#[test]
+	fn on_idle_keeps_forced_bags() {
+		ExtBuilder::default().build_and_execute(|| {
+			System::set_block_number(1);
+			// given 2, with a score of 1_000, forced into the bag 10
+			NextVoteWeight::set(1_000);
+			StakingMock::set_score_of(&1, 10);
+			assert_ok!(BagsList::force_set_bag(Origin::root(), 2, 10));
+			assert_eq!(BagsList::bag_for(&2), Some((10, 1_000)));
+
+			// then it is not moved back by `on_idle`,
+			for n in 2..6 {
+				BagsList::on_idle(n, Weight::MAX);
+			}
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![3, 4])]);
+			// nor by a rebag, which is not rewarded.
+			assert_eq!(BagsList::rebag_needed(&2), None);
+			assert_eq!(
+				BagsList::rebag(Origin::signed(0), 2).map(|info| info.pays_fee),
+				Ok(Pays::Yes)
+			);
+			assert_eq!(BagsList::bag_for(&2), Some((10, 1_000)));
+
+			// when its score belongs in another bag than when it was forced
+			StakingMock::set_score_of(&2, 20);
+			for n in 6..10 {
+				BagsList::on_idle(n, Weight::MAX);
+			}
+
+			// then it is rebagged again.
+			assert_eq!(BagsList::bag_for(&2), Some((20, 20)));
+			assert!(!ForcedBags::<Runtime>::contains_key(2));
+		});
+	}
+
+	#[test]
+	fn on_idle_migrates_thresholds_first() {
+		ExtBuilder::default().add_ids(vec![(42, 20), (43, 25)]).build_and_execute(|| {
+			System::set_block_number(1);
+			let chunk_weight = |n| <() as WeightInfo>::migrate_chunk(n);
+
+			// given bag 30 is removed and bag 500 is inserted
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000), (42, 20), (43, 25)]
+					.into_iter()
+					.collect(),
+			);
+			let new = vec![10, 20, 500, 1_000, 2_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+
+			// when there is only enough weight for 4 ids
+			let consumed = BagsList::on_idle(1, chunk_weight(4));
+
+			// then only they are checked, and none is rebagged
+			assert_eq!(consumed, chunk_weight(4));
+			assert!(matches!(
+				System::events().last().unwrap().event,
+				mock::Event::BagsList(crate::Event::ThresholdMigrationProgressed(4, _))
+			));
+			assert!(ThresholdMigrationCursor::<Runtime>::get().is_some());
+			assert!(PendingThresholdMigration::<Runtime>::exists());
+			assert_eq!(AutoRebagCursor::<Runtime>::get(), None);
+
+			// when
+			BagsList::on_idle(2, Weight::MAX);
+
+			// then the migration resumes where it left off, and is over.
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (500, vec![43]), (1_000, vec![2, 3, 4])]
+			);
+			assert_eq!(ThresholdMigrationCursor::<Runtime>::get(), None);
+			assert!(!PendingThresholdMigration::<Runtime>::exists());
+		});
+	}
+
+	#[test]
+	fn on_idle_does_not_migrate_thresholds_while_locked() {
+		ExtBuilder::default().add_ids(vec![(43, 25)]).build_and_execute(|| {
+			// given
+			NextVoteWeightMap::set(
+				vec![(1, 10), (2, 1_000), (3, 1_000), (4, 1_000), (43, 25)]
+					.into_iter()
+					.collect(),
+			);
+			let new = vec![10, 500, 1_000].try_into().unwrap();
+			assert_ok!(BagsList::set_bag_thresholds(Origin::root(), new));
+
+			// when
+			BagsList::lock(ListLock::Election);
+			BagsList::on_idle(1, Weight::MAX);
+
+			// then nothing moves until the list is unlocked.
+			assert!(PendingThresholdMigration::<Runtime>::exists());
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			BagsList::unlock(ListLock::Election);
+			BagsList::on_idle(2, Weight::MAX);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (500, vec![43]), (1_000, vec![2, 3, 4])]
+			);
+		});
+	}
+
+	#[test]
+	fn on_idle_can_be_disabled() {
+		ExtBuilder::default().build_and_execute(|| {
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 10);
+			StakingMock::set_score_of(&4, 10);
+			MaxAutoRebagPerBlock::set(0);
+
+			assert_storage_noop!(assert_eq!(BagsList::on_idle(1, Weight::MAX), 0));
+			assert_eq!(misplaced_count(), 3);
+		});
+	}
+}
+
+mod unsigned {
+	use super::*;
+	use codec::{Decode, Encode};
+	use frame_support::{
+		pallet_prelude::InvalidTransaction,
+		traits::Hooks,
+		unsigned::{TransactionSource, ValidateUnsigned},
+	};
+
+	fn validate(dislocated: AccountId) -> bool {
+		<BagsList as ValidateUnsigned>::validate_unsigned(
+			TransactionSource::Local,
+			&crate::Call::rebag_unsigned { dislocated },
+		)
+		.is_ok()
+	}
+
+	#[test]
+	fn rebag_unsigned_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			StakingMock::set_score_of(&1, 1_000);
+
+			// a signed origin is not allowed
+			assert_noop!(
+				BagsList::rebag_unsigned(Origin::signed(0), 1),
+				sp_runtime::DispatchError::BadOrigin
+			);
+
+			// when
+			assert_ok!(BagsList::rebag_unsigned(Origin::none(), 1));
 
 			// then
-			assert_eq!(get_list_as_ids(), vec![3, 4]);
-			assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![3, 4])]);
-			ensure_left(1, 2);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![2, 3, 4, 1])]);
+		});
+	}
 
-			// when removing all remaining ids
-			BagsList::on_remove(&4);
-			assert_eq!(get_list_as_ids(), vec![3]);
-			ensure_left(4, 1);
-			BagsList::on_remove(&3);
+	#[test]
+	fn validate_unsigned_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			StakingMock::set_score_of(&1, 10);
+			StakingMock::set_score_of(&2, 10);
 
-			// then the storage is completely cleaned up
-			assert_eq!(get_list_as_ids(), Vec::<AccountId>::new());
-			ensure_left(3, 0);
+			// an id in the wrong bag can be rebagged
+			assert!(validate(2));
+			assert_ok!(<BagsList as ValidateUnsigned>::pre_dispatch(
+				&crate::Call::rebag_unsigned { dislocated: 2 }
+			));
+
+			// at most once in the pool, until the next scan of the offchain worker
+			let valid = <BagsList as ValidateUnsigned>::validate_unsigned(
+				TransactionSource::Local,
+				&crate::Call::rebag_unsigned { dislocated: 2 },
+			)
+			.unwrap();
+			assert_eq!(valid.provides, vec![("BagsListOffchainRebag", 2u32).encode()]);
+			assert_eq!(valid.longevity, OffchainRepeat::get());
+			assert_eq!(valid.priority, UnsignedPriority::get());
+			assert!(!valid.propagate);
+
+			// an id in the correct bag, or not in the list, can not
+			assert!(!validate(1));
+			assert!(!validate(42));
+			assert_eq!(
+				<BagsList as ValidateUnsigned>::pre_dispatch(&crate::Call::rebag_unsigned {
+					dislocated: 1
+				}),
+				Err(InvalidTransaction::Stale.into())
+			);
+
+			// an id in the wrong bag, which has no room for it, can not either
+			MaxBagSize::set(3);
+			BagOverflowPolicy::set(BagOverflow::Reject);
+			StakingMock::set_score_of(&1, 1_000);
+			assert!(!validate(1));
+
+			// transactions not coming from the local node are rejected
+			assert_eq!(
+				<BagsList as ValidateUnsigned>::validate_unsigned(
+					TransactionSource::External,
+					&crate::Call::rebag_unsigned { dislocated: 2 },
+				),
+				InvalidTransaction::Call.into()
+			);
 		});
 	}
 
 	#[test]
-	fn contains_works() {
+	fn offchain_worker_submits_rebags() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify();
+		ext.execute_with(|| {
+			// given 2 and 3 are in the wrong bag
+			StakingMock::set_score_of(&1, 10);
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 20);
+			StakingMock::set_score_of(&4, 1_000);
+
+			// when it is not time to run, nothing is submitted
+			BagsList::offchain_worker(4);
+			assert!(pool.read().transactions.is_empty());
+
+			// when
+			BagsList::offchain_worker(5);
+
+			// then
+			let rebagged = pool
+				.read()
+				.transactions
+				.iter()
+				.map(|tx| match UncheckedExtrinsic::decode(&mut &tx[..]).unwrap().function {
+					mock::Call::BagsList(crate::Call::rebag_unsigned { dislocated }) => dislocated,
+					_ => panic!("unexpected call"),
+				})
+				.collect::<Vec<_>>();
+			assert_eq!(rebagged, vec![2, 3]);
+		});
+	}
+
+	#[test]
+	fn offchain_worker_respects_max_rebags() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify();
+		ext.execute_with(|| {
+			// given all ids are in the wrong bag
+			StakingMock::set_score_of(&1, 1_000);
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 10);
+			StakingMock::set_score_of(&4, 10);
+			MaxOffchainRebags::set(2);
+
+			// when
+			BagsList::offchain_worker(5);
+
+			// then
+			assert_eq!(pool.read().transactions.len(), 2);
+		});
+	}
+}
+
+mod signed_extension {
+	use super::*;
+	use frame_support::{pallet_prelude::InvalidTransaction, weights::DispatchInfo};
+	use sp_runtime::traits::SignedExtension;
+
+	fn validate(dislocated: AccountId) -> sp_runtime::transaction_validity::TransactionValidity {
+		let call = mock::Call::BagsList(crate::Call::rebag { dislocated });
+		CheckRebag::<Runtime>::new().validate(&0, &call, &DispatchInfo::default(), 0)
+	}
+
+	#[test]
+	fn check_rebag_rejects_redundant_rebags() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert!(GENESIS_IDS.iter().all(|(id, _)| BagsList::contains(id)));
+			StakingMock::set_score_of(&1, 10);
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 10);
 
-			let non_existent_ids = vec![&42, &666, &13];
-			assert!(non_existent_ids.iter().all(|id| !BagsList::contains(id)));
-		})
+			// an id in the wrong bag can be rebagged, at most once in the pool
+			let valid = validate(2).unwrap();
+			assert_eq!(valid.provides, validate(2).unwrap().provides);
+			assert_ne!(valid.provides, validate(3).unwrap().provides);
+
+			// an id in the correct bag, or not in the list, can not
+			assert_eq!(validate(1), InvalidTransaction::Stale.into());
+			assert_eq!(validate(42), InvalidTransaction::Stale.into());
+
+			// other calls pass through
+			let call = mock::Call::BagsList(crate::Call::put_in_front_of { lighter: 2 });
+			assert_eq!(
+				CheckRebag::<Runtime>::new().validate(&0, &call, &DispatchInfo::default(), 0),
+				Ok(Default::default())
+			);
+		});
+	}
+}
+
+mod migrations {
+	use super::*;
+	use crate::migrations::{
+		bags_within, check_occupancy, occupancy, AddScore, MigrateThresholds, Occupancy,
+		PopulateBagCounters,
+	};
+	use frame_election_provider_support::VoteWeight;
+	use frame_support::{
+		parameter_types,
+		traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	};
+
+	parameter_types! {
+		pub const OldThresholds: &'static [VoteWeight] =
+			&[10, 20, 30, 40, 50, 60, 1_000, 2_000, 10_000];
+	}
+
+	#[test]
+	fn migrate_thresholds_works() {
+		ExtBuilder::default()
+			.add_ids(vec![(710, 15), (712, 2_000)])
+			.build_and_execute(|| {
+				// given
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![
+						(10, vec![1]),
+						(20, vec![710]),
+						(1_000, vec![2, 3, 4]),
+						(2_000, vec![712])
+					]
+				);
+
+				// when the new thresholds add `15` and remove `2_000`
+				BagThresholds::set(&[10, 15, 20, 30, 40, 50, 60, 1_000, 10_000]);
+				assert!(!bags_within::<Runtime, ()>(BagThresholds::get()));
+				MigrateThresholds::<Runtime, (), OldThresholds>::on_runtime_upgrade();
+
+				// then all affected ids are moved into their new bags
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![
+						(10, vec![1]),
+						(15, vec![710]),
+						(1_000, vec![2, 3, 4]),
+						(10_000, vec![712])
+					]
+				);
+				assert!(bags_within::<Runtime, ()>(BagThresholds::get()));
+				assert_eq!(BagsList::count(), 6);
+			});
+	}
+
+	#[test]
+	fn check_occupancy_works() {
+		ExtBuilder::default()
+			.add_ids(vec![(710, 15), (712, 2_000)])
+			.build_and_execute(|| {
+				// given
+				let pre = occupancy::<Runtime, ()>();
+				assert_eq!(
+					pre.bags.clone().into_iter().collect::<Vec<_>>(),
+					vec![(10, 1), (20, 1), (1_000, 3), (2_000, 1)]
+				);
+				assert_eq!(pre.total, 6);
+
+				// when
+				BagThresholds::set(&[10, 15, 20, 30, 40, 50, 60, 1_000, 10_000]);
+				MigrateThresholds::<Runtime, (), OldThresholds>::on_runtime_upgrade();
+
+				// then
+				let post = occupancy::<Runtime, ()>();
+				assert_eq!(
+					post.bags.clone().into_iter().collect::<Vec<_>>(),
+					vec![(10, 1), (15, 1), (1_000, 3), (10_000, 1)]
+				);
+				let affected = List::<Runtime>::bags_affected_by(OldThresholds::get());
+				assert_eq!(affected.iter().copied().collect::<Vec<_>>(), vec![20, 2_000]);
+				assert_eq!(check_occupancy(&pre, &post, &affected), Ok(()));
+			});
+	}
+
+	#[test]
+	fn check_occupancy_detects_lost_ids() {
+		let pre = Occupancy::<VoteWeight> {
+			bags: vec![(10, 1), (20, 1), (1_000, 3), (2_000, 1)].into_iter().collect(),
+			total: 6,
+		};
+		let affected = vec![20, 2_000].into_iter().collect();
+
+		// ids of the affected bags can go anywhere.
+		let post = Occupancy::<VoteWeight> {
+			bags: vec![(10, 1), (15, 1), (1_000, 4)].into_iter().collect(),
+			total: 6,
+		};
+		assert_eq!(check_occupancy(&pre, &post, &affected), Ok(()));
+
+		// but none can be lost.
+		let lost = Occupancy::<VoteWeight> { total: 5, ..post.clone() };
+		assert_eq!(
+			check_occupancy(&pre, &lost, &affected),
+			Err("number of ids changed during migration")
+		);
+
+		// nor miscounted.
+		let miscounted = Occupancy::<VoteWeight> {
+			bags: vec![(10, 1), (15, 1), (1_000, 3)].into_iter().collect(),
+			..post.clone()
+		};
+		assert_eq!(
+			check_occupancy(&pre, &miscounted, &affected),
+			Err("the bags do not hold every id of the list")
+		);
+
+		// and the bags that are not affected keep their ids.
+		let moved = Occupancy::<VoteWeight> {
+			bags: vec![(15, 2), (1_000, 4)].into_iter().collect(),
+			..post
+		};
+		assert_eq!(
+			check_occupancy(&pre, &moved, &affected),
+			Err("ids left a bag whose range did not change")
+		);
+	}
+
+	#[test]
+	fn migrate_thresholds_is_noop_without_changes() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_storage_noop!(
+				MigrateThresholds::<Runtime, (), OldThresholds>::on_runtime_upgrade()
+			);
+		});
+	}
+
+	/// Store every node as it was stored before it recorded its score.
+	fn store_pre_score_nodes() {
+		for (id, node) in ListNodes::<Runtime>::iter().collect::<Vec<_>>() {
+			let old = (id, node.prev_id().cloned(), node.next_id().cloned(), node.bag_upper());
+			frame_support::storage::unhashed::put(&ListNodes::<Runtime>::hashed_key_for(id), &old);
+		}
+	}
+
+	#[test]
+	fn populate_bag_counters_works() {
+		// the nodes are left in the layout without score, which only `AddScore` migrates.
+		ExtBuilder::default().add_ids(vec![(710, 15)]).build_and_execute_no_post_check(|| {
+			// given a list that was built before the counters existed
+			let _ = CounterForBag::<Runtime>::remove_all(None);
+			assert_eq!(BagsList::count_in_bag(1_000), 0);
+			assert_eq!(
+				List::<Runtime>::sanity_check(SanityCheckLevel::Links),
+				Err("bag length != CounterForBag")
+			);
+			// and before the nodes recorded their score.
+			store_pre_score_nodes();
+			StorageVersion::new(0).put::<BagsList>();
+
+			// when
+			PopulateBagCounters::<Runtime>::on_runtime_upgrade();
+
+			// then
+			assert_eq!(CounterForBag::<Runtime>::iter().count(), 3);
+			assert_eq!(BagsList::count_in_bag(10), 1);
+			assert_eq!(BagsList::count_in_bag(20), 1);
+			assert_eq!(BagsList::count_in_bag(1_000), 3);
+			assert_eq!(BagsList::on_chain_storage_version(), 1);
+		});
+	}
+
+	#[test]
+	fn add_score_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given nodes that were stored before they recorded their score
+			store_pre_score_nodes();
+			BagHistogram::<Runtime>::kill();
+			StorageVersion::new(1).put::<BagsList>();
+			// and current scores that differ from the ones the ids were inserted with
+			NextVoteWeightMap::set(
+				vec![(1, 9), (2, 1_000), (3, 999), (4, 500)].into_iter().collect(),
+			);
+
+			// when
+			AddScore::<Runtime>::on_runtime_upgrade();
+
+			// then
+			assert_eq!(list::Node::<Runtime>::get(&3).unwrap().score(), 999);
+			assert_eq!(list::Node::<Runtime>::get(&3).unwrap().next_id(), Some(&4));
+			assert_eq!(
+				BagsList::bag_histogram(),
+				vec![
+					BagOccupancy { bag_upper: 10, count: 1, total_score: 9 },
+					BagOccupancy { bag_upper: 1_000, count: 3, total_score: 2_499 },
+				]
+			);
+			assert_eq!(BagsList::on_chain_storage_version(), STORAGE_VERSION);
+		});
+	}
+
+	#[test]
+	fn genesis_sets_the_current_storage_version() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(BagsList::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(SecondBagsList::on_chain_storage_version(), STORAGE_VERSION);
+		});
+	}
+
+	#[test]
+	fn versioned_migrations_only_run_on_the_previous_version() {
+		ExtBuilder::default().build_and_execute(|| {
+			// already at the current version, nothing runs.
+			assert_storage_noop!(PopulateBagCounters::<Runtime>::on_runtime_upgrade());
+			assert_storage_noop!(AddScore::<Runtime>::on_runtime_upgrade());
+
+			// v2 does not run on top of v0, v1 must run first.
+			store_pre_score_nodes();
+			StorageVersion::new(0).put::<BagsList>();
+			assert_storage_noop!(AddScore::<Runtime>::on_runtime_upgrade());
+
+			// when run in order
+			<(PopulateBagCounters<Runtime>, AddScore<Runtime>)>::on_runtime_upgrade();
+
+			// then
+			assert_eq!(BagsList::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(List::<Runtime>::sanity_check(SanityCheckLevel::Full), Ok(()));
+			// and the version of the other instance is tracked separately.
+			assert_eq!(SecondBagsList::on_chain_storage_version(), STORAGE_VERSION);
+		});
+	}
+}
+
+mod make_bags {
+	use super::*;
+	use crate::make_bags::{constant_ratio, make_bags, thresholds};
+	use frame_support::traits::U128CurrencyToVote;
+
+	#[test]
+	fn thresholds_are_a_geometric_series() {
+		let (thresholds, ratio) =
+			make_bags::<u128, U128CurrencyToVote>(100_000_000_000_000, 1_000_000_000_000_000, 200);
+
+		// the same values as the node runtime's `voter_bags`.
+		assert_eq!(ratio, 1.0628253590743408);
+		assert_eq!(thresholds.len(), 200);
+		assert_eq!(
+			thresholds[..4],
+			[100_000_000_000_000, 106_282_535_907_434, 112_959_774_389_150, 120_056_512_776_105]
+		);
+		assert_eq!(thresholds.last(), Some(&VoteWeight::MAX));
+		assert!(thresholds.windows(2).all(|w| w[0] < w[1]));
+	}
+
+	#[test]
+	fn existential_weight_scales_with_issuance() {
+		// an issuance beyond `u64::MAX` scales the existential deposit down.
+		let issuance = u64::MAX as u128 * 4;
+		let (thresholds, _) = make_bags::<u128, U128CurrencyToVote>(400, issuance, 10);
+		assert_eq!(thresholds[0], 100);
+		assert_eq!(thresholds.len(), 10);
+	}
+
+	#[test]
+	fn thresholds_small_counts() {
+		assert!(thresholds(10, constant_ratio(10, 0), 0).is_empty());
+		assert_eq!(thresholds(10, constant_ratio(10, 1), 1), vec![VoteWeight::MAX]);
+		assert_eq!(thresholds(10, constant_ratio(10, 2), 2), vec![10, VoteWeight::MAX]);
+		// a ratio too small to make progress still produces strictly increasing thresholds.
+		assert_eq!(thresholds(10, 1.0, 4), vec![10, 11, 12, VoteWeight::MAX]);
+	}
+}
+
+mod instances {
+	use super::*;
+	use frame_election_provider_support::ScoreProvider;
+	use frame_support::{
+		instances::{Instance1, Instance2},
+		traits::{Currency, Hooks, ReservableCurrency},
+		weights::Weight,
+	};
+
+	#[test]
+	fn instances_are_independent() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given the second instance starts out empty
+			assert_eq!(SecondBagsList::count(), 0);
+			assert_eq!(List::<Runtime, Instance1>::get_bags(), vec![]);
+
+			// when inserting ids into the second instance
+			assert_ok!(SecondBagsList::on_insert(1, 150));
+			assert_ok!(SecondBagsList::on_insert(5, 1_000));
+
+			// then they are placed according to its own thresholds
+			assert_eq!(
+				List::<Runtime, Instance1>::get_bags(),
+				vec![(200, vec![1]), (1 << 64, vec![5])]
+			);
+			assert_eq!(SecondBagsList::iter().collect::<Vec<_>>(), vec![5, 1]);
+
+			// and scores beyond `u64::MAX` are supported
+			assert_ok!(SecondBagsList::on_insert(6, u128::MAX));
+			assert_eq!(
+				List::<Runtime, Instance1>::get_bags(),
+				vec![(200, vec![1]), (1 << 64, vec![5]), (u128::MAX, vec![6])]
+			);
+			assert_eq!(SecondBagsList::iter().collect::<Vec<_>>(), vec![6, 5, 1]);
+			assert_ok!(List::<Runtime, Instance1>::sanity_check(SanityCheckLevel::Full));
+
+			// and the first instance is not affected
+			assert_eq!(BagsList::count(), 4);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// when removing an id from the first instance
+			BagsList::on_remove(&1);
+
+			// then it is still in the second instance
+			assert!(!BagsList::contains(&1));
+			assert!(SecondBagsList::contains(&1));
+		});
+	}
+
+	#[test]
+	fn scores_can_come_from_any_pallet() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given bids backed by the reserved balances of their bidders
+			for (bidder, bid) in [(10, 5), (11, 50), (12, 500)] {
+				Balances::make_free_balance_be(&bidder, 1_000);
+				assert_ok!(Balances::reserve(&bidder, bid));
+				assert_ok!(BidQueue::on_insert(bidder, ReservedBalanceScore::score(&bidder)));
+			}
+
+			// then the queue is sorted by bid
+			assert_eq!(
+				List::<Runtime, Instance2>::get_bags(),
+				vec![(10, vec![10]), (100, vec![11]), (1_000, vec![12])]
+			);
+			assert_eq!(BidQueue::iter().collect::<Vec<_>>(), vec![12, 11, 10]);
+
+			// when a bid is raised, anyone can rebag it
+			assert_ok!(Balances::reserve(&10, 595));
+			assert_eq!(BidQueue::rebag_needed(&10), Some(1_000));
+			assert_ok!(BidQueue::rebag(Origin::signed(0), 10));
+			assert_eq!(BidQueue::iter().collect::<Vec<_>>(), vec![12, 10, 11]);
+
+			// when a bid is lowered, it is rebagged in `on_idle`
+			Balances::unreserve(&12, 495);
+			MaxAutoRebagPerBlock::set(3);
+			<BidQueue as Hooks<u64>>::on_idle(1, Weight::MAX);
+			assert_eq!(
+				List::<Runtime, Instance2>::get_bags(),
+				vec![(10, vec![12]), (100, vec![11]), (1_000, vec![10])]
+			);
+
+			// when a bid is withdrawn, it leaves the queue
+			Balances::unreserve(&11, 50);
+			BidQueue::on_remove(&11);
+			assert_eq!(BidQueue::iter().collect::<Vec<_>>(), vec![10, 12]);
+			assert_ok!(List::<Runtime, Instance2>::sanity_check(SanityCheckLevel::Full));
+
+			// and the other lists are not affected
+			assert_eq!(BagsList::count(), 4);
+			assert_eq!(SecondBagsList::count(), 0);
+		});
+	}
+}
+
+mod chaos {
+	use super::*;
+	use frame_support::{traits::Hooks, weights::Weight};
+	use proptest::{collection::vec, prelude::*, sample::subsequence};
+	use std::collections::BTreeSet;
+
+	/// The thresholds that the list is migrated between: those of the mock, and a few more.
+	const THRESHOLDS_POOL: [VoteWeight; 12] =
+		[10, 15, 20, 30, 40, 50, 60, 500, 1_000, 2_000, 10_000, 20_000];
+
+	/// A staking-style operation on the list, or on the scores behind it.
+	#[derive(Clone, Debug)]
+	enum Op {
+		/// A new voter, inserted with its score.
+		Insert(AccountId, VoteWeight),
+		/// A voter that is chilled.
+		Remove(AccountId),
+		/// A voter whose score changes, and is reported to the list.
+		Update(AccountId, VoteWeight),
+		/// A voter whose score changes without the list knowing, e.g. after a slash.
+		Drift(AccountId, VoteWeight),
+		/// A permissionless rebag.
+		Rebag(AccountId),
+		/// A heavier voter passing a lighter one of its bag.
+		PutInFrontOf(AccountId, AccountId),
+		/// New bag thresholds, migrated to in one go.
+		Migrate(Vec<VoteWeight>),
+	}
+
+	fn score() -> impl Strategy<Value = VoteWeight> {
+		prop_oneof![
+			4 => 0..=70u64,
+			3 => prop::sample::select(THRESHOLDS_POOL.to_vec()),
+			2 => 0..=25_000u64,
+			1 => any::<u64>(),
+		]
+	}
+
+	fn op() -> impl Strategy<Value = Op> {
+		// a small id space, so that operations often hit ids that are already in the list.
+		let id = 0..32u32;
+		prop_oneof![
+			4 => (id.clone(), score()).prop_map(|(id, score)| Op::Insert(id, score)),
+			2 => id.clone().prop_map(Op::Remove),
+			3 => (id.clone(), score()).prop_map(|(id, score)| Op::Update(id, score)),
+			2 => (id.clone(), score()).prop_map(|(id, score)| Op::Drift(id, score)),
+			2 => id.clone().prop_map(Op::Rebag),
+			2 => (id.clone(), id).prop_map(|(heavier, lighter)| Op::PutInFrontOf(heavier, lighter)),
+			1 => subsequence(THRESHOLDS_POOL.to_vec(), 0..=THRESHOLDS_POOL.len()).prop_map(Op::Migrate),
+		]
+	}
+
+	/// Apply `op`, and return the ids that it may have moved within the list.
+	fn apply(op: Op) -> Vec<AccountId> {
+		match op {
+			Op::Insert(id, score) => {
+				StakingMock::set_score_of(&id, score);
+				// duplicates are rejected, and must leave the list intact.
+				let _ = BagsList::on_insert(id, score);
+				vec![id]
+			},
+			Op::Remove(id) => {
+				BagsList::on_remove(&id);
+				vec![id]
+			},
+			Op::Update(id, score) => {
+				StakingMock::set_score_of(&id, score);
+				BagsList::on_update(&id, score);
+				vec![id]
+			},
+			Op::Drift(id, score) => {
+				StakingMock::set_score_of(&id, score);
+				vec![id]
+			},
+			Op::Rebag(id) => {
+				let _ = BagsList::rebag(Origin::signed(0), id);
+				vec![id]
+			},
+			Op::PutInFrontOf(heavier, lighter) => {
+				let _ = BagsList::put_in_front_of(Origin::signed(heavier), lighter);
+				vec![heavier]
+			},
+			Op::Migrate(new) => {
+				let old = list::thresholds::<Runtime, ()>();
+				let bags = ListNodes::<Runtime>::iter()
+					.map(|(id, node)| (id, node.bag_upper()))
+					.collect::<Vec<_>>();
+				// rejected while the ids are still migrating to the previous thresholds.
+				if BagsList::set_bag_thresholds(Origin::root(), new.try_into().unwrap()).is_err() {
+					return vec![]
+				}
+
+				let affected = List::<Runtime>::bags_affected_by(&old);
+				bags.into_iter()
+					.filter(|(_, bag_upper)| affected.contains(bag_upper))
+					.map(|(id, _)| id)
+					.collect()
+			},
+		}
+	}
+
+	/// The ids that are not in the bag of their score, and may thus be moved by `on_idle`.
+	fn misplaced() -> Vec<AccountId> {
+		ListNodes::<Runtime>::iter_values()
+			.filter(|node| node.is_misplaced(StakingMock::score(node.id())))
+			.map(|node| *node.id())
+			.collect()
+	}
+
+	proptest! {
+		#![proptest_config(ProptestConfig::with_cases(64))]
+
+		#[test]
+		fn blocks_of_random_operations_preserve_the_list(
+			insertion in prop_oneof![Just(BagInsertion::Tail), Just(BagInsertion::Head)],
+			blocks in vec(vec(op(), 0..16), 1..32),
+		) {
+			ExtBuilder::default().build().execute_with(|| {
+				BagInsertionPolicy::set(insertion);
+				for (n, ops) in blocks.into_iter().enumerate() {
+					let block = n as u64 + 1;
+					System::set_block_number(block);
+					let before = get_list_as_ids();
+
+					let mut moved = misplaced().into_iter().collect::<BTreeSet<_>>();
+					for op in ops {
+						moved.extend(apply(op));
+					}
+					BagsList::on_idle(block, Weight::MAX);
+
+					prop_assert_eq!(List::<Runtime>::sanity_check(SanityCheckLevel::Full), Ok(()));
+					prop_assert_eq!(BagsList::count() as usize, get_list_as_ids().len());
+
+					// the ids that nothing could have moved keep their relative order.
+					let after = get_list_as_ids();
+					let unmoved = |ids: Vec<AccountId>| {
+						ids.into_iter().filter(|id| !moved.contains(id)).collect::<Vec<_>>()
+					};
+					prop_assert_eq!(unmoved(before), unmoved(after));
+				}
+				Ok(())
+			})?;
+		}
 	}
 }
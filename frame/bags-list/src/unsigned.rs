@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The offchain worker of the bags-list pallet, which finds dislocated ids and submits unsigned
+//! `rebag_unsigned` transactions for them.
+
+use crate::{list, Call, Config, Error, List, Pallet};
+use frame_election_provider_support::ScoreProvider;
+use frame_support::traits::Get;
+use frame_system::offchain::SubmitTransaction;
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Scan the list for dislocated ids, and submit a `rebag_unsigned` transaction for each of
+	/// them, up to `T::MaxOffchainRebags`.
+	///
+	/// Returns the number of transactions submitted.
+	pub(crate) fn offchain_rebag() -> u32 {
+		let max_rebags = T::MaxOffchainRebags::get();
+		let mut submitted = 0;
+		if Self::is_locked() {
+			return submitted
+		}
+
+		for node in List::<T, I>::iter() {
+			if submitted >= max_rebags {
+				break
+			}
+
+			let id = node.id();
+			if node.destination(T::ScoreProvider::score(id)).is_none() {
+				continue
+			}
+
+			let call = Call::<T, I>::rebag_unsigned { dislocated: id.clone() };
+			match SubmitTransaction::<T, Call<T, I>>::submit_unsigned_transaction(call.into()) {
+				Ok(()) => submitted += 1,
+				Err(()) => crate::log!(warn, "failed to submit rebag transaction for {:?}", id),
+			}
+		}
+
+		crate::log!(debug, "offchain worker submitted {} rebag transactions", submitted);
+		submitted
+	}
+
+	/// Checks that a rebag of `dislocated` would do something; the list must not be locked, and
+	/// `dislocated` must be in the list, out of its rebag cooldown, and in the wrong bag, with room
+	/// to be moved out of it.
+	pub(crate) fn rebag_pre_dispatch_checks(dislocated: &T::AccountId) -> Result<(), Error<T, I>> {
+		frame_support::ensure!(!Self::is_locked(), Error::<T, I>::Locked);
+		Self::ensure_rebag_cooled_down(dislocated)?;
+		let node = list::Node::<T, I>::get(dislocated).ok_or(Error::<T, I>::NotInList)?;
+		frame_support::ensure!(
+			node.destination(T::ScoreProvider::score(dislocated)).is_some(),
+			Error::<T, I>::NotMisplaced
+		);
+		Ok(())
+	}
+}
@@ -18,8 +18,8 @@
 //! Autogenerated weights for pallet_bags_list
 //!
 //! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2021-09-15, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 128
+//! DATE: 2026-10-15, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
+//! EXECUTION: Some(Native), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 128
 
 // Executed Command:
 // target/release/substrate
@@ -29,8 +29,7 @@
 // --repeat=20
 // --pallet=pallet_bags_list
 // --extrinsic=*
-// --execution=wasm
-// --wasm-execution=compiled
+// --execution=native
 // --heap-pages=4096
 // --output=./frame/bags-list/src/weights.rs
 // --template=./.maintain/frame-weight-template.hbs
@@ -47,49 +46,374 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn rebag_non_terminal() -> Weight;
 	fn rebag_terminal() -> Weight;
+	fn rebag_noop() -> Weight;
+	fn put_in_front_of() -> Weight;
+	fn sanity_check(n: u32, ) -> Weight;
+	fn rebag_scan_per_node() -> Weight;
+	fn set_bag_thresholds(n: u32, ) -> Weight;
+	fn migrate_chunk(n: u32, ) -> Weight;
+	fn prune_dangling_nodes(n: u32, ) -> Weight;
+	fn force_set_bag() -> Weight;
+	fn heal_broken_links(w: u32, ) -> Weight;
 }
 
 /// Weights for pallet_bags_list using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList LastRebag (r:1 w:1)
 	// Storage: Staking Bonded (r:1 w:0)
 	// Storage: Staking Ledger (r:1 w:0)
-	// Storage: BagsList ListNodes (r:4 w:4)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: BagsList BagHistogram (r:1 w:1)
 	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList LastRewardedRebag (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
 	fn rebag_non_terminal() -> Weight {
-		(74_175_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(7 as Weight))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+		(94_196_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
 	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList LastRebag (r:1 w:1)
 	// Storage: Staking Bonded (r:1 w:0)
 	// Storage: Staking Ledger (r:1 w:0)
-	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
 	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: BagsList ListNodes (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastRewardedRebag (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
 	fn rebag_terminal() -> Weight {
-		(73_305_000 as Weight)
+		(99_374_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList LastRebag (r:1 w:0)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: System Account (r:1 w:0)
+	fn rebag_noop() -> Weight {
+		(37_568_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListNodes (r:4 w:4)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList ListBags (r:1 w:1)
+	fn put_in_front_of() -> Weight {
+		(31_918_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: BagsList CounterForListNodes (r:1 w:0)
+	// Storage: BagsList ListNodes (r:2 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ListBags (r:200 w:0)
+	// Storage: BagsList PendingRemovals (r:1 w:0)
+	// Storage: BagsList PendingThresholdMigration (r:1 w:0)
+	// Storage: BagsList CounterForBag (r:2 w:0)
+	// Storage: BagsList BagHistogram (r:1 w:0)
+	fn sanity_check(n: u32, ) -> Weight {
+		(54_848_000 as Weight)
+			// Standard Error: 355_000
+			.saturating_add((22_930_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(331 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: BagsList PendingRebags (r:1 w:0)
+	// Storage: BagsList AutoRebagCursor (r:1 w:1)
+	// Storage: BagsList ListNodes (r:4 w:3)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn rebag_scan_per_node() -> Weight {
+		(77_151_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(16 as Weight))
+			.saturating_add(T::DbWeight::get().writes(11 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList PendingThresholdMigration (r:1 w:1)
+	// Storage: BagsList ListBagThresholds (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:0)
+	// Storage: BagsList ThresholdMigrationCursor (r:0 w:1)
+	fn set_bag_thresholds(n: u32, ) -> Weight {
+		(0 as Weight)
+			// Standard Error: 87_000
+			.saturating_add((4_628_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ThresholdMigrationCursor (r:1 w:1)
+	// Storage: BagsList ListNodes (r:2 w:1)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn migrate_chunk(n: u32, ) -> Weight {
+		(6_644_445_000 as Weight)
+			// Standard Error: 960_000
+			.saturating_add((36_584_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList PruneCursor (r:1 w:1)
+	// Storage: BagsList ListNodes (r:2 w:1)
+	// Storage: Staking Nominators (r:1 w:0)
+	// Storage: BagsList PendingRemovals (r:1 w:0)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: System Account (r:1 w:0)
+	// Storage: BagsList LastRebag (r:0 w:1)
+	// Storage: BagsList ForcedBags (r:0 w:1)
+	// Storage: BagsList LastRewardedRebag (r:0 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	// Storage: BagsList PendingRebags (r:0 w:1)
+	fn prune_dangling_nodes(n: u32, ) -> Weight {
+		(0 as Weight)
+			// Standard Error: 38_000
+			.saturating_add((23_474_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ListNodes (r:4 w:4)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList ForcedBags (r:0 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn force_set_bag() -> Weight {
+		(80_382_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:0 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn heal_broken_links(w: u32, ) -> Weight {
+		(0 as Weight)
+			// Standard Error: 22_000
+			.saturating_add((8_983_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(w as Weight)))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
 	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList LastRebag (r:1 w:1)
 	// Storage: Staking Bonded (r:1 w:0)
 	// Storage: Staking Ledger (r:1 w:0)
-	// Storage: BagsList ListNodes (r:4 w:4)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: BagsList BagHistogram (r:1 w:1)
 	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList LastRewardedRebag (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
 	fn rebag_non_terminal() -> Weight {
-		(74_175_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+		(94_196_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
 	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList LastRebag (r:1 w:1)
 	// Storage: Staking Bonded (r:1 w:0)
 	// Storage: Staking Ledger (r:1 w:0)
-	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
 	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: BagsList ListNodes (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastRewardedRebag (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
 	fn rebag_terminal() -> Weight {
-		(73_305_000 as Weight)
+		(99_374_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList LastRebag (r:1 w:0)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: System Account (r:1 w:0)
+	fn rebag_noop() -> Weight {
+		(37_568_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListNodes (r:4 w:4)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList ListBags (r:1 w:1)
+	fn put_in_front_of() -> Weight {
+		(31_918_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	// Storage: BagsList CounterForListNodes (r:1 w:0)
+	// Storage: BagsList ListNodes (r:2 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ListBags (r:200 w:0)
+	// Storage: BagsList PendingRemovals (r:1 w:0)
+	// Storage: BagsList PendingThresholdMigration (r:1 w:0)
+	// Storage: BagsList CounterForBag (r:2 w:0)
+	// Storage: BagsList BagHistogram (r:1 w:0)
+	fn sanity_check(n: u32, ) -> Weight {
+		(54_848_000 as Weight)
+			// Standard Error: 355_000
+			.saturating_add((22_930_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(331 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: BagsList PendingRebags (r:1 w:0)
+	// Storage: BagsList AutoRebagCursor (r:1 w:1)
+	// Storage: BagsList ListNodes (r:4 w:3)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn rebag_scan_per_node() -> Weight {
+		(77_151_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(16 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(11 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList PendingThresholdMigration (r:1 w:1)
+	// Storage: BagsList ListBagThresholds (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:0)
+	// Storage: BagsList ThresholdMigrationCursor (r:0 w:1)
+	fn set_bag_thresholds(n: u32, ) -> Weight {
+		(0 as Weight)
+			// Standard Error: 87_000
+			.saturating_add((4_628_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ThresholdMigrationCursor (r:1 w:1)
+	// Storage: BagsList ListNodes (r:2 w:1)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn migrate_chunk(n: u32, ) -> Weight {
+		(6_644_445_000 as Weight)
+			// Standard Error: 960_000
+			.saturating_add((36_584_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((4 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList PruneCursor (r:1 w:1)
+	// Storage: BagsList ListNodes (r:2 w:1)
+	// Storage: Staking Nominators (r:1 w:0)
+	// Storage: BagsList PendingRemovals (r:1 w:0)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: System Account (r:1 w:0)
+	// Storage: BagsList LastRebag (r:0 w:1)
+	// Storage: BagsList ForcedBags (r:0 w:1)
+	// Storage: BagsList LastRewardedRebag (r:0 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	// Storage: BagsList PendingRebags (r:0 w:1)
+	fn prune_dangling_nodes(n: u32, ) -> Weight {
+		(0 as Weight)
+			// Standard Error: 38_000
+			.saturating_add((23_474_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ListNodes (r:4 w:4)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: BagsList CounterForBag (r:2 w:2)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList ForcedBags (r:0 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn force_set_bag() -> Weight {
+		(80_382_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(10 as Weight))
+	}
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ListBagThresholds (r:1 w:0)
+	// Storage: BagsList ForcedBags (r:0 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn heal_broken_links(w: u32, ) -> Weight {
+		(0 as Weight)
+			// Standard Error: 22_000
+			.saturating_add((8_983_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(w as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
 	}
 }
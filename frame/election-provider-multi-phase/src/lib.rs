@@ -1140,6 +1140,14 @@ pub mod pallet {
 	#[pallet::getter(fn snapshot_metadata)]
 	pub type SnapshotMetadata<T: Config> = StorageValue<_, SolutionOrSnapshotSize>;
 
+	/// The upper thresholds of the bags that the voters of the [`RoundSnapshot`] are sorted into,
+	/// as given by [`ElectionDataProvider::voter_bag_thresholds`].
+	///
+	/// Only exists when [`Snapshot`] is present.
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_bag_thresholds)]
+	pub type SnapshotBagThresholds<T: Config> = StorageValue<_, Vec<VoteWeight>>;
+
 	// The following storage items collectively comprise `SignedSubmissions<T>`, and should never be
 	// accessed independently. Instead, get `Self::signed_submissions()`, modify it as desired, and
 	// then do `signed_submissions.put()` when you're done with it.
@@ -1234,6 +1242,8 @@ impl<T: Config> Pallet<T> {
 	pub fn on_initialize_open_signed() {
 		log!(info, "Starting signed phase round {}.", Self::round());
 		<CurrentPhase<T>>::put(Phase::Signed);
+		// solutions are computed against the snapshot, which must remain in the voters' order.
+		T::DataProvider::freeze_voters();
 		Self::deposit_event(Event::SignedPhaseStarted(Self::round()));
 	}
 
@@ -1242,6 +1252,7 @@ impl<T: Config> Pallet<T> {
 		let round = Self::round();
 		log!(info, "Starting unsigned phase round {} enabled {}.", round, enabled);
 		<CurrentPhase<T>>::put(Phase::Unsigned((enabled, now)));
+		T::DataProvider::freeze_voters();
 		Self::deposit_event(Event::UnsignedPhaseStarted(round));
 	}
 
@@ -1307,6 +1318,7 @@ impl<T: Config> Pallet<T> {
 	/// 1. [`SnapshotMetadata`]
 	/// 2. [`RoundSnapshot`]
 	/// 3. [`DesiredTargets`]
+	/// 4. [`SnapshotBagThresholds`]
 	///
 	/// Returns `Ok(())` if operation is okay.
 	///
@@ -1320,7 +1332,8 @@ impl<T: Config> Pallet<T> {
 		let internal_weight =
 			T::WeightInfo::create_snapshot_internal(voters.len() as u32, targets.len() as u32);
 		Self::create_snapshot_internal(targets, voters, desired_targets);
-		Self::register_weight(internal_weight);
+		<SnapshotBagThresholds<T>>::put(T::DataProvider::voter_bag_thresholds());
+		Self::register_weight(internal_weight.saturating_add(T::DbWeight::get().writes(1)));
 		Ok(())
 	}
 
@@ -1339,6 +1352,7 @@ impl<T: Config> Pallet<T> {
 		<Snapshot<T>>::kill();
 		<SnapshotMetadata<T>>::kill();
 		<DesiredTargets<T>>::kill();
+		<SnapshotBagThresholds<T>>::kill();
 	}
 
 	/// Checks the feasibility of a solution.
@@ -1432,12 +1446,14 @@ impl<T: Config> Pallet<T> {
 	/// 1. Increment round.
 	/// 2. Change phase to [`Phase::Off`]
 	/// 3. Clear all snapshot data.
+	/// 4. Unfreeze the voters of the data provider.
 	fn rotate_round() {
 		// Inc round.
 		<Round<T>>::mutate(|r| *r += 1);
 
 		// Phase is off now.
 		<CurrentPhase<T>>::put(Phase::Off);
+		T::DataProvider::unfreeze_voters();
 
 		// Kill snapshots.
 		Self::kill_snapshot();
@@ -1503,6 +1519,9 @@ impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for Pallet<T> {
 			Err(why) => {
 				log!(error, "Entering emergency mode: {:?}", why);
 				<CurrentPhase<T>>::put(Phase::Emergency);
+				// an emergency solution is not computed against the snapshot, so the voters need
+				// not stay frozen until governance submits one.
+				T::DataProvider::unfreeze_voters();
 				Err(why)
 			},
 		}
@@ -1700,7 +1719,7 @@ mod tests {
 	use crate::{
 		mock::{
 			multi_phase_events, roll_to, AccountId, ExtBuilder, MockWeightInfo, MultiPhase,
-			Runtime, SignedMaxSubmissions, System, TargetIndex, Targets,
+			Runtime, SignedMaxSubmissions, System, TargetIndex, Targets, VotersFrozen,
 		},
 		Phase,
 	};
@@ -1773,6 +1792,39 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn voters_are_frozen_while_solutions_are_accepted() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(14);
+			assert!(!VotersFrozen::get());
+
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(VotersFrozen::get());
+
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(VotersFrozen::get());
+
+			assert_ok!(MultiPhase::elect());
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(!VotersFrozen::get());
+		});
+
+		// without a signed phase, the voters are frozen from the start of the unsigned phase.
+		ExtBuilder::default().phases(0, 10).build_and_execute(|| {
+			roll_to(19);
+			assert!(!VotersFrozen::get());
+
+			roll_to(20);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert!(VotersFrozen::get());
+
+			assert_ok!(MultiPhase::elect());
+			assert!(!VotersFrozen::get());
+		})
+	}
+
 	#[test]
 	fn signed_phase_void() {
 		ExtBuilder::default().phases(0, 10).build_and_execute(|| {
@@ -1938,9 +1990,12 @@ mod tests {
 
 			// Zilch solutions thus far.
 			assert!(MultiPhase::queued_solution().is_none());
+			assert!(VotersFrozen::get());
 			assert_eq!(MultiPhase::elect().unwrap_err(), ElectionError::Fallback("NoFallback."));
 			// phase is now emergency.
 			assert_eq!(MultiPhase::current_phase(), Phase::Emergency);
+			// and the voters are not kept frozen.
+			assert!(!VotersFrozen::get());
 		})
 	}
 
@@ -254,6 +254,8 @@ parameter_types! {
 	];
 
 	pub static DesiredTargets: u32 = 2;
+	pub static VotersFrozen: bool = false;
+	pub static VoterBagThresholds: Vec<VoteWeight> = vec![];
 	pub static SignedPhase: BlockNumber = 10;
 	pub static UnsignedPhase: BlockNumber = 5;
 	pub static SignedMaxSubmissions: u32 = 5;
@@ -451,6 +453,18 @@ impl ElectionDataProvider<AccountId, u64> for StakingMock {
 		now + EpochLength::get() - now % EpochLength::get()
 	}
 
+	fn freeze_voters() {
+		VotersFrozen::set(true);
+	}
+
+	fn unfreeze_voters() {
+		VotersFrozen::set(false);
+	}
+
+	fn voter_bag_thresholds() -> Vec<VoteWeight> {
+		VoterBagThresholds::get()
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	fn put_snapshot(
 		voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
@@ -23,7 +23,7 @@ use crate::{
 	WeightInfo,
 };
 use codec::Encode;
-use frame_election_provider_support::{NposSolver, PerThing128};
+use frame_election_provider_support::{NposSolver, PerThing128, VoteWeight};
 use frame_support::{dispatch::DispatchResult, ensure, traits::Get};
 use frame_system::offchain::SubmitTransaction;
 use sp_arithmetic::Perbill;
@@ -356,9 +356,24 @@ impl<T: Config> Pallet<T> {
 			.map(|assignment| IndexAssignmentOf::<T>::new(&assignment, &voter_index, &target_index))
 			.collect::<Result<Vec<_>, _>>()?;
 
-		// trim assignments list for weight and length.
+		// trim assignments list for weight and length, the lowest bags of voters first.
 		let size =
 			SolutionOrSnapshotSize { voters: voters.len() as u32, targets: targets.len() as u32 };
+		let bag_thresholds = Self::snapshot_bag_thresholds().unwrap_or_default();
+		let assignment_stake = |assignment: &IndexAssignmentOf<T>| {
+			voters
+				.get(assignment.who.saturated_into::<usize>())
+				.map(|(_, stake, _)| *stake)
+				.unwrap_or_default()
+		};
+		Self::trim_assignments_to_bags(
+			desired_targets,
+			size,
+			T::MinerMaxWeight::get(),
+			&bag_thresholds,
+			&mut index_assignments,
+			assignment_stake,
+		);
 		Self::trim_assignments_weight(
 			desired_targets,
 			size,
@@ -381,6 +396,60 @@ impl<T: Config> Pallet<T> {
 		Ok((RawSolution { solution, score, round }, size))
 	}
 
+	/// Reduce the size of the solution to fit into the block w.r.t. weight, by removing whole bags
+	/// of voters, starting with the lowest one.
+	///
+	/// `bag_thresholds` are the upper thresholds of the bags the voters are sorted into (see
+	/// [`crate::SnapshotBagThresholds`]), and the assignments must be sorted by decreasing
+	/// `stake_of`. Unlike [`Self::trim_assignments_weight`], which keeps an arbitrary part of the
+	/// lowest bag it cuts into, this removes that bag entirely, such that the voters kept only
+	/// depend on the bags they are in.
+	///
+	/// Nothing is removed if there are no bags, or if all the voters that fit are in the same
+	/// bag; [`Self::trim_assignments_weight`] must then still truncate the assignments.
+	pub fn trim_assignments_to_bags(
+		desired_targets: u32,
+		size: SolutionOrSnapshotSize,
+		max_weight: Weight,
+		bag_thresholds: &[VoteWeight],
+		assignments: &mut Vec<IndexAssignmentOf<T>>,
+		stake_of: impl Fn(&IndexAssignmentOf<T>) -> VoteWeight,
+	) {
+		let maximum_allowed_voters: usize =
+			Self::maximum_voter_for_weight::<T::WeightInfo>(desired_targets, size, max_weight)
+				.saturated_into();
+		if bag_thresholds.is_empty() || assignments.len() <= maximum_allowed_voters {
+			return
+		}
+
+		let bag_of = |assignment: &IndexAssignmentOf<T>| {
+			let stake = stake_of(assignment);
+			bag_thresholds
+				.iter()
+				.copied()
+				.find(|threshold| *threshold >= stake)
+				.unwrap_or(VoteWeight::MAX)
+		};
+		// the highest bag that does not fit entirely, and thus goes along with all the lower ones.
+		let cut_bag = bag_of(&assignments[maximum_allowed_voters]);
+		let kept = assignments[..maximum_allowed_voters]
+			.iter()
+			.take_while(|assignment| bag_of(assignment) > cut_bag)
+			.count();
+		if kept == 0 {
+			return
+		}
+
+		log!(
+			debug,
+			"from {} assignments, removing {} in the bags up to {} for weight",
+			assignments.len(),
+			assignments.len() - kept,
+			cut_bag,
+		);
+		assignments.truncate(kept);
+	}
+
 	/// Greedily reduce the size of the solution to fit into the block w.r.t. weight.
 	///
 	/// The weight of the solution is foremost a function of the number of voters (i.e.
@@ -742,13 +811,14 @@ mod tests {
 		mock::{
 			roll_to, roll_to_with_ocw, trim_helpers, witness, BlockNumber, Call as OuterCall,
 			ExtBuilder, Extrinsic, MinerMaxWeight, MultiPhase, Origin, Runtime, System,
-			TestNposSolution, TrimHelpers, UnsignedPhase,
+			TestNposSolution, TrimHelpers, UnsignedPhase, VoterBagThresholds,
 		},
 		CurrentPhase, InvalidTransaction, Phase, QueuedSolution, TransactionSource,
 		TransactionValidityError,
 	};
 	use codec::Decode;
 	use frame_benchmarking::Zero;
+	use frame_election_provider_support::ElectionProvider;
 	use frame_support::{assert_noop, assert_ok, dispatch::Dispatchable, traits::OffchainWorker};
 	use sp_npos_elections::IndexAssignment;
 	use sp_runtime::{
@@ -1026,6 +1096,40 @@ mod tests {
 			})
 	}
 
+	#[test]
+	fn miner_trims_weight_by_whole_bags() {
+		ExtBuilder::default()
+			.miner_weight(25)
+			.mock_weight_info(true)
+			.build_and_execute(|| {
+				// given voters of stake 10 in the lowest bag, and the self votes of 30 and 40 in
+				// bags of their own.
+				VoterBagThresholds::set(vec![10, 35]);
+				roll_to(25);
+				assert!(MultiPhase::current_phase().is_unsigned());
+				assert_eq!(MultiPhase::snapshot_bag_thresholds(), Some(vec![10, 35]));
+
+				// when the solution is trimmed to fit the weight of 3 voters
+				let (raw, witness) =
+					MultiPhase::mine_solution::<<Runtime as Config>::Solver>().unwrap();
+				let solution_weight = <Runtime as Config>::WeightInfo::submit_unsigned(
+					witness.voters,
+					witness.targets,
+					raw.solution.voter_count() as u32,
+					raw.solution.unique_targets().len() as u32,
+				);
+
+				// then the lowest bag is removed entirely, rather than only some of its voters.
+				assert_eq!(raw.solution.voter_count(), 2);
+				assert_eq!(solution_weight, 20);
+				assert_ok!(MultiPhase::submit_unsigned(Origin::none(), Box::new(raw), witness));
+
+				// and the thresholds go along with the snapshot.
+				assert_ok!(MultiPhase::elect());
+				assert!(MultiPhase::snapshot_bag_thresholds().is_none());
+			})
+	}
+
 	#[test]
 	fn miner_will_not_submit_if_not_enough_winners() {
 		let (mut ext, _) = ExtBuilder::default().desired_targets(8).build_offchainify(0);
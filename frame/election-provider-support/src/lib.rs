@@ -161,7 +161,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod onchain;
-use frame_support::traits::Get;
+use frame_support::{traits::Get, weights::Weight, RuntimeDebug};
+use sp_arithmetic::traits::Bounded;
 use sp_std::{fmt::Debug, prelude::*};
 
 /// Re-export some type as they are used in the interface.
@@ -177,6 +178,10 @@ pub mod data_provider {
 	pub type Result<T> = sp_std::result::Result<T, &'static str>;
 }
 
+/// A page of voters, along with the opaque cursor of the next page, if any, as returned by
+/// [`ElectionDataProvider::voters_paged`].
+pub type VoterPage<AccountId> = (Vec<(AccountId, VoteWeight, Vec<AccountId>)>, Option<Vec<u8>>);
+
 /// Something that can provide the data to an [`ElectionProvider`].
 pub trait ElectionDataProvider<AccountId, BlockNumber> {
 	/// Maximum number of votes per voter that this data provider is providing.
@@ -204,6 +209,29 @@ pub trait ElectionDataProvider<AccountId, BlockNumber> {
 		maybe_max_len: Option<usize>,
 	) -> data_provider::Result<Vec<(AccountId, VoteWeight, Vec<AccountId>)>>;
 
+	/// A single page of the voters of [`Self::voters`], of at most `page_size` voters, starting
+	/// right after `cursor`, or from the beginning if `cursor` is `None`.
+	///
+	/// Returns the page, and the opaque cursor from which to get the next one, or `None` if this
+	/// was the last page. This allows a stateful election provider to take the voters over
+	/// several blocks, in which case it should [`Self::freeze_voters`] from the first page to the
+	/// last one.
+	///
+	/// By default, the voters are not paged: the first page is `Self::voters(Some(page_size))`,
+	/// and it is also the last one.
+	///
+	/// This should be implemented as a self-weighing function. The implementor should register its
+	/// appropriate weight at the end of execution with the system pallet directly.
+	fn voters_paged(
+		page_size: usize,
+		cursor: Option<Vec<u8>>,
+	) -> data_provider::Result<VoterPage<AccountId>> {
+		match cursor {
+			None => Self::voters(Some(page_size)).map(|voters| (voters, None)),
+			Some(_) => Ok((Vec::new(), None)),
+		}
+	}
+
 	/// The number of targets to elect.
 	///
 	/// This should be implemented as a self-weighing function. The implementor should register its
@@ -218,6 +246,25 @@ pub trait ElectionDataProvider<AccountId, BlockNumber> {
 	/// This is only useful for stateful election providers.
 	fn next_election_prediction(now: BlockNumber) -> BlockNumber;
 
+	/// Stop the voters from being reordered, until [`Self::unfreeze_voters`] is called.
+	///
+	/// This is called by stateful election providers once the snapshot is taken, for as long as
+	/// solutions computed against it are accepted, such that voters do not move in the meantime.
+	/// Changes to the voters that would reorder them should be queued, and applied once unfrozen.
+	fn freeze_voters() {}
+
+	/// Undo [`Self::freeze_voters`].
+	fn unfreeze_voters() {}
+
+	/// The upper thresholds of the bags that the voters are sorted into, from the lowest to the
+	/// highest, or nothing if they are not sorted into bags.
+	///
+	/// The highest bag implicitly ends at `VoteWeight::MAX`. Election providers can use this to
+	/// trim the voters of a solution a whole bag at a time, starting with the lowest one.
+	fn voter_bag_thresholds() -> Vec<VoteWeight> {
+		Vec::new()
+	}
+
 	/// Utility function only to be used in benchmarking scenarios, to be implemented optionally,
 	/// else a noop.
 	#[cfg(any(feature = "runtime-benchmarks", test))]
@@ -297,73 +344,280 @@ impl<AccountId, BlockNumber> ElectionProvider<AccountId, BlockNumber> for () {
 	}
 }
 
+/// The owner of a lock on a [`SortedListProvider`], see [`SortedListProvider::lock`].
+#[derive(
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+	RuntimeDebug,
+)]
+pub enum ListLock {
+	/// An election is computed against a snapshot of the list.
+	Election,
+	/// The list is being rebuilt from scratch.
+	Rebuild,
+}
+
+impl ListLock {
+	/// The number of distinct owners, i.e. the most locks that can be held at once.
+	pub const COUNT: u32 = 2;
+}
+
+/// A chunk of a [`SortedListProvider`], taken within a weight budget with
+/// [`SortedListProvider::iter_with_budget`].
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BudgetedIter<AccountId> {
+	/// The ids taken, in iteration order.
+	pub ids: Vec<AccountId>,
+	/// The weight consumed to take them.
+	pub consumed: Weight,
+	/// The id to resume the iteration after, i.e. the last id taken, or the `start` of the
+	/// iteration if no id was taken.
+	pub cursor: Option<AccountId>,
+	/// Whether the end of the list was reached.
+	pub exhausted: bool,
+}
+
+impl<AccountId: Clone> BudgetedIter<AccountId> {
+	/// Take ids from `iter`, which resumes the iteration after `start`, for as long as the weight
+	/// of reading them fits in `budget`, given that each id read costs `weight_per_id`.
+	///
+	/// This is meant for lists in which the cost of iteration is merely proportional to the number
+	/// of ids read, e.g. all the keys of a storage map.
+	pub fn take(
+		mut iter: impl Iterator<Item = AccountId>,
+		start: Option<AccountId>,
+		weight_per_id: Weight,
+		budget: Weight,
+	) -> Self {
+		let mut taken = Self { ids: Vec::new(), consumed: 0, cursor: start, exhausted: false };
+		while taken.consumed.saturating_add(weight_per_id) <= budget {
+			taken.consumed = taken.consumed.saturating_add(weight_per_id);
+			match iter.next() {
+				Some(id) => {
+					taken.cursor = Some(id.clone());
+					taken.ids.push(id);
+				},
+				None => {
+					taken.exhausted = true;
+					break
+				},
+			}
+		}
+		taken
+	}
+}
+
 /// A utility trait for something to implement `ElectionDataProvider` in a sensible way.
 ///
 /// This is generic over `AccountId` and it can represent a validator, a nominator, or any other
 /// entity.
 ///
-/// To simplify the trait, the `VoteWeight` is hardcoded as the weight of each entity. The weights
-/// are ascending, the higher, the better. In the long term, if this trait ends up having use cases
-/// outside of the election context, it is easy enough to make it generic over the `VoteWeight`.
+/// Each entity has a score of type [`Self::Score`]. The scores are ascending, the higher, the
+/// better.
 ///
 /// Something that implements this trait will do a best-effort sort over ids, and thus can be
 /// used on the implementing side of [`ElectionDataProvider`].
 pub trait SortedListProvider<AccountId> {
 	/// The list's error type.
-	type Error;
+	type Error: Debug;
+
+	/// The type used by the list to compare nodes for ordering.
+	type Score: Bounded;
 
 	/// An iterator over the list, which can have `take` called on it.
 	fn iter() -> Box<dyn Iterator<Item = AccountId>>;
 
+	/// An iterator over the list, starting right after the given `start` id, which can have
+	/// `take` called on it. `start` itself is not included in the output.
+	///
+	/// Returns an error if `start` is not in the list.
+	fn iter_from(start: &AccountId) -> Result<Box<dyn Iterator<Item = AccountId>>, Self::Error>;
+
+	/// An iterator over the list, in the order of [`Self::iter`], along with the current score of
+	/// each id.
+	///
+	/// This is meant for consumers that need nothing but the ids and their scores, e.g. analysis
+	/// tools, and not what the owner of the list builds out of them, such as voters and their
+	/// targets.
+	///
+	/// By default, the scores are those of [`Self::get_score`], so a list that keeps the scores
+	/// its ids were placed with must override this.
+	fn iter_with_score() -> Box<dyn Iterator<Item = (AccountId, Self::Score)>>
+	where
+		Self: 'static,
+		AccountId: 'static,
+	{
+		Box::new(Self::iter().filter_map(|id| Self::get_score(&id).map(|score| (id, score))))
+	}
+
+	/// An iterator over the list, in the order of [`Self::iter`], along with the upper threshold
+	/// of the bag of each id.
+	///
+	/// A list that is not made of bags holds all of its ids in a single bag, with the maximum
+	/// score as its upper threshold.
+	fn iter_with_bag() -> Box<dyn Iterator<Item = (AccountId, Self::Score)>>
+	where
+		Self: 'static,
+		AccountId: 'static,
+	{
+		Box::new(Self::iter().map(|id| (id, Self::Score::max_value())))
+	}
+
+	/// The number of ids in the bag with the upper threshold `bag_upper`, as given by
+	/// [`Self::iter_with_bag`].
+	fn count_in_bag(_bag_upper: Self::Score) -> u32 {
+		Self::count()
+	}
+
+	/// Take ids from the list, in the order of [`Self::iter`], or of [`Self::iter_from`] if
+	/// `start` is given, for as long as the weight of traversing the list fits in `budget`.
+	///
+	/// Unlike `take`-ing a fixed number of ids, this accounts for everything that is read to
+	/// find them, such that the consumed weight is an upper bound of the actual cost. The
+	/// iteration can be resumed with the returned cursor as `start`.
+	///
+	/// Returns an error if `start` is not in the list.
+	fn iter_with_budget(
+		start: Option<&AccountId>,
+		budget: Weight,
+	) -> Result<BudgetedIter<AccountId>, Self::Error>;
+
 	/// The current count of ids in the list.
 	fn count() -> u32;
 
 	/// Return true if the list already contains `id`.
 	fn contains(id: &AccountId) -> bool;
 
+	/// Get the score `id` was last placed in the list with, i.e. the one that its position is based
+	/// on, or `None` if it is not in the list.
+	fn get_score(id: &AccountId) -> Option<Self::Score>;
+
 	/// Hook for inserting a new id.
-	fn on_insert(id: AccountId, weight: VoteWeight) -> Result<(), Self::Error>;
+	///
+	/// Returns an error if `id` could not be inserted, e.g. because it is already in the list, in
+	/// which case the list must be left as it was.
+	fn on_insert(id: AccountId, score: Self::Score) -> Result<(), Self::Error>;
+
+	/// Hook for inserting a new id, right next to `hint`, an id expected to be in the same part of
+	/// the list, e.g. as computed off-chain.
+	///
+	/// The hint may make the insertion cheaper, but it is only a hint: a wrong one must not fail
+	/// the insertion. By default, it is ignored.
+	fn on_insert_with_hint(
+		id: AccountId,
+		score: Self::Score,
+		hint: &AccountId,
+	) -> Result<(), Self::Error> {
+		let _ = hint;
+		Self::on_insert(id, score)
+	}
 
 	/// Hook for updating a single id.
-	fn on_update(id: &AccountId, weight: VoteWeight);
+	///
+	/// Returns `true` if `id` changed its position in the list as a result, e.g. by moving into
+	/// another bag.
+	fn on_update(id: &AccountId, score: Self::Score) -> bool;
 
 	/// Hook for removing am id from the list.
 	fn on_remove(id: &AccountId);
 
-	/// Regenerate this list from scratch. Returns the count of items inserted.
+	/// Lock the list against reordering on behalf of `owner`, until [`Self::unlock`] is called
+	/// with the same `owner`.
+	///
+	/// This is meant for a pallet that relies on the order of the list over several blocks, e.g.
+	/// while an election is computed against a snapshot of it, such that no id moves in the
+	/// meantime. The list stays locked for as long as any owner holds a lock, such that owners do
+	/// not lift each other's locks. Ids can still be inserted and removed while the list is
+	/// locked, and an iteration from an id removed meanwhile, see [`Self::iter_from`], should
+	/// still resume where that id was.
+	fn lock(_owner: ListLock) {}
+
+	/// Undo [`Self::lock`] for `owner`. This does nothing if `owner` holds no lock.
+	fn unlock(_owner: ListLock) {}
+
+	/// Schedule a re-check of the scores of the highest ids of the list, e.g. at the end of an
+	/// era, when rewards changed the scores of many of them at once.
+	///
+	/// The list may do this lazily, over several blocks.
+	fn schedule_refresh() {}
+
+	/// Regenerate this list from scratch: wipe all of its data, then insert each of `all` with
+	/// its score, as given by `score_of`.
 	///
-	/// This should typically only be used at a runtime upgrade.
-	fn regenerate(
+	/// Returns the count of items inserted, and the items that could not be, e.g. because the list
+	/// has no room for them. Callers that keep their items in sync with the list must drop those.
+	///
+	/// WARNING: this reads and writes storage for every item of the list, and thus should only be
+	/// used for disaster recovery, in a runtime upgrade, or in testing (e.g. try-runtime); never
+	/// in a regular block.
+	fn unsafe_regenerate(
 		all: impl IntoIterator<Item = AccountId>,
-		weight_of: Box<dyn Fn(&AccountId) -> VoteWeight>,
-	) -> u32;
+		score_of: Box<dyn Fn(&AccountId) -> Self::Score>,
+	) -> (u32, Vec<AccountId>);
 
-	/// Remove `maybe_count` number of items from the list. Returns the number of items actually
-	/// removed. WARNING: removes all items if `maybe_count` is `None`, which should never be done
-	/// in production settings because it can lead to an unbounded amount of storage accesses.
-	fn clear(maybe_count: Option<u32>) -> u32;
+	/// Remove `maybe_count` number of items from the list, along with all the auxiliary data of
+	/// the list. Returns the number of items actually removed.
+	///
+	/// WARNING: this does not notify anyone, and leaves the list in an inconsistent state unless
+	/// all items are removed, so it should only be used for regeneration, migrations and tests.
+	/// It removes all items if `maybe_count` is `None`, which should never be done in production
+	/// settings because it can lead to an unbounded amount of storage accesses.
+	fn unsafe_clear(maybe_count: Option<u32>) -> u32;
 
 	/// Sanity check internal state of list. Only meant for debug compilation.
 	fn sanity_check() -> Result<(), &'static str>;
 
+	/// The upper thresholds of the bags that the list is sorted into, from the lowest to the
+	/// highest, or nothing if the list is not made of bags.
+	fn bag_thresholds() -> Vec<Self::Score> {
+		Vec::new()
+	}
+
+	/// The id at the very bottom of the list, i.e. the tail of its lowest non-empty bag, which
+	/// [`Self::iter`] yields last.
+	///
+	/// This is meant for evicting the id with the lowest score, e.g. when the list is full. It is
+	/// `None` if the list is empty, or if it cannot find its lowest id cheaply.
+	fn lowest() -> Option<AccountId> {
+		None
+	}
+
 	/// If `who` changes by the returned amount they are guaranteed to have a worst case change
 	/// in their list position.
 	#[cfg(feature = "runtime-benchmarks")]
-	fn weight_update_worst_case(_who: &AccountId, _is_increase: bool) -> VoteWeight {
-		VoteWeight::MAX
+	fn weight_update_worst_case(_who: &AccountId, _is_increase: bool) -> Self::Score {
+		Self::Score::max_value()
 	}
 }
 
-/// Something that can provide the `VoteWeight` of an account. Similar to [`ElectionProvider`] and
+/// Something that can provide the score of an account. Similar to [`ElectionProvider`] and
 /// [`ElectionDataProvider`], this should typically be implementing by whoever is supposed to *use*
 /// `SortedListProvider`.
-pub trait VoteWeightProvider<AccountId> {
-	/// Get the current `VoteWeight` of `who`.
-	fn vote_weight(who: &AccountId) -> VoteWeight;
+pub trait ScoreProvider<AccountId> {
+	/// The type of the score.
+	type Score;
+
+	/// Get the current score of `who`.
+	fn score(who: &AccountId) -> Self::Score;
+
+	/// A closure that gets the current score of any account, for scoring many accounts at once.
+	///
+	/// Whatever the scores depend on, e.g. the total issuance, can then be read only once: the
+	/// closure must only be used while that does not change, e.g. within a single block. By
+	/// default, this is [`Self::score`].
+	fn score_fn() -> Box<dyn Fn(&AccountId) -> Self::Score> {
+		Box::new(|who| Self::score(who))
+	}
 
-	/// For tests and benchmarks, set the `VoteWeight`.
+	/// For tests and benchmarks, set the score of `who`.
 	#[cfg(any(feature = "runtime-benchmarks", test))]
-	fn set_vote_weight_of(_: &AccountId, _: VoteWeight) {}
+	fn set_score_of(_: &AccountId, _: Self::Score) {}
 }
 
 /// Something that can compute the result to an NPoS solution.
@@ -178,6 +178,8 @@ impl pallet_staking::Config for Test {
 	type ElectionProvider = onchain::OnChainSequentialPhragmen<Self>;
 	type GenesisElectionProvider = Self::ElectionProvider;
 	type SortedListProvider = pallet_staking::UseNominatorsMap<Self>;
+	type TargetList = pallet_staking::UseValidatorsMap<Self>;
+	type AutoChillNominators = ();
 	type WeightInfo = ();
 }
 
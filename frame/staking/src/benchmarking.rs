@@ -21,7 +21,7 @@ use super::*;
 use crate::Pallet as Staking;
 use testing_utils::*;
 
-use frame_election_provider_support::SortedListProvider;
+use frame_election_provider_support::{ListLock, SortedListProvider};
 use frame_support::{
 	dispatch::UnfilteredDispatchable,
 	pallet_prelude::*,
@@ -157,12 +157,42 @@ impl<T: Config> ListScenario<T> {
 	/// also elicit a worst case for other known `SortedListProvider` implementations; although
 	/// this may not be true against unknown `SortedListProvider` implementations.
 	fn new(origin_weight: BalanceOf<T>, is_increase: bool) -> Result<Self, &'static str> {
+		Self::with_role(origin_weight, is_increase, false)
+	}
+
+	/// The same scenario as [`Self::new`], where the stashes are validators in
+	/// [`Config::TargetList`] rather than nominators in [`Config::SortedListProvider`].
+	///
+	/// A validator is also looked up in [`Config::SortedListProvider`], and its weight is read
+	/// from its exposure in the current era, so this is the worst case of the calls that update
+	/// both lists.
+	fn new_validators(origin_weight: BalanceOf<T>, is_increase: bool) -> Result<Self, &'static str> {
+		Self::with_role(origin_weight, is_increase, true)
+	}
+
+	fn with_role(
+		origin_weight: BalanceOf<T>,
+		is_increase: bool,
+		validators: bool,
+	) -> Result<Self, &'static str> {
 		ensure!(!origin_weight.is_zero(), "origin weight must be greater than 0");
 
 		// burn the entire issuance.
 		let i = T::Currency::burn(T::Currency::total_issuance());
 		sp_std::mem::forget(i);
 
+		let take_role = |controller: T::AccountId| -> DispatchResult {
+			if validators {
+				Staking::<T>::validate(RawOrigin::Signed(controller).into(), Default::default())
+			} else {
+				Staking::<T>::nominate(
+					RawOrigin::Signed(controller).into(),
+					// NOTE: these don't really need to be validators.
+					vec![T::Lookup::unlookup(account("random_validator", 0, SEED))],
+				)
+			}
+		};
+
 		// create accounts with the origin weight
 
 		let (origin_stash1, origin_controller1) = create_stash_controller_with_balance::<T>(
@@ -170,25 +200,21 @@ impl<T: Config> ListScenario<T> {
 			origin_weight,
 			Default::default(),
 		)?;
-		Staking::<T>::nominate(
-			RawOrigin::Signed(origin_controller1.clone()).into(),
-			// NOTE: these don't really need to be validators.
-			vec![T::Lookup::unlookup(account("random_validator", 0, SEED))],
-		)?;
+		take_role(origin_controller1.clone())?;
 
 		let (_origin_stash2, origin_controller2) = create_stash_controller_with_balance::<T>(
 			USER_SEED + 3,
 			origin_weight,
 			Default::default(),
 		)?;
-		Staking::<T>::nominate(
-			RawOrigin::Signed(origin_controller2.clone()).into(),
-			vec![T::Lookup::unlookup(account("random_validator", 0, SEED))].clone(),
-		)?;
+		take_role(origin_controller2)?;
 
 		// find a destination weight that will trigger the worst case scenario
-		let dest_weight_as_vote =
-			T::SortedListProvider::weight_update_worst_case(&origin_stash1, is_increase);
+		let dest_weight_as_vote = if validators {
+			T::TargetList::weight_update_worst_case(&origin_stash1, is_increase)
+		} else {
+			T::SortedListProvider::weight_update_worst_case(&origin_stash1, is_increase)
+		};
 
 		let total_issuance = T::Currency::total_issuance();
 
@@ -201,10 +227,7 @@ impl<T: Config> ListScenario<T> {
 			dest_weight,
 			Default::default(),
 		)?;
-		Staking::<T>::nominate(
-			RawOrigin::Signed(dest_controller1).into(),
-			vec![T::Lookup::unlookup(account("random_validator", 0, SEED))],
-		)?;
+		take_role(dest_controller1)?;
 
 		Ok(ListScenario { origin_stash1, origin_controller1, dest_weight })
 	}
@@ -231,12 +254,12 @@ benchmarks! {
 		// clean up any existing state.
 		clear_validators_and_nominators::<T>();
 
-		let origin_weight = MinNominatorBond::<T>::get().max(T::Currency::minimum_balance());
+		let origin_weight = MinValidatorBond::<T>::get().max(T::Currency::minimum_balance());
 
 		// setup the worst case list scenario.
 
-		// the weight the nominator will start at.
-		let scenario = ListScenario::<T>::new(origin_weight, true)?;
+		// the weight the validator will start at.
+		let scenario = ListScenario::<T>::new_validators(origin_weight, true)?;
 
 		let max_additional = scenario.dest_weight.clone() - origin_weight;
 
@@ -262,12 +285,12 @@ benchmarks! {
 
 		// setup the worst case list scenario.
 		let total_issuance = T::Currency::total_issuance();
-		// the weight the nominator will start at. The value used here is expected to be
+		// the weight the validator will start at. The value used here is expected to be
 		// significantly higher than the first position in a list (e.g. the first bag threshold).
 		let origin_weight = BalanceOf::<T>::try_from(952_994_955_240_703u128)
 			.map_err(|_| "balance expected to be a u128")
 			.unwrap();
-		let scenario = ListScenario::<T>::new(origin_weight, false)?;
+		let scenario = ListScenario::<T>::new_validators(origin_weight, false)?;
 
 		let stash = scenario.origin_stash1.clone();
 		let controller = scenario.origin_controller1.clone();
@@ -344,12 +367,23 @@ benchmarks! {
 		let stash = scenario.origin_stash1.clone();
 		assert!(T::SortedListProvider::contains(&stash));
 
+		// and the bag they are inserted into in the target list is not empty, so its tail must
+		// be updated.
+		let (_, other_controller) = create_stash_controller_with_balance::<T>(
+			USER_SEED + 4,
+			origin_weight,
+			Default::default(),
+		)?;
+		Staking::<T>::validate(RawOrigin::Signed(other_controller).into(), Default::default())?;
+		assert_eq!(T::TargetList::count(), 1);
+
 		let prefs = ValidatorPrefs::default();
 		whitelist_account!(controller);
 	}: _(RawOrigin::Signed(controller), prefs)
 	verify {
 		assert!(Validators::<T>::contains_key(&stash));
 		assert!(!T::SortedListProvider::contains(&stash));
+		assert!(T::TargetList::contains(&stash));
 	}
 
 	kick {
@@ -452,6 +486,7 @@ benchmarks! {
 
 		// setup a worst case list scenario. Note that we don't care about the setup of the
 		// destination position because we are doing a removal from the list but no insert.
+		// Chilling a validator removes it from the target list in the same way, at the same cost.
 		let scenario = ListScenario::<T>::new(origin_weight, true)?;
 		let controller = scenario.origin_controller1.clone();
 		let stash = scenario.origin_stash1.clone();
@@ -622,13 +657,13 @@ benchmarks! {
 		// clean up any existing state.
 		clear_validators_and_nominators::<T>();
 
-		let origin_weight = MinNominatorBond::<T>::get()
+		let origin_weight = MinValidatorBond::<T>::get()
 			.max(T::Currency::minimum_balance())
 			// we use 100 to play friendly with the list threshold values in the mock
 			.max(100u32.into());
 
 		// setup a worst case list scenario.
-		let scenario = ListScenario::<T>::new(origin_weight, true)?;
+		let scenario = ListScenario::<T>::new_validators(origin_weight, true)?;
 		let dest_weight = scenario.dest_weight.clone();
 
 		// rebond an amount that will give the user dest_weight
@@ -809,7 +844,8 @@ benchmarks! {
 		let v in (MAX_VALIDATORS / 2) .. MAX_VALIDATORS;
 		// number of nominator intention.
 		let n in (MAX_NOMINATORS / 2) .. MAX_NOMINATORS;
-		// total number of slashing spans. Assigned to validators randomly.
+		// total number of slashing spans. Assigned to validators randomly. They are read lazily
+		// while iterating the nominators, so only those of nominated validators are counted.
 		let s in 1 .. 20;
 
 		let validators = create_validators_with_nominators_for_era::<T>(
@@ -860,6 +896,23 @@ benchmarks! {
 		assert_eq!(ChillThreshold::<T>::get(), Some(Percent::from_percent(100)));
 	}
 
+	force_rebuild_voter_list {
+		// refilling the list costs more per nominator than clearing it, so the calls that refill
+		// the list are the worst case.
+		let l in 1 .. MAX_NOMINATORS;
+		create_validators_with_nominators_for_era::<T>(1, l, 1, false, None)?;
+		assert_eq!(T::SortedListProvider::count(), l);
+
+		// a rebuild that is done clearing the list.
+		let _ = T::SortedListProvider::unsafe_clear(None);
+		T::SortedListProvider::lock(ListLock::Rebuild);
+		VoterListRebuilding::<T>::put(VoterListRebuild::Refilling(None));
+	}: _(RawOrigin::Root, l)
+	verify {
+		assert_eq!(T::SortedListProvider::count(), l);
+		assert!(VoterListRebuilding::<T>::get().is_some());
+	}
+
 	chill_other {
 		// clean up any existing state.
 		clear_validators_and_nominators::<T>();
@@ -291,6 +291,7 @@ pub mod weights;
 mod pallet;
 
 use codec::{Decode, Encode, HasCompact};
+use frame_election_provider_support::VoteWeight;
 use frame_support::{
 	traits::{Currency, Get},
 	weights::Weight,
@@ -333,6 +334,13 @@ pub type RewardPoint = u32;
 pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+/// The voting data of a single voter: its id, its vote weight and its targets.
+pub type VotingDataOf<T> = (
+	<T as frame_system::Config>::AccountId,
+	VoteWeight,
+	Vec<<T as frame_system::Config>::AccountId>,
+);
+
 type PositiveImbalanceOf<T> = <<T as Config>::Currency as Currency<
 	<T as frame_system::Config>::AccountId,
 >>::PositiveImbalance;
@@ -573,6 +581,29 @@ pub struct Nominations<AccountId> {
 	pub suppressed: bool,
 }
 
+/// The position of a paged voter snapshot, as returned by [`Pallet::get_npos_voters_paged`].
+///
+/// All the validators are returned first, then the nominators, in the order of
+/// [`Config::SortedListProvider`]. The cursor records the last voter returned, which is excluded
+/// from the next page.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum VoterCursor<AccountId> {
+	/// The last voter returned is this validator.
+	Validator(AccountId),
+	/// The last voter returned is this nominator.
+	Nominator(AccountId),
+}
+
+/// The progress of a rebuild of [`Config::SortedListProvider`], see
+/// [`Call::force_rebuild_voter_list`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum VoterListRebuild<AccountId> {
+	/// The list is being cleared.
+	Clearing,
+	/// The list is being refilled from the nominators, resuming right after this one, if any.
+	Refilling(Option<AccountId>),
+}
+
 /// The amount of exposure (to slashing) than an individual nominator has.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub struct IndividualExposure<AccountId, Balance: HasCompact> {
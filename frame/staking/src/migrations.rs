@@ -18,11 +18,130 @@
 
 use super::*;
 
+/// How often, in ids, the progress of migrations that go over all the stakers is logged.
+const PROGRESS_INTERVAL: usize = 1_000;
+
+/// Log the progress of `ids` being migrated every [`PROGRESS_INTERVAL`] ids.
+fn with_progress<T: Config, I: Iterator>(
+	ids: I,
+	what: &'static str,
+) -> impl Iterator<Item = I::Item> {
+	ids.enumerate().map(move |(index, id)| {
+		if (index + 1) % PROGRESS_INTERVAL == 0 {
+			crate::log!(info, "👜 migrated {} {} so far", index + 1, what);
+		}
+		id
+	})
+}
+
+/// Migration to a sorted [`Config::TargetList`].
+///
+/// This must be included in the runtime upgrade that introduces a target list other than
+/// [`UseValidatorsMap`] on a chain that already has validators.
+pub mod target_list {
+	use frame_election_provider_support::SortedListProvider;
+	use frame_support::traits::{Get, OnGenesis, OnRuntimeUpgrade};
+	use sp_runtime::traits::Saturating;
+	use sp_std::{boxed::Box, marker::PhantomData};
+
+	use crate::{Config, CounterForValidators, Pallet, Validators, Weight};
+
+	/// [`migrate`] and [`post_migrate`] as a single [`OnRuntimeUpgrade`], to be included in the
+	/// runtime upgrade that introduces the target list.
+	///
+	/// This only does anything while [`Config::TargetList`] is empty, so it is safe to leave in the
+	/// runtime for later upgrades. `L` is the pallet of the target list, if any: a pallet added by
+	/// the upgrade has no genesis, so its [`OnGenesis`], e.g. the initialisation of its storage
+	/// version, is run here while the list is empty.
+	pub struct MigrateToTargetList<T, L = ()>(PhantomData<(T, L)>);
+
+	impl<T: Config, L: OnGenesis> OnRuntimeUpgrade for MigrateToTargetList<T, L> {
+		fn on_runtime_upgrade() -> Weight {
+			if T::TargetList::count() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			// an empty list is in the current layout of its storage, whatever its version.
+			L::on_genesis();
+			if CounterForValidators::<T>::get() > 0 {
+				migrate::<T>()
+			} else {
+				T::DbWeight::get().reads_writes(2, 1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			post_migrate::<T>()
+		}
+	}
+
+	/// Insert all the current validators into [`Config::TargetList`].
+	///
+	/// The validators that do not fit into it are chilled, as in [`Pallet::do_add_validator`].
+	pub fn migrate<T: Config>() -> Weight {
+		let (migrated, rejected) = T::TargetList::unsafe_regenerate(
+			super::with_progress::<T, _>(Validators::<T>::iter().map(|(id, _)| id), "targets"),
+			Box::new(|who| Pallet::<T>::target_weight_of(who)),
+		);
+		for who in rejected {
+			crate::log!(warn, "chilling validator {:?}, which does not fit into the list", who);
+			Validators::<T>::remove(&who);
+			CounterForValidators::<T>::mutate(|x| x.saturating_dec());
+		}
+		debug_assert_eq!(T::TargetList::sanity_check(), Ok(()));
+		crate::log!(info, "👜 completed staking target list migration with {} targets", migrated);
+
+		T::BlockWeights::get().max_block
+	}
+
+	#[cfg(feature = "try-runtime")]
+	pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+		T::TargetList::sanity_check().map_err(|_| "TargetList is not in a sane state.")?;
+		frame_support::ensure!(
+			T::TargetList::count() == CounterForValidators::<T>::get(),
+			"TargetList does not hold all the validators."
+		);
+		crate::log!(info, "👜 staking target list migration passes POST migrate checks ✅",);
+		Ok(())
+	}
+}
+
+/// Migration to a sorted [`Config::SortedListProvider`], populated with the nominators of the
+/// chain.
+///
+/// Validators are not part of the voter list; with a [`Config::TargetList`] other than
+/// [`UseValidatorsMap`], they are inserted into it along with the nominators, see
+/// [`target_list`].
 pub mod v8 {
 	use frame_election_provider_support::SortedListProvider;
-	use frame_support::traits::Get;
+	use frame_support::traits::{Get, OnRuntimeUpgrade};
+	use sp_runtime::traits::Saturating;
+	use sp_std::marker::PhantomData;
+
+	use crate::{Config, CounterForNominators, Nominators, Pallet, StorageVersion, Weight};
 
-	use crate::{Config, Nominators, Pallet, StorageVersion, Weight};
+	/// [`migrate`], [`pre_migrate`] and [`post_migrate`] as a single [`OnRuntimeUpgrade`], to be
+	/// included in the runtime upgrade that introduces the sorted lists.
+	///
+	/// This only does anything once: on the first upgrade from `Releases::V7_0_0`.
+	pub struct MigrateToV8<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV8<T> {
+		fn on_runtime_upgrade() -> Weight {
+			migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			pre_migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			post_migrate::<T>()
+		}
+	}
 
 	#[cfg(feature = "try-runtime")]
 	pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
@@ -36,15 +155,25 @@ pub mod v8 {
 	}
 
 	/// Migration to sorted [`SortedListProvider`].
+	///
+	/// The nominators are inserted into [`Config::SortedListProvider`], and the validators into
+	/// [`Config::TargetList`]. The nominators that do not fit into the list are chilled, as in
+	/// [`Pallet::do_add_nominator`].
 	pub fn migrate<T: Config>() -> Weight {
 		if StorageVersion::<T>::get() == crate::Releases::V7_0_0 {
 			crate::log!(info, "migrating staking to Releases::V8_0_0");
 
-			let migrated = T::SortedListProvider::regenerate(
-				Nominators::<T>::iter().map(|(id, _)| id),
+			let (migrated, rejected) = T::SortedListProvider::unsafe_regenerate(
+				super::with_progress::<T, _>(Nominators::<T>::iter().map(|(id, _)| id), "voters"),
 				Pallet::<T>::weight_of_fn(),
 			);
+			for who in rejected {
+				crate::log!(warn, "chilling nominator {:?}, which does not fit into the list", who);
+				Nominators::<T>::remove(&who);
+				CounterForNominators::<T>::mutate(|x| x.saturating_dec());
+			}
 			debug_assert_eq!(T::SortedListProvider::sanity_check(), Ok(()));
+			super::target_list::migrate::<T>();
 
 			StorageVersion::<T>::put(crate::Releases::V8_0_0);
 			crate::log!(
@@ -63,6 +192,11 @@ pub mod v8 {
 	pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
 		T::SortedListProvider::sanity_check()
 			.map_err(|_| "SortedListProvider is not in a sane state.")?;
+		frame_support::ensure!(
+			T::SortedListProvider::count() == CounterForNominators::<T>::get(),
+			"SortedListProvider does not hold all the nominators."
+		);
+		super::target_list::post_migrate::<T>()?;
 		crate::log!(info, "👜 staking bags-list migration passes POST migrate checks ✅",);
 		Ok(())
 	}
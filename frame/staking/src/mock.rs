@@ -104,7 +104,7 @@ frame_support::construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Staking: pallet_staking::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
-		BagsList: pallet_bags_list::{Pallet, Call, Storage, Event<T>},
+		BagsList: pallet_bags_list::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -131,6 +131,7 @@ parameter_types! {
 	pub static SlashDeferDuration: EraIndex = 0;
 	pub static Period: BlockNumber = 5;
 	pub static Offset: BlockNumber = 0;
+	pub static AutoChillNominators: bool = false;
 }
 
 impl frame_system::Config for Test {
@@ -246,13 +247,53 @@ const THRESHOLDS: [sp_npos_elections::VoteWeight; 9] =
 
 parameter_types! {
 	pub static BagThresholds: &'static [sp_npos_elections::VoteWeight] = &THRESHOLDS;
+	pub const MaxBagThresholds: u32 = 20;
+	pub const MaxAutoRebagPerBlock: u32 = 0;
+	pub const DisplacementMargin: Perbill = Perbill::from_percent(10);
+	pub const BagsListOffchainRepeat: BlockNumber = 0;
+	pub const MaxOffchainRebags: u32 = 0;
+	pub const BagsListUnsignedPriority: u64 = 0;
+	pub const BagsListSanityCheckDepth: pallet_bags_list::SanityCheckLevel =
+		pallet_bags_list::SanityCheckLevel::Full;
+	pub static MaxBagSize: u32 = u32::MAX;
+	pub const BagOverflowPolicy: pallet_bags_list::BagOverflow =
+		pallet_bags_list::BagOverflow::SpillLower;
+	pub const BagInsertionPolicy: pallet_bags_list::BagInsertion =
+		pallet_bags_list::BagInsertion::Tail;
+	pub const BagsListPalletId: frame_support::PalletId = frame_support::PalletId(*b"py/bagsl");
+	pub const RebagReward: Balance = 0;
+	pub const RebagRewardCooldown: BlockNumber = 0;
+	pub const RebagCooldown: BlockNumber = 0;
+	pub const RebagDeposit: Balance = 0;
+	pub const PruneReward: Balance = 0;
 }
 
 impl pallet_bags_list::Config for Test {
 	type Event = Event;
 	type WeightInfo = ();
-	type VoteWeightProvider = Staking;
+	type Score = sp_npos_elections::VoteWeight;
+	type ScoreProvider = Staking;
 	type BagThresholds = BagThresholds;
+	type MaxBagThresholds = MaxBagThresholds;
+	type MaxBagSize = MaxBagSize;
+	type BagOverflowPolicy = BagOverflowPolicy;
+	type BagInsertionPolicy = BagInsertionPolicy;
+	type SanityCheckDepth = BagsListSanityCheckDepth;
+	type MaxAutoRebagPerBlock = MaxAutoRebagPerBlock;
+	type RefreshTopBags = ();
+	type DisplacementMargin = DisplacementMargin;
+	type MinScoreBag = ();
+	type OffchainRepeat = BagsListOffchainRepeat;
+	type MaxOffchainRebags = MaxOffchainRebags;
+	type UnsignedPriority = BagsListUnsignedPriority;
+	type Currency = Balances;
+	type PalletId = BagsListPalletId;
+	type RebagReward = RebagReward;
+	type RebagRewardCooldown = RebagRewardCooldown;
+	type RebagCooldown = RebagCooldown;
+	type RebagDeposit = RebagDeposit;
+	type Members = crate::IsVoter<Self>;
+	type PruneReward = PruneReward;
 }
 
 impl onchain::Config for Test {
@@ -282,6 +323,9 @@ impl crate::pallet::pallet::Config for Test {
 	type WeightInfo = ();
 	// NOTE: consider a macro and use `UseNominatorsMap<Self>` as well.
 	type SortedListProvider = BagsList;
+	// NOTE: the order of the targets affects the election results all tests are written against.
+	type TargetList = crate::UseValidatorsMap<Self>;
+	type AutoChillNominators = AutoChillNominators;
 }
 
 impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
@@ -18,14 +18,14 @@
 //! Implementations for the Staking FRAME Pallet.
 
 use frame_election_provider_support::{
-	data_provider, ElectionDataProvider, ElectionProvider, SortedListProvider, Supports,
-	VoteWeight, VoteWeightProvider,
+	data_provider, BudgetedIter, ElectionDataProvider, ElectionProvider, ListLock, ScoreProvider,
+	SortedListProvider, Supports, VoteWeight, VoterPage,
 };
 use frame_support::{
 	pallet_prelude::*,
 	traits::{
-		Currency, CurrencyToVote, EstimateNextNewSession, Get, Imbalance, LockableCurrency,
-		OnUnbalanced, UnixTime, WithdrawReasons,
+		Contains, Currency, CurrencyToVote, EstimateNextNewSession, Get, Imbalance,
+		LockableCurrency, OnUnbalanced, UnixTime, WithdrawReasons,
 	},
 	weights::{Weight, WithPostDispatchInfo},
 };
@@ -39,12 +39,15 @@ use sp_staking::{
 	offence::{OffenceDetails, OnOffenceHandler},
 	SessionIndex,
 };
-use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
 
 use crate::{
 	log, slashing, weights::WeightInfo, ActiveEraInfo, BalanceOf, EraIndex, EraPayout, Exposure,
 	ExposureOf, Forcing, IndividualExposure, Nominations, PositiveImbalanceOf, RewardDestination,
-	SessionInterface, StakingLedger, ValidatorPrefs,
+	SessionInterface, StakingLedger, ValidatorPrefs, VoterCursor, VotingDataOf,
 };
 
 use super::{pallet::*, STAKING_ID};
@@ -83,6 +86,43 @@ impl<T: Config> Pallet<T> {
 		Self::slashable_balance_of_vote_weight(who, issuance)
 	}
 
+	/// The weight of a validator in [`Config::TargetList`].
+	///
+	/// This is the total stake backing the validator, its own included, in the current era. If the
+	/// validator is not elected in the current era, this is only its own stake.
+	///
+	/// The exposure of an era does not change with the bonds of the nominators backing the
+	/// validator, so the weight of an elected validator is only updated when a new era is planned,
+	/// while the weight of the others follows their own ledger.
+	pub fn target_weight_of(who: &T::AccountId) -> VoteWeight {
+		let issuance = T::Currency::total_issuance();
+		match Self::current_era().map(|era| <ErasStakers<T>>::get(era, who).total) {
+			Some(total) if !total.is_zero() => T::CurrencyToVote::to_vote(total, issuance),
+			_ => Self::slashable_balance_of_vote_weight(who, issuance),
+		}
+	}
+
+	/// Update the weight of `who` in [`Config::SortedListProvider`], if they exist in it.
+	///
+	/// Returns `true` if `who` changed position in the list. The ledger of `who` must be updated
+	/// prior to calling this.
+	pub(crate) fn update_voter_weight(who: &T::AccountId) -> bool {
+		if !T::SortedListProvider::contains(who) {
+			return false
+		}
+		let moved = T::SortedListProvider::on_update(who, Self::weight_of(who));
+		debug_assert_eq!(T::SortedListProvider::sanity_check(), Ok(()));
+		moved
+	}
+
+	/// Update the weight of `who` in [`Config::TargetList`], if they exist in it.
+	pub(crate) fn update_target_weight(who: &T::AccountId) {
+		if T::TargetList::contains(who) {
+			T::TargetList::on_update(who, Self::target_weight_of(who));
+			debug_assert_eq!(T::TargetList::sanity_check(), Ok(()));
+		}
+	}
+
 	pub(super) fn do_payout_stakers(
 		validator_stash: T::AccountId,
 		era: EraIndex,
@@ -218,6 +258,22 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// The nominator to chill to make room for `who` as a new nominator, if
+	/// [`Config::AutoChillNominators`] is set.
+	///
+	/// This is the lowest id of [`Config::SortedListProvider`], as long as it is a nominator with
+	/// a lower weight than `who`; a validator at the bottom of the list is never chilled.
+	pub(crate) fn nominator_to_auto_chill(who: &T::AccountId) -> Option<T::AccountId> {
+		if !T::AutoChillNominators::get() {
+			return None
+		}
+		T::SortedListProvider::lowest().filter(|lowest| {
+			lowest != who &&
+				Nominators::<T>::contains_key(lowest) &&
+				Self::weight_of(lowest) < Self::weight_of(who)
+		})
+	}
+
 	/// Actually make a payment to a staker. This uses the currency's reward function
 	/// to pay the right payee for the given staker account.
 	fn make_payout(stash: &T::AccountId, amount: BalanceOf<T>) -> Option<PositiveImbalanceOf<T>> {
@@ -233,6 +289,8 @@ impl<T: Config> Pallet<T> {
 					l.total += amount;
 					let r = T::Currency::deposit_into_existing(stash, amount).ok();
 					Self::update_ledger(&controller, &l);
+					Self::update_voter_weight(stash);
+					Self::update_target_weight(stash);
 					r
 				}),
 			RewardDestination::Account(dest_account) =>
@@ -374,6 +432,10 @@ impl<T: Config> Pallet<T> {
 			// Set ending era reward.
 			<ErasValidatorReward<T>>::insert(&active_era.index, validator_payout);
 			T::RewardRemainder::on_unbalanced(T::Currency::issue(rest));
+
+			// the inflation of this era changes the total issuance, and thus the weights of the
+			// nominators, starting with the highest bags.
+			T::SortedListProvider::schedule_refresh();
 		}
 	}
 
@@ -470,10 +532,19 @@ impl<T: Config> Pallet<T> {
 
 		// Populate stakers, exposures, and the snapshot of validator prefs.
 		let mut total_stake: BalanceOf<T> = Zero::zero();
+		let total_issuance = T::Currency::total_issuance();
 		exposures.into_iter().for_each(|(stash, exposure)| {
 			total_stake = total_stake.saturating_add(exposure.total);
 			<ErasStakers<T>>::insert(new_planned_era, &stash, &exposure);
 
+			// the new exposure is the new weight of this validator in the target list.
+			if T::TargetList::contains(&stash) {
+				T::TargetList::on_update(
+					&stash,
+					T::CurrencyToVote::to_vote(exposure.total, total_issuance),
+				);
+			}
+
 			let mut exposure_clipped = exposure;
 			let clipped_max_len = T::MaxNominatorRewardedPerValidator::get() as usize;
 			if exposure_clipped.others.len() > clipped_max_len {
@@ -492,6 +563,20 @@ impl<T: Config> Pallet<T> {
 			<ErasValidatorPrefs<T>>::insert(&new_planned_era, stash, pref);
 		}
 
+		// the validators elected in the previous era but not in this one are back to their own
+		// stake in the target list.
+		if let Some(previous_era) = new_planned_era.checked_sub(1) {
+			let elected = elected_stashes.iter().collect::<BTreeSet<_>>();
+			for (stash, _) in <ErasStakers<T>>::iter_prefix(previous_era) {
+				if !elected.contains(&stash) && T::TargetList::contains(&stash) {
+					T::TargetList::on_update(
+						&stash,
+						Self::slashable_balance_of_vote_weight(&stash, total_issuance),
+					);
+				}
+			}
+		}
+
 		if new_planned_era > 0 {
 			log!(
 				info,
@@ -651,9 +736,54 @@ impl<T: Config> Pallet<T> {
 	///
 	/// All nominations that have been submitted before the last non-zero slash of the validator are
 	/// auto-chilled, but still count towards the limit imposed by `maybe_max_len`.
+	///
+	/// The slashing spans are read lazily, only for the targets of the nominators that are
+	/// iterated over.
 	pub fn get_npos_voters(
 		maybe_max_len: Option<usize>,
 	) -> Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		let NposVoters {
+			voters: all_voters,
+			validators_taken,
+			nominators_taken,
+			slashing_spans_read,
+			mut nominators_iter,
+		} = Self::collect_npos_voters(maybe_max_len);
+
+		let composition = nominators_iter.take_composition();
+		if !composition.is_empty() {
+			Self::deposit_event(Event::<T>::SnapshotComposition(composition));
+		}
+		if let Some((last_bag, taken, skipped)) = nominators_iter.truncation() {
+			log!(
+				info,
+				"nominators truncated in bag {}: {} taken, {} skipped",
+				last_bag,
+				taken,
+				skipped
+			);
+			Self::deposit_event(Event::<T>::SnapshotTruncated(last_bag, taken, skipped));
+		}
+
+		Self::register_weight(T::WeightInfo::get_npos_voters(
+			validators_taken,
+			nominators_taken,
+			slashing_spans_read,
+		));
+
+		log!(
+			info,
+			"generated {} npos voters, {} from validators and {} nominators",
+			all_voters.len(),
+			validators_taken,
+			nominators_taken
+		);
+		all_voters
+	}
+
+	/// Collect the voters of [`Self::get_npos_voters`], without any side effect: this neither
+	/// registers its weight nor deposits any event.
+	fn collect_npos_voters(maybe_max_len: Option<usize>) -> NposVoters<T> {
 		let max_allowed_len = {
 			let nominator_count = CounterForNominators::<T>::get() as usize;
 			let validator_count = CounterForValidators::<T>::get() as usize;
@@ -675,14 +805,15 @@ impl<T: Config> Pallet<T> {
 
 		// .. and grab whatever we have left from nominators.
 		let nominators_quota = (max_allowed_len as u32).saturating_sub(validators_taken);
-		let slashing_spans = <SlashingSpans<T>>::iter().collect::<BTreeMap<_, _>>();
+		// the last non-zero slash of each target, read lazily as the nominators are iterated.
+		let mut last_slashes = BTreeMap::new();
 
 		// track the count of nominators added to `all_voters
 		let mut nominators_taken = 0u32;
 		// track every nominator iterated over, but not necessarily added to `all_voters`
 		let mut nominators_seen = 0u32;
 
-		let mut nominators_iter = T::SortedListProvider::iter();
+		let mut nominators_iter = BagTracker::<T>::new();
 		while nominators_taken < nominators_quota && nominators_seen < nominators_quota * 2 {
 			let nominator = match nominators_iter.next() {
 				Some(nominator) => {
@@ -695,14 +826,11 @@ impl<T: Config> Pallet<T> {
 			if let Some(Nominations { submitted_in, mut targets, suppressed: _ }) =
 				<Nominators<T>>::get(&nominator)
 			{
-				targets.retain(|stash| {
-					slashing_spans
-						.get(stash)
-						.map_or(true, |spans| submitted_in >= spans.last_nonzero_slash())
-				});
+				Self::retain_unslashed_targets(&mut targets, submitted_in, &mut last_slashes);
 				if !targets.len().is_zero() {
 					all_voters.push((nominator.clone(), Self::weight_of(&nominator), targets));
 					nominators_taken.saturating_inc();
+					nominators_iter.note_taken();
 				}
 			} else {
 				log!(error, "invalid item in `SortedListProvider`: {:?}", nominator)
@@ -712,35 +840,185 @@ impl<T: Config> Pallet<T> {
 		// all_voters should have not re-allocated.
 		debug_assert!(all_voters.capacity() == max_allowed_len);
 
+		NposVoters {
+			voters: all_voters,
+			validators_taken,
+			nominators_taken,
+			slashing_spans_read: Self::slashing_spans_read(&last_slashes),
+			nominators_iter,
+		}
+	}
+
+	/// Retain only the `targets` of nominations `submitted_in` a given era that have not been
+	/// slashed since.
+	///
+	/// The slashing spans of each target are only read the first time they are needed, and then
+	/// cached in `last_slashes`.
+	fn retain_unslashed_targets(
+		targets: &mut Vec<T::AccountId>,
+		submitted_in: EraIndex,
+		last_slashes: &mut BTreeMap<T::AccountId, Option<EraIndex>>,
+	) {
+		targets.retain(|stash| {
+			last_slashes
+				.entry(stash.clone())
+				.or_insert_with(|| {
+					<SlashingSpans<T>>::get(stash).map(|spans| spans.last_nonzero_slash())
+				})
+				.map_or(true, |last_nonzero_slash| submitted_in >= last_nonzero_slash)
+		});
+	}
+
+	/// The number of slashing spans actually found while filling `last_slashes`, as expected by
+	/// [`WeightInfo::get_npos_voters`].
+	fn slashing_spans_read(last_slashes: &BTreeMap<T::AccountId, Option<EraIndex>>) -> u32 {
+		last_slashes
+			.values()
+			.filter(|last_nonzero_slash| last_nonzero_slash.is_some())
+			.count() as u32
+	}
+
+	/// Get a single page of the voters of the next election, of at most `page_size` voters,
+	/// starting right after `cursor`, or from the beginning if `cursor` is `None`.
+	///
+	/// The voters are the same as [`Self::get_npos_voters`] with no length limit, in the same
+	/// order: first all the validators, each with a self vote, then the nominators, as returned by
+	/// [`Config::SortedListProvider`]. This allows the snapshot to be built across multiple
+	/// blocks.
+	///
+	/// Returns the page, and the cursor from which to get the next one, or `None` if this was the
+	/// last page. As with [`Self::get_npos_voters`], nominators whose nominations have all been
+	/// auto-chilled are skipped, and at most `2 * page_size` nominators are iterated over in a
+	/// single page, meaning that a page can be shorter than `page_size` without being the last.
+	///
+	/// If the nominator of `cursor` is removed from [`Config::SortedListProvider`] in between two
+	/// pages, there is no position to resume from, and an empty last page is returned.
+	///
+	/// This does not lock [`Config::SortedListProvider`]. A caller that takes the pages over
+	/// several blocks should freeze the voters with
+	/// [`ElectionDataProvider::freeze_voters`] before the first page, and unfreeze them once done
+	/// with the last one, such that nominators are neither skipped nor taken twice by moving
+	/// within the list in the meantime.
+	///
+	/// This function is self-weighing as [`DispatchClass::Mandatory`].
+	pub fn get_npos_voters_paged(
+		page_size: usize,
+		cursor: Option<VoterCursor<T::AccountId>>,
+	) -> (Vec<VotingDataOf<T>>, Option<VoterCursor<T::AccountId>>) {
+		let mut voters = Vec::<_>::with_capacity(page_size);
+		let mut next_cursor = None;
+
+		// first, the validators, unless the previous page was already into the nominators.
+		let mut validators_taken = 0u32;
+		let maybe_validators = match &cursor {
+			None => Some(<Validators<T>>::iter_keys()),
+			Some(VoterCursor::Validator(last)) =>
+				Some(<Validators<T>>::iter_keys_from(<Validators<T>>::hashed_key_for(last))),
+			Some(VoterCursor::Nominator(_)) => None,
+		};
+		if let Some(mut validators) = maybe_validators {
+			while voters.len() < page_size {
+				match validators.next() {
+					Some(validator) => {
+						voters.push((
+							validator.clone(),
+							Self::weight_of(&validator),
+							vec![validator.clone()],
+						));
+						validators_taken.saturating_inc();
+						next_cursor = Some(VoterCursor::Validator(validator));
+					},
+					None => break,
+				}
+			}
+		}
+
+		// .. then the nominators, if the page is not full yet.
+		let mut nominators_taken = 0u32;
+		let mut nominators_seen = 0u32;
+		let mut last_slashes = BTreeMap::new();
+		let nominators_quota = page_size.saturating_sub(voters.len()) as u32;
+		let maybe_nominators = match &cursor {
+			_ if nominators_quota.is_zero() => None,
+			Some(VoterCursor::Nominator(last)) => match T::SortedListProvider::iter_from(last) {
+				Ok(nominators) => Some(nominators),
+				Err(_) => {
+					log!(warn, "cursor {:?} is not in `SortedListProvider` anymore", last);
+					None
+				},
+			},
+			_ => Some(T::SortedListProvider::iter()),
+		};
+		if let Some(mut nominators) = maybe_nominators {
+			while nominators_taken < nominators_quota && nominators_seen < nominators_quota * 2 {
+				let nominator = match nominators.next() {
+					Some(nominator) => {
+						nominators_seen.saturating_inc();
+						nominator
+					},
+					None => break,
+				};
+
+				if let Some(Nominations { submitted_in, mut targets, suppressed: _ }) =
+					<Nominators<T>>::get(&nominator)
+				{
+					Self::retain_unslashed_targets(&mut targets, submitted_in, &mut last_slashes);
+					if !targets.len().is_zero() {
+						voters.push((nominator.clone(), Self::weight_of(&nominator), targets));
+						nominators_taken.saturating_inc();
+					}
+				} else {
+					log!(error, "invalid item in `SortedListProvider`: {:?}", nominator)
+				}
+				next_cursor = Some(VoterCursor::Nominator(nominator));
+			}
+		}
+
+		// a page that could not be filled means that we have run out of voters.
+		if voters.len() < page_size && nominators_seen < nominators_quota * 2 {
+			next_cursor = None;
+		}
+
 		Self::register_weight(T::WeightInfo::get_npos_voters(
 			validators_taken,
 			nominators_taken,
-			slashing_spans.len() as u32,
+			Self::slashing_spans_read(&last_slashes),
 		));
 
 		log!(
-			info,
-			"generated {} npos voters, {} from validators and {} nominators",
-			all_voters.len(),
+			debug,
+			"generated a page of {} npos voters, {} from validators and {} nominators",
+			voters.len(),
 			validators_taken,
 			nominators_taken
 		);
-		all_voters
+		(voters, next_cursor)
+	}
+
+	/// The first `n` voters of an upcoming npos election, with their vote weight.
+	///
+	/// These are exactly the voters that would be in a snapshot of at most `n` voters, as built by
+	/// [`Self::get_npos_voters`], in the same order. This is meant to be queried offchain, e.g. by
+	/// a nominator that wants to know whether it would make the cut.
+	///
+	/// Unlike [`Self::get_npos_voters`], this has no side effect.
+	pub fn electing_preview(n: u32) -> Vec<(T::AccountId, VoteWeight)> {
+		Self::collect_npos_voters(Some(n as usize))
+			.voters
+			.into_iter()
+			.map(|(voter, weight, _)| (voter, weight))
+			.collect()
 	}
 
 	/// Get the targets for an upcoming npos election.
 	///
+	/// The targets are all the validators, in the order of [`Config::TargetList`].
+	///
 	/// This function is self-weighing as [`DispatchClass::Mandatory`].
 	pub fn get_npos_targets() -> Vec<T::AccountId> {
-		let mut validator_count = 0u32;
-		let targets = Validators::<T>::iter()
-			.map(|(v, _)| {
-				validator_count.saturating_inc();
-				v
-			})
-			.collect::<Vec<_>>();
+		let targets = T::TargetList::iter().collect::<Vec<_>>();
 
-		Self::register_weight(T::WeightInfo::get_npos_targets(validator_count));
+		Self::register_weight(T::WeightInfo::get_npos_targets(targets.len() as u32));
 
 		targets
 	}
@@ -748,26 +1026,64 @@ impl<T: Config> Pallet<T> {
 	/// This function will add a nominator to the `Nominators` storage map,
 	/// [`SortedListProvider`] and keep track of the `CounterForNominators`.
 	///
-	/// If the nominator already exists, their nominations will be updated.
+	/// If the nominator already exists, their nominations will be updated, and their position in
+	/// [`Config::SortedListProvider`] refreshed. Returns an error, and changes nothing, if a new
+	/// nominator cannot be inserted into [`Config::SortedListProvider`].
 	///
 	/// NOTE: you must ALWAYS use this function to add nominator or update their targets. Any access
 	/// to `Nominators`, its counter, or `VoterList` outside of this function is almost certainly
 	/// wrong.
-	pub fn do_add_nominator(who: &T::AccountId, nominations: Nominations<T::AccountId>) {
+	pub fn do_add_nominator(
+		who: &T::AccountId,
+		nominations: Nominations<T::AccountId>,
+	) -> DispatchResult {
 		if !Nominators::<T>::contains_key(who) {
-			// maybe update the counter.
-			CounterForNominators::<T>::mutate(|x| x.saturating_inc());
-
-			// maybe update sorted list. Error checking is defensive-only - this should never fail.
-			if T::SortedListProvider::on_insert(who.clone(), Self::weight_of(who)).is_err() {
+			// maybe update sorted list. A duplicate is defensive-only - this should never happen,
+			// and the nominator can be kept where it is. Any other failure is reported.
+			if T::SortedListProvider::contains(who) {
 				log!(warn, "attempt to insert duplicate nominator ({:#?})", who);
 				debug_assert!(false, "attempt to insert duplicate nominator");
-			};
+			} else if let Err(e) =
+				T::SortedListProvider::on_insert(who.clone(), Self::weight_of(who))
+			{
+				log!(warn, "failed to insert nominator {:?} into the list: {:?}", who, e);
+				return Err(Error::<T>::ListInsertFailed.into())
+			}
+
+			// maybe update the counter.
+			CounterForNominators::<T>::mutate(|x| x.saturating_inc());
 
 			debug_assert_eq!(T::SortedListProvider::sanity_check(), Ok(()));
+		} else {
+			// a nominator changing their targets is placed according to their current weight.
+			Self::update_voter_weight(who);
 		}
 
 		Nominators::<T>::insert(who, nominations);
+		Ok(())
+	}
+
+	/// Add all the nominators of the genesis at once, like [`Self::do_add_nominator`] would, but
+	/// inserting them into the [`SortedListProvider`] in a single batch.
+	///
+	/// This must only be called at genesis, while the [`SortedListProvider`] holds no one.
+	pub(crate) fn do_add_genesis_nominators(
+		nominators: Vec<(T::AccountId, Nominations<T::AccountId>)>,
+	) {
+		let ids = nominators.iter().map(|(who, _)| who.clone()).collect::<Vec<_>>();
+		let (_, rejected) = T::SortedListProvider::unsafe_regenerate(ids, Self::weight_of_fn());
+		for (who, nominations) in nominators {
+			// as in `do_add_nominator`, someone who cannot be in the list is not a nominator.
+			if rejected.contains(&who) {
+				log!(warn, "genesis nominator {:?} does not fit into the list", who);
+				continue
+			}
+			if !Nominators::<T>::contains_key(&who) {
+				CounterForNominators::<T>::mutate(|x| x.saturating_inc());
+			}
+			Nominators::<T>::insert(who, nominations);
+		}
+		debug_assert_eq!(T::SortedListProvider::sanity_check(), Ok(()));
 	}
 
 	/// This function will remove a nominator from the `Nominators` storage map,
@@ -794,16 +1110,36 @@ impl<T: Config> Pallet<T> {
 	/// This function will add a validator to the `Validators` storage map, and keep track of the
 	/// `CounterForValidators`.
 	///
-	/// If the validator already exists, their preferences will be updated.
+	/// If the validator already exists, their preferences will be updated, and their position in
+	/// [`Config::TargetList`] refreshed. Returns an error, and changes nothing, if a new validator
+	/// cannot be inserted into [`Config::TargetList`].
 	///
 	/// NOTE: you must ALWAYS use this function to add a validator to the system. Any access to
 	/// `Validators`, its counter, or `VoterList` outside of this function is almost certainly
 	/// wrong.
-	pub fn do_add_validator(who: &T::AccountId, prefs: ValidatorPrefs) {
+	pub fn do_add_validator(who: &T::AccountId, prefs: ValidatorPrefs) -> DispatchResult {
 		if !Validators::<T>::contains_key(who) {
-			CounterForValidators::<T>::mutate(|x| x.saturating_inc())
+			// maybe update sorted list. A duplicate is defensive-only - this should never happen,
+			// and the validator can be kept where it is. Any other failure is reported.
+			if T::TargetList::contains(who) {
+				log!(warn, "attempt to insert duplicate validator ({:#?})", who);
+				debug_assert!(false, "attempt to insert duplicate validator");
+			} else if let Err(e) =
+				T::TargetList::on_insert(who.clone(), Self::target_weight_of(who))
+			{
+				log!(warn, "failed to insert validator {:?} into the list: {:?}", who, e);
+				return Err(Error::<T>::ListInsertFailed.into())
+			}
+
+			CounterForValidators::<T>::mutate(|x| x.saturating_inc());
+
+			debug_assert_eq!(T::TargetList::sanity_check(), Ok(()));
+		} else {
+			// a validator changing their preferences is placed according to their current weight.
+			Self::update_target_weight(who);
 		}
 		Validators::<T>::insert(who, prefs);
+		Ok(())
 	}
 
 	/// This function will remove a validator from the `Validators` storage map,
@@ -818,6 +1154,9 @@ impl<T: Config> Pallet<T> {
 		if Validators::<T>::contains_key(who) {
 			Validators::<T>::remove(who);
 			CounterForValidators::<T>::mutate(|x| x.saturating_dec());
+			T::TargetList::on_remove(who);
+			debug_assert_eq!(T::TargetList::sanity_check(), Ok(()));
+			debug_assert_eq!(CounterForValidators::<T>::get(), T::TargetList::count());
 			true
 		} else {
 			false
@@ -835,6 +1174,85 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
+/// The voters of an npos election, as collected by [`Pallet::collect_npos_voters`].
+struct NposVoters<T: Config> {
+	voters: Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>,
+	validators_taken: u32,
+	nominators_taken: u32,
+	/// The number of slashing spans read to filter the nominations.
+	slashing_spans_read: u32,
+	/// The iterator the nominators were taken from, which knows in which bags they were.
+	nominators_iter: BagTracker<T>,
+}
+
+/// An iterator over the ids of [`Config::SortedListProvider`], which keeps track of the bag it
+/// stops in, such that a capped iteration can tell where it was cut, and of how many of the ids of
+/// each bag were taken.
+pub(crate) struct BagTracker<T: Config> {
+	inner: Box<dyn Iterator<Item = (T::AccountId, VoteWeight)>>,
+	/// The upper threshold of the bag of the last id iterated, if any.
+	current_bag: Option<VoteWeight>,
+	/// The number of ids iterated in `current_bag`.
+	seen_in_bag: u32,
+	/// The upper threshold of each bag in which some ids were taken, with their number.
+	composition: Vec<(VoteWeight, u32)>,
+}
+
+impl<T: Config> BagTracker<T> {
+	pub(crate) fn new() -> Self {
+		Self {
+			inner: T::SortedListProvider::iter_with_bag(),
+			current_bag: None,
+			seen_in_bag: 0,
+			composition: Vec::new(),
+		}
+	}
+
+	/// Note that the last id iterated was taken.
+	pub(crate) fn note_taken(&mut self) {
+		let bag_upper = match self.current_bag {
+			Some(bag_upper) => bag_upper,
+			None => return,
+		};
+		match self.composition.last_mut() {
+			Some((last_bag, taken)) if *last_bag == bag_upper => taken.saturating_inc(),
+			_ => self.composition.push((bag_upper, 1)),
+		}
+	}
+
+	/// The upper threshold of each bag in which some ids were taken, from the first iterated to
+	/// the last, along with the number of ids taken in it.
+	pub(crate) fn take_composition(&mut self) -> Vec<(VoteWeight, u32)> {
+		sp_std::mem::take(&mut self.composition)
+	}
+
+	/// If the iteration stopped before the end of the list, the upper threshold of the bag it
+	/// stopped in, along with the number of ids of that bag that were iterated and that were not.
+	///
+	/// This reads one more id of the list, to tell whether there is any left.
+	pub(crate) fn truncation(mut self) -> Option<(VoteWeight, u32, u32)> {
+		let last_bag = self.current_bag?;
+		self.inner.next()?;
+		let skipped =
+			T::SortedListProvider::count_in_bag(last_bag).saturating_sub(self.seen_in_bag);
+		Some((last_bag, self.seen_in_bag, skipped))
+	}
+}
+
+impl<T: Config> Iterator for BagTracker<T> {
+	type Item = T::AccountId;
+
+	fn next(&mut self) -> Option<T::AccountId> {
+		let (id, bag_upper) = self.inner.next()?;
+		if self.current_bag != Some(bag_upper) {
+			self.current_bag = Some(bag_upper);
+			self.seen_in_bag = 0;
+		}
+		self.seen_in_bag.saturating_inc();
+		Some(id)
+	}
+}
+
 impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet<T> {
 	const MAXIMUM_VOTES_PER_VOTER: u32 = T::MAX_NOMINATIONS;
 
@@ -861,10 +1279,32 @@ impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet
 		Ok(voters)
 	}
 
+	fn voters_paged(
+		page_size: usize,
+		cursor: Option<Vec<u8>>,
+	) -> data_provider::Result<VoterPage<T::AccountId>> {
+		let cursor = cursor
+			.map(|cursor| VoterCursor::<T::AccountId>::decode(&mut &cursor[..]))
+			.transpose()
+			.map_err(|_| "invalid voter cursor")?;
+
+		let (voters, next) = Self::get_npos_voters_paged(page_size, cursor);
+		debug_assert!(voters.len() <= page_size);
+
+		Ok((voters, next.map(|next| next.encode())))
+	}
+
 	fn targets(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<T::AccountId>> {
-		let target_count = CounterForValidators::<T>::get();
+		debug_assert_eq!(
+			CounterForValidators::<T>::get(),
+			T::TargetList::count(),
+			"target_count must be accurate",
+		);
 
-		// We can't handle this case yet -- return an error.
+		// We can't handle this case yet -- return an error. The scores of the validators that are
+		// not elected are not kept up to date, so truncating the list could leave out the wrong
+		// ones.
+		let target_count = T::TargetList::count();
 		if maybe_max_len.map_or(false, |max_len| target_count > max_len as u32) {
 			return Err("Target snapshot too big")
 		}
@@ -922,7 +1362,8 @@ impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet
 				claimed_rewards: vec![],
 			},
 		);
-		Self::do_add_nominator(&voter, Nominations { targets, submitted_in: 0, suppressed: false });
+		Self::do_add_nominator(&voter, Nominations { targets, submitted_in: 0, suppressed: false })
+			.expect("the voters of benchmarks fit in the list");
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -942,7 +1383,20 @@ impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet
 		Self::do_add_validator(
 			&target,
 			ValidatorPrefs { commission: Perbill::zero(), blocked: false },
-		);
+		)
+		.expect("the targets of benchmarks fit in the list");
+	}
+
+	fn freeze_voters() {
+		T::SortedListProvider::lock(ListLock::Election);
+	}
+
+	fn unfreeze_voters() {
+		T::SortedListProvider::unlock(ListLock::Election);
+	}
+
+	fn voter_bag_thresholds() -> Vec<VoteWeight> {
+		T::SortedListProvider::bag_thresholds()
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -953,7 +1407,8 @@ impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet
 		<Nominators<T>>::remove_all(None);
 		<CounterForNominators<T>>::kill();
 		<CounterForValidators<T>>::kill();
-		let _ = T::SortedListProvider::clear(None);
+		let _ = T::SortedListProvider::unsafe_clear(None);
+		let _ = T::TargetList::unsafe_clear(None);
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -981,7 +1436,8 @@ impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet
 			Self::do_add_validator(
 				&v,
 				ValidatorPrefs { commission: Perbill::zero(), blocked: false },
-			);
+			)
+			.expect("the targets of benchmarks fit in the list");
 		});
 
 		voters.into_iter().for_each(|(v, s, t)| {
@@ -1002,7 +1458,8 @@ impl<T: Config> ElectionDataProvider<T::AccountId, BlockNumberFor<T>> for Pallet
 			Self::do_add_nominator(
 				&v,
 				Nominations { targets: t, submitted_in: 0, suppressed: false },
-			);
+			)
+			.expect("the voters of benchmarks fit in the list");
 		});
 	}
 }
@@ -1207,9 +1664,15 @@ where
 					{
 						let slash_cost = (6, 5);
 						let reward_cost = (2, 2);
+						// the validator is repositioned in the target list, the nominators are
+						// only looked up in it.
+						let target_list_cost = (8, 5);
 						add_db_reads_writes(
-							(1 + nominators_len) * slash_cost.0 + reward_cost.0 * reporters_len,
-							(1 + nominators_len) * slash_cost.1 + reward_cost.1 * reporters_len,
+							(1 + nominators_len) * slash_cost.0 +
+								target_list_cost.0 + nominators_len +
+								reward_cost.0 * reporters_len,
+							(1 + nominators_len) * slash_cost.1 +
+								target_list_cost.1 + reward_cost.1 * reporters_len,
 						);
 					}
 				} else {
@@ -1228,13 +1691,19 @@ where
 	}
 }
 
-impl<T: Config> VoteWeightProvider<T::AccountId> for Pallet<T> {
-	fn vote_weight(who: &T::AccountId) -> VoteWeight {
+impl<T: Config> ScoreProvider<T::AccountId> for Pallet<T> {
+	type Score = VoteWeight;
+
+	fn score(who: &T::AccountId) -> VoteWeight {
 		Self::weight_of(who)
 	}
 
+	fn score_fn() -> Box<dyn Fn(&T::AccountId) -> VoteWeight> {
+		Self::weight_of_fn()
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
-	fn set_vote_weight_of(who: &T::AccountId, weight: VoteWeight) {
+	fn set_score_of(who: &T::AccountId, weight: VoteWeight) {
 		// this will clearly results in an inconsistent state, but it should not matter for a
 		// benchmark.
 		use sp_std::convert::TryInto;
@@ -1260,44 +1729,188 @@ impl<T: Config> VoteWeightProvider<T::AccountId> for Pallet<T> {
 pub struct UseNominatorsMap<T>(sp_std::marker::PhantomData<T>);
 impl<T: Config> SortedListProvider<T::AccountId> for UseNominatorsMap<T> {
 	type Error = ();
+	type Score = VoteWeight;
 
 	/// Returns iterator over voter list, which can have `take` called on it.
 	fn iter() -> Box<dyn Iterator<Item = T::AccountId>> {
 		Box::new(Nominators::<T>::iter().map(|(n, _)| n))
 	}
+	fn iter_from(
+		start: &T::AccountId,
+	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
+		if Nominators::<T>::contains_key(start) {
+			let start_key = Nominators::<T>::hashed_key_for(start);
+			Ok(Box::new(Nominators::<T>::iter_from(start_key).map(|(n, _)| n)))
+		} else {
+			Err(())
+		}
+	}
+	fn iter_with_budget(
+		start: Option<&T::AccountId>,
+		budget: Weight,
+	) -> Result<BudgetedIter<T::AccountId>, Self::Error> {
+		let iter = match start {
+			Some(start) => Self::iter_from(start)?,
+			None => Self::iter(),
+		};
+		Ok(BudgetedIter::take(iter, start.cloned(), T::DbWeight::get().reads(1), budget))
+	}
 	fn count() -> u32 {
 		CounterForNominators::<T>::get()
 	}
 	fn contains(id: &T::AccountId) -> bool {
 		Nominators::<T>::contains_key(id)
 	}
+	fn get_score(id: &T::AccountId) -> Option<VoteWeight> {
+		Self::contains(id).then(|| Pallet::<T>::weight_of(id))
+	}
 	fn on_insert(_: T::AccountId, _weight: VoteWeight) -> Result<(), Self::Error> {
 		// nothing to do on insert.
 		Ok(())
 	}
-	fn on_update(_: &T::AccountId, _weight: VoteWeight) {
-		// nothing to do on update.
+	fn on_update(_: &T::AccountId, _weight: VoteWeight) -> bool {
+		// nothing to do on update, and the order of the map never changes.
+		false
 	}
 	fn on_remove(_: &T::AccountId) {
 		// nothing to do on remove.
 	}
-	fn regenerate(
+	fn unsafe_regenerate(
 		_: impl IntoIterator<Item = T::AccountId>,
 		_: Box<dyn Fn(&T::AccountId) -> VoteWeight>,
-	) -> u32 {
+	) -> (u32, Vec<T::AccountId>) {
 		// nothing to do upon regenerate.
-		0
+		(0, Vec::new())
 	}
 	fn sanity_check() -> Result<(), &'static str> {
 		Ok(())
 	}
-	fn clear(maybe_count: Option<u32>) -> u32 {
-		Nominators::<T>::remove_all(maybe_count);
-		if let Some(count) = maybe_count {
-			CounterForNominators::<T>::mutate(|noms| *noms - count);
-			count
+	fn unsafe_clear(maybe_count: Option<u32>) -> u32 {
+		match Nominators::<T>::remove_all(maybe_count) {
+			sp_io::KillStorageResult::AllRemoved(_) => CounterForNominators::<T>::take(),
+			sp_io::KillStorageResult::SomeRemaining(removed) => {
+				CounterForNominators::<T>::mutate(|noms| *noms = noms.saturating_sub(removed));
+				removed
+			},
+		}
+	}
+}
+
+/// A simple target list implementation that does not require any additional pallets. Note, this
+/// does not provide validators in sorted order. If you desire validators in a sorted order take a
+/// look at [`pallet-bags-list`], with [`TargetScoreProvider`] as its score provider.
+pub struct UseValidatorsMap<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> SortedListProvider<T::AccountId> for UseValidatorsMap<T> {
+	type Error = ();
+	type Score = VoteWeight;
+
+	/// Returns iterator over target list, which can have `take` called on it.
+	fn iter() -> Box<dyn Iterator<Item = T::AccountId>> {
+		Box::new(Validators::<T>::iter().map(|(v, _)| v))
+	}
+	fn iter_from(
+		start: &T::AccountId,
+	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
+		if Validators::<T>::contains_key(start) {
+			let start_key = Validators::<T>::hashed_key_for(start);
+			Ok(Box::new(Validators::<T>::iter_from(start_key).map(|(v, _)| v)))
 		} else {
-			CounterForNominators::<T>::take()
+			Err(())
 		}
 	}
+	fn iter_with_budget(
+		start: Option<&T::AccountId>,
+		budget: Weight,
+	) -> Result<BudgetedIter<T::AccountId>, Self::Error> {
+		let iter = match start {
+			Some(start) => Self::iter_from(start)?,
+			None => Self::iter(),
+		};
+		Ok(BudgetedIter::take(iter, start.cloned(), T::DbWeight::get().reads(1), budget))
+	}
+	fn count() -> u32 {
+		CounterForValidators::<T>::get()
+	}
+	fn contains(id: &T::AccountId) -> bool {
+		Validators::<T>::contains_key(id)
+	}
+	fn get_score(id: &T::AccountId) -> Option<VoteWeight> {
+		Self::contains(id).then(|| Pallet::<T>::target_weight_of(id))
+	}
+	fn on_insert(_: T::AccountId, _weight: VoteWeight) -> Result<(), Self::Error> {
+		// nothing to do on insert.
+		Ok(())
+	}
+	fn on_update(_: &T::AccountId, _weight: VoteWeight) -> bool {
+		// nothing to do on update, and the order of the map never changes.
+		false
+	}
+	fn on_remove(_: &T::AccountId) {
+		// nothing to do on remove.
+	}
+	fn unsafe_regenerate(
+		_: impl IntoIterator<Item = T::AccountId>,
+		_: Box<dyn Fn(&T::AccountId) -> VoteWeight>,
+	) -> (u32, Vec<T::AccountId>) {
+		// nothing to do upon regenerate.
+		(0, Vec::new())
+	}
+	fn sanity_check() -> Result<(), &'static str> {
+		Ok(())
+	}
+	fn unsafe_clear(maybe_count: Option<u32>) -> u32 {
+		match Validators::<T>::remove_all(maybe_count) {
+			sp_io::KillStorageResult::AllRemoved(_) => CounterForValidators::<T>::take(),
+			sp_io::KillStorageResult::SomeRemaining(removed) => {
+				CounterForValidators::<T>::mutate(|vals| *vals = vals.saturating_sub(removed));
+				removed
+			},
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn weight_update_worst_case(who: &T::AccountId, is_increase: bool) -> VoteWeight {
+		// the order of the map never changes, so any new weight is as good as any other.
+		let weight = Pallet::<T>::target_weight_of(who);
+		if is_increase {
+			weight.saturating_mul(2)
+		} else {
+			weight / 2
+		}
+	}
+}
+
+/// The score provider of [`Config::TargetList`], scoring validators by
+/// [`Pallet::target_weight_of`].
+pub struct TargetScoreProvider<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> ScoreProvider<T::AccountId> for TargetScoreProvider<T> {
+	type Score = VoteWeight;
+
+	fn score(who: &T::AccountId) -> VoteWeight {
+		Pallet::<T>::target_weight_of(who)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn set_score_of(who: &T::AccountId, weight: VoteWeight) {
+		// without an exposure in the current era, the target weight is the validator's own stake.
+		<Pallet<T> as ScoreProvider<T::AccountId>>::set_score_of(who, weight)
+	}
+}
+
+/// The accounts that belong in [`Config::SortedListProvider`]: the nominators.
+///
+/// Validators are only kept in [`Config::TargetList`], see [`IsTarget`].
+pub struct IsVoter<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> Contains<T::AccountId> for IsVoter<T> {
+	fn contains(who: &T::AccountId) -> bool {
+		Nominators::<T>::contains_key(who)
+	}
+}
+
+/// The accounts that belong in [`Config::TargetList`]: the validators.
+pub struct IsTarget<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> Contains<T::AccountId> for IsTarget<T> {
+	fn contains(who: &T::AccountId) -> bool {
+		Validators::<T>::contains_key(who)
+	}
 }
@@ -17,7 +17,7 @@
 
 //! Staking FRAME Pallet.
 
-use frame_election_provider_support::SortedListProvider;
+use frame_election_provider_support::{ListLock, SortedListProvider, VoteWeight};
 use frame_support::{
 	pallet_prelude::*,
 	traits::{
@@ -28,7 +28,7 @@ use frame_support::{
 };
 use frame_system::{ensure_root, ensure_signed, offchain::SendTransactionTypes, pallet_prelude::*};
 use sp_runtime::{
-	traits::{CheckedSub, SaturatedConversion, StaticLookup, Zero},
+	traits::{CheckedSub, SaturatedConversion, Saturating, StaticLookup, Zero},
 	DispatchError, Perbill, Percent,
 };
 use sp_staking::SessionIndex;
@@ -42,7 +42,7 @@ use crate::{
 	log, migrations, slashing, weights::WeightInfo, ActiveEraInfo, BalanceOf, EraIndex, EraPayout,
 	EraRewardPoints, Exposure, Forcing, NegativeImbalanceOf, Nominations, PositiveImbalanceOf,
 	Releases, RewardDestination, SessionInterface, StakingLedger, UnappliedSlash, UnlockChunk,
-	ValidatorPrefs,
+	ValidatorPrefs, VoterListRebuild,
 };
 
 pub const MAX_UNLOCKING_CHUNKS: usize = 32;
@@ -144,7 +144,23 @@ pub mod pallet {
 		/// Something that can provide a sorted list of voters in a somewhat sorted way. The
 		/// original use case for this was designed with [`pallet_bags_list::Pallet`] in mind. If
 		/// the bags-list is not desired, [`impls::UseNominatorsMap`] is likely the desired option.
-		type SortedListProvider: SortedListProvider<Self::AccountId>;
+		type SortedListProvider: SortedListProvider<Self::AccountId, Score = VoteWeight>;
+
+		/// Something that can provide a sorted list of targets (validators) in a somewhat sorted
+		/// way, ordered by [`Pallet::target_weight_of`]. Like the voters, this is designed with
+		/// (another instance of) [`pallet_bags_list::Pallet`] in mind, using
+		/// [`impls::TargetScoreProvider`] as its score provider. If the bags-list is not desired,
+		/// [`impls::UseValidatorsMap`] is likely the desired option.
+		type TargetList: SortedListProvider<Self::AccountId, Score = VoteWeight>;
+
+		/// Whether a new nominator can take the place of the lowest one once
+		/// [`MaxNominatorsCount`] is reached, instead of being rejected.
+		///
+		/// If so, the lowest id of [`Config::SortedListProvider`] is chilled to make room, as long
+		/// as it is a nominator with a lower weight than the new one. This needs a
+		/// `SortedListProvider` that can tell its lowest id, such as [`pallet_bags_list`].
+		#[pallet::constant]
+		type AutoChillNominators: Get<bool>;
 
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
@@ -450,6 +466,11 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type ChillThreshold<T: Config> = StorageValue<_, Percent, OptionQuery>;
 
+	/// The progress of the ongoing [`Call::force_rebuild_voter_list`], if any.
+	#[pallet::storage]
+	pub(crate) type VoterListRebuilding<T: Config> =
+		StorageValue<_, VoterListRebuild<T::AccountId>, OptionQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub history_depth: u32,
@@ -497,6 +518,9 @@ pub mod pallet {
 			MinNominatorBond::<T>::put(self.min_nominator_bond);
 			MinValidatorBond::<T>::put(self.min_validator_bond);
 
+			// nominators are added once all stakers are bonded, to insert them into the
+			// `SortedListProvider` in a single batch.
+			let mut nominators = Vec::with_capacity(self.stakers.len());
 			for &(ref stash, ref controller, balance, ref status) in &self.stakers {
 				log!(
 					trace,
@@ -520,13 +544,29 @@ pub mod pallet {
 						T::Origin::from(Some(controller.clone()).into()),
 						Default::default(),
 					),
-					crate::StakerStatus::Nominator(votes) => <Pallet<T>>::nominate(
-						T::Origin::from(Some(controller.clone()).into()),
-						votes.iter().map(|l| T::Lookup::unlookup(l.clone())).collect(),
-					),
+					crate::StakerStatus::Nominator(votes) => {
+						assert!(
+							balance >= MinNominatorBond::<T>::get(),
+							"Nominator does not bond enough to nominate."
+						);
+						assert!(
+							!votes.is_empty() && votes.len() <= T::MAX_NOMINATIONS as usize,
+							"Nominator has too few or too many targets."
+						);
+						nominators.push((
+							stash.clone(),
+							Nominations {
+								targets: votes.clone(),
+								submitted_in: 0,
+								suppressed: false,
+							},
+						));
+						Ok(())
+					},
 					_ => Ok(()),
 				});
 			}
+			<Pallet<T>>::do_add_genesis_nominators(nominators);
 
 			// all voters are reported to the `SortedListProvider`.
 			assert_eq!(
@@ -534,6 +574,11 @@ pub mod pallet {
 				CounterForNominators::<T>::get(),
 				"not all genesis stakers were inserted into sorted list provider, something is wrong."
 			);
+			assert_eq!(
+				T::TargetList::count(),
+				CounterForValidators::<T>::get(),
+				"not all genesis validators were inserted into the target list, something is wrong."
+			);
 		}
 	}
 
@@ -573,6 +618,25 @@ pub mod pallet {
 		Chilled(T::AccountId),
 		/// The stakers' rewards are getting paid. \[era_index, validator_stash\]
 		PayoutStarted(EraIndex, T::AccountId),
+		/// A nominator has been chilled to make room for a new nominator with a higher weight, as
+		/// `MaxNominatorsCount` was reached. \[chilled, stash\]
+		AutoChilled(T::AccountId, T::AccountId),
+		/// The nominators of an election snapshot were capped within the bag with the upper
+		/// threshold `last_bag`: `taken` of its nominators were iterated, and `skipped` were left
+		/// out, along with all the lower bags. \[last_bag, taken, skipped\]
+		SnapshotTruncated(VoteWeight, u32, u32),
+		/// The nominators of an election snapshot were taken from these bags, given by their upper
+		/// threshold from the highest to the lowest, along with the number of nominators taken
+		/// from each. \[composition\]
+		SnapshotComposition(Vec<(VoteWeight, u32)>),
+		/// Some ids were removed from the voter list by a rebuild, and more calls are needed to
+		/// remove the others. \[removed\]
+		VoterListCleared(u32),
+		/// Some nominators were inserted into the voter list by a rebuild, and more calls are
+		/// needed to insert the others. \[inserted\]
+		VoterListRefilled(u32),
+		/// The voter list was rebuilt from all the nominators. \[count\]
+		VoterListRebuilt(u32),
 	}
 
 	#[pallet::error]
@@ -625,6 +689,9 @@ pub mod pallet {
 		/// There are too many validators in the system. Governance needs to adjust the staking
 		/// settings to keep things safe for the runtime.
 		TooManyValidators,
+		/// A nominator or validator could not be inserted into its sorted list, e.g. because the
+		/// bag it belongs in is full.
+		ListInsertFailed,
 	}
 
 	#[pallet::hooks]
@@ -786,10 +853,8 @@ pub mod pallet {
 				// NOTE: ledger must be updated prior to calling `Self::weight_of`.
 				Self::update_ledger(&controller, &ledger);
 				// update this staker in the sorted list, if they exist in it.
-				if T::SortedListProvider::contains(&stash) {
-					T::SortedListProvider::on_update(&stash, Self::weight_of(&ledger.stash));
-					debug_assert_eq!(T::SortedListProvider::sanity_check(), Ok(()));
-				}
+				Self::update_voter_weight(&stash);
+				Self::update_target_weight(&stash);
 
 				Self::deposit_event(Event::<T>::Bonded(stash.clone(), extra));
 			}
@@ -854,9 +919,8 @@ pub mod pallet {
 				Self::update_ledger(&controller, &ledger);
 
 				// update this staker in the sorted list, if they exist in it.
-				if T::SortedListProvider::contains(&ledger.stash) {
-					T::SortedListProvider::on_update(&ledger.stash, Self::weight_of(&ledger.stash));
-				}
+				Self::update_voter_weight(&ledger.stash);
+				Self::update_target_weight(&ledger.stash);
 
 				Self::deposit_event(Event::<T>::Unbonded(ledger.stash, value));
 			}
@@ -946,8 +1010,9 @@ pub mod pallet {
 				}
 			}
 
+			// added first, such that nothing changes if it fails.
+			Self::do_add_validator(stash, prefs)?;
 			Self::do_remove_nominator(stash);
-			Self::do_add_validator(stash, prefs);
 			Ok(())
 		}
 
@@ -961,8 +1026,11 @@ pub mod pallet {
 		/// - The transaction's complexity is proportional to the size of `targets` (N)
 		/// which is capped at CompactAssignments::LIMIT (MAX_NOMINATIONS).
 		/// - Both the reads and writes follow a similar pattern.
+		/// - If [`Config::AutoChillNominators`] is set, this may also chill another nominator.
 		/// # </weight>
-		#[pallet::weight(T::WeightInfo::nominate(targets.len() as u32))]
+		#[pallet::weight(T::WeightInfo::nominate(targets.len() as u32).saturating_add(
+			if T::AutoChillNominators::get() { T::WeightInfo::chill() } else { 0 }
+		))]
 		pub fn nominate(
 			origin: OriginFor<T>,
 			targets: Vec<<T::Lookup as StaticLookup>::Source>,
@@ -974,15 +1042,18 @@ pub mod pallet {
 			let stash = &ledger.stash;
 
 			// Only check limits if they are not already a nominator.
+			let mut auto_chilled = None;
 			if !Nominators::<T>::contains_key(stash) {
 				// If this error is reached, we need to adjust the `MinNominatorBond` and start
 				// calling `chill_other`. Until then, we explicitly block new nominators to protect
-				// the runtime.
+				// the runtime, unless the lowest nominator can be chilled to make room.
 				if let Some(max_nominators) = MaxNominatorsCount::<T>::get() {
-					ensure!(
-						CounterForNominators::<T>::get() < max_nominators,
-						Error::<T>::TooManyNominators
-					);
+					if CounterForNominators::<T>::get() >= max_nominators {
+						auto_chilled = Some(
+							Self::nominator_to_auto_chill(stash)
+								.ok_or(Error::<T>::TooManyNominators)?,
+						);
+					}
 				}
 			}
 
@@ -1012,8 +1083,13 @@ pub mod pallet {
 				suppressed: false,
 			};
 
+			// added first, such that nothing changes if it fails.
+			Self::do_add_nominator(stash, nominations)?;
 			Self::do_remove_validator(stash);
-			Self::do_add_nominator(stash, nominations);
+			if let Some(chilled) = auto_chilled {
+				Self::chill_stash(&chilled);
+				Self::deposit_event(Event::<T>::AutoChilled(chilled, stash.clone()));
+			}
 			Ok(())
 		}
 
@@ -1356,9 +1432,8 @@ pub mod pallet {
 
 			// NOTE: ledger must be updated prior to calling `Self::weight_of`.
 			Self::update_ledger(&controller, &ledger);
-			if T::SortedListProvider::contains(&ledger.stash) {
-				T::SortedListProvider::on_update(&ledger.stash, Self::weight_of(&ledger.stash));
-			}
+			Self::update_voter_weight(&ledger.stash);
+			Self::update_target_weight(&ledger.stash);
 
 			let removed_chunks = 1u32 // for the case where the last iterated chunk is not removed
 				.saturating_add(initial_unlocking)
@@ -1578,6 +1653,99 @@ pub mod pallet {
 			Self::chill_stash(&stash);
 			Ok(())
 		}
+
+		/// Rebuild [`Config::SortedListProvider`] from scratch, out of all the nominators.
+		///
+		/// This is a break-glass tool for when the invariants of the list are irreparably broken.
+		/// Up to `limit` ids, along with the auxiliary data of the list, are first removed per
+		/// call; once the list is empty, up to `limit` nominators of [`Nominators`] are inserted
+		/// back per call, resuming where the previous call stopped. The list is locked against
+		/// reordering from the first call until the last one, but it is only partially filled in
+		/// the meantime, so the calls should follow each other closely.
+		///
+		/// Origin must be Root to call this function.
+		///
+		/// A nominator that cannot be inserted back, e.g. because there is no room for it, is
+		/// chilled, as in [`Pallet::do_add_nominator`].
+		///
+		/// Emits `VoterListCleared` or `VoterListRefilled` while more calls are needed, then
+		/// `VoterListRebuilt`. Emits `Chilled` for each nominator that is chilled.
+		///
+		/// # <weight>
+		/// - Each of the `limit` ids removed costs less than each of the `limit` ids inserted,
+		///   which is what the benchmark measures.
+		/// # </weight>
+		#[pallet::weight((
+			T::WeightInfo::force_rebuild_voter_list(*limit),
+			DispatchClass::Operational,
+		))]
+		pub fn force_rebuild_voter_list(origin: OriginFor<T>, limit: u32) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let progress = VoterListRebuilding::<T>::get().unwrap_or_else(|| {
+				// no nominator may move within the list until it is rebuilt.
+				T::SortedListProvider::lock(ListLock::Rebuild);
+				VoterListRebuild::Clearing
+			});
+
+			let mut budget = limit;
+			let after = match progress {
+				VoterListRebuild::Clearing => {
+					let removed = T::SortedListProvider::unsafe_clear(Some(budget));
+					if !T::SortedListProvider::count().is_zero() {
+						VoterListRebuilding::<T>::put(VoterListRebuild::Clearing);
+						Self::deposit_event(Event::<T>::VoterListCleared(removed));
+						return Ok(())
+					}
+					budget.saturating_reduce(removed);
+					None
+				},
+				VoterListRebuild::Refilling(after) => after,
+			};
+
+			let nominators = match &after {
+				Some(last) =>
+					Nominators::<T>::iter_keys_from(Nominators::<T>::hashed_key_for(last)),
+				None => Nominators::<T>::iter_keys(),
+			};
+			let mut inserted = 0u32;
+			let mut last = after;
+			for who in nominators.take(budget as usize) {
+				// nominators that joined since the rebuild started are already in the list.
+				if !T::SortedListProvider::contains(&who) {
+					if let Err(err) =
+						T::SortedListProvider::on_insert(who.clone(), Self::weight_of(&who))
+					{
+						log!(
+							warn,
+							"chilling {:?}, which does not fit into the list: {:?}",
+							who,
+							err
+						);
+						// not through `chill_stash`, the list is not complete yet.
+						Nominators::<T>::remove(&who);
+						CounterForNominators::<T>::mutate(|x| x.saturating_dec());
+						Self::deposit_event(Event::<T>::Chilled(who.clone()));
+					}
+				}
+				inserted.saturating_inc();
+				last = Some(who);
+			}
+
+			if inserted == budget {
+				VoterListRebuilding::<T>::put(VoterListRebuild::Refilling(last));
+				Self::deposit_event(Event::<T>::VoterListRefilled(inserted));
+				return Ok(())
+			}
+
+			VoterListRebuilding::<T>::kill();
+			T::SortedListProvider::unlock(ListLock::Rebuild);
+			let count = T::SortedListProvider::count();
+			debug_assert_eq!(count, CounterForNominators::<T>::get());
+			debug_assert_eq!(T::SortedListProvider::sanity_check(), Ok(()));
+			Self::deposit_event(Event::<T>::VoterListRebuilt(count));
+			Ok(())
+		}
 	}
 }
 
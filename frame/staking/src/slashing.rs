@@ -583,6 +583,9 @@ pub fn do_slash<T: Config>(
 		}
 
 		<Pallet<T>>::update_ledger(&controller, &ledger);
+		// reposition the slashed staker in the sorted lists, if they exist in them.
+		<Pallet<T>>::update_voter_weight(stash);
+		<Pallet<T>>::update_target_weight(stash);
 
 		// trigger the event
 		<Pallet<T>>::deposit_event(super::Event::<T>::Slashed(stash.clone(), value));
@@ -38,11 +38,12 @@ const SEED: u32 = 0;
 pub fn clear_validators_and_nominators<T: Config>() {
 	Validators::<T>::remove_all(None);
 	CounterForValidators::<T>::kill();
+	let _ = T::TargetList::unsafe_clear(None);
 
 	// whenever we touch nominators counter we should update `T::SortedListProvider` as well.
 	Nominators::<T>::remove_all(None);
 	CounterForNominators::<T>::kill();
-	let _ = T::SortedListProvider::clear(None);
+	let _ = T::SortedListProvider::unsafe_clear(None);
 }
 
 /// Grab a funded user.
@@ -18,7 +18,9 @@
 //! Tests for the module.
 
 use super::{Event, *};
-use frame_election_provider_support::{ElectionProvider, SortedListProvider, Support};
+use frame_election_provider_support::{
+	ElectionDataProvider, ElectionProvider, SortedListProvider, Support,
+};
 use frame_support::{
 	assert_noop, assert_ok,
 	dispatch::WithPostDispatchInfo,
@@ -3563,7 +3565,9 @@ fn offences_weight_calculated_correctly() {
 			// `slash_cost` * nominators (1)
 			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(6, 5)
 			// `reward_cost` * reporters (1)
-			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(2, 2);
+			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(2, 2)
+			// `target_list_cost`, and one target list lookup * nominators (1)
+			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(8 + 1, 5);
 
 		assert_eq!(Staking::on_offence(&one_offender, &[Perbill::from_percent(50)], 0), one_offence_unapplied_weight);
 	});
@@ -3896,6 +3900,32 @@ mod election_data_provider {
 		})
 	}
 
+	#[test]
+	fn electing_preview_is_the_head_of_the_snapshot() {
+		ExtBuilder::default()
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.build_and_execute(|| {
+				let snapshot = Staking::voters(None).unwrap();
+				assert_eq!(snapshot.len(), 5);
+
+				let events = System::events().len();
+				let block_weight = System::block_weight().total();
+				for n in [0, 3, 4, 5, 10] {
+					assert_eq!(
+						Staking::electing_preview(n),
+						snapshot
+							.iter()
+							.take(n as usize)
+							.map(|(who, weight, _)| (*who, *weight))
+							.collect::<Vec<_>>()
+					);
+				}
+				// unlike the snapshot, the preview neither deposits events nor registers weight.
+				assert_eq!(System::events().len(), events);
+				assert_eq!(System::block_weight().total(), block_weight);
+			});
+	}
+
 	#[test]
 	fn voters_exclude_slashed() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -4015,6 +4045,86 @@ mod election_data_provider {
 	// valid nominators until we reach max len voters; which is opposed to simply stopping after we
 	// have iterated max len voters, but not adding all of them to voters due to some nominators not
 	// having valid targets.
+	#[test]
+	fn capped_voters_report_truncation() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 600, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+			.build_and_execute(|| {
+				// given 101 and 61 in bag 1000, and 71 in bag 10.
+				assert_eq!(
+					<Test as Config>::SortedListProvider::iter().collect::<Vec<_>>(),
+					vec![101, 61, 71]
+				);
+				let truncations = || {
+					staking_events()
+						.into_iter()
+						.filter(|e| matches!(e, Event::SnapshotTruncated(..)))
+						.collect::<Vec<_>>()
+				};
+
+				// when the cut is within bag 1000
+				assert_eq!(Staking::voters(Some(4)).unwrap().len(), 4);
+				// then one of its nominators is skipped.
+				assert_eq!(truncations(), vec![Event::SnapshotTruncated(1_000, 1, 1)]);
+
+				// when the cut is right at the end of bag 1000
+				System::reset_events();
+				assert_eq!(Staking::voters(Some(5)).unwrap().len(), 5);
+				// then none of its nominators is skipped, only the lower bag.
+				assert_eq!(truncations(), vec![Event::SnapshotTruncated(1_000, 2, 0)]);
+
+				// when all the voters are taken
+				System::reset_events();
+				assert_eq!(Staking::voters(Some(6)).unwrap().len(), 6);
+				// then nothing is truncated.
+				assert!(truncations().is_empty());
+			});
+	}
+
+	#[test]
+	fn snapshot_reports_composition() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 600, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+			.add_staker(81, 80, 10, StakerStatus::<AccountId>::Nominator(vec![11]))
+			.build_and_execute(|| {
+				// given 101 and 61 in bag 1000, and 71 and 81 in bag 10.
+				assert_eq!(
+					<Test as Config>::SortedListProvider::iter().collect::<Vec<_>>(),
+					vec![101, 61, 71, 81]
+				);
+				let compositions = || {
+					staking_events()
+						.into_iter()
+						.filter_map(|e| match e {
+							Event::SnapshotComposition(composition) => Some(composition),
+							_ => None,
+						})
+						.collect::<Vec<_>>()
+				};
+
+				// when all the voters are taken
+				assert_eq!(Staking::voters(None).unwrap().len(), 7);
+				// then every bag is reported.
+				assert_eq!(compositions(), vec![vec![(1_000, 2), (10, 2)]]);
+
+				// when the cut is within the lowest bag
+				System::reset_events();
+				assert_eq!(Staking::voters(Some(6)).unwrap().len(), 6);
+				// then only the nominators taken from it are counted.
+				assert_eq!(compositions(), vec![vec![(1_000, 2), (10, 1)]]);
+
+				// when no nominator is taken
+				System::reset_events();
+				assert_eq!(Staking::voters(Some(3)).unwrap().len(), 3);
+				// then nothing is reported.
+				assert!(compositions().is_empty());
+			});
+	}
+
 	#[test]
 	fn get_max_len_voters_even_if_some_nominators_are_slashed() {
 		ExtBuilder::default()
@@ -4073,6 +4183,163 @@ mod election_data_provider {
 			});
 	}
 
+	/// Collect all the pages of `get_npos_voters_paged` of the given size, in order.
+	fn all_voter_pages(page_size: usize) -> Vec<Vec<VotingDataOf<Test>>> {
+		let mut pages = vec![];
+		let mut cursor = None;
+		loop {
+			let (page, next) = Staking::get_npos_voters_paged(page_size, cursor);
+			assert!(page.len() <= page_size);
+			pages.push(page);
+			match next {
+				Some(next) => cursor = Some(next),
+				None => break pages,
+			}
+		}
+	}
+
+	#[test]
+	fn paged_voters_are_provided_to_the_election() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+			.build_and_execute(|| {
+				let all_voters = Staking::voters(None).unwrap();
+
+				let mut paged = vec![];
+				let mut cursor = None;
+				loop {
+					let (page, next) = Staking::voters_paged(4, cursor).unwrap();
+					assert!(page.len() <= 4);
+					paged.extend(page);
+					match next {
+						Some(next) => cursor = Some(next),
+						None => break,
+					}
+				}
+				assert_eq!(paged, all_voters);
+
+				// a cursor that is not one of staking is rejected.
+				assert_eq!(Staking::voters_paged(4, Some(vec![42])), Err("invalid voter cursor"));
+			});
+	}
+
+	#[test]
+	fn paged_voters_are_the_same_as_all_voters() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+			.build_and_execute(|| {
+				let all_voters = Staking::get_npos_voters(None);
+				assert_eq!(all_voters.len(), 6);
+
+				for page_size in 1..=7 {
+					let pages = all_voter_pages(page_size);
+					assert_eq!(
+						pages.len(),
+						(6 + page_size - 1) / page_size + (6 % page_size == 0) as usize
+					);
+					assert_eq!(pages.into_iter().flatten().collect::<Vec<_>>(), all_voters);
+				}
+			});
+	}
+
+	#[test]
+	fn paged_voters_moves_from_validators_to_nominators() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			let validators = <Validators<Test>>::iter_keys().collect::<Vec<_>>();
+			assert_eq!(validators.len(), 3);
+
+			// the first page is all validators.
+			let (page, cursor) = Staking::get_npos_voters_paged(3, None);
+			assert_eq!(page.iter().map(|(who, _, _)| *who).collect::<Vec<_>>(), validators);
+			assert_eq!(cursor, Some(VoterCursor::Validator(validators[2])));
+
+			// the second page is the only nominator, and is the last one.
+			let (page, cursor) = Staking::get_npos_voters_paged(3, cursor);
+			assert_eq!(page, vec![(101, 500, vec![11, 21])]);
+			assert_eq!(cursor, None);
+		});
+	}
+
+	#[test]
+	fn paged_voters_exclude_slashed() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.build_and_execute(|| {
+				run_to_block(25);
+				// 61 only nominates 21, so it is skipped once 21 is slashed.
+				add_slash(&21);
+
+				let pages = all_voter_pages(4);
+				assert_eq!(
+					pages
+						.into_iter()
+						.flatten()
+						.map(|(who, _, targets)| (who, targets))
+						.filter(|(who, _)| *who == 61 || *who == 101)
+						.collect::<Vec<_>>(),
+					vec![(101, vec![11])]
+				);
+			});
+	}
+
+	#[test]
+	fn paged_voters_resume_if_cursor_is_removed() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.build_and_execute(|| {
+				Staking::freeze_voters();
+				let (page, cursor) = Staking::get_npos_voters_paged(4, None);
+				assert_eq!(page.len(), 4);
+				assert_eq!(cursor, Some(VoterCursor::Nominator(61)));
+
+				// 61 stops nominating in between two pages.
+				assert_ok!(Staking::chill(Origin::signed(60)));
+				assert!(!<Test as Config>::SortedListProvider::contains(&61));
+
+				// the next page still follows 61.
+				let (page, cursor) = Staking::get_npos_voters_paged(4, cursor);
+				assert_eq!(page.iter().map(|(who, _, _)| *who).collect::<Vec<_>>(), vec![101]);
+				assert_eq!(cursor, None);
+
+				Staking::unfreeze_voters();
+				assert_eq!(BagsList::bag_for(&61), None);
+			});
+	}
+
+	#[test]
+	fn paged_voters_leave_locking_to_the_caller() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.build_and_execute(|| {
+				// taking a page does not lock the list by itself, so an abandoned paged snapshot
+				// leaves nothing behind.
+				let (_, cursor) = Staking::get_npos_voters_paged(4, None);
+				assert!(cursor.is_some());
+				assert!(!BagsList::is_locked());
+
+				// the caller freezes the voters for as long as it takes pages.
+				Staking::freeze_voters();
+				// 101 belongs in a higher bag, but is not moved until the snapshot is done.
+				assert_ok!(Staking::bond_extra(Origin::signed(101), 1_000));
+				assert_eq!(BagsList::bag_for(&101), Some((1_000, 2_000)));
+
+				let (_, cursor) = Staking::get_npos_voters_paged(4, cursor);
+				assert_eq!(cursor, None);
+				Staking::unfreeze_voters();
+				assert!(!BagsList::is_locked());
+
+				assert_ok!(BagsList::rebag(Origin::signed(1), 101));
+				assert_eq!(BagsList::bag_for(&101), Some((2_000, 2_000)));
+			});
+	}
+
 	#[test]
 	fn estimate_next_election_works() {
 		ExtBuilder::default().session_per_era(5).period(5).build_and_execute(|| {
@@ -4085,7 +4352,7 @@ mod election_data_provider {
 			// election
 			run_to_block(20);
 			assert_eq!(Staking::next_election_prediction(System::block_number()), 45);
-			assert_eq!(staking_events().len(), 1);
+			assert_eq!(staking_events().len(), 2);
 			assert_eq!(*staking_events().last().unwrap(), Event::StakersElected);
 
 			for b in 21..45 {
@@ -4096,7 +4363,7 @@ mod election_data_provider {
 			// election
 			run_to_block(45);
 			assert_eq!(Staking::next_election_prediction(System::block_number()), 70);
-			assert_eq!(staking_events().len(), 3);
+			assert_eq!(staking_events().len(), 5);
 			assert_eq!(*staking_events().last().unwrap(), Event::StakersElected);
 
 			Staking::force_no_eras(Origin::root()).unwrap();
@@ -4119,7 +4386,7 @@ mod election_data_provider {
 			MinimumValidatorCount::<Test>::put(2);
 			run_to_block(55);
 			assert_eq!(Staking::next_election_prediction(System::block_number()), 55 + 25);
-			assert_eq!(staking_events().len(), 6);
+			assert_eq!(staking_events().len(), 10);
 			assert_eq!(*staking_events().last().unwrap(), Event::StakersElected);
 			// The new era has been planned, forcing is changed from `ForceNew` to `NotForcing`.
 			assert_eq!(ForceEra::<Test>::get(), Forcing::NotForcing);
@@ -4418,6 +4685,188 @@ fn capped_stakers_works() {
 	})
 }
 
+#[test]
+fn force_rebuild_voter_list_works() {
+	ExtBuilder::default()
+		.nominate(true)
+		.add_staker(61, 60, 20, StakerStatus::<AccountId>::Nominator(vec![21]))
+		.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+		.build_and_execute(|| {
+			// given
+			let voters = <Test as Config>::SortedListProvider::iter().collect::<Vec<_>>();
+			assert_eq!(voters, vec![101, 61, 71]);
+			assert_noop!(Staking::force_rebuild_voter_list(Origin::signed(10), 10), BadOrigin);
+
+			// when clearing one id at a time
+			assert_ok!(Staking::force_rebuild_voter_list(Origin::root(), 1));
+
+			// then more calls are needed.
+			assert_eq!(<Test as Config>::SortedListProvider::count(), 2);
+			assert_eq!(*staking_events().last().unwrap(), Event::VoterListCleared(1));
+
+			// when the rest is cleared
+			assert_ok!(Staking::force_rebuild_voter_list(Origin::root(), 10));
+
+			// then the list is regenerated from the nominators.
+			assert_eq!(*staking_events().last().unwrap(), Event::VoterListRebuilt(3));
+			assert_eq!(<Test as Config>::SortedListProvider::iter().collect::<Vec<_>>(), voters);
+			assert_eq!(<Test as Config>::SortedListProvider::sanity_check(), Ok(()));
+		})
+}
+
+#[test]
+fn force_rebuild_voter_list_refills_in_chunks_while_locked() {
+	ExtBuilder::default()
+		.nominate(true)
+		.add_staker(61, 60, 20, StakerStatus::<AccountId>::Nominator(vec![21]))
+		.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+		.build_and_execute(|| {
+			// given
+			let voters = <Test as Config>::SortedListProvider::iter().collect::<Vec<_>>();
+			assert_eq!(voters, vec![101, 61, 71]);
+
+			// when the list is cleared, and one nominator is inserted back
+			assert_ok!(Staking::force_rebuild_voter_list(Origin::root(), 4));
+
+			// then more calls are needed, and the list is locked in the meantime.
+			assert_eq!(*staking_events().last().unwrap(), Event::VoterListRefilled(1));
+			assert_eq!(<Test as Config>::SortedListProvider::count(), 1);
+			assert!(BagsList::is_locked());
+
+			// when a nominator joins in the meantime
+			bond_nominator(81, 80, 500, vec![11]);
+			// and the voters are frozen for an election
+			Staking::freeze_voters();
+
+			// when the rest is inserted
+			assert_ok!(Staking::force_rebuild_voter_list(Origin::root(), 1));
+			assert_eq!(*staking_events().last().unwrap(), Event::VoterListRefilled(1));
+			assert_ok!(Staking::force_rebuild_voter_list(Origin::root(), 10));
+
+			// then every nominator is in the list exactly once.
+			assert_eq!(*staking_events().last().unwrap(), Event::VoterListRebuilt(4));
+			assert_eq!(<Test as Config>::SortedListProvider::sanity_check(), Ok(()));
+			let mut rebuilt = <Test as Config>::SortedListProvider::iter().collect::<Vec<_>>();
+			rebuilt.sort();
+			assert_eq!(rebuilt, vec![61, 71, 81, 101]);
+
+			// and the rebuild does not lift the lock of the election.
+			assert!(BagsList::is_locked());
+			Staking::unfreeze_voters();
+			assert!(!BagsList::is_locked());
+		})
+}
+
+#[test]
+fn force_rebuild_voter_list_chills_the_nominators_without_room() {
+	ExtBuilder::default()
+		.nominate(true)
+		.add_staker(61, 60, 10, StakerStatus::<AccountId>::Nominator(vec![21]))
+		.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+		.build_and_execute(|| {
+			// given bag 10, the lowest, can only hold one of 61 and 71 once rebuilt
+			MaxBagSize::set(1);
+
+			// when
+			assert_ok!(Staking::force_rebuild_voter_list(Origin::root(), 10));
+
+			// then the one that does not fit is chilled.
+			let chilled = staking_events()
+				.into_iter()
+				.filter_map(|e| if let Event::Chilled(who) = e { Some(who) } else { None })
+				.collect::<Vec<_>>();
+			assert_eq!(chilled.len(), 1);
+			assert!(chilled[0] == 61 || chilled[0] == 71);
+			assert!(!Nominators::<Test>::contains_key(chilled[0]));
+			assert_eq!(*staking_events().last().unwrap(), Event::VoterListRebuilt(2));
+			assert_eq!(CounterForNominators::<Test>::get(), 2);
+			assert_eq!(<Test as Config>::SortedListProvider::sanity_check(), Ok(()));
+		})
+}
+
+#[test]
+fn nominate_fails_if_the_list_has_no_room() {
+	ExtBuilder::default().nominate(true).build_and_execute(|| {
+		// given bag 10, the lowest, can only hold one id
+		MaxBagSize::set(1);
+		let (first, first_controller) =
+			testing_utils::create_stash_controller_with_balance::<Test>(
+				1,
+				5,
+				RewardDestination::Controller,
+			)
+			.unwrap();
+		let (second, second_controller) =
+			testing_utils::create_stash_controller_with_balance::<Test>(
+				2,
+				5,
+				RewardDestination::Controller,
+			)
+			.unwrap();
+		assert_ok!(Staking::nominate(Origin::signed(first_controller), vec![11]));
+		assert!(<Test as Config>::SortedListProvider::contains(&first));
+
+		// then the second nominator of bag 10 cannot be inserted, and is not a nominator either.
+		assert_noop!(
+			Staking::nominate(Origin::signed(second_controller), vec![11]),
+			Error::<Test>::ListInsertFailed
+		);
+		assert!(!Nominators::<Test>::contains_key(second));
+		assert_eq!(
+			CounterForNominators::<Test>::get(),
+			<Test as Config>::SortedListProvider::count()
+		);
+	})
+}
+
+#[test]
+fn auto_chill_makes_room_for_heavier_nominators() {
+	ExtBuilder::default().nominate(true).build_and_execute(|| {
+		// only 101, with a weight of 500, is a nominator.
+		assert_eq!(CounterForNominators::<Test>::get(), 1);
+		assert_ok!(Staking::set_staking_limits(Origin::root(), 10, 10, Some(1), None, None));
+
+		let (_, light_controller) = testing_utils::create_stash_controller_with_balance::<Test>(
+			1,
+			100,
+			RewardDestination::Controller,
+		)
+		.unwrap();
+		let (heavy, heavy_controller) =
+			testing_utils::create_stash_controller_with_balance::<Test>(
+				2,
+				1000,
+				RewardDestination::Controller,
+			)
+			.unwrap();
+
+		// not opted in, so even a heavier nominator is rejected.
+		assert_noop!(
+			Staking::nominate(Origin::signed(heavy_controller), vec![11]),
+			Error::<Test>::TooManyNominators
+		);
+
+		AutoChillNominators::set(true);
+
+		// a lighter nominator than the lowest one is still rejected.
+		assert_noop!(
+			Staking::nominate(Origin::signed(light_controller), vec![11]),
+			Error::<Test>::TooManyNominators
+		);
+
+		// a heavier one takes the place of the lowest one.
+		assert_ok!(Staking::nominate(Origin::signed(heavy_controller), vec![11]));
+		assert!(!Nominators::<Test>::contains_key(101));
+		assert!(Nominators::<Test>::contains_key(heavy));
+		assert_eq!(CounterForNominators::<Test>::get(), 1);
+		assert_eq!(<Test as Config>::SortedListProvider::iter().collect::<Vec<_>>(), vec![heavy]);
+		assert_eq!(
+			staking_events().into_iter().rev().take(2).collect::<Vec<_>>(),
+			vec![Event::AutoChilled(101, heavy), Event::Chilled(101)]
+		);
+	})
+}
+
 mod sorted_list_provider {
 	use super::*;
 	use frame_election_provider_support::SortedListProvider;
@@ -4441,4 +4890,384 @@ mod sorted_list_provider {
 			assert_eq!(<Test as Config>::SortedListProvider::iter().collect::<Vec<_>>(), vec![101]);
 		});
 	}
+
+	#[test]
+	fn killed_stashes_are_removed_from_sorted_lists() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			// given a nominator and a validator in the lists
+			assert!(<Test as Config>::SortedListProvider::contains(&101));
+			assert!(<Test as Config>::TargetList::contains(&11));
+			// which `frame_system` can not reap while they are bonded
+			assert!(frame_system::Pallet::<Test>::consumers(&101) > 0);
+			assert!(frame_system::Pallet::<Test>::consumers(&11) > 0);
+
+			// when their stashes are killed, as done by `reap_stash` and `withdraw_unbonded`
+			assert_ok!(Staking::kill_stash(&101, 0));
+			assert_ok!(Staking::kill_stash(&11, 0));
+
+			// then they are removed from the lists
+			assert!(!<Test as Config>::SortedListProvider::contains(&101));
+			assert!(!<Test as Config>::TargetList::contains(&11));
+			assert_eq!(<Test as Config>::SortedListProvider::count(), 0);
+		});
+	}
+
+	#[test]
+	fn leaving_stakers_are_removed_from_sorted_lists() {
+		// no minimum bond, so that stakers can unbond everything without chilling first.
+		ExtBuilder::default()
+			.nominate(true)
+			.min_nominator_bond(0)
+			.min_validator_bond(0)
+			.build_and_execute(|| {
+				// given a nominator and two validators in the lists
+				assert!(<Test as Config>::SortedListProvider::contains(&101));
+				assert!(<Test as Config>::TargetList::contains(&11));
+				assert!(<Test as Config>::TargetList::contains(&21));
+
+				// when the nominator and a validator unbond everything, without chilling
+				assert_ok!(Staking::unbond(Origin::signed(100), 1_000));
+				assert_ok!(Staking::unbond(Origin::signed(10), 1_000));
+				// then they are still in the lists until their funds are withdrawn
+				assert!(<Test as Config>::SortedListProvider::contains(&101));
+				assert!(<Test as Config>::TargetList::contains(&11));
+
+				// when they withdraw all of their funds
+				start_active_era(active_era() + BondingDuration::get());
+				assert_ok!(Staking::withdraw_unbonded(Origin::signed(100), 0));
+				assert_ok!(Staking::withdraw_unbonded(Origin::signed(10), 0));
+
+				// then they are removed from the lists
+				assert!(!<Test as Config>::SortedListProvider::contains(&101));
+				assert!(!<Test as Config>::TargetList::contains(&11));
+				assert_eq!(<Test as Config>::SortedListProvider::count(), 0);
+
+				// and so is a validator that is force unstaked
+				assert_ok!(Staking::force_unstake(Origin::root(), 21, 0));
+				assert!(!<Test as Config>::TargetList::contains(&21));
+			});
+	}
+
+	#[test]
+	fn ledger_changes_rebag_immediately() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given a nominator in bag 1_000
+			bond_nominator(61, 60, 100, vec![11]);
+			let _ = Balances::make_free_balance_be(&61, 3_000);
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (1_000, 1_000));
+
+			// when it bonds extra, it moves up right away
+			assert_ok!(Staking::bond_extra(Origin::signed(61), 1_500));
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (2_000, 2_000));
+
+			// when it unbonds, it moves down
+			assert_ok!(Staking::unbond(Origin::signed(60), 1_590));
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (10, 10));
+
+			// when it rebonds, it moves up again
+			assert_ok!(Staking::rebond(Origin::signed(60), 40));
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (50, 50));
+
+			// withdrawing the unlocked funds does not change the active stake, nor the bag
+			mock::start_active_era(active_era() + BondingDuration::get());
+			assert_ok!(Staking::withdraw_unbonded(Origin::signed(60), 0));
+			assert_eq!(Staking::ledger(60).unwrap().total, 50);
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (50, 50));
+		});
+	}
+
+	#[test]
+	fn re_nominate_refreshes_placement() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given a nominator in bag 1_000
+			bond_nominator(61, 60, 100, vec![11]);
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (1_000, 1_000));
+
+			// whose active stake grew without the list knowing
+			let mut ledger = Staking::ledger(60).unwrap();
+			ledger.active = 1_600;
+			ledger.total = 1_600;
+			<Ledger<Test>>::insert(60, ledger);
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (1_000, 2_000));
+
+			// when it changes its nominations, it is placed anew
+			assert_ok!(Staking::nominate(Origin::signed(60), vec![21]));
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (2_000, 2_000));
+		});
+	}
+
+	#[test]
+	fn staked_rewards_rebag_immediately() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			// given 101, which nominates 11 and 21 and restakes its rewards, in bag 1_000
+			assert_eq!(BagsList::bag_for(&101).unwrap(), (1_000, 1_000));
+			assert_eq!(Staking::payee(101), RewardDestination::Staked);
+
+			// when rewards worth more than the rest of its bag are paid
+			mock::start_active_era(1);
+			Pallet::<Test>::reward_by_ids(vec![(11, 1)]);
+			mock::start_active_era(2);
+			mock::make_all_reward_payment(1);
+
+			// then it is in the bag of its new weight right away
+			let (bag, notional_bag) = BagsList::bag_for(&101).unwrap();
+			assert_eq!(bag, notional_bag);
+			assert_eq!(bag, 2_000);
+		});
+	}
+
+	#[test]
+	fn update_voter_weight_reports_movement() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given a nominator in bag 1_000
+			bond_nominator(61, 60, 100, vec![11]);
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (1_000, 1_000));
+
+			// when its ledger is unchanged, it does not move
+			assert!(!Staking::update_voter_weight(&61));
+
+			// when its active stake grows, it moves into a higher bag
+			let mut ledger = Staking::ledger(60).unwrap();
+			ledger.active = 1_600;
+			ledger.total = 1_600;
+			<Ledger<Test>>::insert(60, ledger);
+			assert!(Staking::update_voter_weight(&61));
+			assert_eq!(BagsList::bag_for(&61).unwrap(), (2_000, 2_000));
+
+			// and accounts that are not voters never move
+			assert!(!Staking::update_voter_weight(&1_234));
+		});
+	}
+
+	#[test]
+	fn slashed_stakers_are_rebagged() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			mock::start_active_era(1);
+			// given 101, which nominates 11 and 21, in bag 1_000
+			assert_eq!(BagsList::bag_for(&101).unwrap(), (1_000, 1_000));
+
+			// when both its validators are heavily slashed
+			on_offence_now(
+				&[
+					OffenceDetails {
+						offender: (11, Staking::eras_stakers(active_era(), 11)),
+						reporters: vec![],
+					},
+					OffenceDetails {
+						offender: (21, Staking::eras_stakers(active_era(), 21)),
+						reporters: vec![],
+					},
+				],
+				&[Perbill::from_percent(90), Perbill::from_percent(90)],
+			);
+
+			// then 101 is moved to the bag of its slashed stake, as part of the slash
+			let (bag, notional_bag) = BagsList::bag_for(&101).unwrap();
+			assert_eq!(bag, notional_bag);
+			assert!(bag < 1_000);
+		});
+	}
+
+	#[test]
+	fn map_lists_iterate_with_budget() {
+		ExtBuilder::default().build_and_execute(|| {
+			type TargetList = <Test as Config>::TargetList;
+			let read = <Test as frame_system::Config>::DbWeight::get().reads(1);
+			let all = TargetList::iter().collect::<Vec<_>>();
+			assert!(all.len() > 2);
+
+			// when the budget allows to read two validators
+			let taken = TargetList::iter_with_budget(None, 2 * read + 1).unwrap();
+
+			// then
+			assert_eq!(taken.ids, all[..2].to_vec());
+			assert_eq!(taken.consumed, 2 * read);
+			assert_eq!(taken.cursor, Some(all[1]));
+			assert!(!taken.exhausted);
+
+			// when the iteration is resumed without limit, then the rest is taken, and the end
+			// of the map is read too
+			let rest = TargetList::iter_with_budget(taken.cursor.as_ref(), Weight::MAX).unwrap();
+			assert_eq!(rest.ids, all[2..].to_vec());
+			assert_eq!(rest.consumed, (all.len() as Weight - 1) * read);
+			assert!(rest.exhausted);
+		});
+	}
+
+	#[test]
+	fn lists_iterate_with_score() {
+		ExtBuilder::default().build_and_execute(|| {
+			type TargetList = <Test as Config>::TargetList;
+			let targets = TargetList::iter_with_score().collect::<Vec<_>>();
+			assert_eq!(
+				targets,
+				TargetList::iter()
+					.map(|who| (who, Staking::target_weight_of(&who)))
+					.collect::<Vec<_>>()
+			);
+			assert!(!targets.is_empty());
+
+			let voters = BagsList::iter_with_score().collect::<Vec<_>>();
+			assert_eq!(
+				voters,
+				BagsList::iter().map(|who| (who, Staking::weight_of(&who))).collect::<Vec<_>>()
+			);
+			assert!(!voters.is_empty());
+		});
+	}
+
+	#[test]
+	fn list_members_are_voters_and_targets() {
+		use frame_support::traits::Contains;
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(BagsList::iter().all(|who| IsVoter::<Test>::contains(&who)));
+			assert!(
+				<Test as Config>::TargetList::iter().all(|who| IsTarget::<Test>::contains(&who))
+			);
+
+			// a chilled nominator is not a member of either list.
+			assert!(IsVoter::<Test>::contains(&101));
+			assert_ok!(Staking::chill(Origin::signed(100)));
+			assert!(!IsVoter::<Test>::contains(&101));
+			assert!(!IsTarget::<Test>::contains(&101));
+		});
+	}
+
+	#[test]
+	fn stale_node_of_new_validator_is_pruned() {
+		use frame_support::traits::Contains;
+		ExtBuilder::default().build_and_execute(|| {
+			// given a nominator that becomes a validator
+			assert_ok!(Staking::validate(Origin::signed(100), Default::default()));
+			assert!(!<Test as Config>::SortedListProvider::contains(&101));
+			assert!(<Test as Config>::TargetList::contains(&101));
+
+			// whose voter node was left behind
+			assert_ok!(<Test as Config>::SortedListProvider::on_insert(101, 500));
+			assert!(!IsVoter::<Test>::contains(&101));
+
+			// when the dangling nodes are pruned
+			assert_ok!(BagsList::prune_dangling_nodes(Origin::signed(1), 10));
+
+			// then the stale node is removed, but not the validator.
+			assert!(!<Test as Config>::SortedListProvider::contains(&101));
+			assert!(<Test as Config>::TargetList::contains(&101));
+			assert!(Validators::<Test>::contains_key(101));
+		});
+	}
+
+	#[test]
+	fn genesis_stakers_are_inserted_into_their_bags() {
+		ExtBuilder::default()
+			.nominate(true)
+			.add_staker(61, 60, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.add_staker(71, 70, 10, StakerStatus::<AccountId>::Nominator(vec![11, 21]))
+			.build_and_execute(|| {
+				// all the genesis nominators are in the list..
+				assert_eq!(
+					<Test as Config>::SortedListProvider::iter().collect::<Vec<_>>(),
+					vec![61, 101, 71]
+				);
+
+				// .. each in the bag of its genesis stake.
+				for nominator in [61, 101, 71] {
+					let (bag, notional_bag) = BagsList::bag_for(&nominator).unwrap();
+					assert_eq!(bag, notional_bag);
+				}
+				assert_eq!(BagsList::bag_for(&61).unwrap().0, 2_000);
+				assert_eq!(BagsList::bag_for(&71).unwrap().0, 10);
+			});
+	}
+}
+
+mod target_list {
+	use super::*;
+	use frame_election_provider_support::VoteWeight;
+
+	#[test]
+	fn target_weight_of_is_backing_stake_if_elected() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			// given 11 is elected, and backed by 101
+			let exposure = ErasStakers::<Test>::get(active_era(), 11);
+			assert!(exposure.total > exposure.own);
+
+			// then its target weight is its total backing
+			assert_eq!(Staking::target_weight_of(&11), exposure.total as VoteWeight);
+			assert!(Staking::target_weight_of(&11) > Staking::weight_of(&11));
+		});
+	}
+
+	#[test]
+	fn target_weight_of_is_own_stake_if_not_elected() {
+		ExtBuilder::default().build_and_execute(|| {
+			// when a new validator joins
+			bond_validator(61, 60, 2_000);
+
+			// then, until it is elected, its target weight is its own stake
+			assert!(ErasStakers::<Test>::get(active_era(), 61).total.is_zero());
+			assert_eq!(Staking::target_weight_of(&61), 2_000);
+			assert_eq!(Staking::target_weight_of(&61), Staking::weight_of(&61));
+		});
+	}
+}
+
+mod migrations {
+	use super::*;
+	use crate::migrations::{target_list::MigrateToTargetList, v8::MigrateToV8};
+	use frame_support::{assert_storage_noop, traits::OnRuntimeUpgrade};
+
+	#[test]
+	fn migrate_to_v8_populates_the_voter_list() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			// given a chain from before the voter list
+			StorageVersion::<Test>::put(Releases::V7_0_0);
+			let _ = BagsList::unsafe_clear(None);
+			assert_eq!(BagsList::count(), 0);
+
+			// when
+			MigrateToV8::<Test>::on_runtime_upgrade();
+
+			// then all the nominators are in their bags
+			assert_eq!(StorageVersion::<Test>::get(), Releases::V8_0_0);
+			assert_eq!(BagsList::count(), CounterForNominators::<Test>::get());
+			assert_eq!(BagsList::bag_for(&101).unwrap(), (1_000, 1_000));
+
+			// and upgrading again does nothing
+			assert_storage_noop!(MigrateToV8::<Test>::on_runtime_upgrade());
+		});
+	}
+
+	#[test]
+	fn migrate_to_v8_chills_nominators_that_do_not_fit() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			// given a chain from before the voter list, which has no room for anyone
+			StorageVersion::<Test>::put(Releases::V7_0_0);
+			let _ = BagsList::unsafe_clear(None);
+			assert!(CounterForNominators::<Test>::get() > 0);
+			MaxBagSize::set(0);
+
+			// when
+			MigrateToV8::<Test>::on_runtime_upgrade();
+
+			// then the nominators are chilled, and the counter kept in sync with the list.
+			assert_eq!(BagsList::count(), 0);
+			assert_eq!(CounterForNominators::<Test>::get(), 0);
+			assert_eq!(Nominators::<Test>::iter().count(), 0);
+		});
+	}
+
+	#[test]
+	fn migrate_to_target_list_only_fills_an_empty_list() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given a target list that already holds all the validators
+			assert_eq!(<Test as Config>::TargetList::count(), CounterForValidators::<Test>::get());
+			assert!(CounterForValidators::<Test>::get() > 0);
+
+			// then the upgrade leaves it alone, and only checks its count
+			assert_storage_noop!(assert_eq!(
+				MigrateToTargetList::<Test>::on_runtime_upgrade(),
+				<Test as frame_system::Config>::DbWeight::get().reads(1)
+			));
+		});
+	}
 }
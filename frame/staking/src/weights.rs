@@ -20,6 +20,11 @@
 //! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
 //! DATE: 2021-09-04, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
 //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 128
+//!
+//! NOTE: the `TargetBagsList` reads and writes of `bond_extra`, `unbond`, `validate` and `rebond`
+//! were added by hand on top of this run, which predates the target list. Their base weights are
+//! from this run. `force_rebuild_voter_list` is a PLACEHOLDER, estimated from `nominate`.
+//! Regenerate this file with the command below to replace them.
 
 // Executed Command:
 // target/release/substrate
@@ -73,6 +78,7 @@ pub trait WeightInfo {
 	fn get_npos_targets(v: u32, ) -> Weight;
 	fn set_staking_limits() -> Weight;
 	fn chill_other() -> Weight;
+	fn force_rebuild_voter_list(l: u32, ) -> Weight;
 }
 
 /// Weights for pallet_staking using the Substrate node and recommended hardware.
@@ -92,25 +98,31 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Staking Bonded (r:1 w:0)
 	// Storage: Staking Ledger (r:1 w:1)
 	// Storage: Balances Locks (r:1 w:1)
-	// Storage: BagsList ListNodes (r:3 w:3)
-	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: BagsList ListNodes (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:3 w:3)
+	// Storage: TargetBagsList ListBags (r:2 w:2)
 	fn bond_extra() -> Weight {
 		(114_296_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
 			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
 	// Storage: Staking Ledger (r:1 w:1)
 	// Storage: Staking Nominators (r:1 w:0)
-	// Storage: Staking MinNominatorBond (r:1 w:0)
+	// Storage: Staking Validators (r:1 w:0)
+	// Storage: Staking MinValidatorBond (r:1 w:0)
 	// Storage: Staking CurrentEra (r:1 w:0)
 	// Storage: Balances Locks (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
-	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: BagsList ListNodes (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:3 w:3)
 	// Storage: Staking Bonded (r:1 w:0)
-	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: TargetBagsList ListBags (r:2 w:2)
 	fn unbond() -> Weight {
 		(121_737_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(12 as Weight))
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
 			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
 	// Storage: Staking Ledger (r:1 w:1)
@@ -151,11 +163,16 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: BagsList ListNodes (r:2 w:2)
 	// Storage: BagsList ListBags (r:1 w:1)
 	// Storage: BagsList CounterForListNodes (r:1 w:1)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:2 w:2)
+	// Storage: TargetBagsList ListBags (r:1 w:1)
+	// Storage: TargetBagsList CounterForListNodes (r:1 w:1)
 	// Storage: Staking CounterForValidators (r:1 w:1)
 	fn validate() -> Weight {
 		(69_092_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(11 as Weight))
-			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads(17 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
 	}
 	// Storage: Staking Ledger (r:1 w:0)
 	// Storage: Staking Nominators (r:1 w:1)
@@ -310,14 +327,17 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Staking Ledger (r:1 w:1)
 	// Storage: Balances Locks (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
-	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: BagsList ListNodes (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:3 w:3)
 	// Storage: Staking Bonded (r:1 w:0)
-	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: TargetBagsList ListBags (r:2 w:2)
 	fn rebond(l: u32, ) -> Weight {
 		(111_858_000 as Weight)
 			// Standard Error: 4_000
 			.saturating_add((36_000 as Weight).saturating_mul(l as Weight))
-			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().reads(12 as Weight))
 			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
 	// Storage: Staking CurrentEra (r:1 w:0)
@@ -442,6 +462,27 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(11 as Weight))
 			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
+	// PLACEHOLDER: not benchmarked yet, see the note at the top of this file.
+	// Storage: Staking VoterListRebuilding (r:1 w:1)
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: Staking Nominators (r:1 w:0)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ListNodes (r:2 w:2)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList CounterForListNodes (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn force_rebuild_voter_list(l: u32, ) -> Weight {
+		(6_281_000 as Weight)
+			// Standard Error: 21_000
+			.saturating_add((48_903_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((9 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((7 as Weight).saturating_mul(l as Weight)))
+	}
 }
 
 // For backwards compatibility and tests
@@ -460,25 +501,31 @@ impl WeightInfo for () {
 	// Storage: Staking Bonded (r:1 w:0)
 	// Storage: Staking Ledger (r:1 w:1)
 	// Storage: Balances Locks (r:1 w:1)
-	// Storage: BagsList ListNodes (r:3 w:3)
-	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: BagsList ListNodes (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:3 w:3)
+	// Storage: TargetBagsList ListBags (r:2 w:2)
 	fn bond_extra() -> Weight {
 		(114_296_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
 	}
 	// Storage: Staking Ledger (r:1 w:1)
 	// Storage: Staking Nominators (r:1 w:0)
-	// Storage: Staking MinNominatorBond (r:1 w:0)
+	// Storage: Staking Validators (r:1 w:0)
+	// Storage: Staking MinValidatorBond (r:1 w:0)
 	// Storage: Staking CurrentEra (r:1 w:0)
 	// Storage: Balances Locks (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
-	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: BagsList ListNodes (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:3 w:3)
 	// Storage: Staking Bonded (r:1 w:0)
-	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: TargetBagsList ListBags (r:2 w:2)
 	fn unbond() -> Weight {
 		(121_737_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(12 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
 	}
 	// Storage: Staking Ledger (r:1 w:1)
@@ -519,11 +566,16 @@ impl WeightInfo for () {
 	// Storage: BagsList ListNodes (r:2 w:2)
 	// Storage: BagsList ListBags (r:1 w:1)
 	// Storage: BagsList CounterForListNodes (r:1 w:1)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:2 w:2)
+	// Storage: TargetBagsList ListBags (r:1 w:1)
+	// Storage: TargetBagsList CounterForListNodes (r:1 w:1)
 	// Storage: Staking CounterForValidators (r:1 w:1)
 	fn validate() -> Weight {
 		(69_092_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(17 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
 	}
 	// Storage: Staking Ledger (r:1 w:0)
 	// Storage: Staking Nominators (r:1 w:1)
@@ -678,14 +730,17 @@ impl WeightInfo for () {
 	// Storage: Staking Ledger (r:1 w:1)
 	// Storage: Balances Locks (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
-	// Storage: BagsList ListNodes (r:3 w:3)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: BagsList ListNodes (r:1 w:0)
+	// Storage: TargetBagsList ListNodes (r:3 w:3)
 	// Storage: Staking Bonded (r:1 w:0)
-	// Storage: BagsList ListBags (r:2 w:2)
+	// Storage: TargetBagsList ListBags (r:2 w:2)
 	fn rebond(l: u32, ) -> Weight {
 		(111_858_000 as Weight)
 			// Standard Error: 4_000
 			.saturating_add((36_000 as Weight).saturating_mul(l as Weight))
-			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(12 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
 	}
 	// Storage: Staking CurrentEra (r:1 w:0)
@@ -810,4 +865,25 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
 	}
+	// PLACEHOLDER: not benchmarked yet, see the note at the top of this file.
+	// Storage: Staking VoterListRebuilding (r:1 w:1)
+	// Storage: BagsList Locks (r:1 w:0)
+	// Storage: Staking Nominators (r:1 w:0)
+	// Storage: Staking Bonded (r:1 w:0)
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: BagsList ListNodes (r:2 w:2)
+	// Storage: BagsList ListBags (r:1 w:1)
+	// Storage: BagsList CounterForListNodes (r:1 w:1)
+	// Storage: BagsList CounterForBag (r:1 w:1)
+	// Storage: BagsList BagHistogram (r:1 w:1)
+	// Storage: BagsList LastPlaced (r:0 w:1)
+	fn force_rebuild_voter_list(l: u32, ) -> Weight {
+		(6_281_000 as Weight)
+			// Standard Error: 21_000
+			.saturating_add((48_903_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((9 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((7 as Weight).saturating_mul(l as Weight)))
+	}
 }
@@ -370,6 +370,23 @@ where
 		}
 	}
 
+	/// Enumerate all elements in the map after a specified `starting_raw_key` in no particular
+	/// order.
+	///
+	/// If you alter the map while doing this, you'll get undefined results.
+	pub fn iter_from(
+		starting_raw_key: Vec<u8>,
+	) -> crate::storage::PrefixIterator<(Key, Value), OnRemovalCounterUpdate<Prefix>> {
+		let map_iterator = <Self as MapWrapper>::Map::iter_from(starting_raw_key);
+		crate::storage::PrefixIterator {
+			prefix: map_iterator.prefix,
+			previous_key: map_iterator.previous_key,
+			drain: map_iterator.drain,
+			closure: map_iterator.closure,
+			phantom: Default::default(),
+		}
+	}
+
 	/// Remove all elements from the map and iterate through them in no particular order.
 	///
 	/// If you add elements to the map while doing this, you'll get undefined results.
@@ -993,6 +1010,7 @@ mod test {
 			A::insert(2, 2);
 
 			assert_eq!(A::iter().collect::<Vec<_>>(), vec![(2, 2), (1, 1)]);
+			assert_eq!(A::iter_from(A::hashed_key_for(2)).collect::<Vec<_>>(), vec![(1, 1)]);
 
 			assert_eq!(A::count(), 2);
 
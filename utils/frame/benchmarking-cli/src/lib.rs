@@ -84,6 +84,15 @@ pub struct BenchmarkCmd {
 	#[structopt(long)]
 	pub template: Option<std::path::PathBuf>,
 
+	/// Output the weights as an additional hardware profile, e.g. `LowEndWeight`, instead of a
+	/// whole weight file.
+	///
+	/// Only the implementation of `WeightInfo` for a new struct with this name is written, and it
+	/// is appended to the existing file at `--output`, next to the profiles of earlier runs, made
+	/// on other reference hardware. `--template` is ignored.
+	#[structopt(long)]
+	pub weight_profile: Option<String>,
+
 	/// Which analysis function to use when outputting benchmarks:
 	/// * min-squares (default)
 	/// * median-slopes
@@ -21,7 +21,8 @@ use core::convert::TryInto;
 use std::{
 	collections::{HashMap, HashSet},
 	fs,
-	path::PathBuf,
+	io::Write,
+	path::{Path, PathBuf},
 };
 
 use inflector::Inflector;
@@ -38,6 +39,7 @@ use sp_runtime::traits::Zero;
 
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 const TEMPLATE: &str = include_str!("./template.hbs");
+const PROFILE_TEMPLATE: &str = include_str!("./profile.hbs");
 
 // This is the final structure we will pass to the Handlebars template.
 #[derive(Serialize, Default, Debug, Clone)]
@@ -50,6 +52,7 @@ struct TemplateData {
 	header: String,
 	cmd: CmdData,
 	benchmarks: Vec<BenchmarkData>,
+	profile: String,
 }
 
 // This was the final data we have about each benchmark.
@@ -258,10 +261,16 @@ pub fn write_results(
 	path: &PathBuf,
 	cmd: &BenchmarkCmd,
 ) -> Result<(), std::io::Error> {
-	// Use custom template if provided.
-	let template: String = match &cmd.template {
-		Some(template_file) => fs::read_to_string(template_file)?,
-		None => TEMPLATE.to_string(),
+	// Use custom template if provided, unless only a profile is output.
+	let template: String = match (&cmd.weight_profile, &cmd.template) {
+		(Some(profile), _) => {
+			if !is_identifier(profile) {
+				return Err(io_error(&format!("invalid weight profile name: {}", profile)))
+			}
+			PROFILE_TEMPLATE.to_string()
+		},
+		(None, Some(template_file)) => fs::read_to_string(template_file)?,
+		(None, None) => TEMPLATE.to_string(),
 	};
 
 	// Use header if provided
@@ -296,12 +305,7 @@ pub fn write_results(
 		analysis_choice: format!("{:?}", analysis_choice),
 	};
 
-	// New Handlebars instance with helpers.
-	let mut handlebars = handlebars::Handlebars::new();
-	handlebars.register_helper("underscore", Box::new(UnderscoreHelper));
-	handlebars.register_helper("join", Box::new(JoinHelper));
-	// Don't HTML escape any characters.
-	handlebars.register_escape_fn(|s| -> String { s.to_string() });
+	let handlebars = new_handlebars();
 
 	// Organize results by pallet into a JSON map
 	let all_results = map_results(batches, storage_info, &analysis_choice)?;
@@ -329,16 +333,49 @@ pub fn write_results(
 			header: header_text.clone(),
 			cmd: cmd_data.clone(),
 			benchmarks: results.clone(),
+			profile: cmd.weight_profile.clone().unwrap_or_default(),
 		};
 
-		let mut output_file = fs::File::create(file_path)?;
-		handlebars
-			.render_template_to_write(&template, &hbs_data, &mut output_file)
+		let rendered = handlebars
+			.render_template(&template, &hbs_data)
 			.map_err(|e| io_error(&e.to_string()))?;
+		match &cmd.weight_profile {
+			Some(profile) => append_profile(&file_path, profile, &rendered)?,
+			None => fs::write(&file_path, rendered)?,
+		}
 	}
 	Ok(())
 }
 
+// Append the `rendered` weights of `profile` to the weight file of an earlier run, at `file_path`.
+// A profile can only be added once.
+fn append_profile(file_path: &Path, profile: &str, rendered: &str) -> Result<(), std::io::Error> {
+	let existing = fs::read_to_string(file_path)?;
+	if existing.contains(&format!("pub struct {}<T>", profile)) {
+		return Err(io_error(&format!("{} already has a {} profile", file_path.display(), profile)))
+	}
+	fs::OpenOptions::new()
+		.append(true)
+		.open(file_path)?
+		.write_all(rendered.as_bytes())
+}
+
+// New Handlebars instance with helpers.
+fn new_handlebars() -> handlebars::Handlebars<'static> {
+	let mut handlebars = handlebars::Handlebars::new();
+	handlebars.register_helper("underscore", Box::new(UnderscoreHelper));
+	handlebars.register_helper("join", Box::new(JoinHelper));
+	// Don't HTML escape any characters.
+	handlebars.register_escape_fn(|s| -> String { s.to_string() });
+	handlebars
+}
+
+// Whether `name` can be used as the name of a Rust struct.
+fn is_identifier(name: &str) -> bool {
+	name.chars().next().map_or(false, |c| c.is_ascii_alphabetic()) &&
+		name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 // This function looks at the keys touched during the benchmark, and the storage info we collected
 // from the pallets, and creates comments with information about the storage keys touched during
 // each benchmark.
@@ -588,4 +625,73 @@ mod test {
 		assert_eq!(second_pallet_benchmark.name, "first_benchmark");
 		check_data(second_pallet_benchmark, "c", 3, 4);
 	}
+
+	#[test]
+	fn profile_template_works() {
+		let mapped_results = map_results(
+			&[test_data(b"first", b"first", BenchmarkParameter::a, 10, 3)],
+			&[],
+			&AnalysisChoice::default(),
+		)
+		.unwrap();
+		let data = TemplateData {
+			pallet: "first_pallet".to_string(),
+			benchmarks: mapped_results.into_values().next().unwrap(),
+			profile: "LowEndWeight".to_string(),
+			..Default::default()
+		};
+
+		let rendered = new_handlebars().render_template(PROFILE_TEMPLATE, &data).unwrap();
+		assert!(rendered.contains("pub struct LowEndWeight<T>(PhantomData<T>);"));
+		assert!(rendered.contains("impl<T: frame_system::Config> WeightInfo for LowEndWeight<T> {"));
+		assert!(rendered.contains("fn first_benchmark(a: u32, _z: u32, ) -> Weight {"));
+		// it is only added to a file that already has the trait and its imports.
+		assert!(!rendered.contains("pub trait WeightInfo"));
+		assert!(!rendered.contains("use "));
+	}
+
+	#[test]
+	fn append_profile_works() {
+		let mapped_results = map_results(
+			&[test_data(b"first", b"first", BenchmarkParameter::a, 10, 3)],
+			&[],
+			&AnalysisChoice::default(),
+		)
+		.unwrap();
+		let data = TemplateData {
+			pallet: "first_pallet".to_string(),
+			benchmarks: mapped_results.into_values().next().unwrap(),
+			profile: "LowEndWeight".to_string(),
+			..Default::default()
+		};
+		let handlebars = new_handlebars();
+		let file_path =
+			std::env::temp_dir().join(format!("append_profile_works_{}.rs", std::process::id()));
+
+		// a profile can not be added to a weight file that does not exist yet.
+		let profile = handlebars.render_template(PROFILE_TEMPLATE, &data).unwrap();
+		assert!(append_profile(&file_path, "LowEndWeight", &profile).is_err());
+
+		// but is added to the weight file of an earlier run, after its default weights.
+		let earlier = "pub trait WeightInfo {\n}\npub struct SubstrateWeight<T>(PhantomData<T>);\n";
+		fs::write(&file_path, earlier).unwrap();
+		append_profile(&file_path, "LowEndWeight", &profile).unwrap();
+		let written = fs::read_to_string(&file_path).unwrap();
+		assert_eq!(written, format!("{}{}", earlier, profile));
+
+		// only once.
+		assert!(append_profile(&file_path, "LowEndWeight", &profile).is_err());
+		assert_eq!(fs::read_to_string(&file_path).unwrap(), written);
+
+		fs::remove_file(&file_path).unwrap();
+	}
+
+	#[test]
+	fn is_identifier_works() {
+		assert!(is_identifier("LowEndWeight"));
+		assert!(is_identifier("Weight_2"));
+		assert!(!is_identifier(""));
+		assert!(!is_identifier("2Weight"));
+		assert!(!is_identifier("Low End"));
+	}
 }
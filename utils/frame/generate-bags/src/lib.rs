@@ -56,6 +56,7 @@
 
 use frame_election_provider_support::VoteWeight;
 use frame_support::traits::Get;
+pub use pallet_bags_list::make_bags::{constant_ratio, thresholds};
 use std::{
 	io::Write,
 	path::{Path, PathBuf},
@@ -70,10 +71,9 @@ fn existential_weight<T: pallet_staking::Config>(
 	total_issuance: u128,
 	minimum_balance: u128,
 ) -> VoteWeight {
-	use frame_support::traits::CurrencyToVote;
 	use std::convert::TryInto;
 
-	T::CurrencyToVote::to_vote(
+	pallet_bags_list::make_bags::existential_weight::<_, T::CurrencyToVote>(
 		minimum_balance
 			.try_into()
 			.map_err(|_| "failed to convert minimum_balance to type Balance")
@@ -102,7 +102,7 @@ fn path_to_header_file() -> Option<PathBuf> {
 }
 
 /// Create an underscore formatter: a formatter which inserts `_` every 3 digits of a number.
-fn underscore_formatter() -> num_format::CustomFormat {
+pub fn underscore_formatter() -> num_format::CustomFormat {
 	num_format::CustomFormat::builder()
 		.grouping(num_format::Grouping::Standard)
 		.separator("_")
@@ -110,56 +110,6 @@ fn underscore_formatter() -> num_format::CustomFormat {
 		.expect("format described here meets all constraints")
 }
 
-/// Compute the constant ratio for the thresholds.
-///
-/// This ratio ensures that each bag, with the possible exceptions of certain small ones and the
-/// final one, is a constant multiple of the previous, while fully occupying the `VoteWeight`
-/// space.
-pub fn constant_ratio(existential_weight: VoteWeight, n_bags: usize) -> f64 {
-	((VoteWeight::MAX as f64 / existential_weight as f64).ln() / ((n_bags - 1) as f64)).exp()
-}
-
-/// Compute the list of bag thresholds.
-///
-/// Returns a list of exactly `n_bags` elements, except in the case of overflow.
-/// The first element is always `existential_weight`.
-/// The last element is always `VoteWeight::MAX`.
-///
-/// All other elements are computed from the previous according to the formula
-/// `threshold[k + 1] = (threshold[k] * ratio).max(threshold[k] + 1);
-pub fn thresholds(
-	existential_weight: VoteWeight,
-	constant_ratio: f64,
-	n_bags: usize,
-) -> Vec<VoteWeight> {
-	const WEIGHT_LIMIT: f64 = VoteWeight::MAX as f64;
-
-	let mut thresholds = Vec::with_capacity(n_bags);
-
-	if n_bags > 1 {
-		thresholds.push(existential_weight);
-	}
-
-	while n_bags > 0 && thresholds.len() < n_bags - 1 {
-		let last = thresholds.last().copied().unwrap_or(existential_weight);
-		let successor = (last as f64 * constant_ratio).round().max(last as f64 + 1.0);
-		if successor < WEIGHT_LIMIT {
-			thresholds.push(successor as VoteWeight);
-		} else {
-			eprintln!("unexpectedly exceeded weight limit; breaking threshold generation loop");
-			break
-		}
-	}
-
-	thresholds.push(VoteWeight::MAX);
-
-	debug_assert_eq!(thresholds.len(), n_bags);
-	debug_assert!(n_bags == 0 || thresholds[0] == existential_weight);
-	debug_assert!(n_bags == 0 || thresholds[thresholds.len() - 1] == VoteWeight::MAX);
-
-	thresholds
-}
-
 /// Write a thresholds module to the path specified.
 ///
 /// Parameters:
@@ -198,23 +148,48 @@ pub fn generate_thresholds<T: pallet_staking::Config>(
 	let file = std::fs::OpenOptions::new().create(true).append(true).open(output)?;
 	let mut buf = std::io::BufWriter::new(file);
 
+	writeln!(buf)?;
+	let existential_weight = existential_weight::<T>(total_issuance, minimum_balance);
+	write_thresholds(
+		&mut buf,
+		existential_weight,
+		n_bags,
+		&[format!("for the {} runtime.", <T as frame_system::Config>::Version::get().spec_name)],
+	)
+}
+
+/// Write a thresholds module to `buf`.
+///
+/// Parameters:
+/// - `existential_weight` the first threshold, see [`thresholds`].
+/// - `n_bags` the number of bags to generate.
+/// - `notes` lines appended to the module documentation, e.g. to record the runtime or the
+///   parameters the thresholds were generated for.
+///
+/// This writes, in order:
+///
+/// - Module documentation noting that this is autogenerated and when.
+/// - Some associated constants.
+/// - The constant array of thresholds.
+pub fn write_thresholds(
+	buf: &mut impl Write,
+	existential_weight: VoteWeight,
+	n_bags: usize,
+	notes: &[String],
+) -> Result<(), std::io::Error> {
 	// create underscore formatter and format buffer
 	let mut num_buf = num_format::Buffer::new();
 	let format = underscore_formatter();
 
 	// module docs
 	let now = chrono::Utc::now();
-	writeln!(buf)?;
 	writeln!(buf, "//! Autogenerated bag thresholds.")?;
 	writeln!(buf, "//!")?;
 	writeln!(buf, "//! Generated on {}", now.to_rfc3339())?;
-	writeln!(
-		buf,
-		"//! for the {} runtime.",
-		<T as frame_system::Config>::Version::get().spec_name,
-	)?;
+	for note in notes {
+		writeln!(buf, "//! {}", note)?;
+	}
 
-	let existential_weight = existential_weight::<T>(total_issuance, minimum_balance);
 	num_buf.write_formatted(&existential_weight, &format);
 	writeln!(buf)?;
 	writeln!(buf, "/// Existential weight for this runtime.")?;
@@ -0,0 +1,64 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Print the set of bag thresholds to be used with pallet-bags-list, as a Rust module.
+
+use frame_support::traits::U128CurrencyToVote;
+use generate_bags::{underscore_formatter, write_thresholds};
+use num_format::ToFormattedString;
+use pallet_bags_list::make_bags;
+use std::io::Write;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+	/// How many bags to generate.
+	#[structopt(long, default_value = "200")]
+	bags: usize,
+
+	/// The total issuance of the currency used to create `VoteWeight`.
+	#[structopt(short, long)]
+	total_issuance: u128,
+
+	/// The minimum account balance (i.e. existential deposit) for the currency used to create
+	/// `VoteWeight`.
+	#[structopt(short, long)]
+	existential_deposit: u128,
+}
+
+fn main() -> Result<(), std::io::Error> {
+	let Opt { bags, total_issuance, existential_deposit } = Opt::from_args();
+
+	let existential_weight = make_bags::existential_weight::<u128, U128CurrencyToVote>(
+		existential_deposit,
+		total_issuance,
+	);
+
+	// record how the thresholds were generated.
+	let format = underscore_formatter();
+	let notes = [
+		format!("by {} v{}, with:", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+		format!("- total issuance: {}", total_issuance.to_formatted_string(&format)),
+		format!("- existential deposit: {}", existential_deposit.to_formatted_string(&format)),
+		format!("- bags: {}", bags),
+	];
+
+	let stdout = std::io::stdout();
+	let mut buf = std::io::BufWriter::new(stdout.lock());
+	write_thresholds(&mut buf, existential_weight, bags, &notes)?;
+	buf.flush()
+}